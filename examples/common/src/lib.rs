@@ -47,22 +47,6 @@ pub mod texture;
 pub mod uni_buffer;
 pub mod vertex_instancing;
 
-// functional tests
-//#[cfg(feature = "funtest")]
-//pub mod funtest_360_manually_drop_framebuffer;
-//#[cfg(feature = "funtest")]
-//pub mod funtest_483_indices_mut_corruption;
-//#[cfg(feature = "funtest")]
-//pub mod funtest_flatten_slice;
-//#[cfg(all(feature = "funtest", feature = "funtest-gl33-f64-uniform"))]
-//pub mod funtest_gl33_f64_uniform;
-//#[cfg(feature = "funtest")]
-//pub mod funtest_pixel_array_encoding;
-//#[cfg(feature = "funtest")]
-//pub mod funtest_scissor_test;
-//#[cfg(feature = "funtest")]
-//pub mod funtest_tess_no_data;
-
 /// Example interface.
 pub trait Example: Sized {
   type Err: From<luminance::backend::Error> + Display;
@@ -70,7 +70,7 @@ const TRI_VERTICES: [Vertex; 3] = [
 struct ShaderUniforms {
   #[uniform(name = "t")]
   time: Uni<f32>,
-  triangle_pos: Uni<[f32; 2]>,
+  triangle_pos: Uni<Vector2<f32>>,
 }
 
 pub struct LocalExample {
@@ -135,7 +135,7 @@ impl Example for LocalExample {
       frame.with_program(program, |mut frame| {
         frame.update(|mut program, unis| {
           program.set(&unis.time, &t)?;
-          program.set(&unis.triangle_pos, triangle_pos.as_ref())
+          program.set(&unis.triangle_pos, &triangle_pos)
         })?;
 
         frame.with_render_state(&RenderState::default(), |mut frame| {
@@ -25,25 +25,10 @@ pub struct CLIOpts {
 
 /// Macro to declaratively add examples.
 macro_rules! examples {
-  (examples: $($ex_name:literal, $test_ident:ident),* ,
-   funtests: $($fun_name:literal $(if $fun_feature_gate:literal)?, $fun_ident:ident),* $(,)?
-  ) => {
+  (examples: $($ex_name:literal, $test_ident:ident),* $(,)?) => {
     fn show_available_examples() {
       println!("simple examples:");
       $( println!("  - {}", $ex_name); )*
-
-      #[cfg(feature = "funtest")]
-      {
-        println!("\nfunctional tests:");
-        $(
-          print!("  - {}", $fun_name);
-          $(
-            #[cfg(feature = $fun_feature_gate)]
-            print!(" (feature: {})", $fun_feature_gate);
-          )?
-          println!("");
-        )*
-      }
     }
 
     // create a function that will run an example based on its name
@@ -56,13 +41,6 @@ macro_rules! examples {
           }
         ),*
 
-        $(
-          #[cfg(all(feature = "funtest" $(, feature = $fun_feature_gate)?))]
-          Some($fun_name) => {
-            run_example::<luminance_examples::$fun_ident::LocalExample>(cli_opts)
-          }
-        ),*
-
         _ => {
           log::warn!("no example found");
           show_available_examples();
@@ -211,16 +189,6 @@ examples! {
   "mrt", mrt,
   "uniform-buffer", uni_buffer,
   "stencil", stencil,
-
-  // functional tests
-  funtests:
-  // "funtest-tess-no-data", funtest_tess_no_data,
-  // "funtest-gl33-f64-uniform" if "funtest-gl33-f64-uniform", funtest_gl33_f64_uniform,
-  // "funtest-scissor-test", funtest_scissor_test,
-  // "funtest-360-manually-drop-framebuffer", funtest_360_manually_drop_framebuffer,
-  // "funtest-flatten-slice", funtest_flatten_slice,
-  // "funtest-pixel-array-encoding", funtest_pixel_array_encoding,
-  // "funtest-483-indices-mut-corruption", funtest_483_indices_mut_corruption,
 }
 
 fn main() {
@@ -59,6 +59,7 @@ pub fn impl_memory_layout(item: DeriveInput, layout: Layout) -> TokenStream {
 
       quote! {
         #[repr(C)]
+        #[derive(Clone, Copy)]
         pub struct #aligned_ident {
           #(#field_decls ,)*
 
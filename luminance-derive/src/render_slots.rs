@@ -22,6 +22,14 @@ pub fn impl_render_slots(item: DeriveInput) -> TokenStream {
           let impl_has_field = quote! {
             impl luminance::has_field::HasField<#field_name> for #type_ident {
               type FieldType = #field_ty;
+
+              fn field(&self) -> &Self::FieldType {
+                &self.#field_ident
+              }
+
+              fn field_mut(&mut self) -> &mut Self::FieldType {
+                &mut self.#field_ident
+              }
             }
           };
 
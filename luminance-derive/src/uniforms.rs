@@ -77,14 +77,26 @@ pub(crate) fn generate_uniforms_impl(
             .map_err(UniformsError::NameError)?;
 
         // the build call is the code that gets a uniform and possibly fails if bound; also handles
-        // renaming
-        let build_call = if unbound {
-          quote! {
-            backend.new_shader_uni(program_handle, #name).or_else(|_| backend.new_shader_uni_unbound(program_handle))?
-          }
+        // renaming, and reports type mismatches on the field rather than on the uniform
+        let field_name = field_ident.to_string();
+        let unbound_arm = if unbound {
+          quote! { Err(_) => backend.new_shader_uni_unbound(program_handle)? }
         } else {
-          quote! {
-            backend.new_shader_uni(program_handle, #name)?
+          quote! { Err(e) => return Err(e) }
+        };
+        let build_call = quote! {
+          match backend.new_shader_uni(program_handle, #name) {
+            Ok(uni) => uni,
+
+            Err(luminance::backend::ShaderError::UniTypeMismatch { rust_type, glsl_type, .. }) => {
+              return Err(luminance::backend::ShaderError::UniTypeMismatch {
+                field: #field_name.to_owned(),
+                rust_type,
+                glsl_type,
+              });
+            }
+
+            #unbound_arm,
           }
         };
 
@@ -1,16 +1,21 @@
-use crate::attrib::{get_field_attr_once, AttrError};
+use crate::attrib::{get_field_attr_once, get_field_flag_once, AttrError};
 use proc_macro::TokenStream;
 use quote::quote;
 use std::error;
 use std::fmt;
-use syn::{Attribute, DataStruct, Field, Fields, Ident, LitBool};
+use syn::{
+  Attribute, DataStruct, Field, Fields, GenericArgument, Ident, LitBool, LitInt, PathArguments,
+  Type,
+};
 
 // accepted sub keys for the "vertex" key
-const KNOWN_SUBKEYS: &[&str] = &["normalized", "namespace"];
+const KNOWN_SUBKEYS: &[&str] = &["normalized", "namespace", "divisor", "double"];
 
 #[derive(Debug)]
 pub(crate) enum StructImplError {
   FieldError(AttrError),
+  DoubleNonF64(Ident),
+  DoubleUnsupported(Ident),
   UnsupportedUnnamed,
   UnsupportedUnit,
 }
@@ -19,6 +24,16 @@ impl fmt::Display for StructImplError {
   fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
     match self {
       StructImplError::FieldError(ref e) => write!(f, "error with vertex attribute field; {}", e),
+      StructImplError::DoubleNonF64(ref field) => write!(
+        f,
+        "vertex attribute field {} is not made of f64, and cannot be double-precision",
+        field
+      ),
+      StructImplError::DoubleUnsupported(ref field) => write!(
+        f,
+        "vertex attribute field {} cannot be double-precision without the shader-f64 feature of luminance",
+        field
+      ),
       StructImplError::UnsupportedUnnamed => f.write_str("unsupported unnamed fields in struct"),
       StructImplError::UnsupportedUnit => f.write_str("unsupported unit struct"),
     }
@@ -52,15 +67,23 @@ where
         .iter()
         .enumerate()
         .map(|(rank, field)| {
-          let field_ident = field.ident.as_ref().unwrap().to_string();
+          let ident = field.ident.as_ref().unwrap();
+          let field_ident = ident.to_string();
           let field_ty = &field.ty;
 
-          let vertex_attrib_desc =
-            field_vertex_attrib_desc(&field, field.ident.as_ref().unwrap(), &namespace)?;
+          let vertex_attrib_desc = field_vertex_attrib_desc(&field, ident, &namespace)?;
 
           let deinterleave_impl = quote! {
             impl luminance::has_field::HasField<#field_ident> for #struct_ident {
               type FieldType = #field_ty;
+
+              fn field(&self) -> &Self::FieldType {
+                &self.#ident
+              }
+
+              fn field_mut(&mut self) -> &mut Self::FieldType {
+                &mut self.#ident
+              }
             }
 
             impl luminance::vertex::Deinterleave<#field_ident> for #struct_ident {
@@ -118,14 +141,46 @@ fn field_vertex_attrib_desc(
     })
     .map_err(StructImplError::FieldError)?;
 
+  // double-precision attributes are converted to single-precision, unless exposed as such with the double flag
+  let double = get_field_flag_once(ident, &field.attrs, "vertex", "double", KNOWN_SUBKEYS)
+    .map_err(StructImplError::FieldError)?;
+
+  if double && !is_f64_type(&field.ty) {
+    return Err(StructImplError::DoubleNonF64(ident.clone()));
+  }
+
+  if double && !cfg!(feature = "shader-f64") {
+    return Err(StructImplError::DoubleUnsupported(ident.clone()));
+  }
+
+  // search for the divisor argument; if not there, instance data advances once per instance
+  let divisor = get_field_attr_once(ident, &field.attrs, "vertex", "divisor", KNOWN_SUBKEYS)
+    .and_then(|lit: LitInt| {
+      lit
+        .base10_parse::<usize>()
+        .ok()
+        .filter(|&divisor| divisor > 0)
+        .ok_or_else(|| AttrError::cannot_parse_attribute(ident.clone(), "vertex", "divisor"))
+    })
+    .or_else(|e| match e {
+      AttrError::CannotFindAttribute(..) => Ok(1),
+      _ => Err(e),
+    })
+    .map_err(StructImplError::FieldError)?;
+
   let field_ty = &field.ty;
   let field_name = field.ident.as_ref().unwrap().to_string();
 
-  let vertex_attrib_desc = if normalized {
-    quote! { (<#field_ty as luminance::vertex::VertexAttrib>::VERTEX_ATTRIB_DESC).normalize() }
-  } else {
-    quote! { <#field_ty as luminance::vertex::VertexAttrib>::VERTEX_ATTRIB_DESC }
-  };
+  let mut vertex_attrib_desc =
+    quote! { <#field_ty as luminance::vertex::VertexAttrib>::VERTEX_ATTRIB_DESC };
+
+  if normalized {
+    vertex_attrib_desc = quote! { (#vertex_attrib_desc).normalize() };
+  }
+
+  if double {
+    vertex_attrib_desc = quote! { (#vertex_attrib_desc).double() };
+  }
 
   let q = quote! {
     luminance::vertex::VertexBufferDesc::new(
@@ -133,11 +188,36 @@ fn field_vertex_attrib_desc(
       #field_name,
       #vertex_attrib_desc,
     )
+    .set_divisor(#divisor)
   };
 
   Ok(q)
 }
 
+/// Whether a field type is made of `f64`, e.g. `f64`, `[f64; 3]` or `Vector3<f64>`.
+fn is_f64_type(ty: &Type) -> bool {
+  match ty {
+    Type::Path(path) => {
+      path
+        .path
+        .segments
+        .last()
+        .map_or(false, |segment| match segment.arguments {
+          PathArguments::None => segment.ident == "f64",
+          PathArguments::AngleBracketed(ref args) => args
+            .args
+            .iter()
+            .any(|arg| matches!(arg, GenericArgument::Type(ty) if is_f64_type(ty))),
+          PathArguments::Parenthesized(..) => false,
+        })
+    }
+    Type::Array(array) => is_f64_type(&array.elem),
+    Type::Group(group) => is_f64_type(&group.elem),
+    Type::Paren(paren) => is_f64_type(&paren.elem),
+    _ => false,
+  }
+}
+
 fn get_namespace<'a, A>(
   ident: &Ident,
   attrs: A,
@@ -0,0 +1,276 @@
+//! Headless [EGL](https://www.khronos.org/egl) backend for [luminance](https://crates.io/crates/luminance).
+//!
+//! [`EglSurface`] creates an OpenGL 3.3 core context without any window, by rendering to an EGL pbuffer. This allows
+//! to render (and read back) framebuffers on machines without any display server or GPU, for instance with Mesa’s
+//! software implementations (llvmpipe, softpipe) on CI boxes.
+//!
+//! The EGL library (`libEGL.so.1`) is loaded at runtime, so that no EGL development package is required to build
+//! this crate.
+
+use khronos_egl as egl;
+use luminance::{
+  backend::FramebufferError,
+  context::Context,
+  dim::{Dim2, Size2},
+  framebuffer::{Back, Framebuffer},
+  render_slots::{DepthRenderSlot, RenderSlots},
+};
+use luminance_gl2::GL33;
+use std::{error, fmt, os::raw::c_void, ptr};
+
+/// `EGL_PLATFORM_SURFACELESS_MESA`, from the `EGL_MESA_platform_surfaceless` extension.
+const PLATFORM_SURFACELESS_MESA: egl::Enum = 0x31DD;
+
+/// Error that can be risen while creating a surface.
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum EglSurfaceError {
+  /// The EGL library cannot be loaded.
+  LoadError(Box<dyn error::Error>),
+
+  /// Initialization of the surface went wrong.
+  ///
+  /// This variant exposes an **EGL** error for further information about what went wrong.
+  InitError(egl::Error),
+
+  /// Error with the backend.
+  BackendError(String),
+}
+
+impl fmt::Display for EglSurfaceError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+    match self {
+      EglSurfaceError::LoadError(e) => write!(f, "cannot load EGL: {}", e),
+      EglSurfaceError::InitError(e) => write!(f, "initialization error: {}", e),
+      EglSurfaceError::BackendError(reason) => write!(f, "backend error: {}", reason),
+    }
+  }
+}
+
+impl From<egl::Error> for EglSurfaceError {
+  fn from(e: egl::Error) -> Self {
+    EglSurfaceError::InitError(e)
+  }
+}
+
+impl error::Error for EglSurfaceError {
+  fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+    match self {
+      EglSurfaceError::LoadError(e) => Some(e.as_ref()),
+      EglSurfaceError::InitError(e) => Some(e),
+      _ => None,
+    }
+  }
+}
+
+/// EGL objects backing a surface.
+///
+/// They are released when dropped, which must happen after the luminance context is dropped. The display is not
+/// terminated, as it is shared by every surface of the process (EGL doesn’t reference-count initializations).
+struct EglContext {
+  egl: egl::DynamicInstance<egl::EGL1_4>,
+  display: egl::Display,
+  surface: egl::Surface,
+  context: egl::Context,
+}
+
+impl fmt::Debug for EglContext {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    f.debug_struct("EglContext")
+      .field("display", &self.display)
+      .field("surface", &self.surface)
+      .field("context", &self.context)
+      .finish()
+  }
+}
+
+impl Drop for EglContext {
+  fn drop(&mut self) {
+    // errors cannot be reported here; everything is released on a best-effort basis
+    // another surface might be current on this thread
+    if self.egl.get_current_context() == Some(self.context) {
+      let _ = self.egl.make_current(self.display, None, None, None);
+    }
+
+    let _ = self.egl.destroy_surface(self.display, self.surface);
+    let _ = self.egl.destroy_context(self.display, self.context);
+  }
+}
+
+/// Headless EGL surface.
+///
+/// This type is a helper that exposes the [`Context`] to perform the rendering part. Its back buffer is an offscreen
+/// pbuffer of the size given at creation; see [`EglSurface::back_buffer`] and [`EglSurface::read_back_buffer`].
+#[derive(Debug)]
+pub struct EglSurface {
+  /// Wrapped luminance context.
+  pub ctx: Context<GL33>,
+
+  // only kept around to be released when dropped; declared after ctx so that it’s dropped after it
+  _egl: EglContext,
+
+  size: [u32; 2],
+}
+
+impl EglSurface {
+  /// Initialize EGL to provide a luminance context with an offscreen back buffer of the given size.
+  ///
+  /// The Mesa surfaceless platform is used when available; the default display is used otherwise.
+  pub fn new_gl33(size: [u32; 2]) -> Result<Self, EglSurfaceError> {
+    let egl = unsafe { egl::DynamicInstance::<egl::EGL1_4>::load_required() }
+      .map_err(|e| EglSurfaceError::LoadError(Box::new(e)))?;
+    let display = Self::initialize_display(&egl)?;
+
+    let config = egl
+      .choose_first_config(
+        display,
+        &[
+          egl::SURFACE_TYPE,
+          egl::PBUFFER_BIT,
+          egl::RENDERABLE_TYPE,
+          egl::OPENGL_BIT,
+          egl::RED_SIZE,
+          8,
+          egl::GREEN_SIZE,
+          8,
+          egl::BLUE_SIZE,
+          8,
+          egl::ALPHA_SIZE,
+          8,
+          egl::DEPTH_SIZE,
+          24,
+          egl::STENCIL_SIZE,
+          8,
+          egl::NONE,
+        ],
+      )?
+      .ok_or_else(|| EglSurfaceError::BackendError("no suitable EGL config".to_owned()))?;
+
+    egl.bind_api(egl::OPENGL_API)?;
+
+    let context = egl.create_context(
+      display,
+      config,
+      None,
+      &[
+        egl::CONTEXT_MAJOR_VERSION,
+        3,
+        egl::CONTEXT_MINOR_VERSION,
+        3,
+        egl::CONTEXT_OPENGL_PROFILE_MASK,
+        egl::CONTEXT_OPENGL_CORE_PROFILE_BIT,
+        egl::NONE,
+      ],
+    )?;
+
+    let surface = egl.create_pbuffer_surface(
+      display,
+      config,
+      &[
+        egl::WIDTH,
+        size[0] as egl::Int,
+        egl::HEIGHT,
+        size[1] as egl::Int,
+        egl::NONE,
+      ],
+    )?;
+
+    let egl = EglContext {
+      egl,
+      display,
+      surface,
+      context,
+    };
+
+    egl
+      .egl
+      .make_current(display, Some(surface), Some(surface), Some(context))?;
+
+    // init OpenGL
+    gl::load_with(|s| {
+      egl
+        .egl
+        .get_proc_address(s)
+        .map_or(ptr::null(), |f| f as *const c_void)
+    });
+
+    let ctx = Context::new(GL33::new)
+      .ok_or_else(|| EglSurfaceError::BackendError("unavailable OpenGL 3.3 state".to_owned()))?;
+    let surface = EglSurface {
+      ctx,
+      _egl: egl,
+      size,
+    };
+
+    Ok(surface)
+  }
+
+  /// Get an initialized display, preferring the Mesa surfaceless platform.
+  fn initialize_display(
+    egl: &egl::DynamicInstance<egl::EGL1_4>,
+  ) -> Result<egl::Display, EglSurfaceError> {
+    let surfaceless = egl
+      .upcast::<egl::EGL1_5>()
+      .and_then(|egl| unsafe {
+        egl
+          .get_platform_display(
+            PLATFORM_SURFACELESS_MESA,
+            egl::DEFAULT_DISPLAY,
+            &[egl::ATTRIB_NONE],
+          )
+          .ok()
+      })
+      .filter(|display| egl.initialize(*display).is_ok());
+
+    if let Some(display) = surfaceless {
+      return Ok(display);
+    }
+
+    let display = unsafe { egl.get_display(egl::DEFAULT_DISPLAY) }
+      .ok_or_else(|| EglSurfaceError::BackendError("no EGL display".to_owned()))?;
+    egl.initialize(display)?;
+
+    Ok(display)
+  }
+
+  /// Size of the back buffer, as `[width, height]`.
+  pub fn size(&self) -> [u32; 2] {
+    self.size
+  }
+
+  /// Get the back buffer, sized as the surface.
+  pub fn back_buffer<RS, DS>(
+    &mut self,
+  ) -> Result<Framebuffer<Dim2, Back<RS>, Back<DS>>, FramebufferError>
+  where
+    RS: RenderSlots,
+    DS: DepthRenderSlot,
+  {
+    let [width, height] = self.size;
+    self.ctx.back_buffer(Size2::new(width, height))
+  }
+
+  /// Read the color of the back buffer.
+  ///
+  /// Pixels are returned as RGBA 8-bit normalized texels, row by row, starting with the bottom row.
+  pub fn read_back_buffer(&mut self) -> Vec<u8> {
+    let [width, height] = self.size;
+    let mut texels = vec![0; width as usize * height as usize * 4];
+
+    unsafe {
+      gl::BindFramebuffer(gl::READ_FRAMEBUFFER, 0);
+      gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+      gl::ReadPixels(
+        0,
+        0,
+        width as _,
+        height as _,
+        gl::RGBA,
+        gl::UNSIGNED_BYTE,
+        texels.as_mut_ptr() as *mut c_void,
+      );
+    }
+
+    texels
+  }
+}
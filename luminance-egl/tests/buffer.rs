@@ -0,0 +1,253 @@
+#![allow(incomplete_features)]
+#![feature(adt_const_params)]
+#![feature(const_cmp)]
+
+use luminance::{
+  backend::BufferError,
+  buffer::Buffer,
+  context::Context,
+  dim::{Dim2, Size2},
+  framebuffer::Framebuffer,
+  namespace,
+  pipeline::PipelineState,
+  pixel::NormRGBA8UI,
+  primitive::Triangle,
+  render_state::RenderState,
+  shader::{InUseUniBuffer, Program, ProgramBuilder, Std140, Uni, UniBuffer},
+  texture::{Mipmaps, TextureSampling},
+  vertex_entity::{Usage, VertexEntity, VertexEntityBuilder, View},
+  vertex_storage::{Shared, Sharing},
+  RenderSlots, Std140, Uniforms, Vertex,
+};
+use luminance_egl::EglSurface;
+use luminance_gl2::GL33;
+use mint::{Vector2, Vector4};
+
+const VS: &str = "
+in vec2 pos;
+
+void main() {
+  gl_Position = vec4(pos, 0., 1.);
+}";
+
+const FS: &str = "
+uniform Tint {
+  vec4 color;
+} tint;
+
+out vec4 frag;
+
+void main() {
+  frag = tint.color;
+}";
+
+namespace! {
+  Namespace = { "pos" }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Vertex)]
+#[vertex(namespace = "Namespace")]
+struct Vertex {
+  pos: Vector2<f32>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, RenderSlots)]
+struct Slots {
+  frag: NormRGBA8UI,
+}
+
+#[derive(Debug, Std140)]
+struct Tint {
+  color: Vector4<f32>,
+}
+
+#[derive(Uniforms)]
+struct ShaderUniforms {
+  #[uniform(name = "Tint")]
+  tint: Uni<UniBuffer<Tint, Std140>>,
+}
+
+const RED: [u8; 4] = [255, 0, 0, 255];
+const BLACK: [u8; 4] = [0, 0, 0, 255];
+
+fn new_program(ctx: &mut Context<GL33>) -> Program<Vertex, (), Triangle, Slots, ShaderUniforms> {
+  ctx
+    .new_program(
+      ProgramBuilder::new()
+        .add_vertex_stage(VS)
+        .no_primitive_stage()
+        .add_shading_stage(FS),
+    )
+    .unwrap()
+}
+
+fn new_tint(ctx: &mut Context<GL33>, color: [f32; 4]) -> Buffer<TintStd140> {
+  let tint = Tint {
+    color: color.into(),
+  };
+  ctx.new_buffer(&[tint.into()], Usage::Static).unwrap()
+}
+
+/// Render the vertex entity with the tint, and return the bottom-right and top-left texels.
+fn render(
+  ctx: &mut Context<GL33>,
+  program: &Program<Vertex, (), Triangle, Slots, ShaderUniforms>,
+  entity: &VertexEntity<Vertex, Triangle, Sharing, (), (), u16>,
+  tint: InUseUniBuffer<Tint, Std140>,
+) -> [[u8; 4]; 2] {
+  let fb: Framebuffer<Dim2, Slots, ()> = ctx
+    .new_framebuffer(Size2::new(4, 4), Mipmaps::No, &TextureSampling::default())
+    .unwrap();
+
+  ctx
+    .with_framebuffer(&fb, &PipelineState::default(), |mut with_framebuffer| {
+      with_framebuffer.with_program(program, |mut with_program| {
+        with_program.update(|mut program, unis| program.set(&unis.tint, &tint))?;
+        with_program.with_render_state(&RenderState::default(), |mut with_render_state| {
+          with_render_state.render_vertex_entity(entity.view(..))
+        })
+      })
+    })
+    .unwrap();
+
+  let texels = ctx.read_texture(&fb.layers().frag).unwrap();
+  let texel = |x: usize, y: usize| {
+    let i = (y * 4 + x) * 4;
+    [texels[i], texels[i + 1], texels[i + 2], texels[i + 3]]
+  };
+  [texel(3, 0), texel(0, 3)]
+}
+
+#[test]
+fn update_and_read_buffer() {
+  let mut surface = EglSurface::new_gl33([8, 8]).expect("EGL surface");
+  let ctx = &mut surface.ctx;
+
+  let buffer = ctx.new_buffer(&[1u32, 2, 3, 4], Usage::Dynamic).unwrap();
+  assert_eq!(buffer.len(), 4);
+  assert_eq!(ctx.read_buffer(&buffer).unwrap(), [1, 2, 3, 4]);
+
+  ctx.update_buffer(&buffer, 2, &[30, 40]).unwrap();
+  assert_eq!(ctx.read_buffer(&buffer).unwrap(), [1, 2, 30, 40]);
+
+  // buffers cannot grow
+  assert!(matches!(
+    ctx.update_buffer(&buffer, 3, &[50, 60]),
+    Err(BufferError::OutOfRange {
+      start: 3,
+      end: 5,
+      len: 4
+    })
+  ));
+  assert!(matches!(
+    ctx.update_buffer(&buffer, usize::MAX, &[50]),
+    Err(BufferError::OutOfRange { .. })
+  ));
+  assert_eq!(ctx.read_buffer(&buffer).unwrap(), [1, 2, 30, 40]);
+}
+
+#[test]
+fn map_buffer() {
+  let mut surface = EglSurface::new_gl33([8, 8]).expect("EGL surface");
+  let ctx = &mut surface.ctx;
+
+  let mut buffer = ctx.new_buffer(&[1u32, 2, 3, 4], Usage::Dynamic).unwrap();
+
+  {
+    let mut slice = ctx.map_buffer(&mut buffer).unwrap();
+    assert_eq!(&*slice, [1, 2, 3, 4]);
+
+    for value in slice.iter_mut() {
+      *value *= 10;
+    }
+  }
+
+  // unmapped when the slice is dropped
+  assert_eq!(ctx.read_buffer(&buffer).unwrap(), [10, 20, 30, 40]);
+}
+
+#[test]
+fn render_shared_buffers() {
+  let mut surface = EglSurface::new_gl33([8, 8]).expect("EGL surface");
+  let ctx = &mut surface.ctx;
+
+  let program = new_program(ctx);
+  let tint = new_tint(ctx, [1., 0., 0., 1.]);
+
+  let vertices = ctx
+    .new_buffer(
+      &[[-1., -1.], [1., -1.], [1., 1.], [-1., 1.]].map(|pos| Vertex { pos: pos.into() }),
+      Usage::Static,
+    )
+    .unwrap();
+  // only the lower-right triangle, twice
+  let indices = ctx
+    .new_buffer(&[0u16, 1, 2, 0, 1, 2], Usage::Dynamic)
+    .unwrap();
+  let quad: VertexEntity<Vertex, Triangle, Sharing, (), (), u16> = ctx
+    .new_vertex_entity(
+      VertexEntityBuilder::new()
+        .add_vertices(Shared::new(&vertices))
+        .add_index_buffer(&indices),
+    )
+    .unwrap();
+
+  let in_use = ctx.use_buffer_as_uniform_buffer(&tint).unwrap();
+  assert_eq!(render(ctx, &program, &quad, in_use), [RED, BLACK]);
+
+  // the vertex entity reads its indices from the buffer
+  ctx.update_buffer(&indices, 3, &[0, 2, 3]).unwrap();
+
+  let in_use = ctx.use_buffer_as_uniform_buffer(&tint).unwrap();
+  assert_eq!(render(ctx, &program, &quad, in_use), [RED, RED]);
+
+  // the vertex entity keeps the buffers alive
+  drop(vertices);
+  drop(indices);
+
+  let in_use = ctx.use_buffer_as_uniform_buffer(&tint).unwrap();
+  assert_eq!(render(ctx, &program, &quad, in_use), [RED, RED]);
+}
+
+#[test]
+fn buffer_as_uniform_buffer() {
+  let mut surface = EglSurface::new_gl33([8, 8]).expect("EGL surface");
+  let ctx = &mut surface.ctx;
+
+  let program = new_program(ctx);
+
+  let vertices = ctx
+    .new_buffer(
+      &[[-1., -1.], [1., -1.], [1., 1.]].map(|pos| Vertex { pos: pos.into() }),
+      Usage::Static,
+    )
+    .unwrap();
+  let indices = ctx.new_buffer(&[0u16, 1, 2], Usage::Static).unwrap();
+  let triangle: VertexEntity<Vertex, Triangle, Sharing, (), (), u16> = ctx
+    .new_vertex_entity(
+      VertexEntityBuilder::new()
+        .add_vertices(Shared::new(&vertices))
+        .add_index_buffer(&indices),
+    )
+    .unwrap();
+
+  let mut tint = new_tint(ctx, [0., 0., 1., 1.]);
+  let in_use = ctx.use_buffer_as_uniform_buffer(&tint).unwrap();
+  assert_eq!(
+    render(ctx, &program, &triangle, in_use),
+    [[0, 0, 255, 255], BLACK]
+  );
+
+  // update the uniform block in place
+  ctx.map_buffer(&mut tint).unwrap()[0] = Tint {
+    color: [0., 1., 0., 1.].into(),
+  }
+  .into();
+
+  let in_use = ctx.use_buffer_as_uniform_buffer(&tint).unwrap();
+  assert_eq!(
+    render(ctx, &program, &triangle, in_use),
+    [[0, 255, 0, 255], BLACK]
+  );
+}
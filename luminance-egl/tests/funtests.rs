@@ -0,0 +1,577 @@
+//! Functional tests.
+//!
+//! Those are regression tests for issues that were found in the wild. They render offscreen on a headless OpenGL 3.3
+//! context and check the texels read back from the framebuffers.
+
+#![allow(incomplete_features)]
+#![feature(adt_const_params)]
+
+use luminance::{
+  backend::PipelineError,
+  context::Context,
+  dim::{Dim2, Size2},
+  framebuffer::Framebuffer,
+  namespace,
+  pipeline::PipelineState,
+  pixel::{NormRGBA8UI, RGB8UI},
+  primitive::{Triangle, TriangleFan},
+  render_state::RenderState,
+  scissor::Scissor,
+  shader::{Program, ProgramBuilder, Uni},
+  texture::{Mipmaps, TextureSampling},
+  vertex_entity::{VertexEntity, VertexEntityBuilder, View},
+  vertex_storage::{Interleaved, Interleaving},
+  RenderSlots, Uniforms, Vertex,
+};
+use luminance_egl::EglSurface;
+use luminance_gl2::GL33;
+use mint::{ColumnMatrix4, Vector4};
+
+const SIZE: u32 = 8;
+
+const CLEAR_COLOR: [f32; 4] = [0., 0., 0., 1.];
+const CLEAR_TEXEL: [u8; 4] = [0, 0, 0, 255];
+const TEXEL: [u8; 4] = [255, 128, 128, 255];
+
+// fullscreen quad generated from the vertex IDs; render it with 4 vertices as a triangle fan
+const QUAD_VS: &str = "
+const vec2[4] POSITIONS = vec2[](
+  vec2(-1., -1.),
+  vec2( 1., -1.),
+  vec2( 1.,  1.),
+  vec2(-1.,  1.)
+);
+
+void main() {
+  gl_Position = vec4(POSITIONS[gl_VertexID], 0., 1.);
+}";
+
+const POS_VS: &str = "
+in vec2 pos;
+
+void main() {
+  gl_Position = vec4(pos, 0., 1.);
+}";
+
+const FS: &str = "
+out vec4 frag;
+
+void main() {
+  frag = vec4(1., .5, .5, 1.);
+}";
+
+namespace! {
+  Namespace = { "pos" }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Vertex)]
+#[vertex(namespace = "Namespace")]
+struct Vertex {
+  pos: mint::Vector2<f32>,
+}
+
+impl Vertex {
+  const fn new(x: f32, y: f32) -> Self {
+    Self {
+      pos: mint::Vector2 { x, y },
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, RenderSlots)]
+struct Slots {
+  frag: NormRGBA8UI,
+}
+
+fn new_surface() -> EglSurface {
+  EglSurface::new_gl33([SIZE, SIZE]).expect("EGL surface")
+}
+
+fn new_framebuffer(ctx: &mut Context<GL33>) -> Framebuffer<Dim2, Slots, ()> {
+  ctx
+    .new_framebuffer(
+      Size2::new(SIZE, SIZE),
+      Mipmaps::No,
+      &TextureSampling::default(),
+    )
+    .unwrap()
+}
+
+/// Read the color layer of a framebuffer and return its texels as RGBA, starting with the bottom row.
+fn read_texels(ctx: &mut Context<GL33>, fb: &Framebuffer<Dim2, Slots, ()>) -> Vec<[u8; 4]> {
+  let texels = ctx.read_texture(&fb.layers().frag).unwrap();
+  assert_eq!(texels.len(), (SIZE * SIZE * 4) as usize);
+
+  texels
+    .chunks(4)
+    .map(|texel| [texel[0], texel[1], texel[2], texel[3]])
+    .collect()
+}
+
+fn texel_at(texels: &[[u8; 4]], x: u32, y: u32) -> [u8; 4] {
+  texels[(x + y * SIZE) as usize]
+}
+
+/// Scissored renders must only touch the pixels of the scissor region.
+#[test]
+fn scissor_test() {
+  let mut surface = new_surface();
+  let ctx = &mut surface.ctx;
+
+  let program: Program<(), (), TriangleFan, Slots, ()> = ctx
+    .new_program(
+      ProgramBuilder::new()
+        .add_vertex_stage(QUAD_VS)
+        .no_primitive_stage()
+        .add_shading_stage(FS),
+    )
+    .unwrap();
+  let quad: VertexEntity<(), TriangleFan, ()> =
+    ctx.new_vertex_entity(VertexEntityBuilder::new()).unwrap();
+  let fb = new_framebuffer(ctx);
+
+  let (w2, h2) = (SIZE / 2, SIZE / 2);
+  let scissor = Scissor::On {
+    x: w2 - w2 / 2,
+    y: h2 - h2 / 2,
+    width: w2,
+    height: h2,
+  };
+
+  ctx
+    .with_framebuffer(
+      &fb,
+      &PipelineState::default().set_clear_color(CLEAR_COLOR),
+      |mut with_framebuffer| {
+        with_framebuffer.with_program(&program, |mut with_program| {
+          with_program.with_render_state(
+            &RenderState::default().set_scissor(scissor),
+            |mut with_render_state| with_render_state.render_vertex_entity(quad.view(..4)),
+          )
+        })
+      },
+    )
+    .unwrap();
+
+  let texels = read_texels(ctx, &fb);
+  for y in 0..SIZE {
+    for x in 0..SIZE {
+      let inside = (2..6).contains(&x) && (2..6).contains(&y);
+      let expected = if inside { TEXEL } else { CLEAR_TEXEL };
+      assert_eq!(texel_at(&texels, x, y), expected, "texel ({}, {})", x, y);
+    }
+  }
+}
+
+/// Textures with rows that are not aligned on 4 bytes must be uploaded and read back correctly.
+#[test]
+fn pixel_array_encoding() {
+  let mut surface = new_surface();
+  let ctx = &mut surface.ctx;
+
+  let reserved = ctx
+    .reserve_texture::<Dim2, RGB8UI>(
+      Size2::new(100, 100),
+      Mipmaps::No,
+      &TextureSampling::default(),
+    )
+    .unwrap();
+  assert_eq!(ctx.read_texture(&reserved).unwrap().len(), 100 * 100 * 3);
+
+  // 3×3 RGB texels, so 9 bytes per row
+  let texels: Vec<u8> = (0..27).collect();
+  let texture = ctx
+    .new_texture::<Dim2, RGB8UI>(
+      Size2::new(3, 3),
+      Mipmaps::No,
+      &TextureSampling::default(),
+      &texels,
+    )
+    .unwrap();
+  assert_eq!(ctx.read_texture(&texture).unwrap(), texels);
+}
+
+/// <https://github.com/phaazon/luminance-rs/issues/360>
+///
+/// Creating a framebuffer after dropping another one must yield a usable framebuffer.
+#[test]
+fn manually_drop_framebuffer_360() {
+  let mut surface = new_surface();
+  let ctx = &mut surface.ctx;
+
+  let fb = new_framebuffer(ctx);
+  drop(fb);
+
+  // #360 occurs here after the drop
+  let fb = new_framebuffer(ctx);
+
+  ctx
+    .with_framebuffer(
+      &fb,
+      &PipelineState::default().set_clear_color([1., 0., 1., 1.]),
+      |_| Ok::<_, PipelineError>(()),
+    )
+    .unwrap();
+
+  let texels = read_texels(ctx, &fb);
+  assert!(texels.iter().all(|&texel| texel == [255, 0, 255, 255]));
+}
+
+/// <https://github.com/phaazon/luminance-rs/issues/483>
+///
+/// Mutating the indices of a vertex entity must neither corrupt them nor be ignored by the next render.
+#[test]
+fn indices_mut_corruption_483() {
+  let mut surface = new_surface();
+  let ctx = &mut surface.ctx;
+
+  let program: Program<Vertex, (), Triangle, Slots, ()> = ctx
+    .new_program(
+      ProgramBuilder::new()
+        .add_vertex_stage(POS_VS)
+        .no_primitive_stage()
+        .add_shading_stage(FS),
+    )
+    .unwrap();
+
+  let vertices = [
+    Vertex::new(-1., -1.),
+    Vertex::new(1., -1.),
+    Vertex::new(1., 1.),
+    Vertex::new(-1., 1.),
+  ];
+
+  // bottom-right half of the viewport
+  let mut entity: VertexEntity<Vertex, Triangle, Interleaving> = ctx
+    .new_vertex_entity(
+      VertexEntityBuilder::new()
+        .add_vertices(Interleaved::new().set_vertices(vertices))
+        .add_indices([0, 1, 2]),
+    )
+    .unwrap();
+
+  // switch to the top-left half of the viewport
+  entity.indices().copy_from_slice(&[0, 2, 3]);
+  ctx.update_indices(&mut entity).unwrap();
+  assert_eq!(entity.indices().as_slice(), [0, 2, 3]);
+
+  let fb = new_framebuffer(ctx);
+
+  ctx
+    .with_framebuffer(
+      &fb,
+      &PipelineState::default().set_clear_color(CLEAR_COLOR),
+      |mut with_framebuffer| {
+        with_framebuffer.with_program(&program, |mut with_program| {
+          with_program.with_render_state(&RenderState::default(), |mut with_render_state| {
+            with_render_state.render_vertex_entity(entity.view(..))
+          })
+        })
+      },
+    )
+    .unwrap();
+
+  let texels = read_texels(ctx, &fb);
+  assert_eq!(texel_at(&texels, 0, SIZE - 1), TEXEL);
+  assert_eq!(texel_at(&texels, SIZE - 1, 0), CLEAR_TEXEL);
+  assert_eq!(entity.indices().as_slice(), [0, 2, 3]);
+}
+
+#[derive(Uniforms)]
+struct TransformUniforms {
+  translation_mat: Uni<ColumnMatrix4<f32>>,
+  aspect_ratio: Uni<f32>,
+}
+
+/// Matrices and scalars must be flattened correctly when passed to uniforms.
+#[test]
+fn flatten_slice() {
+  let mut surface = new_surface();
+  let ctx = &mut surface.ctx;
+
+  let vs = "
+in vec2 pos;
+
+uniform mat4 translation_mat;
+uniform float aspect_ratio;
+
+void main() {
+  vec2 p = pos;
+  p.y *= aspect_ratio;
+  gl_Position = translation_mat * vec4(p, 0., 1.);
+}";
+
+  let program: Program<Vertex, (), TriangleFan, Slots, TransformUniforms> = ctx
+    .new_program(
+      ProgramBuilder::new()
+        .add_vertex_stage(vs)
+        .no_primitive_stage()
+        .add_shading_stage(FS),
+    )
+    .unwrap();
+
+  // left half of the viewport
+  let vertices = [
+    Vertex::new(-1., -1.),
+    Vertex::new(0., -1.),
+    Vertex::new(0., 1.),
+    Vertex::new(-1., 1.),
+  ];
+  let quad: VertexEntity<Vertex, TriangleFan, Interleaving> = ctx
+    .new_vertex_entity(
+      VertexEntityBuilder::new().add_vertices(Interleaved::new().set_vertices(vertices)),
+    )
+    .unwrap();
+  let fb = new_framebuffer(ctx);
+
+  // move the quad to the right half of the viewport
+  let translation_mat = ColumnMatrix4 {
+    x: Vector4 {
+      x: 1.,
+      y: 0.,
+      z: 0.,
+      w: 0.,
+    },
+    y: Vector4 {
+      x: 0.,
+      y: 1.,
+      z: 0.,
+      w: 0.,
+    },
+    z: Vector4 {
+      x: 0.,
+      y: 0.,
+      z: 1.,
+      w: 0.,
+    },
+    w: Vector4 {
+      x: 1.,
+      y: 0.,
+      z: 0.,
+      w: 1.,
+    },
+  };
+
+  ctx
+    .with_framebuffer(
+      &fb,
+      &PipelineState::default().set_clear_color(CLEAR_COLOR),
+      |mut with_framebuffer| {
+        with_framebuffer.with_program(&program, |mut with_program| {
+          with_program.update(|mut program, unis| {
+            program.set(&unis.translation_mat, &translation_mat)?;
+            program.set(&unis.aspect_ratio, &0.5)
+          })?;
+          with_program.with_render_state(&RenderState::default(), |mut with_render_state| {
+            with_render_state.render_vertex_entity(quad.view(..))
+          })
+        })
+      },
+    )
+    .unwrap();
+
+  let texels = read_texels(ctx, &fb);
+  for y in 0..SIZE {
+    for x in 0..SIZE {
+      let inside = x >= SIZE / 2 && (2..6).contains(&y);
+      let expected = if inside { TEXEL } else { CLEAR_TEXEL };
+      assert_eq!(texel_at(&texels, x, y), expected, "texel ({}, {})", x, y);
+    }
+  }
+}
+
+/// Vertex entities without any data are attributeless; rendering them without vertices must not render anything.
+#[test]
+fn vertex_entity_no_data() {
+  let mut surface = new_surface();
+  let ctx = &mut surface.ctx;
+
+  let program: Program<(), (), TriangleFan, Slots, ()> = ctx
+    .new_program(
+      ProgramBuilder::new()
+        .add_vertex_stage(QUAD_VS)
+        .no_primitive_stage()
+        .add_shading_stage(FS),
+    )
+    .unwrap();
+  let entity: VertexEntity<(), TriangleFan, ()> =
+    ctx.new_vertex_entity(VertexEntityBuilder::new()).unwrap();
+  assert_eq!(entity.vertex_count(), 0);
+  assert_eq!(entity.index_count(), 0);
+
+  let fb = new_framebuffer(ctx);
+
+  ctx
+    .with_framebuffer(
+      &fb,
+      &PipelineState::default().set_clear_color(CLEAR_COLOR),
+      |mut with_framebuffer| {
+        with_framebuffer.with_program(&program, |mut with_program| {
+          with_program.with_render_state(&RenderState::default(), |mut with_render_state| {
+            with_render_state.render_vertex_entity(entity.view(..))
+          })
+        })
+      },
+    )
+    .unwrap();
+
+  let texels = read_texels(ctx, &fb);
+  assert!(texels.iter().all(|&texel| texel == CLEAR_TEXEL));
+}
+
+#[cfg(feature = "shader-f64")]
+#[derive(Uniforms)]
+struct F64Uniforms {
+  color: Uni<mint::Vector3<f64>>,
+}
+
+/// Double-precision uniforms must be uploaded as such.
+#[cfg(feature = "shader-f64")]
+#[test]
+fn gl33_f64_uniform() {
+  let mut surface = new_surface();
+  let ctx = &mut surface.ctx;
+
+  let fs = "
+out vec4 frag;
+
+uniform dvec3 color;
+
+void main() {
+  frag = vec4(vec3(color), 1.);
+}";
+
+  let program: Program<(), (), TriangleFan, Slots, F64Uniforms> = ctx
+    .new_program(
+      ProgramBuilder::new()
+        .add_vertex_stage(QUAD_VS)
+        .no_primitive_stage()
+        .add_shading_stage(fs),
+    )
+    .unwrap();
+  let quad: VertexEntity<(), TriangleFan, ()> =
+    ctx.new_vertex_entity(VertexEntityBuilder::new()).unwrap();
+  let fb = new_framebuffer(ctx);
+
+  let color = mint::Vector3 {
+    x: 1.,
+    y: 0.5,
+    z: 0.5,
+  };
+
+  ctx
+    .with_framebuffer(&fb, &PipelineState::default(), |mut with_framebuffer| {
+      with_framebuffer.with_program(&program, |mut with_program| {
+        with_program.update(|mut program, unis| program.set(&unis.color, &color))?;
+        with_program.with_render_state(&RenderState::default(), |mut with_render_state| {
+          with_render_state.render_vertex_entity(quad.view(..4))
+        })
+      })
+    })
+    .unwrap();
+
+  let texels = read_texels(ctx, &fb);
+  assert!(texels.iter().all(|&texel| texel == TEXEL));
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Vertex)]
+#[vertex(namespace = "Namespace")]
+struct F64Vertex {
+  pos: [f64; 2],
+}
+
+#[cfg(feature = "shader-f64")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Vertex)]
+#[vertex(namespace = "Namespace")]
+struct DoubleVertex {
+  #[vertex(double)]
+  pos: [f64; 2],
+}
+
+/// Render a quad covering the framebuffer with `program`, and check that all the texels are written.
+fn render_f64_quad<V>(
+  ctx: &mut Context<GL33>,
+  program: &Program<V, (), TriangleFan, Slots, ()>,
+  pos: impl Fn([f64; 2]) -> V,
+) where
+  V: luminance::vertex::Vertex + luminance::vertex::CompatibleVertex<V>,
+{
+  let vertices = [[-1., -1.], [1., -1.], [1., 1.], [-1., 1.]].map(pos);
+  let quad: VertexEntity<V, TriangleFan, Interleaving> = ctx
+    .new_vertex_entity(
+      VertexEntityBuilder::new().add_vertices(Interleaved::new().set_vertices(vertices)),
+    )
+    .unwrap();
+  let fb = new_framebuffer(ctx);
+
+  ctx
+    .with_framebuffer(&fb, &PipelineState::default(), |mut with_framebuffer| {
+      with_framebuffer.with_program(program, |mut with_program| {
+        with_program.with_render_state(&RenderState::default(), |mut with_render_state| {
+          with_render_state.render_vertex_entity(quad.view(..))
+        })
+      })
+    })
+    .unwrap();
+
+  let texels = read_texels(ctx, &fb);
+  assert!(texels.iter().all(|&texel| texel == TEXEL));
+}
+
+/// Double-precision vertex attributes are converted to single-precision by default.
+#[test]
+fn gl33_f64_vertex_attrib() {
+  let mut surface = new_surface();
+  let ctx = &mut surface.ctx;
+
+  let program: Program<F64Vertex, (), TriangleFan, Slots, ()> = ctx
+    .new_program(
+      ProgramBuilder::new()
+        .add_vertex_stage(POS_VS)
+        .no_primitive_stage()
+        .add_shading_stage(FS),
+    )
+    .unwrap();
+  render_f64_quad(ctx, &program, |pos| F64Vertex { pos });
+}
+
+/// Double-precision vertex attributes exposed as such must be fed to double-precision inputs.
+#[cfg(feature = "shader-f64")]
+#[test]
+fn gl33_double_vertex_attrib() {
+  let mut surface = new_surface();
+  let ctx = &mut surface.ctx;
+
+  let vs = "
+in dvec2 pos;
+
+void main() {
+  gl_Position = vec4(vec2(pos), 0., 1.);
+}";
+
+  let program: Program<DoubleVertex, (), TriangleFan, Slots, ()> = ctx
+    .new_program(
+      ProgramBuilder::new()
+        .add_vertex_stage(vs)
+        .no_primitive_stage()
+        .add_shading_stage(FS),
+    )
+    .unwrap();
+  render_f64_quad(ctx, &program, |pos| DoubleVertex { pos });
+
+  // single-precision inputs cannot be fed with double-precision attributes
+  let program: Result<Program<DoubleVertex, (), TriangleFan, Slots, ()>, _> = ctx.new_program(
+    ProgramBuilder::new()
+      .add_vertex_stage(POS_VS)
+      .no_primitive_stage()
+      .add_shading_stage(FS),
+  );
+  assert!(program
+    .err()
+    .unwrap()
+    .to_string()
+    .contains("disagree on being double-precision"));
+}
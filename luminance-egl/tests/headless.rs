@@ -0,0 +1,54 @@
+use luminance::pipeline::PipelineState;
+use luminance_egl::EglSurface;
+use std::{sync::mpsc, thread};
+
+#[test]
+fn clear_back_buffer() {
+  let mut surface = EglSurface::new_gl33([4, 2]).expect("EGL surface");
+  let back_buffer = surface.back_buffer::<(), ()>().unwrap();
+
+  surface
+    .ctx
+    .with_framebuffer(
+      &back_buffer,
+      &PipelineState::default().set_clear_color([1., 0., 1., 1.]),
+      |_| Ok::<_, luminance::backend::PipelineError>(()),
+    )
+    .unwrap();
+
+  let texels = surface.read_back_buffer();
+  assert_eq!(texels.len(), 4 * 2 * 4);
+  assert!(texels.chunks(4).all(|texel| texel == [255, 0, 255, 255]));
+}
+
+/// Dropping a surface must not release the EGL display shared with the surfaces of other threads.
+#[test]
+fn drop_surface() {
+  let (created_tx, created_rx) = mpsc::channel();
+  let (dropped_tx, dropped_rx) = mpsc::channel();
+
+  let other = thread::spawn(move || {
+    let surface = EglSurface::new_gl33([4, 2]).expect("EGL surface");
+    created_tx.send(()).unwrap();
+    dropped_rx.recv().unwrap();
+    drop(surface);
+  });
+
+  let mut surface = EglSurface::new_gl33([4, 2]).expect("EGL surface");
+  created_rx.recv().unwrap();
+  dropped_tx.send(()).unwrap();
+  other.join().unwrap();
+
+  let back_buffer = surface.back_buffer::<(), ()>().unwrap();
+  surface
+    .ctx
+    .with_framebuffer(
+      &back_buffer,
+      &PipelineState::default().set_clear_color([0., 1., 0., 1.]),
+      |_| Ok::<_, luminance::backend::PipelineError>(()),
+    )
+    .unwrap();
+
+  let texels = surface.read_back_buffer();
+  assert!(texels.chunks(4).all(|texel| texel == [0, 255, 0, 255]));
+}
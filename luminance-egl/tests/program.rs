@@ -0,0 +1,600 @@
+#![allow(incomplete_features)]
+#![feature(adt_const_params)]
+#![feature(const_cmp)]
+
+use luminance::{
+  backend::ShaderError,
+  dim::Dim,
+  namespace,
+  pixel::{NormRGBA8UI, Type},
+  primitive::Triangle,
+  shader::{
+    diagnostic::{Severity, Stage},
+    reflection::{ActiveAttrib, ActiveBlockMember, ActiveUniform},
+    Program, ProgramBuilder, Std140, Uni, UniBuffer, UniDim, UniMatDim, UniType,
+  },
+  RenderSlots, Std140, Uniforms, Vertex,
+};
+use luminance_egl::EglSurface;
+use mint::{ColumnMatrix4, Vector2, Vector3, Vector4};
+
+const VS: &str = "
+in vec2 pos;
+in vec3 color;
+in vec4 unused;
+
+uniform mat4 model;
+
+out vec3 v_color;
+
+void main() {
+  gl_Position = model * vec4(pos, 0., 1.);
+  v_color = color;
+}";
+
+const FS: &str = "
+in vec3 v_color;
+
+uniform float weights[3];
+uniform sampler2D tex;
+
+uniform Material {
+  vec2 scale;
+  float gain;
+  vec4 tint;
+};
+
+out vec4 frag;
+
+void main() {
+  vec4 texel = texture(tex, v_color.xy * scale);
+  frag = (texel + tint) * gain * (weights[0] + weights[1] + weights[2]);
+}";
+
+namespace! {
+  Namespace = { "pos", "color" }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Vertex)]
+#[vertex(namespace = "Namespace")]
+struct Vertex {
+  pos: Vector2<f32>,
+  color: Vector3<f32>,
+}
+
+// integral positions are exposed as ivec2, not vec2
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Vertex)]
+#[vertex(namespace = "Namespace")]
+struct IntVertex {
+  pos: Vector2<i32>,
+  color: Vector3<f32>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, RenderSlots)]
+struct Slots {
+  frag: NormRGBA8UI,
+}
+
+#[test]
+fn reflect_program() {
+  let mut surface = EglSurface::new_gl33([8, 8]).expect("EGL surface");
+  let ctx = &mut surface.ctx;
+
+  let program: Program<Vertex, (), Triangle, Slots, ()> = ctx
+    .new_program(
+      ProgramBuilder::new()
+        .add_vertex_stage(VS)
+        .no_primitive_stage()
+        .add_shading_stage(FS),
+    )
+    .unwrap();
+
+  let reflection = ctx.reflect_program(&program).unwrap();
+
+  // unused inputs are optimized out
+  assert_eq!(
+    reflection.attributes,
+    [
+      ActiveAttrib {
+        name: "color".to_owned(),
+        ty: Some(UniType::Floating(UniDim::Dim3)),
+        glsl_type: "vec3".to_owned(),
+        len: 1,
+        array: false,
+      },
+      ActiveAttrib {
+        name: "pos".to_owned(),
+        ty: Some(UniType::Floating(UniDim::Dim2)),
+        glsl_type: "vec2".to_owned(),
+        len: 1,
+        array: false,
+      },
+    ]
+  );
+
+  assert_eq!(
+    reflection.uniforms,
+    [
+      ActiveUniform {
+        name: "model".to_owned(),
+        ty: Some(UniType::Matrix(UniMatDim::Mat44)),
+        glsl_type: "mat4".to_owned(),
+        len: 1,
+        array: false,
+      },
+      ActiveUniform {
+        name: "tex".to_owned(),
+        ty: Some(UniType::Sampler(Type::Floating, Dim::Dim2)),
+        glsl_type: "sampler2D".to_owned(),
+        len: 1,
+        array: false,
+      },
+      ActiveUniform {
+        name: "weights".to_owned(),
+        ty: Some(UniType::Floating(UniDim::Dim1)),
+        glsl_type: "float".to_owned(),
+        len: 3,
+        array: true,
+      },
+    ]
+  );
+
+  // blocks are laid out with std140
+  assert_eq!(reflection.uniform_blocks.len(), 1);
+  let material = reflection.uniform_block("Material").unwrap();
+  assert_eq!(material.size, 32);
+  assert_eq!(
+    material.members,
+    [
+      ActiveBlockMember {
+        name: "scale".to_owned(),
+        ty: Some(UniType::Floating(UniDim::Dim2)),
+        glsl_type: "vec2".to_owned(),
+        len: 1,
+        array: false,
+        offset: 0,
+      },
+      ActiveBlockMember {
+        name: "gain".to_owned(),
+        ty: Some(UniType::Floating(UniDim::Dim1)),
+        glsl_type: "float".to_owned(),
+        len: 1,
+        array: false,
+        offset: 8,
+      },
+      ActiveBlockMember {
+        name: "tint".to_owned(),
+        ty: Some(UniType::Floating(UniDim::Dim4)),
+        glsl_type: "vec4".to_owned(),
+        len: 1,
+        array: false,
+        offset: 16,
+      },
+    ]
+  );
+}
+
+const TINT_VS: &str = "
+in vec2 pos;
+
+uniform vec3 tint;
+
+out vec3 v_color;
+
+void main() {
+  gl_Position = vec4(pos, 0., 1.);
+  v_color = tint;
+}";
+
+const TINT_FS: &str = "
+in vec3 v_color;
+
+out vec4 frag;
+
+void main() {
+  frag = vec4(v_color, 1.);
+}";
+
+// mismatches are reported on the field, not on the uniform it’s bound to
+#[derive(Uniforms)]
+struct ScalarTint {
+  #[allow(dead_code)]
+  #[uniform(name = "tint")]
+  color: Uni<f32>,
+}
+
+#[derive(Uniforms)]
+struct VectorTint {
+  #[allow(dead_code)]
+  tint: Uni<Vector3<f32>>,
+}
+
+#[test]
+fn uniform_type_mismatch() {
+  let mut surface = EglSurface::new_gl33([8, 8]).expect("EGL surface");
+  let ctx = &mut surface.ctx;
+
+  let err = ctx
+    .new_program::<Vertex, (), Triangle, Slots, ScalarTint, ()>(
+      ProgramBuilder::new()
+        .add_vertex_stage(TINT_VS)
+        .no_primitive_stage()
+        .add_shading_stage(TINT_FS),
+    )
+    .err()
+    .expect("mismatching uniform");
+
+  match err {
+    ShaderError::UniTypeMismatch {
+      ref field,
+      ref rust_type,
+      ref glsl_type,
+    } => {
+      assert_eq!(field, "color");
+      assert_eq!(rust_type, "f32");
+      assert_eq!(glsl_type, "vec3");
+    }
+
+    err => panic!("unexpected error: {}", err),
+  }
+
+  assert_eq!(
+    err.to_string(),
+    "uniform field \"color\" is declared as f32 in Rust but as vec3 in the program"
+  );
+
+  assert!(ctx
+    .new_program::<Vertex, (), Triangle, Slots, VectorTint, ()>(
+      ProgramBuilder::new()
+        .add_vertex_stage(TINT_VS)
+        .no_primitive_stage()
+        .add_shading_stage(TINT_FS),
+    )
+    .is_ok());
+}
+
+const GAIN_VS: &str = "
+in vec2 pos;
+
+uniform float gain;
+uniform float weights[2];
+
+out vec3 v_color;
+
+void main() {
+  gl_Position = vec4(pos, 0., 1.);
+  v_color = vec3(gain * (weights[0] + weights[1]));
+}";
+
+#[derive(Uniforms)]
+struct Gains {
+  #[allow(dead_code)]
+  gain: Uni<f32>,
+  #[allow(dead_code)]
+  weights: Uni<[f32; 2]>,
+}
+
+#[derive(Uniforms)]
+struct ArrayGain {
+  #[allow(dead_code)]
+  gain: Uni<[f32; 16]>,
+  #[allow(dead_code)]
+  weights: Uni<[f32; 2]>,
+}
+
+#[derive(Uniforms)]
+struct ScalarWeights {
+  #[allow(dead_code)]
+  gain: Uni<f32>,
+  #[allow(dead_code)]
+  weights: Uni<f32>,
+}
+
+/// Arrays and non-array uniforms of the same type don’t match.
+#[test]
+fn uniform_array_mismatch() {
+  let mut surface = EglSurface::new_gl33([8, 8]).expect("EGL surface");
+  let ctx = &mut surface.ctx;
+
+  assert!(ctx
+    .new_program::<Vertex, (), Triangle, Slots, Gains, ()>(
+      ProgramBuilder::new()
+        .add_vertex_stage(GAIN_VS)
+        .no_primitive_stage()
+        .add_shading_stage(TINT_FS),
+    )
+    .is_ok());
+
+  let err = ctx
+    .new_program::<Vertex, (), Triangle, Slots, ArrayGain, ()>(
+      ProgramBuilder::new()
+        .add_vertex_stage(GAIN_VS)
+        .no_primitive_stage()
+        .add_shading_stage(TINT_FS),
+    )
+    .err()
+    .expect("array bound to a scalar");
+  assert_eq!(
+    err.to_string(),
+    "uniform field \"gain\" is declared as [f32; 16] in Rust but as float in the program"
+  );
+
+  let err = ctx
+    .new_program::<Vertex, (), Triangle, Slots, ScalarWeights, ()>(
+      ProgramBuilder::new()
+        .add_vertex_stage(GAIN_VS)
+        .no_primitive_stage()
+        .add_shading_stage(TINT_FS),
+    )
+    .err()
+    .expect("scalar bound to an array");
+  assert_eq!(
+    err.to_string(),
+    "uniform field \"weights\" is declared as f32 in Rust but as float[2] in the program"
+  );
+}
+
+const BLOCK_VS: &str = "
+in vec2 pos;
+
+uniform Tint {
+  vec4 color;
+  float gain;
+};
+
+out vec3 v_color;
+
+void main() {
+  gl_Position = vec4(pos, 0., 1.);
+  v_color = color.rgb * gain;
+}";
+
+#[derive(Debug, Std140)]
+struct Tint {
+  color: Vector4<f32>,
+  gain: f32,
+}
+
+#[derive(Debug, Std140)]
+struct Color {
+  color: Vector4<f32>,
+}
+
+#[derive(Uniforms)]
+struct TintBlock {
+  #[allow(dead_code)]
+  #[uniform(name = "Tint")]
+  tint: Uni<UniBuffer<Tint, Std140>>,
+}
+
+#[derive(Uniforms)]
+struct ColorBlock {
+  #[allow(dead_code)]
+  #[uniform(name = "Tint")]
+  tint: Uni<UniBuffer<Color, Std140>>,
+}
+
+/// Uniform blocks must have the size of the aligned Rust type.
+#[test]
+fn uniform_block_size_mismatch() {
+  let mut surface = EglSurface::new_gl33([8, 8]).expect("EGL surface");
+  let ctx = &mut surface.ctx;
+
+  assert!(ctx
+    .new_program::<Vertex, (), Triangle, Slots, TintBlock, ()>(
+      ProgramBuilder::new()
+        .add_vertex_stage(BLOCK_VS)
+        .no_primitive_stage()
+        .add_shading_stage(TINT_FS),
+    )
+    .is_ok());
+
+  let err = ctx
+    .new_program::<Vertex, (), Triangle, Slots, ColorBlock, ()>(
+      ProgramBuilder::new()
+        .add_vertex_stage(BLOCK_VS)
+        .no_primitive_stage()
+        .add_shading_stage(TINT_FS),
+    )
+    .err()
+    .expect("mismatching uniform block");
+  assert_eq!(
+    err.to_string(),
+    "uniform field \"tint\" is declared as uniform block of 16 bytes in Rust but as uniform block of 32 bytes in \
+     the program"
+  );
+}
+
+#[test]
+fn vertex_attrib_type_mismatch() {
+  let mut surface = EglSurface::new_gl33([8, 8]).expect("EGL surface");
+  let ctx = &mut surface.ctx;
+
+  let err = ctx
+    .new_program::<IntVertex, (), Triangle, Slots, VectorTint, ()>(
+      ProgramBuilder::new()
+        .add_vertex_stage(TINT_VS)
+        .no_primitive_stage()
+        .add_shading_stage(TINT_FS),
+    )
+    .err()
+    .expect("mismatching vertex attribute");
+
+  match err {
+    ShaderError::VertexAttribTypeMismatch {
+      ref name,
+      ref rust_type,
+      ref glsl_type,
+    } => {
+      assert_eq!(name, "pos");
+      assert_eq!(rust_type, "[i32; 2]");
+      assert_eq!(glsl_type, "vec2");
+    }
+
+    err => panic!("unexpected error: {}", err),
+  }
+}
+
+const MATRIX_VS: &str = "
+in vec2 pos;
+
+uniform mat3x4 transform;
+
+out vec3 v_color;
+
+void main() {
+  gl_Position = transform * vec3(pos, 1.);
+  v_color = vec3(1.);
+}";
+
+const MATRIX_INPUT_VS: &str = "
+in vec2 pos;
+in mat2x3 color;
+
+out vec3 v_color;
+
+void main() {
+  gl_Position = vec4(pos, 0., 1.);
+  v_color = color[0] + color[1];
+}";
+
+#[derive(Uniforms)]
+struct Transform {
+  #[allow(dead_code)]
+  transform: Uni<ColumnMatrix4<f32>>,
+}
+
+/// Uniforms and vertex inputs of types that luminance cannot represent, such as non-square matrices, never match.
+#[test]
+fn non_square_matrix_mismatch() {
+  let mut surface = EglSurface::new_gl33([8, 8]).expect("EGL surface");
+  let ctx = &mut surface.ctx;
+
+  let err = ctx
+    .new_program::<Vertex, (), Triangle, Slots, Transform, ()>(
+      ProgramBuilder::new()
+        .add_vertex_stage(MATRIX_VS)
+        .no_primitive_stage()
+        .add_shading_stage(TINT_FS),
+    )
+    .err()
+    .expect("non-square matrix uniform");
+  assert_eq!(
+    err.to_string(),
+    "uniform field \"transform\" is declared as [[f32; 4]; 4] in Rust but as mat3x4 in the program"
+  );
+
+  let err = ctx
+    .new_program::<Vertex, (), Triangle, Slots, (), ()>(
+      ProgramBuilder::new()
+        .add_vertex_stage(MATRIX_INPUT_VS)
+        .no_primitive_stage()
+        .add_shading_stage(TINT_FS),
+    )
+    .err()
+    .expect("non-square matrix vertex input");
+
+  match err {
+    ShaderError::VertexAttribTypeMismatch {
+      ref name,
+      ref glsl_type,
+      ..
+    } => {
+      assert_eq!(name, "color");
+      assert_eq!(glsl_type, "mat2x3");
+    }
+
+    err => panic!("unexpected error: {}", err),
+  }
+}
+
+const BROKEN_FS: &str = "
+in vec3 v_color;
+
+out vec4 frag;
+
+void main() {
+  frag = vec4(v_color, undeclared);
+}";
+
+/// Compilation errors are located in the code of the stage, not in the code prepended to it by the backend.
+#[test]
+fn compilation_diagnostics() {
+  let mut surface = EglSurface::new_gl33([8, 8]).expect("EGL surface");
+  let ctx = &mut surface.ctx;
+
+  let err = ctx
+    .new_program::<Vertex, (), Triangle, Slots, (), ()>(
+      ProgramBuilder::new()
+        .add_vertex_stage(TINT_VS)
+        .no_primitive_stage()
+        .add_shading_stage(BROKEN_FS),
+    )
+    .err()
+    .expect("compilation error");
+
+  match err {
+    ShaderError::Compilation {
+      stage,
+      ref diagnostics,
+    } => {
+      assert_eq!(stage, Stage::Shading);
+
+      let diagnostic = diagnostics
+        .iter()
+        .find(|diagnostic| diagnostic.severity == Severity::Error)
+        .expect("error diagnostic");
+      assert_eq!(diagnostic.stage, Some(Stage::Shading));
+      assert_eq!(diagnostic.line, Some(7));
+      assert_eq!(
+        diagnostic.source_line.as_deref(),
+        Some("  frag = vec4(v_color, undeclared);")
+      );
+      assert!(diagnostic.message.contains("undeclared"));
+    }
+
+    err => panic!("unexpected error: {}", err),
+  }
+
+  assert!(err
+    .to_string()
+    .contains("\n  7 |   frag = vec4(v_color, undeclared);"));
+}
+
+// the vertex stage outputs a vec3
+const MISMATCHING_FS: &str = "
+in vec4 v_color;
+
+out vec4 frag;
+
+void main() {
+  frag = v_color;
+}";
+
+#[test]
+fn link_diagnostics() {
+  let mut surface = EglSurface::new_gl33([8, 8]).expect("EGL surface");
+  let ctx = &mut surface.ctx;
+
+  let err = ctx
+    .new_program::<Vertex, (), Triangle, Slots, (), ()>(
+      ProgramBuilder::new()
+        .add_vertex_stage(TINT_VS)
+        .no_primitive_stage()
+        .add_shading_stage(MISMATCHING_FS),
+    )
+    .err()
+    .expect("link error");
+
+  match err {
+    ShaderError::Link { ref diagnostics } => {
+      assert!(diagnostics.iter().any(|diagnostic| {
+        diagnostic.severity == Severity::Error && diagnostic.message.contains("v_color")
+      }));
+    }
+
+    err => panic!("unexpected error: {}", err),
+  }
+}
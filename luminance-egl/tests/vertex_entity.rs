@@ -0,0 +1,1115 @@
+#![allow(incomplete_features)]
+#![feature(adt_const_params)]
+
+use luminance::{
+  context::Context,
+  dim::{Dim2, Size2},
+  framebuffer::Framebuffer,
+  indirect::{DrawArraysCommand, DrawCommand, IndirectBuffer, IndirectCommand},
+  namespace,
+  pipeline::PipelineState,
+  pixel::NormRGBA8UI,
+  primitive::{self, Patch, Triangle, TriangleAdjacency, TriangleStrip},
+  render_state::RenderState,
+  shader::{Program, ProgramBuilder},
+  texture::{Mipmaps, TextureSampling},
+  vertex::Int2_10_10_10Rev,
+  vertex_entity::{Index, StreamingStrategy, Usage, VertexEntity, VertexEntityBuilder, View},
+  vertex_storage::{Deinterleaved, Deinterleaving, Interleaved, Interleaving, VertexStorageFamily},
+  RenderSlots, Vertex,
+};
+use luminance_egl::EglSurface;
+use luminance_gl2::GL33;
+use mint::{ColumnMatrix4, Vector2, Vector3};
+
+const VS: &str = "
+in vec2 pos;
+in vec3 color;
+
+out vec3 v_color;
+
+void main() {
+  gl_Position = vec4(pos, 0., 1.);
+  v_color = color;
+}";
+
+const FS: &str = "
+in vec3 v_color;
+
+out vec4 frag;
+
+void main() {
+  frag = vec4(v_color, 1.);
+}";
+
+const INSTANCED_VS: &str = "
+in vec2 pos;
+in vec3 color;
+in mat4 model;
+
+out vec3 v_color;
+
+void main() {
+  gl_Position = model * vec4(pos, 0., 1.);
+  v_color = color;
+}";
+
+// green triangles if exactly one of their edges is shared with another triangle, red otherwise
+const ADJACENCY_GS: &str = "
+layout (triangles_adjacency) in;
+layout (triangle_strip, max_vertices = 3) out;
+
+out vec3 v_color;
+
+void main() {
+  int neighbors = 0;
+
+  // a border edge is adjacent to the opposite vertex of its own triangle
+  for (int i = 0; i < 3; ++i) {
+    if (gl_in[2 * i + 1].gl_Position != gl_in[(2 * i + 4) % 6].gl_Position) {
+      ++neighbors;
+    }
+  }
+
+  for (int i = 0; i < 6; i += 2) {
+    gl_Position = gl_in[i].gl_Position;
+    v_color = neighbors == 1 ? vec3(0., 1., 0.) : vec3(1., 0., 0.);
+    EmitVertex();
+  }
+
+  EndPrimitive();
+}";
+
+// quads subdivided four times in each direction
+const TESS_CONTROL: &str = "
+layout (vertices = 4) out;
+
+in vec3 v_color[];
+out vec3 tc_color[];
+
+void main() {
+  gl_out[gl_InvocationID].gl_Position = gl_in[gl_InvocationID].gl_Position;
+  tc_color[gl_InvocationID] = v_color[gl_InvocationID];
+
+  gl_TessLevelOuter = float[4](4., 4., 4., 4.);
+  gl_TessLevelInner = float[2](4., 4.);
+}";
+
+// bilinear interpolation of the corners of the quads, given counter-clockwise
+const TESS_EVAL: &str = "
+layout (quads) in;
+
+in vec3 tc_color[];
+out vec3 v_color;
+
+void main() {
+  vec2 uv = gl_TessCoord.xy;
+  vec4 bottom = mix(gl_in[0].gl_Position, gl_in[1].gl_Position, uv.x);
+  vec4 top = mix(gl_in[3].gl_Position, gl_in[2].gl_Position, uv.x);
+  gl_Position = mix(bottom, top, uv.y);
+  v_color = mix(mix(tc_color[0], tc_color[1], uv.x), mix(tc_color[3], tc_color[2], uv.x), uv.y);
+}";
+
+namespace! {
+  Namespace = { "pos", "color", "model": 4 }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Vertex)]
+#[vertex(namespace = "Namespace")]
+struct Vertex {
+  pos: Vector2<f32>,
+  color: Vector3<f32>,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Vertex)]
+#[vertex(namespace = "Namespace")]
+struct Transform {
+  model: ColumnMatrix4<f32>,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Vertex)]
+#[vertex(namespace = "Namespace")]
+struct Compressed {
+  pos: [half::f16; 2],
+  #[vertex(normalized = true)]
+  color: Int2_10_10_10Rev,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, RenderSlots)]
+struct Slots {
+  frag: NormRGBA8UI,
+}
+
+/// Two triangles per half of the viewport; the left half first.
+fn positions() -> Vec<Vector2<f32>> {
+  [(-1., 0.), (0., 1.)]
+    .into_iter()
+    .flat_map(|(x0, x1)| {
+      [
+        [x0, -1.],
+        [x1, -1.],
+        [x1, 1.],
+        [x0, -1.],
+        [x1, 1.],
+        [x0, 1.],
+      ]
+    })
+    .map(Vector2::from)
+    .collect()
+}
+
+fn colors(left: [f32; 3], right: [f32; 3]) -> Vec<Vector3<f32>> {
+  [left; 6]
+    .into_iter()
+    .chain([right; 6])
+    .map(Vector3::from)
+    .collect()
+}
+
+#[test]
+fn update_deinterleaved_vertices_range() {
+  let mut surface = EglSurface::new_gl33([8, 8]).expect("EGL surface");
+  let ctx = &mut surface.ctx;
+
+  let program: Program<Vertex, (), Triangle, Slots, ()> = ctx
+    .new_program(
+      ProgramBuilder::new()
+        .add_vertex_stage(VS)
+        .no_primitive_stage()
+        .add_shading_stage(FS),
+    )
+    .unwrap();
+
+  let red = [1., 0., 0.];
+  let green = [0., 1., 0.];
+  let blue = [0., 0., 1.];
+
+  let mut entity: VertexEntity<Vertex, Triangle, Deinterleaving> = ctx
+    .new_vertex_entity(
+      VertexEntityBuilder::new().add_vertices(
+        Deinterleaved::new()
+          .set_components::<"pos">(positions())
+          .set_components::<"color">(colors(red, red)),
+      ),
+    )
+    .unwrap();
+
+  // change all the colors, but only upload the ones of the right half
+  *entity.vertices() = Deinterleaved::new()
+    .set_components::<"pos">(positions())
+    .set_components::<"color">(colors(blue, green));
+  ctx.update_vertices_range(&mut entity, 6..).unwrap();
+
+  let fb: Framebuffer<Dim2, Slots, ()> = ctx
+    .new_framebuffer(Size2::new(8, 8), Mipmaps::No, &TextureSampling::default())
+    .unwrap();
+
+  ctx
+    .with_framebuffer(&fb, &PipelineState::default(), |mut with_framebuffer| {
+      with_framebuffer.with_program(&program, |mut with_program| {
+        with_program.with_render_state(&RenderState::default(), |mut with_render_state| {
+          with_render_state.render_vertex_entity(entity.view(..))
+        })
+      })
+    })
+    .unwrap();
+
+  let texels = ctx.read_texture(&fb.layers().frag).unwrap();
+  for (i, texel) in texels.chunks(4).enumerate() {
+    let expected = if i % 8 < 4 {
+      [255, 0, 0, 255]
+    } else {
+      [0, 255, 0, 255]
+    };
+    assert_eq!(texel, expected, "texel ({}, {})", i % 8, i / 8);
+  }
+}
+
+/// Colors of deinterleaved vertices split from interleaved ones can be edited in place and uploaded alone.
+#[test]
+fn update_vertex_attribute() {
+  let mut surface = EglSurface::new_gl33([8, 8]).expect("EGL surface");
+  let ctx = &mut surface.ctx;
+
+  let program: Program<Vertex, (), Triangle, Slots, ()> = ctx
+    .new_program(
+      ProgramBuilder::new()
+        .add_vertex_stage(VS)
+        .no_primitive_stage()
+        .add_shading_stage(FS),
+    )
+    .unwrap();
+
+  let vertices: Vec<_> = positions()
+    .into_iter()
+    .zip(colors([1., 0., 0.], [1., 0., 0.]))
+    .map(|(pos, color)| Vertex { pos, color })
+    .collect();
+  let mut entity: VertexEntity<Vertex, Triangle, Deinterleaving> = ctx
+    .new_vertex_entity(
+      VertexEntityBuilder::new().add_vertices(Deinterleaved::from_vertices(&vertices)),
+    )
+    .unwrap();
+
+  // turn the right half green
+  for color in &mut entity.vertices().components_mut::<"color">()[6..] {
+    *color = Vector3::from([0., 1., 0.]);
+  }
+  ctx
+    .update_vertex_attribute::<"color", _, _, _, _, _>(&mut entity)
+    .unwrap();
+
+  let fb: Framebuffer<Dim2, Slots, ()> = ctx
+    .new_framebuffer(Size2::new(8, 8), Mipmaps::No, &TextureSampling::default())
+    .unwrap();
+
+  ctx
+    .with_framebuffer(&fb, &PipelineState::default(), |mut with_framebuffer| {
+      with_framebuffer.with_program(&program, |mut with_program| {
+        with_program.with_render_state(&RenderState::default(), |mut with_render_state| {
+          with_render_state.render_vertex_entity(entity.view(..))
+        })
+      })
+    })
+    .unwrap();
+
+  let texels = ctx.read_texture(&fb.layers().frag).unwrap();
+  for (i, texel) in texels.chunks(4).enumerate() {
+    let expected = if i % 8 < 4 {
+      [255, 0, 0, 255]
+    } else {
+      [0, 255, 0, 255]
+    };
+    assert_eq!(texel, expected, "texel ({}, {})", i % 8, i / 8);
+  }
+
+  // the number of vertices cannot change when updating a single attribute
+  *entity.vertices() = Deinterleaved::new()
+    .set_components::<"pos">(positions().repeat(2))
+    .set_components::<"color">(colors([0., 0., 1.], [0., 0., 1.]).repeat(2));
+  assert!(ctx
+    .update_vertex_attribute::<"color", _, _, _, _, _>(&mut entity)
+    .is_err());
+}
+
+fn render_halves<S>(
+  ctx: &mut Context<GL33>,
+  program: &Program<Vertex, (), Triangle, Slots, ()>,
+  entity: &VertexEntity<Vertex, Triangle, S>,
+) -> Vec<[u8; 4]>
+where
+  S: VertexStorageFamily,
+{
+  let fb: Framebuffer<Dim2, Slots, ()> = ctx
+    .new_framebuffer(Size2::new(8, 8), Mipmaps::No, &TextureSampling::default())
+    .unwrap();
+
+  ctx
+    .with_framebuffer(&fb, &PipelineState::default(), |mut with_framebuffer| {
+      with_framebuffer.with_program(program, |mut with_program| {
+        with_program.with_render_state(&RenderState::default(), |mut with_render_state| {
+          with_render_state.render_vertex_entity(entity.view(..))
+        })
+      })
+    })
+    .unwrap();
+
+  // sample the middle of both halves
+  let texels = ctx.read_texture(&fb.layers().frag).unwrap();
+  [2, 6]
+    .into_iter()
+    .map(|x| {
+      let i = (4 * 8 + x) * 4;
+      [texels[i], texels[i + 1], texels[i + 2], texels[i + 3]]
+    })
+    .collect()
+}
+
+#[test]
+fn grow_interleaved_vertices_and_switch_indices() {
+  let mut surface = EglSurface::new_gl33([8, 8]).expect("EGL surface");
+  let ctx = &mut surface.ctx;
+
+  let program: Program<Vertex, (), Triangle, Slots, ()> = ctx
+    .new_program(
+      ProgramBuilder::new()
+        .add_vertex_stage(VS)
+        .no_primitive_stage()
+        .add_shading_stage(FS),
+    )
+    .unwrap();
+
+  let vertices: Vec<Vertex> = positions()
+    .into_iter()
+    .zip(colors([1., 0., 0.], [0., 1., 0.]))
+    .map(|(pos, color)| Vertex { pos, color })
+    .collect();
+
+  // start with the left half only
+  let mut entity: VertexEntity<Vertex, Triangle, Interleaving> = ctx
+    .new_vertex_entity(
+      VertexEntityBuilder::new().add_vertices(Interleaved::new().set_vertices(&vertices[..6])),
+    )
+    .unwrap();
+
+  let red = [255, 0, 0, 255];
+  let green = [0, 255, 0, 255];
+  let black = [0, 0, 0, 255];
+  assert_eq!(render_halves(ctx, &program, &entity), [red, black]);
+
+  // grow the storage past what was allocated
+  entity
+    .vertices()
+    .vertices_mut()
+    .extend_from_slice(&vertices[6..]);
+  ctx.update_vertices(&mut entity).unwrap();
+  assert_eq!(entity.vertex_count(), 12);
+  assert_eq!(render_halves(ctx, &program, &entity), [red, green]);
+
+  // switch to indexed rendering, only rendering the right half
+  entity.indices().extend(6..12);
+  ctx.update_indices(&mut entity).unwrap();
+  assert_eq!(entity.vertex_count(), 6);
+  assert_eq!(render_halves(ctx, &program, &entity), [black, green]);
+
+  // and back to direct rendering
+  entity.indices().clear();
+  ctx.update_indices(&mut entity).unwrap();
+  assert_eq!(entity.vertex_count(), 12);
+  assert_eq!(render_halves(ctx, &program, &entity), [red, green]);
+}
+
+/// Deinterleaved storages can start empty and grow.
+#[test]
+fn grow_empty_deinterleaved_vertices() {
+  let mut surface = EglSurface::new_gl33([8, 8]).expect("EGL surface");
+  let ctx = &mut surface.ctx;
+
+  let program: Program<Vertex, (), Triangle, Slots, ()> = ctx
+    .new_program(
+      ProgramBuilder::new()
+        .add_vertex_stage(VS)
+        .no_primitive_stage()
+        .add_shading_stage(FS),
+    )
+    .unwrap();
+
+  let mut entity: VertexEntity<Vertex, Triangle, Deinterleaving> = ctx
+    .new_vertex_entity(VertexEntityBuilder::new().add_vertices(Deinterleaved::new()))
+    .unwrap();
+
+  let vertices: Vec<Vertex> = positions()
+    .into_iter()
+    .zip(colors([1., 0., 0.], [0., 1., 0.]))
+    .map(|(pos, color)| Vertex { pos, color })
+    .collect();
+
+  let red = [255, 0, 0, 255];
+  let green = [0, 255, 0, 255];
+  let black = [0, 0, 0, 255];
+  assert_eq!(entity.vertex_count(), 0);
+  assert_eq!(render_halves(ctx, &program, &entity), [black, black]);
+
+  entity.vertices().extend_from_vertices(&vertices[..6]);
+  ctx.update_vertices(&mut entity).unwrap();
+  assert_eq!(entity.vertex_count(), 6);
+  assert_eq!(render_halves(ctx, &program, &entity), [red, black]);
+
+  entity.vertices().extend_from_vertices(&vertices[6..]);
+  ctx.update_vertices(&mut entity).unwrap();
+  assert_eq!(entity.vertex_count(), 12);
+  assert_eq!(render_halves(ctx, &program, &entity), [red, green]);
+}
+
+#[test]
+fn update_with_usages() {
+  let mut surface = EglSurface::new_gl33([8, 8]).expect("EGL surface");
+  let ctx = &mut surface.ctx;
+
+  let program: Program<Vertex, (), Triangle, Slots, ()> = ctx
+    .new_program(
+      ProgramBuilder::new()
+        .add_vertex_stage(VS)
+        .no_primitive_stage()
+        .add_shading_stage(FS),
+    )
+    .unwrap();
+
+  let red = [255, 0, 0, 255];
+  let black = [0, 0, 0, 255];
+  let usages = [
+    Usage::Static,
+    Usage::Dynamic,
+    Usage::Stream(StreamingStrategy::Orphaning),
+    Usage::Stream(StreamingStrategy::RoundRobin(3)),
+  ];
+
+  for usage in usages {
+    let vertices: Vec<Vertex> = positions()
+      .into_iter()
+      .zip(colors([1., 0., 0.], [1., 0., 0.]))
+      .map(|(pos, color)| Vertex { pos, color })
+      .collect();
+
+    let mut entity: VertexEntity<Vertex, Triangle, Interleaving> = ctx
+      .new_vertex_entity(
+        VertexEntityBuilder::new()
+          .add_vertices(Interleaved::new().set_vertices(vertices))
+          .add_indices((0..12).collect::<Vec<_>>())
+          .set_vertex_usage(usage)
+          .set_index_usage(usage),
+      )
+      .unwrap();
+
+    // go around the round-robin buffers more than once, so that stale buffers get updated
+    for frame in 0..5 {
+      let blue = frame as f32 / 4.;
+      for vertex in &mut entity.vertices().vertices_mut()[6..] {
+        vertex.color = Vector3::from([0., 1., blue]);
+      }
+      ctx.update_vertices_range(&mut entity, 6..).unwrap();
+
+      // render either both halves, or the left half twice
+      let right_visible = frame % 2 == 0;
+      let offset = if right_visible { 6 } else { 0 };
+      for (i, index) in entity.indices()[6..].iter_mut().enumerate() {
+        *index = offset + i as u32;
+      }
+      ctx.update_indices_range(&mut entity, 6..).unwrap();
+
+      let right = if right_visible {
+        [0, 255, (blue * 255.).round() as u8, 255]
+      } else {
+        black
+      };
+      assert_eq!(
+        render_halves(ctx, &program, &entity),
+        [red, right],
+        "{:?}, frame {}",
+        usage,
+        frame
+      );
+    }
+  }
+}
+
+/// Render two quads as triangle strips separated by a primitive restart, the left one red and the right one green.
+fn render_restarted_strips<I>(ctx: &mut Context<GL33>, indices: Vec<I>) -> Vec<[u8; 4]>
+where
+  I: Index,
+{
+  let program: Program<Vertex, (), TriangleStrip, Slots, ()> = ctx
+    .new_program(
+      ProgramBuilder::new()
+        .add_vertex_stage(VS)
+        .no_primitive_stage()
+        .add_shading_stage(FS),
+    )
+    .unwrap();
+
+  let vertices: Vec<Vertex> = [(-1., 0., [1., 0., 0.]), (0., 1., [0., 1., 0.])]
+    .into_iter()
+    .flat_map(|(x0, x1, color)| {
+      [[x0, -1.], [x1, -1.], [x0, 1.], [x1, 1.]].map(|pos| Vertex {
+        pos: Vector2::from(pos),
+        color: Vector3::from(color),
+      })
+    })
+    .collect();
+
+  let entity: VertexEntity<Vertex, TriangleStrip, Interleaving, (), (), I> = ctx
+    .new_vertex_entity(
+      VertexEntityBuilder::new()
+        .add_vertices(Interleaved::new().set_vertices(vertices))
+        .add_indices(indices),
+    )
+    .unwrap();
+
+  let fb: Framebuffer<Dim2, Slots, ()> = ctx
+    .new_framebuffer(Size2::new(8, 8), Mipmaps::No, &TextureSampling::default())
+    .unwrap();
+
+  ctx
+    .with_framebuffer(&fb, &PipelineState::default(), |mut with_framebuffer| {
+      with_framebuffer.with_program(&program, |mut with_program| {
+        with_program.with_render_state(&RenderState::default(), |mut with_render_state| {
+          with_render_state.render_vertex_entity(entity.view(..))
+        })
+      })
+    })
+    .unwrap();
+
+  ctx
+    .read_texture(&fb.layers().frag)
+    .unwrap()
+    .chunks(4)
+    .map(|texel| [texel[0], texel[1], texel[2], texel[3]])
+    .collect()
+}
+
+#[test]
+fn index_types() {
+  let mut surface = EglSurface::new_gl33([8, 8]).expect("EGL surface");
+  let ctx = &mut surface.ctx;
+
+  let red = [255, 0, 0, 255];
+  let green = [0, 255, 0, 255];
+  let expected: Vec<_> = (0..64)
+    .map(|i| if i % 8 < 4 { red } else { green })
+    .collect();
+
+  assert_eq!(
+    render_restarted_strips(ctx, vec![0u8, 1, 2, 3, u8::MAX, 4, 5, 6, 7]),
+    expected
+  );
+  assert_eq!(
+    render_restarted_strips(ctx, vec![0u16, 1, 2, 3, u16::MAX, 4, 5, 6, 7]),
+    expected
+  );
+  assert_eq!(
+    render_restarted_strips(ctx, vec![0u32, 1, 2, 3, u32::MAX, 4, 5, 6, 7]),
+    expected
+  );
+}
+
+#[test]
+fn packed_meshes_batch() {
+  let mut surface = EglSurface::new_gl33([8, 8]).expect("EGL surface");
+  let ctx = &mut surface.ctx;
+
+  let program: Program<Vertex, (), Triangle, Slots, ()> = ctx
+    .new_program(
+      ProgramBuilder::new()
+        .add_vertex_stage(VS)
+        .no_primitive_stage()
+        .add_shading_stage(FS),
+    )
+    .unwrap();
+
+  // two quads packed in the same vertex entity, each with its own indices
+  let vertices: Vec<Vertex> = [(-1., 0., [1., 0., 0.]), (0., 1., [0., 1., 0.])]
+    .into_iter()
+    .flat_map(|(x0, x1, color)| {
+      [[x0, -1.], [x1, -1.], [x1, 1.], [x0, 1.]].map(|pos| Vertex {
+        pos: Vector2::from(pos),
+        color: Vector3::from(color),
+      })
+    })
+    .collect();
+  let quad_indices = [0u16, 1, 2, 0, 2, 3];
+
+  let entity: VertexEntity<Vertex, Triangle, Interleaving, (), (), u16> = ctx
+    .new_vertex_entity(
+      VertexEntityBuilder::new()
+        .add_vertices(Interleaved::new().set_vertices(vertices))
+        .add_indices(quad_indices.repeat(2)),
+    )
+    .unwrap();
+
+  let left = entity.view(..6);
+  let right = entity.view(..6).set_start_vertex(6).set_base_vertex(4);
+
+  let fb: Framebuffer<Dim2, Slots, ()> = ctx
+    .new_framebuffer(Size2::new(8, 8), Mipmaps::No, &TextureSampling::default())
+    .unwrap();
+
+  ctx
+    .with_framebuffer(&fb, &PipelineState::default(), |mut with_framebuffer| {
+      with_framebuffer.with_program(&program, |mut with_program| {
+        with_program.with_render_state(&RenderState::default(), |mut with_render_state| {
+          with_render_state.render_vertex_entity_batch(&[left, right])
+        })
+      })
+    })
+    .unwrap();
+
+  let texels = ctx.read_texture(&fb.layers().frag).unwrap();
+  for (i, texel) in texels.chunks(4).enumerate() {
+    let expected = if i % 8 < 4 {
+      [255, 0, 0, 255]
+    } else {
+      [0, 255, 0, 255]
+    };
+    assert_eq!(texel, expected, "texel ({}, {})", i % 8, i / 8);
+  }
+}
+
+fn render_halves_indirect<C>(
+  ctx: &mut Context<GL33>,
+  program: &Program<Vertex, (), Triangle, Slots, ()>,
+  entity: &VertexEntity<Vertex, Triangle, Interleaving>,
+  indirect_buffer: &IndirectBuffer<C>,
+) -> Vec<[u8; 4]>
+where
+  C: IndirectCommand,
+{
+  let fb: Framebuffer<Dim2, Slots, ()> = ctx
+    .new_framebuffer(Size2::new(8, 8), Mipmaps::No, &TextureSampling::default())
+    .unwrap();
+
+  ctx
+    .with_framebuffer(&fb, &PipelineState::default(), |mut with_framebuffer| {
+      with_framebuffer.with_program(program, |mut with_program| {
+        with_program.with_render_state(&RenderState::default(), |mut with_render_state| {
+          with_render_state.render_indirect(entity, indirect_buffer, ..)
+        })
+      })
+    })
+    .unwrap();
+
+  let texels = ctx.read_texture(&fb.layers().frag).unwrap();
+  [2, 6]
+    .into_iter()
+    .map(|x| {
+      let i = (4 * 8 + x) * 4;
+      [texels[i], texels[i + 1], texels[i + 2], texels[i + 3]]
+    })
+    .collect()
+}
+
+#[test]
+fn render_indirect() {
+  let mut surface = EglSurface::new_gl33([8, 8]).expect("EGL surface");
+  let ctx = &mut surface.ctx;
+
+  let program: Program<Vertex, (), Triangle, Slots, ()> = ctx
+    .new_program(
+      ProgramBuilder::new()
+        .add_vertex_stage(VS)
+        .no_primitive_stage()
+        .add_shading_stage(FS),
+    )
+    .unwrap();
+
+  let quads: Vec<Vertex> = [(-1., 0., [1., 0., 0.]), (0., 1., [0., 1., 0.])]
+    .into_iter()
+    .flat_map(|(x0, x1, color)| {
+      [[x0, -1.], [x1, -1.], [x1, 1.], [x0, 1.]].map(|pos| Vertex {
+        pos: Vector2::from(pos),
+        color: Vector3::from(color),
+      })
+    })
+    .collect();
+  let indices: Vec<u32> = [0, 1, 2, 0, 2, 3].repeat(2);
+
+  // only the second quad is rendered at first
+  let indirect_buffer = ctx
+    .new_indirect_buffer(&[
+      DrawCommand::new(0, 1, 0),
+      DrawCommand::new(6, 1, 6).set_base_vertex(4),
+    ])
+    .unwrap();
+
+  let indexed: VertexEntity<Vertex, Triangle, Interleaving> = ctx
+    .new_vertex_entity(
+      VertexEntityBuilder::new()
+        .add_vertices(Interleaved::new().set_vertices(quads.clone()))
+        .add_indices(indices.clone()),
+    )
+    .unwrap();
+  assert_eq!(
+    render_halves_indirect(ctx, &program, &indexed, &indirect_buffer),
+    [[0, 0, 0, 255], [0, 255, 0, 255]]
+  );
+
+  ctx
+    .update_indirect_buffer(&indirect_buffer, 0, &[DrawCommand::new(6, 1, 0)])
+    .unwrap();
+  assert_eq!(
+    ctx.read_indirect_buffer(&indirect_buffer).unwrap(),
+    [
+      DrawCommand::new(6, 1, 0),
+      DrawCommand::new(6, 1, 6).set_base_vertex(4)
+    ]
+  );
+  assert_eq!(
+    render_halves_indirect(ctx, &program, &indexed, &indirect_buffer),
+    [[255, 0, 0, 255], [0, 255, 0, 255]]
+  );
+
+  // negative base vertices move indices backwards: the indices of the second quad render the first one
+  let absolute: VertexEntity<Vertex, Triangle, Interleaving> = ctx
+    .new_vertex_entity(
+      VertexEntityBuilder::new()
+        .add_vertices(Interleaved::new().set_vertices(quads.clone()))
+        .add_indices(vec![0, 1, 2, 0, 2, 3, 4, 5, 6, 4, 6, 7]),
+    )
+    .unwrap();
+  let backwards_buffer = ctx
+    .new_indirect_buffer(&[
+      DrawCommand::new(6, 1, 6).set_base_vertex(-4),
+      DrawCommand::new(6, 1, 6),
+    ])
+    .unwrap();
+  assert_eq!(
+    render_halves_indirect(ctx, &program, &absolute, &backwards_buffer),
+    [[255, 0, 0, 255], [0, 255, 0, 255]]
+  );
+
+  // the same quads, without indices
+  let unrolled: Vec<Vertex> = indices
+    .iter()
+    .enumerate()
+    .map(|(i, &index)| quads[index as usize + i / 6 * 4])
+    .collect();
+  let direct: VertexEntity<Vertex, Triangle, Interleaving> = ctx
+    .new_vertex_entity(
+      VertexEntityBuilder::new().add_vertices(Interleaved::new().set_vertices(unrolled)),
+    )
+    .unwrap();
+  let arrays_buffer = ctx
+    .new_indirect_buffer(&[
+      DrawArraysCommand::new(6, 1, 0),
+      DrawArraysCommand::new(6, 1, 6),
+    ])
+    .unwrap();
+  assert_eq!(
+    render_halves_indirect(ctx, &program, &direct, &arrays_buffer),
+    [[255, 0, 0, 255], [0, 255, 0, 255]]
+  );
+
+  // the kind of commands must match the vertex entity
+  let fb: Framebuffer<Dim2, Slots, ()> = ctx
+    .new_framebuffer(Size2::new(8, 8), Mipmaps::No, &TextureSampling::default())
+    .unwrap();
+  let result = ctx.with_framebuffer(&fb, &PipelineState::default(), |mut with_framebuffer| {
+    with_framebuffer.with_program(&program, |mut with_program| {
+      with_program.with_render_state(&RenderState::default(), |mut with_render_state| {
+        with_render_state.render_indirect(&direct, &indirect_buffer, ..)
+      })
+    })
+  });
+  assert!(result.is_err());
+}
+
+// writes a command per point, rendering the quad on its side of the viewport if the point is green
+const CULL_VS: &str = "
+in vec2 pos;
+in vec3 color;
+
+out vec3 v_color;
+flat out uint count;
+flat out uint instance_count;
+flat out uint first;
+flat out uint base_instance;
+
+void main() {
+  gl_Position = vec4(pos, 0., 1.);
+  v_color = color;
+
+  count = color.g > 0. ? 6u : 0u;
+  instance_count = 1u;
+  first = pos.x > 0. ? 6u : 0u;
+  base_instance = 0u;
+}";
+
+#[test]
+fn render_indirect_with_captured_commands() {
+  let mut surface = EglSurface::new_gl33([8, 8]).expect("EGL surface");
+  let ctx = &mut surface.ctx;
+
+  let program: Program<Vertex, (), Triangle, Slots, ()> = ctx
+    .new_program(
+      ProgramBuilder::new()
+        .add_vertex_stage(VS)
+        .no_primitive_stage()
+        .add_shading_stage(FS),
+    )
+    .unwrap();
+  let cull: Program<Vertex, (), primitive::Point, Slots, (), DrawArraysCommand> = ctx
+    .new_program(
+      ProgramBuilder::new()
+        .add_vertex_stage(CULL_VS)
+        .no_primitive_stage()
+        .add_shading_stage(FS)
+        .capture_varyings(),
+    )
+    .unwrap();
+
+  let quads: Vec<Vertex> = [(-1., 0., [1., 0., 0.]), (0., 1., [0., 1., 0.])]
+    .into_iter()
+    .flat_map(|(x0, x1, color)| {
+      [
+        [x0, -1.],
+        [x1, -1.],
+        [x1, 1.],
+        [x0, 1.],
+        [x0, -1.],
+        [x1, 1.],
+      ]
+      .map(|pos| Vertex {
+        pos: Vector2::from(pos),
+        color: Vector3::from(color),
+      })
+    })
+    .collect();
+  let quads: VertexEntity<Vertex, Triangle, Interleaving> = ctx
+    .new_vertex_entity(
+      VertexEntityBuilder::new().add_vertices(Interleaved::new().set_vertices(quads)),
+    )
+    .unwrap();
+
+  // one point per quad; only the second one is green
+  let points: VertexEntity<Vertex, primitive::Point, Interleaving> = ctx
+    .new_vertex_entity(
+      VertexEntityBuilder::new().add_vertices(Interleaved::new().set_vertices(vec![
+        Vertex {
+          pos: Vector2::from([-0.5, 0.]),
+          color: Vector3::from([1., 0., 0.]),
+        },
+        Vertex {
+          pos: Vector2::from([0.5, 0.]),
+          color: Vector3::from([0., 1., 0.]),
+        },
+      ])),
+    )
+    .unwrap();
+
+  let indirect_buffer = ctx
+    .new_indirect_buffer(&[DrawArraysCommand::default(); 2])
+    .unwrap();
+  let fb: Framebuffer<Dim2, Slots, ()> = ctx
+    .new_framebuffer(Size2::new(8, 8), Mipmaps::No, &TextureSampling::default())
+    .unwrap();
+
+  ctx
+    .with_framebuffer(&fb, &PipelineState::default(), |mut with_framebuffer| {
+      with_framebuffer.with_program(&cull, |mut with_program| {
+        with_program.with_render_state(&RenderState::default(), |mut with_render_state| {
+          with_render_state.render_vertex_entity_with_feedback(points.view(..), &indirect_buffer)
+        })
+      })
+    })
+    .unwrap();
+
+  assert_eq!(
+    ctx.read_indirect_buffer(&indirect_buffer).unwrap(),
+    [
+      DrawArraysCommand::new(0, 1, 0),
+      DrawArraysCommand::new(6, 1, 6)
+    ]
+  );
+  assert_eq!(
+    render_halves_indirect(ctx, &program, &quads, &indirect_buffer),
+    [[0, 0, 0, 255], [0, 255, 0, 255]]
+  );
+}
+
+#[test]
+fn instance_matrices() {
+  let mut surface = EglSurface::new_gl33([8, 8]).expect("EGL surface");
+  let ctx = &mut surface.ctx;
+
+  let program: Program<Vertex, Transform, Triangle, Slots, ()> = ctx
+    .new_program(
+      ProgramBuilder::new()
+        .add_vertex_stage(INSTANCED_VS)
+        .no_primitive_stage()
+        .add_shading_stage(FS),
+    )
+    .unwrap();
+
+  // the left half, rendered as is and moved to the right half
+  let vertices: Vec<Vertex> = positions()
+    .into_iter()
+    .zip(colors([1., 0., 0.], [1., 0., 0.]))
+    .map(|(pos, color)| Vertex { pos, color })
+    .take(6)
+    .collect();
+  let transforms = [0., 1.].map(|tx| Transform {
+    model: ColumnMatrix4::from([
+      [1., 0., 0., 0.],
+      [0., 1., 0., 0.],
+      [0., 0., 1., 0.],
+      [tx, 0., 0., 1.],
+    ]),
+  });
+
+  let entity: VertexEntity<Vertex, Triangle, Interleaving, Transform, Interleaving> = ctx
+    .new_vertex_entity(
+      VertexEntityBuilder::new()
+        .add_vertices(Interleaved::new().set_vertices(vertices))
+        .add_instances(Interleaved::new().set_vertices(transforms)),
+    )
+    .unwrap();
+
+  let fb: Framebuffer<Dim2, Slots, ()> = ctx
+    .new_framebuffer(Size2::new(8, 8), Mipmaps::No, &TextureSampling::default())
+    .unwrap();
+
+  ctx
+    .with_framebuffer(&fb, &PipelineState::default(), |mut with_framebuffer| {
+      with_framebuffer.with_program(&program, |mut with_program| {
+        with_program.with_render_state(&RenderState::default(), |mut with_render_state| {
+          with_render_state.render_vertex_entity(entity.view(..).set_instance_count(2))
+        })
+      })
+    })
+    .unwrap();
+
+  let texels = ctx.read_texture(&fb.layers().frag).unwrap();
+  for (i, texel) in texels.chunks(4).enumerate() {
+    assert_eq!(texel, [255, 0, 0, 255], "texel ({}, {})", i % 8, i / 8);
+  }
+}
+
+#[test]
+fn compressed_vertices() {
+  let mut surface = EglSurface::new_gl33([8, 8]).expect("EGL surface");
+  let ctx = &mut surface.ctx;
+
+  let program: Program<Compressed, (), Triangle, Slots, ()> = ctx
+    .new_program(
+      ProgramBuilder::new()
+        .add_vertex_stage(VS)
+        .no_primitive_stage()
+        .add_shading_stage(FS),
+    )
+    .unwrap();
+
+  // the left half is green and the right half half-blue; negative components are clamped by the fragment output
+  let vertices: Vec<Compressed> = positions()
+    .into_iter()
+    .zip(
+      [[-1., 1., 0., 0.]; 6]
+        .into_iter()
+        .chain([[0., 0., 0.5, 0.]; 6]),
+    )
+    .map(|(pos, color)| Compressed {
+      pos: [pos.x, pos.y].map(half::f16::from_f32),
+      color: Int2_10_10_10Rev::from_normalized(color),
+    })
+    .collect();
+
+  let entity: VertexEntity<Compressed, Triangle, Interleaving> = ctx
+    .new_vertex_entity(
+      VertexEntityBuilder::new().add_vertices(Interleaved::new().set_vertices(vertices)),
+    )
+    .unwrap();
+
+  let fb: Framebuffer<Dim2, Slots, ()> = ctx
+    .new_framebuffer(Size2::new(8, 8), Mipmaps::No, &TextureSampling::default())
+    .unwrap();
+
+  ctx
+    .with_framebuffer(&fb, &PipelineState::default(), |mut with_framebuffer| {
+      with_framebuffer.with_program(&program, |mut with_program| {
+        with_program.with_render_state(&RenderState::default(), |mut with_render_state| {
+          with_render_state.render_vertex_entity(entity.view(..))
+        })
+      })
+    })
+    .unwrap();
+
+  let texels = ctx.read_texture(&fb.layers().frag).unwrap();
+  for (i, texel) in texels.chunks(4).enumerate() {
+    let expected = if i % 8 < 4 {
+      [0, 255, 0, 255]
+    } else {
+      [0, 0, 128, 255]
+    };
+    assert_eq!(texel, expected, "texel ({}, {})", i % 8, i / 8);
+  }
+}
+
+#[test]
+fn triangle_adjacency() {
+  let mut surface = EglSurface::new_gl33([8, 8]).expect("EGL surface");
+  let ctx = &mut surface.ctx;
+
+  let program: Program<Vertex, (), TriangleAdjacency, Slots, ()> = ctx
+    .new_program(
+      ProgramBuilder::new()
+        .add_vertex_stage(VS)
+        .add_primitive_stage(ADJACENCY_GS)
+        .add_shading_stage(FS),
+    )
+    .unwrap();
+
+  let vertices = [[-1., -1.], [1., -1.], [1., 1.], [-1., 1.]].map(|pos| Vertex {
+    pos: Vector2::from(pos),
+    color: Vector3::from([1., 0., 0.]),
+  });
+  let entity: VertexEntity<Vertex, TriangleAdjacency, Interleaving> = ctx
+    .new_vertex_entity(
+      VertexEntityBuilder::new()
+        .add_vertices(Interleaved::new().set_vertices(vertices))
+        .add_indices(primitive::triangle_adjacency_indices(&[
+          0u32, 1, 2, 0, 2, 3,
+        ])),
+    )
+    .unwrap();
+
+  let fb: Framebuffer<Dim2, Slots, ()> = ctx
+    .new_framebuffer(Size2::new(8, 8), Mipmaps::No, &TextureSampling::default())
+    .unwrap();
+
+  ctx
+    .with_framebuffer(&fb, &PipelineState::default(), |mut with_framebuffer| {
+      with_framebuffer.with_program(&program, |mut with_program| {
+        with_program.with_render_state(&RenderState::default(), |mut with_render_state| {
+          with_render_state.render_vertex_entity(entity.view(..))
+        })
+      })
+    })
+    .unwrap();
+
+  let texels = ctx.read_texture(&fb.layers().frag).unwrap();
+  for (i, texel) in texels.chunks(4).enumerate() {
+    assert_eq!(texel, [0, 255, 0, 255], "texel ({}, {})", i % 8, i / 8);
+  }
+}
+
+#[test]
+fn tessellated_patches() {
+  let mut surface = EglSurface::new_gl33([8, 8]).expect("EGL surface");
+  let ctx = &mut surface.ctx;
+
+  let program: Program<Vertex, (), Patch<4>, Slots, ()> = ctx
+    .new_program(
+      ProgramBuilder::new()
+        .add_vertex_stage(VS)
+        .add_tess_control_stage::<4>(TESS_CONTROL)
+        .add_tess_eval_stage(TESS_EVAL)
+        .no_primitive_stage()
+        .add_shading_stage(FS),
+    )
+    .unwrap();
+
+  // one patch per half of the viewport
+  let vertices: Vec<Vertex> = [(-1., 0.), (0., 1.)]
+    .into_iter()
+    .flat_map(|(x0, x1)| [[x0, -1.], [x1, -1.], [x1, 1.], [x0, 1.]])
+    .map(|pos| Vertex {
+      pos: Vector2::from(pos),
+      color: Vector3::from([0., 1., 0.]),
+    })
+    .collect();
+  let entity: VertexEntity<Vertex, Patch<4>, Interleaving> = ctx
+    .new_vertex_entity(
+      VertexEntityBuilder::new().add_vertices(Interleaved::new().set_vertices(vertices)),
+    )
+    .unwrap();
+
+  let fb: Framebuffer<Dim2, Slots, ()> = ctx
+    .new_framebuffer(Size2::new(8, 8), Mipmaps::No, &TextureSampling::default())
+    .unwrap();
+
+  ctx
+    .with_framebuffer(&fb, &PipelineState::default(), |mut with_framebuffer| {
+      with_framebuffer.with_program(&program, |mut with_program| {
+        with_program.with_render_state(&RenderState::default(), |mut with_render_state| {
+          with_render_state.render_vertex_entity(entity.view(..))
+        })
+      })
+    })
+    .unwrap();
+
+  let texels = ctx.read_texture(&fb.layers().frag).unwrap();
+  for (i, texel) in texels.chunks(4).enumerate() {
+    assert_eq!(texel, [0, 255, 0, 255], "texel ({}, {})", i % 8, i / 8);
+  }
+}
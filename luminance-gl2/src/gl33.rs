@@ -1,32 +1,45 @@
 use core::fmt;
-use gl::types::{GLboolean, GLchar, GLenum, GLfloat, GLint, GLsizei, GLubyte, GLuint};
+use gl::types::{
+  GLbitfield, GLboolean, GLchar, GLenum, GLfloat, GLint, GLintptr, GLsizei, GLsizeiptr, GLsync,
+  GLubyte, GLuint,
+};
 use luminance::{
   backend::{
-    Backend, FramebufferBackend, FramebufferError, PipelineBackend, PipelineError, QueryBackend,
-    QueryError, ResourceMapper, ShaderBackend, ShaderError, TextureBackend, TextureError,
-    VertexEntityBackend, VertexEntityError,
+    self, Backend, BufferBackend, FramebufferBackend, FramebufferError, IndirectBufferBackend,
+    IndirectBufferError, PipelineBackend, PipelineError, QueryBackend, QueryError, ResourceMapper,
+    ShaderBackend, ShaderError, TextureBackend, TextureError, VertexEntityBackend,
+    VertexEntityError,
   },
   blending::{BlendingMode, Equation, Factor},
+  buffer::{self, BufferSliceMut},
   context::ContextActive,
   depth_stencil::{Comparison, DepthTest, DepthWrite, StencilOp, StencilTest},
   dim::{Dim, Dimensionable},
   face_culling::{FaceCulling, FaceCullingFace, FaceCullingOrder},
   framebuffer::{Back, Framebuffer},
+  indirect::{IndirectBuffer, IndirectCommand},
   pipeline::{PipelineState, Viewport, WithFramebuffer, WithProgram, WithRenderState},
   pixel::{Format, Pixel, PixelFormat, PixelType, Size, Type},
   primitive::{Connector, Primitive},
   render_slots::{DepthChannel, DepthRenderSlot, RenderChannel, RenderSlots},
   scissor::Scissor,
   shader::{
-    InUseUniBuffer, MemoryLayout, Program, Uni, UniBuffer, UniBufferRef, UniType, Uniform, Uniforms,
+    diagnostic::{parse_glsl_log, Diagnostic, Severity, Stage},
+    reflection::{
+      ActiveAttrib, ActiveBlockMember, ActiveUniform, ActiveUniformBlock, ProgramReflection,
+    },
+    InUseUniBuffer, MemoryLayout, Program, Uni, UniBuffer, UniBufferRef, UniDim, UniMatDim,
+    UniType, Uniform, Uniforms,
   },
   texture::{InUseTexture, MagFilter, MinFilter, Mipmaps, Texture, TextureSampling, Wrap},
   vertex::{
     Normalized, Vertex, VertexAttribDesc, VertexAttribDim, VertexAttribType, VertexBufferDesc,
   },
-  vertex_entity::{VertexEntity, VertexEntityBuilder, VertexEntityView},
+  vertex_entity::{
+    Index, IndexType, StreamingStrategy, Usage, VertexEntity, VertexEntityBuilder, VertexEntityView,
+  },
   vertex_storage::{
-    AsVertexStorage, Deinterleaved, Interleaved, VertexStorage, VertexStorageFamily,
+    AsVertexStorage, Deinterleaved, Interleaved, Shared, VertexStorage, VertexStorageFamily,
   },
 };
 use std::{
@@ -35,11 +48,14 @@ use std::{
   ffi::{c_char, c_void, CStr, CString},
   marker::PhantomData,
   mem,
-  ops::{Deref, DerefMut},
+  ops::{Deref, DerefMut, Range},
   ptr::{self, null, null_mut},
   rc::Rc,
 };
 
+// how long to wait on a fence before waiting again, in nanoseconds
+const FENCE_TIMEOUT_NS: u64 = 1_000_000_000;
+
 /// Cached value.
 ///
 /// A cached value is used to prevent issuing costy GPU commands if we know the target value is
@@ -118,6 +134,8 @@ pub struct State {
   programs: HashMap<usize, ProgramData>,
   uni_buffers: HashMap<usize, BufferWithBinding>,
   uni_buffer_bindings: Rc<RefCell<ResourceMapper>>,
+  indirect_buffers: HashMap<usize, Buffer>,
+  buffers: HashMap<usize, BufferWithBinding>,
 
   // viewport
   viewport: Cached<[GLint; 4]>,
@@ -153,6 +171,10 @@ pub struct State {
 
   // vertex restart
   primitive_restart: Cached<bool>,
+  primitive_restart_index: Cached<u32>,
+
+  // number of vertices per patch
+  patch_vertices: Cached<usize>,
 
   // array buffer
   bound_array_buffer: Cached<GLuint>,
@@ -163,6 +185,9 @@ pub struct State {
   // uniform buffer
   bound_uni_buffer: Cached<GLuint>,
 
+  // draw indirect buffer
+  bound_draw_indirect_buffer: Cached<GLuint>,
+
   // framebuffer
   bound_draw_framebuffer: Cached<GLuint>,
 
@@ -186,6 +211,15 @@ pub struct State {
 
   // GLSL version;
   glsl_version: Option<String>,
+
+  // whether draw calls can start from a base instance (OpenGL 4.2 or GL_ARB_base_instance)
+  base_instance_supported: bool,
+
+  // whether draw calls can read their parameters from buffers (OpenGL 4.0 or GL_ARB_draw_indirect)
+  draw_indirect_supported: bool,
+
+  // whether programs can have tessellation stages (OpenGL 4.0 or GL_ARB_tessellation_shader)
+  tessellation_supported: bool,
 }
 
 // TLS synchronization barrier for `GLState`.
@@ -216,6 +250,8 @@ impl State {
     let uni_buffer_bindings = Rc::new(RefCell::new(ResourceMapper::new(
       GL33::get_max_uni_buffer_bindings(),
     )));
+    let indirect_buffers = HashMap::new();
+    let buffers = HashMap::new();
     let viewport = Cached::empty();
     let clear_color = Cached::empty();
     let clear_depth = Cached::empty();
@@ -235,9 +271,12 @@ impl State {
     let scissor = Cached::empty();
     let scissor_region = Cached::empty();
     let primitive_restart = Cached::empty();
+    let primitive_restart_index = Cached::empty();
+    let patch_vertices = Cached::empty();
     let bound_array_buffer = Cached::empty();
     let bound_element_array_buffer = Cached::empty();
     let bound_uni_buffer = Cached::empty();
+    let bound_draw_indirect_buffer = Cached::empty();
     let bound_draw_framebuffer = Cached::empty();
     let bound_vertex_array = Cached::empty();
     let current_program = Cached::empty();
@@ -246,6 +285,9 @@ impl State {
     let renderer_name = None;
     let gl_version = None;
     let glsl_version = None;
+    let base_instance_supported = GL33::is_base_instance_supported();
+    let draw_indirect_supported = GL33::is_draw_indirect_supported();
+    let tessellation_supported = GL33::is_tessellation_supported();
 
     State {
       _phantom: PhantomData,
@@ -257,6 +299,8 @@ impl State {
       programs,
       uni_buffers,
       uni_buffer_bindings,
+      indirect_buffers,
+      buffers,
       context_active,
       viewport,
       clear_color,
@@ -277,9 +321,12 @@ impl State {
       scissor,
       scissor_region,
       primitive_restart,
+      primitive_restart_index,
+      patch_vertices,
       bound_array_buffer,
       bound_element_array_buffer,
       bound_uni_buffer,
+      bound_draw_indirect_buffer,
       bound_draw_framebuffer,
       bound_vertex_array,
       current_program,
@@ -288,6 +335,9 @@ impl State {
       renderer_name,
       gl_version,
       glsl_version,
+      base_instance_supported,
+      draw_indirect_supported,
+      tessellation_supported,
     }
   }
 
@@ -335,10 +385,12 @@ impl State {
     }
   }
 
+  /// Bind a uniform buffer, or a buffer used as a uniform buffer.
   fn bind_uni_buffer(&mut self, handle: usize) -> Result<usize, ShaderError> {
     let buffer_data = self
       .uni_buffers
       .get_mut(&handle)
+      .or_else(|| self.buffers.get_mut(&handle))
       .ok_or_else(|| ShaderError::NoData { handle })?;
 
     match buffer_data.binding {
@@ -354,7 +406,11 @@ impl State {
 
         // if a uniform buffer was previously bound there, remove its binding; we stole it
         if let Some(handle) = old_uni_buffer_handle {
-          if let Some(old_data) = self.uni_buffers.get_mut(&handle) {
+          if let Some(old_data) = self
+            .uni_buffers
+            .get_mut(&handle)
+            .or_else(|| self.buffers.get_mut(&handle))
+          {
             old_data.binding = None;
           }
         }
@@ -390,6 +446,7 @@ impl State {
     let buffer_data = self
       .uni_buffers
       .get_mut(&handle)
+      .or_else(|| self.buffers.get_mut(&handle))
       .ok_or_else(|| ShaderError::NoData { handle })?;
 
     if let Some(binding) = buffer_data.binding {
@@ -431,6 +488,35 @@ impl State {
       self.uni_buffers.remove(&handle);
     }
   }
+
+  fn drop_indirect_buffer(&mut self, handle: usize) {
+    if self.is_context_active() {
+      self.indirect_buffers.remove(&handle);
+    }
+  }
+
+  fn drop_buffer(&mut self, handle: usize) {
+    if self.is_context_active() {
+      self.buffers.remove(&handle);
+    }
+  }
+
+  /// Reject commands with a base instance if base instances are not supported.
+  fn check_base_instances<C>(&self, commands: &[C]) -> Result<(), IndirectBufferError>
+  where
+    C: IndirectCommand,
+  {
+    if self.base_instance_supported {
+      return Ok(());
+    }
+
+    match commands.iter().find(|command| command.base_instance() != 0) {
+      Some(command) => Err(IndirectBufferError::UnsupportedBaseInstance {
+        base_instance: command.base_instance(),
+      }),
+      None => Ok(()),
+    }
+  }
 }
 
 #[derive(Debug)]
@@ -465,10 +551,21 @@ impl DerefMut for StateRef {
 #[derive(Debug)]
 struct Buffer {
   handle: GLuint,
+
+  // allocated size, in bytes
+  capacity: usize,
+
+  // usage hint passed to OpenGL when (re)allocating
+  usage: GLenum,
+
+  // fence signaled when the GPU is done with the draws reading the buffer, if any
+  fence: Option<GLsync>,
 }
 
 impl Drop for Buffer {
   fn drop(&mut self) {
+    self.delete_fence();
+
     unsafe {
       gl::DeleteBuffers(1, &self.handle);
     }
@@ -476,8 +573,21 @@ impl Drop for Buffer {
 }
 
 impl Buffer {
-  fn from_slice<T>(state: &StateRef, target: GLenum, slice: &[T]) -> Self {
-    let mut st = state.borrow_mut();
+  fn from_slice<T>(state: &StateRef, target: GLenum, slice: &[T], usage: GLenum) -> Self {
+    Self::new(
+      &mut state.borrow_mut().bound_array_buffer,
+      target,
+      slice,
+      usage,
+    )
+  }
+
+  fn new<T>(
+    bound_array_buffer: &mut Cached<GLuint>,
+    target: GLenum,
+    slice: &[T],
+    usage: GLenum,
+  ) -> Self {
     let mut handle: GLuint = 0;
 
     unsafe {
@@ -485,30 +595,185 @@ impl Buffer {
       gl::BindBuffer(target, handle);
     }
 
-    st.bound_array_buffer.set(handle);
+    // only the array buffer binding is cached; other targets must not invalidate it
+    if target == gl::ARRAY_BUFFER {
+      bound_array_buffer.set(handle);
+    }
 
     let len = slice.len();
     let bytes = mem::size_of::<T>() * len;
 
     unsafe {
-      gl::BufferData(target, bytes as isize, slice.as_ptr() as _, gl::STREAM_DRAW);
+      gl::BufferData(target, bytes as isize, slice.as_ptr() as _, usage);
+    }
+
+    Buffer {
+      handle,
+      capacity: bytes,
+      usage,
+      fence: None,
+    }
+  }
+
+  /// Upload the `range` of `values`, which is the whole content of the buffer.
+  ///
+  /// If `values` doesn’t fit in the buffer anymore, the buffer is reallocated and all of `values` is uploaded.
+  fn upload<T>(
+    &mut self,
+    bound_array_buffer: &mut Cached<GLuint>,
+    values: &[T],
+    range: Range<usize>,
+  ) -> Result<(), BufferError> {
+    if mem::size_of_val(values) > self.capacity {
+      self.realloc(bound_array_buffer, values);
+      Ok(())
+    } else {
+      self.update(bound_array_buffer, &values[range.clone()], range.start)
+    }
+  }
+
+  /// Reallocate the buffer to hold `values`.
+  ///
+  /// The capacity of the buffer is at least doubled, so that pushing values one at a time doesn’t reallocate every
+  /// time.
+  fn realloc<T>(&mut self, bound_array_buffer: &mut Cached<GLuint>, values: &[T]) {
+    let capacity = mem::size_of_val(values).max(self.capacity * 2);
+    self.alloc(bound_array_buffer, values, capacity);
+  }
+
+  /// Orphan the buffer and upload all of `values` to its new storage.
+  ///
+  /// The driver can then hand out a fresh storage instead of waiting for the GPU to be done with the previous one.
+  fn orphan<T>(&mut self, bound_array_buffer: &mut Cached<GLuint>, values: &[T]) {
+    let capacity = mem::size_of_val(values).max(self.capacity);
+    self.alloc(bound_array_buffer, values, capacity);
+  }
+
+  /// Allocate a new storage of `capacity` bytes for the buffer and upload `values` at its beginning.
+  fn alloc<T>(&mut self, bound_array_buffer: &mut Cached<GLuint>, values: &[T], capacity: usize) {
+    bound_array_buffer.set_if_invalid(self.handle, || unsafe {
+      gl::BindBuffer(gl::ARRAY_BUFFER, self.handle);
+    });
+
+    unsafe {
+      gl::BufferData(
+        gl::ARRAY_BUFFER,
+        capacity as GLsizeiptr,
+        ptr::null(),
+        self.usage,
+      );
+      gl::BufferSubData(
+        gl::ARRAY_BUFFER,
+        0,
+        mem::size_of_val(values) as GLsizeiptr,
+        values.as_ptr() as *const c_void,
+      );
+    }
+
+    self.capacity = capacity;
+
+    // the fenced draws read the previous storage
+    self.delete_fence();
+  }
+
+  /// Fence the draws issued so far, which read the buffer.
+  fn fence(&mut self) {
+    self.delete_fence();
+    self.fence = Some(unsafe { gl::FenceSync(gl::SYNC_GPU_COMMANDS_COMPLETE, 0) });
+  }
+
+  /// Wait for the GPU to be done with the fenced draws, if any.
+  fn wait(&mut self) {
+    if let Some(fence) = self.fence.take() {
+      unsafe {
+        // commands are flushed so that the fence gets signaled at some point
+        while gl::ClientWaitSync(fence, gl::SYNC_FLUSH_COMMANDS_BIT, FENCE_TIMEOUT_NS)
+          == gl::TIMEOUT_EXPIRED
+        {}
+        gl::DeleteSync(fence);
+      }
     }
+  }
 
-    Buffer { handle }
+  fn delete_fence(&mut self) {
+    if let Some(fence) = self.fence.take() {
+      unsafe {
+        gl::DeleteSync(fence);
+      }
+    }
   }
 
+  /// Update the buffer with `values`, starting at the `start`-th element.
+  ///
+  /// Only the updated region of the buffer is mapped.
   fn update<T>(
     &self,
     bound_array_buffer: &mut Cached<GLuint>,
     values: &[T],
     start: usize,
-    len: usize,
   ) -> Result<(), BufferError> {
+    self.write(
+      bound_array_buffer,
+      values,
+      start,
+      gl::MAP_WRITE_BIT | gl::MAP_INVALIDATE_RANGE_BIT,
+    )
+  }
+
+  /// Update the buffer with `values`, starting at the `start`-th element, without waiting for the GPU to be done
+  /// reading it.
+  ///
+  /// The GPU must not be reading the updated region anymore, which is ensured by waiting on the fence of the buffer.
+  fn update_unsynchronized<T>(
+    &self,
+    bound_array_buffer: &mut Cached<GLuint>,
+    values: &[T],
+    start: usize,
+  ) -> Result<(), BufferError> {
+    self.write(
+      bound_array_buffer,
+      values,
+      start,
+      gl::MAP_WRITE_BIT | gl::MAP_INVALIDATE_RANGE_BIT | gl::MAP_UNSYNCHRONIZED_BIT,
+    )
+  }
+
+  /// Map the region of the buffer starting at the `start`-th element with `access` and write `values` to it.
+  fn write<T>(
+    &self,
+    bound_array_buffer: &mut Cached<GLuint>,
+    values: &[T],
+    start: usize,
+    access: GLbitfield,
+  ) -> Result<(), BufferError> {
+    if values.is_empty() {
+      return Ok(());
+    }
+
     bound_array_buffer.set_if_invalid(self.handle, || unsafe {
       gl::BindBuffer(gl::ARRAY_BUFFER, self.handle);
     });
 
-    let ptr = unsafe { gl::MapBuffer(gl::ARRAY_BUFFER, gl::WRITE_ONLY) as *mut T };
+    let offset = mem::size_of::<T>() * start;
+    let bytes = mem::size_of_val(values);
+
+    if offset + bytes > self.capacity {
+      return Err(BufferError::OutOfBounds {
+        handle: self.handle as _,
+        offset,
+        bytes,
+        capacity: self.capacity,
+      });
+    }
+
+    let ptr = unsafe {
+      gl::MapBufferRange(
+        gl::ARRAY_BUFFER,
+        offset as GLintptr,
+        bytes as GLsizeiptr,
+        access,
+      ) as *mut T
+    };
 
     if ptr.is_null() {
       return Err(BufferError::CannotUpdate {
@@ -517,13 +782,50 @@ impl Buffer {
     }
 
     unsafe {
-      let src = values.as_ptr();
-      std::ptr::copy_nonoverlapping(src, ptr.add(start), len);
+      std::ptr::copy_nonoverlapping(values.as_ptr(), ptr, values.len());
       gl::UnmapBuffer(gl::ARRAY_BUFFER);
     }
 
     Ok(())
   }
+
+  /// Read back `len` values, starting at the `start`-th element.
+  fn read<T>(
+    &self,
+    bound_array_buffer: &mut Cached<GLuint>,
+    start: usize,
+    len: usize,
+  ) -> Result<Vec<T>, BufferError> {
+    let offset = mem::size_of::<T>() * start;
+    let bytes = mem::size_of::<T>() * len;
+
+    if offset + bytes > self.capacity {
+      return Err(BufferError::OutOfBounds {
+        handle: self.handle as _,
+        offset,
+        bytes,
+        capacity: self.capacity,
+      });
+    }
+
+    bound_array_buffer.set_if_invalid(self.handle, || unsafe {
+      gl::BindBuffer(gl::ARRAY_BUFFER, self.handle);
+    });
+
+    let mut values = Vec::with_capacity(len);
+
+    unsafe {
+      gl::GetBufferSubData(
+        gl::ARRAY_BUFFER,
+        offset as GLintptr,
+        bytes as GLsizeiptr,
+        values.as_mut_ptr() as *mut c_void,
+      );
+      values.set_len(len);
+    }
+
+    Ok(values)
+  }
 }
 
 #[derive(Debug)]
@@ -555,18 +857,146 @@ impl Drop for BufferWithBinding {
   }
 }
 
+/// Buffers backing a vertex entity storage, updated according to the usage of the storage.
+#[derive(Debug)]
+struct StorageBuffer {
+  // buffers to cycle through; there is more than one only with round-robin streaming
+  buffers: Vec<Buffer>,
+
+  // index of the buffer currently used for rendering
+  current: usize,
+
+  usage: Usage,
+}
+
+impl StorageBuffer {
+  fn new<T>(
+    bound_array_buffer: &mut Cached<GLuint>,
+    target: GLenum,
+    slice: &[T],
+    usage: Usage,
+  ) -> Self {
+    let count = match usage {
+      Usage::Stream(StreamingStrategy::RoundRobin(count)) => count.max(1),
+      _ => 1,
+    };
+
+    // only the first buffer is bound to the target, as the others are attached when they are cycled to
+    let buffers = (0..count)
+      .map(|i| {
+        let target = if i == 0 { target } else { gl::ARRAY_BUFFER };
+        Buffer::new(bound_array_buffer, target, slice, GL33::opengl_usage(usage))
+      })
+      .collect();
+
+    Self {
+      buffers,
+      current: 0,
+      usage,
+    }
+  }
+
+  fn handle(&self) -> GLuint {
+    self.buffers[self.current].handle
+  }
+
+  /// Fence the draws reading the buffer currently used for rendering.
+  ///
+  /// Only round-robin buffers are updated without synchronization, so other storages are not fenced.
+  fn fence(&mut self) {
+    if self.buffers.len() > 1 {
+      self.buffers[self.current].fence();
+    }
+  }
+
+  /// Upload the `range` of `values`, which is the whole content of the storage.
+  ///
+  /// Streamed storages always upload all of `values`. Returns whether the buffer used for rendering has changed, in
+  /// which case it must be attached to the vertex array again.
+  fn upload<T>(
+    &mut self,
+    bound_array_buffer: &mut Cached<GLuint>,
+    values: &[T],
+    range: Range<usize>,
+  ) -> Result<bool, BufferError> {
+    match self.usage {
+      Usage::Static | Usage::Dynamic => {
+        self.buffers[self.current].upload(bound_array_buffer, values, range)?;
+        Ok(false)
+      }
+
+      Usage::Stream(StreamingStrategy::Orphaning) => {
+        self.buffers[self.current].orphan(bound_array_buffer, values);
+        Ok(false)
+      }
+
+      Usage::Stream(StreamingStrategy::RoundRobin(_)) => {
+        let next = (self.current + 1) % self.buffers.len();
+        let buffer = &mut self.buffers[next];
+
+        if mem::size_of_val(values) > buffer.capacity {
+          buffer.realloc(bound_array_buffer, values);
+        } else if next == self.current {
+          // a single buffer might still be rendered
+          buffer.update(bound_array_buffer, values, 0)?;
+        } else {
+          // the other buffers were rendered at least one update ago, and the GPU is most likely done with them
+          buffer.wait();
+          buffer.update_unsynchronized(bound_array_buffer, values, 0)?;
+        }
+
+        let changed = next != self.current;
+        self.current = next;
+        Ok(changed)
+      }
+    }
+  }
+}
+
 #[derive(Debug)]
 enum VertexEntityBuffers {
-  Interleaved(Buffer),
-  Deinterleaved(Vec<Buffer>),
+  Interleaved(StorageBuffer),
+  Deinterleaved(Vec<StorageBuffer>),
+
+  // buffer owned by a buffer resource
+  Shared,
 }
 
 #[derive(Debug)]
 struct VertexEntityData {
   vao: GLuint,
   vertex_buffers: Option<VertexEntityBuffers>,
-  index_buffer: Option<Buffer>,
+  vertex_len: usize,
+  index_buffer: Option<StorageBuffer>,
+  shared_index_buffer: Option<GLuint>,
+  index_type: IndexType,
   instance_buffers: Option<VertexEntityBuffers>,
+  vertex_usage: Usage,
+  index_usage: Usage,
+  instance_usage: Usage,
+}
+
+impl VertexEntityData {
+  /// Fence the round-robin buffers of the vertex entity after it has been rendered.
+  fn fence(&mut self) {
+    for buffers in self
+      .vertex_buffers
+      .iter_mut()
+      .chain(&mut self.instance_buffers)
+    {
+      match buffers {
+        VertexEntityBuffers::Interleaved(buffer) => buffer.fence(),
+        VertexEntityBuffers::Deinterleaved(buffers) => {
+          buffers.iter_mut().for_each(StorageBuffer::fence)
+        }
+        VertexEntityBuffers::Shared => (),
+      }
+    }
+
+    if let Some(ref mut index_buffer) = self.index_buffer {
+      index_buffer.fence();
+    }
+  }
 }
 
 impl Drop for VertexEntityData {
@@ -584,7 +1014,16 @@ struct BuiltVertexBuffers {
 
 #[derive(Debug)]
 pub enum BufferError {
-  CannotUpdate { handle: usize },
+  CannotUpdate {
+    handle: usize,
+  },
+
+  OutOfBounds {
+    handle: usize,
+    offset: usize,
+    bytes: usize,
+    capacity: usize,
+  },
 }
 
 impl std::error::Error for BufferError {}
@@ -593,6 +1032,16 @@ impl fmt::Display for BufferError {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     match self {
       BufferError::CannotUpdate { handle } => write!(f, "cannot slice buffer {}", handle),
+      BufferError::OutOfBounds {
+        handle,
+        offset,
+        bytes,
+        capacity,
+      } => write!(
+        f,
+        "cannot update {} bytes at offset {} of buffer {} (capacity = {})",
+        bytes, offset, handle, capacity
+      ),
     }
   }
 }
@@ -1203,7 +1652,12 @@ impl Drop for TextureData {
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum StageError {
   /// Occurs when a shader fails to compile.
-  CompilationFailed { ty: GLenum, reason: String },
+  ///
+  /// Diagnostics are located in the code of the stage, without the prelude added to it.
+  CompilationFailed {
+    ty: GLenum,
+    diagnostics: Vec<Diagnostic>,
+  },
 
   /// Occurs when you try to create a shader which type is not supported on the current hardware.
   UnsupportedType(GLenum),
@@ -1212,11 +1666,16 @@ pub enum StageError {
 impl fmt::Display for StageError {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     match self {
-      StageError::CompilationFailed { ty, reason } => write!(
-        f,
-        "stage compilation failed: type={}, reason={}",
-        ty, reason
-      ),
+      StageError::CompilationFailed { ty, diagnostics } => {
+        write!(f, "stage compilation failed: type={}", ty)?;
+
+        for diagnostic in diagnostics {
+          write!(f, "\n{}", diagnostic)?;
+        }
+
+        Ok(())
+      }
+
       StageError::UnsupportedType(ty) => write!(f, "unsupported stage type: {}", ty),
     }
   }
@@ -1226,8 +1685,15 @@ impl std::error::Error for StageError {}
 
 impl From<StageError> for ShaderError {
   fn from(e: StageError) -> Self {
-    ShaderError::Creation {
-      cause: Some(Box::new(e)),
+    match e {
+      StageError::CompilationFailed { ty, diagnostics } => ShaderError::Compilation {
+        stage: StageHandle::stage(ty),
+        diagnostics,
+      },
+
+      e => ShaderError::Creation {
+        cause: Some(Box::new(e)),
+      },
     }
   }
 }
@@ -1244,12 +1710,19 @@ impl StageHandle {
     if handle == 0 {
       return Err(StageError::CompilationFailed {
         ty,
-        reason: "unable to create shader stage".to_owned(),
+        diagnostics: vec![Diagnostic {
+          stage: Some(Self::stage(ty)),
+          line: None,
+          column: None,
+          severity: Severity::Error,
+          message: "unable to create shader stage".to_owned(),
+          source_line: None,
+        }],
       });
     }
 
     unsafe {
-      let c_code = CString::new(Self::glsl_pragma_src(code).as_bytes()).unwrap();
+      let c_code = CString::new(Self::glsl_pragma_src(ty, code).as_bytes()).unwrap();
       gl::ShaderSource(handle, 1, [c_code.as_ptr()].as_ptr(), null());
       gl::CompileShader(handle);
 
@@ -1268,10 +1741,14 @@ impl StageHandle {
 
         log.set_len(log_len as usize);
 
-        return Err(StageError::CompilationFailed {
-          ty,
-          reason: String::from_utf8(log).unwrap(),
-        });
+        let prelude_lines = Self::prelude_lines(ty);
+        let diagnostics = parse_glsl_log(
+          &String::from_utf8_lossy(&log),
+          Some(Self::stage(ty)),
+          |_| Some((code, prelude_lines)),
+        );
+
+        return Err(StageError::CompilationFailed { ty, diagnostics });
       }
 
       Ok(StageHandle { handle })
@@ -1282,17 +1759,45 @@ impl StageHandle {
   const GLSL_PRAGMA: &str = "#version 330 core\n\
                            #extension GL_ARB_separate_shader_objects : require\n
                            #extension GL_ARB_gpu_shader_fp64 : require\n\
-                           layout(std140) uniform;\n";
+                           #extension GL_ARB_vertex_attrib_64bit : require\n";
   #[cfg(not(feature = "shader-f64"))]
   const GLSL_PRAGMA: &str = "#version 330 core\n\
-                           #extension GL_ARB_separate_shader_objects : require\n\
-                           layout(std140) uniform;\n";
+                           #extension GL_ARB_separate_shader_objects : require\n";
 
-  fn glsl_pragma_src(src: &str) -> String {
+  /// Prelude prepended to the code of stages of type `ty`.
+  fn glsl_pragma(ty: GLenum) -> String {
     let mut pragma = String::from(Self::GLSL_PRAGMA);
+
+    // tessellation stages are not part of GLSL 3.30
+    if ty == gl::TESS_CONTROL_SHADER || ty == gl::TESS_EVALUATION_SHADER {
+      pragma.push_str("#extension GL_ARB_tessellation_shader : require\n");
+    }
+
+    // extensions must be enabled before any declaration
+    pragma.push_str("layout(std140) uniform;\n");
+    pragma
+  }
+
+  fn glsl_pragma_src(ty: GLenum, src: &str) -> String {
+    let mut pragma = Self::glsl_pragma(ty);
     pragma.push_str(src);
     pragma
   }
+
+  /// Number of lines of the prelude, to remap the lines reported by the compiler to the code of the stage.
+  fn prelude_lines(ty: GLenum) -> usize {
+    Self::glsl_pragma(ty).matches('\n').count()
+  }
+
+  fn stage(ty: GLenum) -> Stage {
+    match ty {
+      gl::VERTEX_SHADER => Stage::Vertex,
+      gl::TESS_CONTROL_SHADER => Stage::TessControl,
+      gl::TESS_EVALUATION_SHADER => Stage::TessEval,
+      gl::GEOMETRY_SHADER => Stage::Primitive,
+      _ => Stage::Shading,
+    }
+  }
 }
 
 impl Drop for StageHandle {
@@ -1305,7 +1810,23 @@ impl Drop for StageHandle {
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum ProgramError {
-  LinkFailed { handle: usize, reason: String },
+  LinkFailed {
+    handle: usize,
+    reason: String,
+  },
+  /// A captured varying cannot be written by transform feedback.
+  UncapturableVarying {
+    name: String,
+  },
+  /// Captured varyings are not tightly packed in their vertex type.
+  PaddedCapturedVaryings,
+  /// A vertex attribute and its shader input disagree on being double-precision.
+  ///
+  /// Double-precision attributes (`VertexAttribType::Floating64`) must be declared as `double`, `dvec*` or `dmat*`
+  /// inputs, and such inputs can only be fed with double-precision attributes.
+  MismatchingDoubleVertexAttrib {
+    name: String,
+  },
 }
 
 impl fmt::Display for ProgramError {
@@ -1314,6 +1835,26 @@ impl fmt::Display for ProgramError {
       ProgramError::LinkFailed { handle, reason } => {
         write!(f, "program {} failed to link: {}", handle, reason)
       }
+
+      ProgramError::UncapturableVarying { name } => {
+        write!(
+          f,
+          "varying {} cannot be captured with transform feedback",
+          name
+        )
+      }
+
+      ProgramError::PaddedCapturedVaryings => {
+        f.write_str("captured varyings are not tightly packed")
+      }
+
+      ProgramError::MismatchingDoubleVertexAttrib { name } => {
+        write!(
+          f,
+          "vertex attribute {} and its shader input disagree on being double-precision",
+          name
+        )
+      }
     }
   }
 }
@@ -1331,6 +1872,9 @@ impl From<ProgramError> for ShaderError {
 #[derive(Debug)]
 struct ProgramData {
   handle: GLuint,
+
+  /// Reflection of the program, cached at creation when uniforms are validated.
+  reflection: Option<ProgramReflection>,
 }
 
 impl Drop for ProgramData {
@@ -1373,45 +1917,296 @@ impl ProgramData {
     Ok(())
   }
 
-  fn bind_vertex_attribs(&self, vertex_desc: Vec<VertexBufferDesc>) -> Result<(), ProgramError> {
-    let mut warnings = Vec::new();
+  /// Declare the varyings captured with transform feedback; must be called before linking.
+  fn capture_varyings(
+    &self,
+    vertex_desc: Vec<VertexBufferDesc>,
+    vertex_size: usize,
+  ) -> Result<(), ProgramError> {
+    if vertex_desc.is_empty() {
+      return Ok(());
+    }
 
-    for desc in vertex_desc {
-      match self.get_vertex_attrib_location(&desc.name) {
-        Some(_) => {
-          let index = desc.index as GLuint;
-
-          // we are not interested in the location as we’re about to change it to what we’ve
-          // decided in the semantics
-          let c_name = CString::new(desc.name.as_bytes()).unwrap();
-          unsafe { gl::BindAttribLocation(self.handle, index, c_name.as_ptr() as *const GLchar) };
-        }
+    // varyings are written as 32-bit components back to back, so the vertex type must match that layout exactly
+    for desc in &vertex_desc {
+      let capturable = desc.attrib_desc.unit_size == 4
+        && matches!(
+          desc.attrib_desc.ty,
+          VertexAttribType::Floating
+            | VertexAttribType::Integral(Normalized::No)
+            | VertexAttribType::Unsigned(Normalized::No)
+        );
 
-        None => warnings.push(format!("{} vertex attribute has no location", desc.name)),
+      if !capturable {
+        return Err(ProgramError::UncapturableVarying {
+          name: desc.name.to_owned(),
+        });
       }
     }
 
-    // we must link again after binding attribute location (yeah it sucks)
-    self.link()
-  }
+    let mut packed_size = 0;
+    for (desc, offset) in vertex_desc.iter().zip(GL33::aligned_offsets(&vertex_desc)) {
+      if offset != packed_size {
+        return Err(ProgramError::PaddedCapturedVaryings);
+      }
 
-  fn get_vertex_attrib_location(&self, name: &str) -> Option<GLuint> {
-    let location = {
-      let c_name = CString::new(name.as_bytes()).unwrap();
-      unsafe { gl::GetAttribLocation(self.handle, c_name.as_ptr() as *const GLchar) }
-    };
+      packed_size += desc.attrib_desc.size();
+    }
 
-    if location < 0 {
-      return None;
+    if packed_size != vertex_size {
+      return Err(ProgramError::PaddedCapturedVaryings);
     }
-    Some(location as _)
-  }
 
-  fn ask_uniform<T>(handle: GLuint, name: &str) -> Option<Uni<T>>
-  where
-    T: Uniform,
-  {
-    let location = {
+    let c_names: Vec<CString> = vertex_desc
+      .iter()
+      .map(|desc| CString::new(desc.name.as_bytes()).unwrap())
+      .collect();
+    let names: Vec<*const GLchar> = c_names.iter().map(|name| name.as_ptr()).collect();
+
+    unsafe {
+      gl::TransformFeedbackVaryings(
+        self.handle,
+        names.len() as _,
+        names.as_ptr(),
+        gl::INTERLEAVED_ATTRIBS,
+      );
+    }
+
+    Ok(())
+  }
+
+  fn bind_vertex_attribs(&self, vertex_desc: Vec<VertexBufferDesc>) -> Result<(), ProgramError> {
+    for desc in vertex_desc {
+      // inactive attributes are optimized out, and are not reflected either
+      if self.get_vertex_attrib_location(desc.name).is_none() {
+        trace!("{} vertex attribute has no location", desc.name);
+        continue;
+      }
+
+      // double-precision inputs are fed with glVertexAttribLPointer, and only them
+      let double_input = self
+        .get_vertex_attrib_type(desc.name)
+        .map_or(false, GL33::is_double_type);
+
+      if double_input != GL33::is_double_attrib(&desc.attrib_desc) {
+        return Err(ProgramError::MismatchingDoubleVertexAttrib {
+          name: desc.name.to_owned(),
+        });
+      }
+
+      let index = desc.index as GLuint;
+
+      // we are not interested in the location as we’re about to change it to what we’ve
+      // decided in the semantics
+      let c_name = CString::new(desc.name.as_bytes()).unwrap();
+      unsafe { gl::BindAttribLocation(self.handle, index, c_name.as_ptr() as *const GLchar) };
+    }
+
+    // we must link again after binding attribute location (yeah it sucks)
+    self.link()
+  }
+
+  fn get_vertex_attrib_location(&self, name: &str) -> Option<GLuint> {
+    let location = {
+      let c_name = CString::new(name.as_bytes()).unwrap();
+      unsafe { gl::GetAttribLocation(self.handle, c_name.as_ptr() as *const GLchar) }
+    };
+
+    if location < 0 {
+      return None;
+    }
+    Some(location as _)
+  }
+
+  /// Type of an active vertex attribute, as declared in the vertex shader.
+  fn get_vertex_attrib_type(&self, name: &str) -> Option<GLenum> {
+    self
+      .active_attribs()
+      .into_iter()
+      .find_map(|(attrib_name, _, ty)| (attrib_name == name).then_some(ty))
+  }
+
+  /// Names, sizes and types of the active vertex attributes.
+  fn active_attribs(&self) -> Vec<(String, GLint, GLenum)> {
+    let mut count: GLint = 0;
+    let mut max_len: GLint = 0;
+
+    unsafe {
+      gl::GetProgramiv(self.handle, gl::ACTIVE_ATTRIBUTES, &mut count);
+      gl::GetProgramiv(self.handle, gl::ACTIVE_ATTRIBUTE_MAX_LENGTH, &mut max_len);
+    }
+
+    (0..count as GLuint)
+      .map(|i| {
+        let mut size: GLint = 0;
+        let mut ty: GLenum = 0;
+        let name = Self::read_name(max_len, |buf_len, len, name| unsafe {
+          gl::GetActiveAttrib(self.handle, i, buf_len, len, &mut size, &mut ty, name);
+        });
+
+        (name, size, ty)
+      })
+      .collect()
+  }
+
+  /// Reflect the active vertex attributes, uniforms and uniform blocks.
+  fn reflect(&self) -> ProgramReflection {
+    // built-in inputs, such as gl_VertexID, are not attributes
+    let mut attributes: Vec<_> = self
+      .active_attribs()
+      .into_iter()
+      .filter(|(name, _, _)| !name.starts_with("gl_"))
+      .map(|(name, size, ty)| {
+        let (name, array) = Self::strip_array_suffix(name);
+
+        ActiveAttrib {
+          name,
+          ty: GL33::reflected_uni_type(ty),
+          glsl_type: GL33::glsl_type_name(ty),
+          len: size as usize,
+          array,
+        }
+      })
+      .collect();
+    attributes.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut block_count: GLint = 0;
+    let mut block_max_len: GLint = 0;
+
+    unsafe {
+      gl::GetProgramiv(self.handle, gl::ACTIVE_UNIFORM_BLOCKS, &mut block_count);
+      gl::GetProgramiv(
+        self.handle,
+        gl::ACTIVE_UNIFORM_BLOCK_MAX_NAME_LENGTH,
+        &mut block_max_len,
+      );
+    }
+
+    let mut uniform_blocks: Vec<_> = (0..block_count as GLuint)
+      .map(|i| {
+        let name = Self::read_name(block_max_len, |buf_len, len, name| unsafe {
+          gl::GetActiveUniformBlockName(self.handle, i, buf_len, len, name);
+        });
+
+        let mut size: GLint = 0;
+        unsafe {
+          gl::GetActiveUniformBlockiv(self.handle, i, gl::UNIFORM_BLOCK_DATA_SIZE, &mut size);
+        }
+
+        ActiveUniformBlock {
+          name,
+          size: size as usize,
+          members: Vec::new(),
+        }
+      })
+      .collect();
+
+    let mut count: GLint = 0;
+    let mut max_len: GLint = 0;
+
+    unsafe {
+      gl::GetProgramiv(self.handle, gl::ACTIVE_UNIFORMS, &mut count);
+      gl::GetProgramiv(self.handle, gl::ACTIVE_UNIFORM_MAX_LENGTH, &mut max_len);
+    }
+
+    let indices: Vec<GLuint> = (0..count as GLuint).collect();
+    let query = |pname| {
+      let mut values = vec![0; indices.len()];
+
+      unsafe {
+        gl::GetActiveUniformsiv(
+          self.handle,
+          count,
+          indices.as_ptr(),
+          pname,
+          values.as_mut_ptr(),
+        );
+      }
+
+      values
+    };
+    let types = query(gl::UNIFORM_TYPE);
+    let sizes = query(gl::UNIFORM_SIZE);
+    let block_indices = query(gl::UNIFORM_BLOCK_INDEX);
+    let offsets = query(gl::UNIFORM_OFFSET);
+
+    let mut uniforms = Vec::new();
+    for i in indices {
+      let name = Self::read_name(max_len, |buf_len, len, name| unsafe {
+        gl::GetActiveUniformName(self.handle, i, buf_len, len, name);
+      });
+      let (name, array) = Self::strip_array_suffix(name);
+      let ty = GL33::reflected_uni_type(types[i as usize] as GLenum);
+      let glsl_type = GL33::glsl_type_name(types[i as usize] as GLenum);
+      let len = sizes[i as usize] as usize;
+
+      // uniforms outside of blocks have a block index of -1
+      match uniform_blocks.get_mut(block_indices[i as usize] as usize) {
+        Some(block) => block.members.push(ActiveBlockMember {
+          name,
+          ty,
+          glsl_type,
+          len,
+          array,
+          offset: offsets[i as usize] as usize,
+        }),
+
+        None => uniforms.push(ActiveUniform {
+          name,
+          ty,
+          glsl_type,
+          len,
+          array,
+        }),
+      }
+    }
+
+    uniforms.sort_by(|a, b| a.name.cmp(&b.name));
+    uniform_blocks.sort_by(|a, b| a.name.cmp(&b.name));
+
+    for block in &mut uniform_blocks {
+      block.members.sort_by_key(|member| member.offset);
+    }
+
+    ProgramReflection {
+      attributes,
+      uniforms,
+      uniform_blocks,
+    }
+  }
+
+  /// Read a name of at most `max_len` bytes (including the nul terminator) with `get`, which receives the size of the
+  /// buffer, the length to write and the buffer to write the name to.
+  fn read_name(max_len: GLint, get: impl FnOnce(GLsizei, *mut GLsizei, *mut GLchar)) -> String {
+    let mut name = vec![0u8; max_len.max(1) as usize];
+    let mut len: GLsizei = 0;
+
+    get(
+      name.len() as GLsizei,
+      &mut len,
+      name.as_mut_ptr() as *mut GLchar,
+    );
+    name.truncate(len as usize);
+
+    String::from_utf8_lossy(&name).into_owned()
+  }
+
+  /// Remove the `[0]` suffix of arrays, returning whether it was present.
+  fn strip_array_suffix(mut name: String) -> (String, bool) {
+    let array = name.ends_with("[0]");
+
+    if array {
+      name.truncate(name.len() - 3);
+    }
+
+    (name, array)
+  }
+
+  fn ask_uniform<T>(handle: GLuint, name: &str) -> Option<Uni<T>>
+  where
+    T: Uniform,
+  {
+    let location = {
       let c_name = CString::new(name.as_bytes()).unwrap();
 
       match T::uni_type() {
@@ -1446,16 +2241,9 @@ impl GL33 {
   pub fn new(context_active: ContextActive) -> Option<Self> {
     let state = StateRef::new(context_active)?;
 
-    // some initialization things
-    Self::init();
-
     Some(Self { state })
   }
 
-  fn init() {
-    unsafe { gl::PrimitiveRestartIndex(u32::MAX) };
-  }
-
   fn get_max(resource: GLenum) -> usize {
     let mut max: GLint = 0;
     unsafe {
@@ -1472,10 +2260,51 @@ impl GL33 {
     Self::get_max(gl::MAX_UNIFORM_BUFFER_BINDINGS)
   }
 
+  /// Major and minor versions of the OpenGL context.
+  fn get_version() -> (GLint, GLint) {
+    let (mut major, mut minor) = (0, 0);
+    unsafe {
+      gl::GetIntegerv(gl::MAJOR_VERSION, &mut major);
+      gl::GetIntegerv(gl::MINOR_VERSION, &mut minor);
+    }
+
+    (major, minor)
+  }
+
+  fn has_extension(extension: &[u8]) -> bool {
+    let mut extension_count: GLint = 0;
+    unsafe {
+      gl::GetIntegerv(gl::NUM_EXTENSIONS, &mut extension_count);
+    }
+
+    (0..extension_count as GLuint).any(|i| unsafe {
+      let name = gl::GetStringi(gl::EXTENSIONS, i);
+      !name.is_null() && CStr::from_ptr(name as _).to_bytes() == extension
+    })
+  }
+
+  fn is_base_instance_supported() -> bool {
+    (Self::get_version() >= (4, 2) || Self::has_extension(b"GL_ARB_base_instance"))
+      && gl::DrawElementsInstancedBaseVertexBaseInstance::is_loaded()
+      && gl::DrawArraysInstancedBaseInstance::is_loaded()
+  }
+
+  fn is_draw_indirect_supported() -> bool {
+    (Self::get_version() >= (4, 0) || Self::has_extension(b"GL_ARB_draw_indirect"))
+      && gl::DrawElementsIndirect::is_loaded()
+      && gl::DrawArraysIndirect::is_loaded()
+  }
+
+  fn is_tessellation_supported() -> bool {
+    (Self::get_version() >= (4, 0) || Self::has_extension(b"GL_ARB_tessellation_shader"))
+      && gl::PatchParameteri::is_loaded()
+  }
+
   fn build_interleaved_buffer<V>(
     &self,
     storage: &Interleaved<V>,
     instanced: bool,
+    usage: Usage,
   ) -> Result<BuiltVertexBuffers, VertexEntityError>
   where
     V: Vertex,
@@ -1491,12 +2320,12 @@ impl GL33 {
       });
     }
 
-    let buffer = Buffer::from_slice(&self.state, gl::ARRAY_BUFFER, &storage.vertices());
-    self
-      .state
-      .borrow_mut()
-      .bound_array_buffer
-      .set(buffer.handle);
+    let buffer = StorageBuffer::new(
+      &mut self.state.borrow_mut().bound_array_buffer,
+      gl::ARRAY_BUFFER,
+      storage.vertices(),
+      usage,
+    );
 
     GL33::set_vertex_pointers(&V::vertex_desc(), instanced);
 
@@ -1510,6 +2339,7 @@ impl GL33 {
     &self,
     storage: &Deinterleaved<V>,
     instanced: bool,
+    usage: Usage,
   ) -> Result<BuiltVertexBuffers, VertexEntityError>
   where
     V: Vertex,
@@ -1520,8 +2350,13 @@ impl GL33 {
       .iter()
       .zip(V::vertex_desc())
       .map(|(vertices, fmt)| {
-        let buffer = Buffer::from_slice(&self.state, gl::ARRAY_BUFFER, &vertices);
-        let field_len = fmt.attrib_desc.unit_size * fmt.attrib_desc.dim.size();
+        let buffer = StorageBuffer::new(
+          &mut self.state.borrow_mut().bound_array_buffer,
+          gl::ARRAY_BUFFER,
+          vertices,
+          usage,
+        );
+        let field_len = fmt.attrib_desc.size();
 
         if len == 0 {
           len = vertices.len() / field_len;
@@ -1529,12 +2364,6 @@ impl GL33 {
           return Err(VertexEntityError::Creation { cause: None });
         }
 
-        self
-          .state
-          .borrow_mut()
-          .bound_array_buffer
-          .set(buffer.handle);
-
         GL33::set_vertex_pointers(&[fmt], instanced);
 
         Ok(buffer)
@@ -1547,10 +2376,33 @@ impl GL33 {
     })
   }
 
+  fn build_shared_buffer<V>(&self, storage: &Shared<V>, instanced: bool) -> BuiltVertexBuffers
+  where
+    V: Vertex,
+  {
+    let handle = storage.buffer_handle() as GLuint;
+
+    self
+      .state
+      .borrow_mut()
+      .bound_array_buffer
+      .set_if_invalid(handle, || unsafe {
+        gl::BindBuffer(gl::ARRAY_BUFFER, handle);
+      });
+
+    GL33::set_vertex_pointers(&V::vertex_desc(), instanced);
+
+    BuiltVertexBuffers {
+      buffers: Some(VertexEntityBuffers::Shared),
+      len: storage.len(),
+    }
+  }
+
   fn build_vertex_buffers<V>(
     &self,
     storage: &mut impl AsVertexStorage<V>,
     instanced: bool,
+    usage: Usage,
   ) -> Result<BuiltVertexBuffers, VertexEntityError>
   where
     V: Vertex,
@@ -1561,27 +2413,235 @@ impl GL33 {
         len: 0,
       }),
 
-      VertexStorage::Interleaved(storage) => self.build_interleaved_buffer(storage, instanced),
-      VertexStorage::Deinterleaved(storage) => self.build_deinterleaved_buffers(storage, instanced),
+      VertexStorage::Interleaved(storage) => {
+        self.build_interleaved_buffer(storage, instanced, usage)
+      }
+
+      VertexStorage::Deinterleaved(storage) => {
+        self.build_deinterleaved_buffers(storage, instanced, usage)
+      }
+
+      VertexStorage::Shared(storage) => Ok(self.build_shared_buffer(storage, instanced)),
+    }
+  }
+
+  /// Update the vertex buffers of a vertex entity with the vertices in `range`.
+  ///
+  /// Updating is allowed only if the storage has the same shape as the one used to create the vertex entity
+  /// (interleaved/interleaved or deinterleaved/deinterleaved). Buffers are reallocated if the storage has outgrown them,
+  /// and the interleaved buffer is created if the vertex entity was created without any vertex.
+  fn update_vertex_buffers<V>(
+    state: &mut State,
+    handle: usize,
+    storage: VertexStorage<V>,
+    instanced: bool,
+    range: Range<usize>,
+  ) -> Result<(), VertexEntityError>
+  where
+    V: Vertex,
+  {
+    let data = state
+      .vertex_entities
+      .get_mut(&handle)
+      .ok_or_else(|| VertexEntityError::UpdateVertexStorage { cause: None })?;
+    let vao = data.vao;
+    let (buffers, usage) = if instanced {
+      (&mut data.instance_buffers, data.instance_usage)
+    } else {
+      data.vertex_len = storage.len();
+      (&mut data.vertex_buffers, data.vertex_usage)
+    };
+
+    match (storage, buffers) {
+      (VertexStorage::Interleaved(storage), Some(VertexEntityBuffers::Interleaved(buffer))) => {
+        let changed = buffer
+          .upload(&mut state.bound_array_buffer, storage.vertices(), range)
+          .map_err(|e| VertexEntityError::UpdateVertexStorage {
+            cause: Some(Box::new(e)),
+          })?;
+
+        if changed {
+          GL33::attach_vertex_buffer(
+            &mut state.bound_vertex_array,
+            &mut state.bound_array_buffer,
+            vao,
+            buffer.handle(),
+            &V::vertex_desc(),
+            instanced,
+          );
+        }
+
+        Ok(())
+      }
+
+      (VertexStorage::Interleaved(storage), buffers @ None) => {
+        if storage.vertices().is_empty() {
+          return Ok(());
+        }
+
+        state.bound_vertex_array.set_if_invalid(vao, || unsafe {
+          gl::BindVertexArray(vao);
+        });
+
+        let buffer = StorageBuffer::new(
+          &mut state.bound_array_buffer,
+          gl::ARRAY_BUFFER,
+          storage.vertices(),
+          usage,
+        );
+        GL33::set_vertex_pointers(&V::vertex_desc(), instanced);
+        *buffers = Some(VertexEntityBuffers::Interleaved(buffer));
+
+        Ok(())
+      }
+
+      (
+        VertexStorage::Deinterleaved(storage),
+        Some(VertexEntityBuffers::Deinterleaved(buffers)),
+      ) => {
+        let components = storage.components_list().iter().zip(V::vertex_desc());
+
+        for ((comp, fmt), buffer) in components.zip(buffers) {
+          // components are stored as bytes, so the range must be expressed in bytes as well
+          let field_len = fmt.attrib_desc.size();
+          let len = storage.len() * field_len;
+          let changed = buffer
+            .upload(
+              &mut state.bound_array_buffer,
+              &comp[..len],
+              range.start * field_len..range.end * field_len,
+            )
+            .map_err(|e| VertexEntityError::UpdateVertexStorage {
+              cause: Some(Box::new(e)),
+            })?;
+
+          if changed {
+            GL33::attach_vertex_buffer(
+              &mut state.bound_vertex_array,
+              &mut state.bound_array_buffer,
+              vao,
+              buffer.handle(),
+              &[fmt],
+              instanced,
+            );
+          }
+        }
+
+        Ok(())
+      }
+
+      _ => Err(VertexEntityError::UpdateVertexStorage { cause: None }),
+    }
+  }
+
+  /// Update the buffer of a single attribute of a vertex entity with deinterleaved vertices.
+  fn update_vertex_attribute_buffer<V>(
+    state: &mut State,
+    handle: usize,
+    storage: &Deinterleaved<V>,
+    rank: usize,
+  ) -> Result<(), VertexEntityError>
+  where
+    V: Vertex,
+  {
+    let data = state
+      .vertex_entities
+      .get_mut(&handle)
+      .ok_or_else(|| VertexEntityError::UpdateVertexStorage { cause: None })?;
+    let vao = data.vao;
+
+    let buffer = match &mut data.vertex_buffers {
+      Some(VertexEntityBuffers::Deinterleaved(buffers)) => &mut buffers[rank],
+      _ => return Err(VertexEntityError::UpdateVertexStorage { cause: None }),
+    };
+
+    // the other attributes are not updated, so the number of vertices cannot change
+    let fmt = V::vertex_desc()[rank];
+    let components = &storage.components_list()[rank];
+    let len = data.vertex_len * fmt.attrib_desc.size();
+
+    if components.len() != len {
+      return Err(VertexEntityError::UpdateVertexStorage { cause: None });
     }
+
+    let changed = buffer
+      .upload(&mut state.bound_array_buffer, components, 0..len)
+      .map_err(|e| VertexEntityError::UpdateVertexStorage {
+        cause: Some(Box::new(e)),
+      })?;
+
+    if changed {
+      GL33::attach_vertex_buffer(
+        &mut state.bound_vertex_array,
+        &mut state.bound_array_buffer,
+        vao,
+        buffer.handle(),
+        &[fmt],
+        false,
+      );
+    }
+
+    Ok(())
+  }
+
+  /// Attach the buffer currently used for rendering a storage to a vertex array.
+  fn attach_vertex_buffer(
+    bound_vertex_array: &mut Cached<GLuint>,
+    bound_array_buffer: &mut Cached<GLuint>,
+    vao: GLuint,
+    handle: GLuint,
+    descriptors: &[VertexBufferDesc],
+    instanced: bool,
+  ) {
+    bound_vertex_array.set_if_invalid(vao, || unsafe {
+      gl::BindVertexArray(vao);
+    });
+
+    bound_array_buffer.set_if_invalid(handle, || unsafe {
+      gl::BindBuffer(gl::ARRAY_BUFFER, handle);
+    });
+
+    GL33::set_vertex_pointers(descriptors, instanced);
   }
 
-  fn build_index_buffer(&mut self, indices: &Vec<u32>) -> Option<Buffer> {
+  fn build_index_buffer<I>(&mut self, indices: &[I], usage: Usage) -> Option<StorageBuffer> {
     if indices.is_empty() {
       return None;
     }
 
-    let buffer = Buffer::from_slice(&self.state, gl::ELEMENT_ARRAY_BUFFER, &indices);
+    let buffer = StorageBuffer::new(
+      &mut self.state.borrow_mut().bound_array_buffer,
+      gl::ELEMENT_ARRAY_BUFFER,
+      indices,
+      usage,
+    );
 
     self
       .state
       .borrow_mut()
       .bound_element_array_buffer
-      .set(buffer.handle);
+      .set(buffer.handle());
 
     Some(buffer)
   }
 
+  /// Use the buffer of an index buffer resource as index buffer of the currently bound vertex array.
+  fn bind_shared_index_buffer<I>(&mut self, indices: &Shared<I>) -> GLuint {
+    let handle = indices.buffer_handle() as GLuint;
+
+    unsafe {
+      gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, handle);
+    }
+
+    self
+      .state
+      .borrow_mut()
+      .bound_element_array_buffer
+      .set(handle);
+
+    handle
+  }
+
   /// Give OpenGL types information on the content of the VBO by setting vertex descriptors and pointers
   /// to buffer memory.
   fn set_vertex_pointers(descriptors: &[VertexBufferDesc], instanced: bool) {
@@ -1638,60 +2698,82 @@ impl GL33 {
 
   /// Set the vertex component OpenGL pointers regarding the index of the component and the vertex
   /// stride.
+  ///
+  /// Components spanning several locations, such as matrices, have one pointer per location, set to consecutive
+  /// indices.
   fn set_component_format(stride: GLsizei, off: usize, desc: &VertexBufferDesc, instanced: bool) {
     let attrib_desc = &desc.attrib_desc;
-    let index = desc.index as GLuint;
+    let location_size = attrib_desc.size() / attrib_desc.locations;
 
-    unsafe {
-      match attrib_desc.ty {
-        VertexAttribType::Floating => {
-          gl::VertexAttribPointer(
-            index,
-            Self::dim_as_size(attrib_desc.dim),
-            Self::opengl_sized_type(&attrib_desc),
-            gl::FALSE,
-            stride,
-            ptr::null::<c_void>().add(off),
-          );
-        }
+    for location in 0..attrib_desc.locations {
+      let index = (desc.index + location) as GLuint;
+      let off = off + location * location_size;
 
-        VertexAttribType::Integral(Normalized::No)
-        | VertexAttribType::Unsigned(Normalized::No)
-        | VertexAttribType::Boolean => {
-          // non-normalized integrals / booleans
-          gl::VertexAttribIPointer(
-            index,
-            Self::dim_as_size(attrib_desc.dim),
-            Self::opengl_sized_type(&attrib_desc),
-            stride,
-            ptr::null::<c_void>().add(off),
-          );
-        }
+      unsafe {
+        match attrib_desc.ty {
+          // non-normalized packed integrals are converted to floating-point values as well
+          VertexAttribType::Floating
+          | VertexAttribType::Integral2_10_10_10Rev(Normalized::No)
+          | VertexAttribType::Unsigned2_10_10_10Rev(Normalized::No) => {
+            gl::VertexAttribPointer(
+              index,
+              Self::dim_as_size(attrib_desc.dim),
+              Self::opengl_sized_type(&attrib_desc),
+              gl::FALSE,
+              stride,
+              ptr::null::<c_void>().add(off),
+            );
+          }
 
-        _ => {
-          // normalized integrals
-          gl::VertexAttribPointer(
-            index,
-            Self::dim_as_size(attrib_desc.dim),
-            Self::opengl_sized_type(&attrib_desc),
-            gl::TRUE,
-            stride,
-            ptr::null::<c_void>().add(off),
-          );
+          #[cfg(feature = "shader-f64")]
+          VertexAttribType::Floating64 => {
+            gl::VertexAttribLPointer(
+              index,
+              Self::dim_as_size(attrib_desc.dim),
+              Self::opengl_sized_type(&attrib_desc),
+              stride,
+              ptr::null::<c_void>().add(off),
+            );
+          }
+
+          VertexAttribType::Integral(Normalized::No)
+          | VertexAttribType::Unsigned(Normalized::No)
+          | VertexAttribType::Boolean => {
+            // non-normalized integrals / booleans
+            gl::VertexAttribIPointer(
+              index,
+              Self::dim_as_size(attrib_desc.dim),
+              Self::opengl_sized_type(&attrib_desc),
+              stride,
+              ptr::null::<c_void>().add(off),
+            );
+          }
+
+          _ => {
+            // normalized integrals
+            gl::VertexAttribPointer(
+              index,
+              Self::dim_as_size(attrib_desc.dim),
+              Self::opengl_sized_type(&attrib_desc),
+              gl::TRUE,
+              stride,
+              ptr::null::<c_void>().add(off),
+            );
+          }
         }
-      }
 
-      // set vertex attribute divisor based on the vertex instancing configuration
-      let divisor = instanced as GLuint;
-      gl::VertexAttribDivisor(index, divisor);
+        // set vertex attribute divisor based on the vertex instancing configuration; per-vertex attributes have none
+        let divisor = if instanced { desc.divisor as GLuint } else { 0 };
+        gl::VertexAttribDivisor(index, divisor);
 
-      gl::EnableVertexAttribArray(index);
+        gl::EnableVertexAttribArray(index);
+      }
     }
   }
 
   /// Weight in bytes of a vertex component.
   fn component_weight(f: &VertexAttribDesc) -> usize {
-    Self::dim_as_size(f.dim) as usize * f.unit_size
+    f.size()
   }
 
   fn dim_as_size(d: VertexAttribDim) -> GLint {
@@ -1711,11 +2793,509 @@ impl GL33 {
       (VertexAttribType::Unsigned(_), 1) | (VertexAttribType::Boolean, 1) => gl::UNSIGNED_BYTE,
       (VertexAttribType::Unsigned(_), 2) => gl::UNSIGNED_SHORT,
       (VertexAttribType::Unsigned(_), 4) => gl::UNSIGNED_INT,
+      (VertexAttribType::Floating, 2) => gl::HALF_FLOAT,
       (VertexAttribType::Floating, 4) => gl::FLOAT,
+      (VertexAttribType::Floating, 8) => gl::DOUBLE,
+      #[cfg(feature = "shader-f64")]
+      (VertexAttribType::Floating64, 8) => gl::DOUBLE,
+      (VertexAttribType::Integral2_10_10_10Rev(_), 4) => gl::INT_2_10_10_10_REV,
+      (VertexAttribType::Unsigned2_10_10_10Rev(_), 4) => gl::UNSIGNED_INT_2_10_10_10_REV,
       _ => panic!("unsupported vertex component format: {:?}", f),
     }
   }
 
+  /// Whether a vertex attribute is fed to double-precision shader inputs.
+  fn is_double_attrib(f: &VertexAttribDesc) -> bool {
+    match f.ty {
+      #[cfg(feature = "shader-f64")]
+      VertexAttribType::Floating64 => true,
+      _ => false,
+    }
+  }
+
+  /// Whether a shader input type is double-precision.
+  fn is_double_type(ty: GLenum) -> bool {
+    matches!(
+      ty,
+      gl::DOUBLE
+        | gl::DOUBLE_VEC2
+        | gl::DOUBLE_VEC3
+        | gl::DOUBLE_VEC4
+        | gl::DOUBLE_MAT2
+        | gl::DOUBLE_MAT3
+        | gl::DOUBLE_MAT4
+        | gl::DOUBLE_MAT2x3
+        | gl::DOUBLE_MAT2x4
+        | gl::DOUBLE_MAT3x2
+        | gl::DOUBLE_MAT3x4
+        | gl::DOUBLE_MAT4x2
+        | gl::DOUBLE_MAT4x3
+    )
+  }
+
+  /// Type of uniforms and vertex attributes as a [`UniType`], if it has one.
+  fn reflected_uni_type(ty: GLenum) -> Option<UniType> {
+    let uni_type = match ty {
+      gl::FLOAT => UniType::Floating(UniDim::Dim1),
+      gl::FLOAT_VEC2 => UniType::Floating(UniDim::Dim2),
+      gl::FLOAT_VEC3 => UniType::Floating(UniDim::Dim3),
+      gl::FLOAT_VEC4 => UniType::Floating(UniDim::Dim4),
+      gl::INT => UniType::Integral(UniDim::Dim1),
+      gl::INT_VEC2 => UniType::Integral(UniDim::Dim2),
+      gl::INT_VEC3 => UniType::Integral(UniDim::Dim3),
+      gl::INT_VEC4 => UniType::Integral(UniDim::Dim4),
+      gl::UNSIGNED_INT => UniType::Unsigned(UniDim::Dim1),
+      gl::UNSIGNED_INT_VEC2 => UniType::Unsigned(UniDim::Dim2),
+      gl::UNSIGNED_INT_VEC3 => UniType::Unsigned(UniDim::Dim3),
+      gl::UNSIGNED_INT_VEC4 => UniType::Unsigned(UniDim::Dim4),
+      gl::BOOL => UniType::Boolean(UniDim::Dim1),
+      gl::BOOL_VEC2 => UniType::Boolean(UniDim::Dim2),
+      gl::BOOL_VEC3 => UniType::Boolean(UniDim::Dim3),
+      gl::BOOL_VEC4 => UniType::Boolean(UniDim::Dim4),
+      gl::FLOAT_MAT2 => UniType::Matrix(UniMatDim::Mat22),
+      gl::FLOAT_MAT3 => UniType::Matrix(UniMatDim::Mat33),
+      gl::FLOAT_MAT4 => UniType::Matrix(UniMatDim::Mat44),
+
+      #[cfg(feature = "shader-f64")]
+      gl::DOUBLE => UniType::Floating64(UniDim::Dim1),
+      #[cfg(feature = "shader-f64")]
+      gl::DOUBLE_VEC2 => UniType::Floating64(UniDim::Dim2),
+      #[cfg(feature = "shader-f64")]
+      gl::DOUBLE_VEC3 => UniType::Floating64(UniDim::Dim3),
+      #[cfg(feature = "shader-f64")]
+      gl::DOUBLE_VEC4 => UniType::Floating64(UniDim::Dim4),
+      #[cfg(feature = "shader-f64")]
+      gl::DOUBLE_MAT2 => UniType::Matrix64(UniMatDim::Mat22),
+      #[cfg(feature = "shader-f64")]
+      gl::DOUBLE_MAT3 => UniType::Matrix64(UniMatDim::Mat33),
+      #[cfg(feature = "shader-f64")]
+      gl::DOUBLE_MAT4 => UniType::Matrix64(UniMatDim::Mat44),
+
+      // floating samplers also sample normalized textures
+      gl::SAMPLER_1D => UniType::Sampler(Type::Floating, Dim::Dim1),
+      gl::SAMPLER_2D => UniType::Sampler(Type::Floating, Dim::Dim2),
+      gl::SAMPLER_3D => UniType::Sampler(Type::Floating, Dim::Dim3),
+      gl::SAMPLER_CUBE => UniType::Sampler(Type::Floating, Dim::Cubemap),
+      gl::SAMPLER_1D_ARRAY => UniType::Sampler(Type::Floating, Dim::Dim1Array),
+      gl::SAMPLER_2D_ARRAY => UniType::Sampler(Type::Floating, Dim::Dim2Array),
+      gl::INT_SAMPLER_1D => UniType::Sampler(Type::Integral, Dim::Dim1),
+      gl::INT_SAMPLER_2D => UniType::Sampler(Type::Integral, Dim::Dim2),
+      gl::INT_SAMPLER_3D => UniType::Sampler(Type::Integral, Dim::Dim3),
+      gl::INT_SAMPLER_CUBE => UniType::Sampler(Type::Integral, Dim::Cubemap),
+      gl::INT_SAMPLER_1D_ARRAY => UniType::Sampler(Type::Integral, Dim::Dim1Array),
+      gl::INT_SAMPLER_2D_ARRAY => UniType::Sampler(Type::Integral, Dim::Dim2Array),
+      gl::UNSIGNED_INT_SAMPLER_1D => UniType::Sampler(Type::Unsigned, Dim::Dim1),
+      gl::UNSIGNED_INT_SAMPLER_2D => UniType::Sampler(Type::Unsigned, Dim::Dim2),
+      gl::UNSIGNED_INT_SAMPLER_3D => UniType::Sampler(Type::Unsigned, Dim::Dim3),
+      gl::UNSIGNED_INT_SAMPLER_CUBE => UniType::Sampler(Type::Unsigned, Dim::Cubemap),
+      gl::UNSIGNED_INT_SAMPLER_1D_ARRAY => UniType::Sampler(Type::Unsigned, Dim::Dim1Array),
+      gl::UNSIGNED_INT_SAMPLER_2D_ARRAY => UniType::Sampler(Type::Unsigned, Dim::Dim2Array),
+
+      // shadow samplers sample depth textures, which are floating textures compared to a reference value
+      gl::SAMPLER_1D_SHADOW => UniType::Sampler(Type::Floating, Dim::Dim1),
+      gl::SAMPLER_2D_SHADOW => UniType::Sampler(Type::Floating, Dim::Dim2),
+      gl::SAMPLER_CUBE_SHADOW => UniType::Sampler(Type::Floating, Dim::Cubemap),
+      gl::SAMPLER_1D_ARRAY_SHADOW => UniType::Sampler(Type::Floating, Dim::Dim1Array),
+      gl::SAMPLER_2D_ARRAY_SHADOW => UniType::Sampler(Type::Floating, Dim::Dim2Array),
+
+      _ => return None,
+    };
+
+    Some(uni_type)
+  }
+
+  /// GLSL name of the type of uniforms and vertex attributes, e.g. `vec3` or `mat3x4`.
+  ///
+  /// Types unknown to OpenGL 3.3 and 4.x are named after their GL enum.
+  fn glsl_type_name(ty: GLenum) -> String {
+    let name = match ty {
+      gl::FLOAT => "float",
+      gl::FLOAT_VEC2 => "vec2",
+      gl::FLOAT_VEC3 => "vec3",
+      gl::FLOAT_VEC4 => "vec4",
+      gl::DOUBLE => "double",
+      gl::DOUBLE_VEC2 => "dvec2",
+      gl::DOUBLE_VEC3 => "dvec3",
+      gl::DOUBLE_VEC4 => "dvec4",
+      gl::INT => "int",
+      gl::INT_VEC2 => "ivec2",
+      gl::INT_VEC3 => "ivec3",
+      gl::INT_VEC4 => "ivec4",
+      gl::UNSIGNED_INT => "uint",
+      gl::UNSIGNED_INT_VEC2 => "uvec2",
+      gl::UNSIGNED_INT_VEC3 => "uvec3",
+      gl::UNSIGNED_INT_VEC4 => "uvec4",
+      gl::BOOL => "bool",
+      gl::BOOL_VEC2 => "bvec2",
+      gl::BOOL_VEC3 => "bvec3",
+      gl::BOOL_VEC4 => "bvec4",
+      gl::FLOAT_MAT2 => "mat2",
+      gl::FLOAT_MAT3 => "mat3",
+      gl::FLOAT_MAT4 => "mat4",
+      gl::FLOAT_MAT2x3 => "mat2x3",
+      gl::FLOAT_MAT2x4 => "mat2x4",
+      gl::FLOAT_MAT3x2 => "mat3x2",
+      gl::FLOAT_MAT3x4 => "mat3x4",
+      gl::FLOAT_MAT4x2 => "mat4x2",
+      gl::FLOAT_MAT4x3 => "mat4x3",
+      gl::DOUBLE_MAT2 => "dmat2",
+      gl::DOUBLE_MAT3 => "dmat3",
+      gl::DOUBLE_MAT4 => "dmat4",
+      gl::DOUBLE_MAT2x3 => "dmat2x3",
+      gl::DOUBLE_MAT2x4 => "dmat2x4",
+      gl::DOUBLE_MAT3x2 => "dmat3x2",
+      gl::DOUBLE_MAT3x4 => "dmat3x4",
+      gl::DOUBLE_MAT4x2 => "dmat4x2",
+      gl::DOUBLE_MAT4x3 => "dmat4x3",
+
+      gl::SAMPLER_1D => "sampler1D",
+      gl::SAMPLER_2D => "sampler2D",
+      gl::SAMPLER_3D => "sampler3D",
+      gl::SAMPLER_CUBE => "samplerCube",
+      gl::SAMPLER_1D_ARRAY => "sampler1DArray",
+      gl::SAMPLER_2D_ARRAY => "sampler2DArray",
+      gl::SAMPLER_CUBE_MAP_ARRAY => "samplerCubeArray",
+      gl::SAMPLER_2D_RECT => "sampler2DRect",
+      gl::SAMPLER_2D_MULTISAMPLE => "sampler2DMS",
+      gl::SAMPLER_2D_MULTISAMPLE_ARRAY => "sampler2DMSArray",
+      gl::SAMPLER_BUFFER => "samplerBuffer",
+      gl::SAMPLER_1D_SHADOW => "sampler1DShadow",
+      gl::SAMPLER_2D_SHADOW => "sampler2DShadow",
+      gl::SAMPLER_CUBE_SHADOW => "samplerCubeShadow",
+      gl::SAMPLER_1D_ARRAY_SHADOW => "sampler1DArrayShadow",
+      gl::SAMPLER_2D_ARRAY_SHADOW => "sampler2DArrayShadow",
+      gl::SAMPLER_CUBE_MAP_ARRAY_SHADOW => "samplerCubeArrayShadow",
+      gl::SAMPLER_2D_RECT_SHADOW => "sampler2DRectShadow",
+      gl::INT_SAMPLER_1D => "isampler1D",
+      gl::INT_SAMPLER_2D => "isampler2D",
+      gl::INT_SAMPLER_3D => "isampler3D",
+      gl::INT_SAMPLER_CUBE => "isamplerCube",
+      gl::INT_SAMPLER_1D_ARRAY => "isampler1DArray",
+      gl::INT_SAMPLER_2D_ARRAY => "isampler2DArray",
+      gl::INT_SAMPLER_CUBE_MAP_ARRAY => "isamplerCubeArray",
+      gl::INT_SAMPLER_2D_RECT => "isampler2DRect",
+      gl::INT_SAMPLER_2D_MULTISAMPLE => "isampler2DMS",
+      gl::INT_SAMPLER_2D_MULTISAMPLE_ARRAY => "isampler2DMSArray",
+      gl::INT_SAMPLER_BUFFER => "isamplerBuffer",
+      gl::UNSIGNED_INT_SAMPLER_1D => "usampler1D",
+      gl::UNSIGNED_INT_SAMPLER_2D => "usampler2D",
+      gl::UNSIGNED_INT_SAMPLER_3D => "usampler3D",
+      gl::UNSIGNED_INT_SAMPLER_CUBE => "usamplerCube",
+      gl::UNSIGNED_INT_SAMPLER_1D_ARRAY => "usampler1DArray",
+      gl::UNSIGNED_INT_SAMPLER_2D_ARRAY => "usampler2DArray",
+      gl::UNSIGNED_INT_SAMPLER_CUBE_MAP_ARRAY => "usamplerCubeArray",
+      gl::UNSIGNED_INT_SAMPLER_2D_RECT => "usampler2DRect",
+      gl::UNSIGNED_INT_SAMPLER_2D_MULTISAMPLE => "usampler2DMS",
+      gl::UNSIGNED_INT_SAMPLER_2D_MULTISAMPLE_ARRAY => "usampler2DMSArray",
+      gl::UNSIGNED_INT_SAMPLER_BUFFER => "usamplerBuffer",
+      gl::UNSIGNED_INT_ATOMIC_COUNTER => "atomic_uint",
+
+      _ => return format!("GL type {ty:#x}"),
+    };
+
+    name.to_owned()
+  }
+
+  /// Whether a uniform declared as `expected` in Rust can be set on a uniform of type `ty`.
+  ///
+  /// Array lengths are not compared, as compilers are free to trim the unused elements of arrays.
+  fn is_compatible_uniform(expected: UniType, ty: UniType) -> bool {
+    match (expected, ty) {
+      (UniType::Sampler(pixel_ty, dim), UniType::Sampler(sampled_ty, sampled_dim)) => {
+        // floating samplers also sample normalized textures
+        let sampled_pixel_ty = match pixel_ty {
+          Type::NormIntegral | Type::NormUnsigned | Type::Floating => Type::Floating,
+          _ => pixel_ty,
+        };
+
+        sampled_pixel_ty == sampled_ty && dim == sampled_dim
+      }
+
+      _ => expected == ty,
+    }
+  }
+
+  /// Whether a vertex attribute can feed a shader input of type `ty`.
+  ///
+  /// Vectors can have a different number of components than the input, as missing components default to
+  /// `(0, 0, 0, 1)` and extra components are dropped; the type of the components must match, though, as well as the
+  /// dimensions of matrices.
+  fn is_compatible_vertex_attrib(desc: &VertexAttribDesc, ty: UniType) -> bool {
+    if desc.locations > 1 {
+      let mat_dim = match (desc.dim, desc.locations) {
+        (VertexAttribDim::Dim2, 2) => UniMatDim::Mat22,
+        (VertexAttribDim::Dim3, 3) => UniMatDim::Mat33,
+        (VertexAttribDim::Dim4, 4) => UniMatDim::Mat44,
+        _ => return false,
+      };
+
+      return match desc.ty {
+        VertexAttribType::Floating => ty == UniType::Matrix(mat_dim),
+        #[cfg(feature = "shader-f64")]
+        VertexAttribType::Floating64 => ty == UniType::Matrix64(mat_dim),
+        _ => false,
+      };
+    }
+
+    match (desc.ty, ty) {
+      // normalized and packed attributes are exposed as floating-point values
+      (
+        VertexAttribType::Floating
+        | VertexAttribType::Integral(Normalized::Yes)
+        | VertexAttribType::Unsigned(Normalized::Yes)
+        | VertexAttribType::Integral2_10_10_10Rev(_)
+        | VertexAttribType::Unsigned2_10_10_10Rev(_),
+        UniType::Floating(_),
+      ) => true,
+      (VertexAttribType::Integral(Normalized::No), UniType::Integral(_)) => true,
+      (VertexAttribType::Unsigned(Normalized::No), UniType::Unsigned(_)) => true,
+      // GLSL has no boolean inputs; booleans are fed as integers
+      (VertexAttribType::Boolean, UniType::Integral(_) | UniType::Unsigned(_)) => true,
+      #[cfg(feature = "shader-f64")]
+      (VertexAttribType::Floating64, UniType::Floating64(_)) => true,
+      _ => false,
+    }
+  }
+
+  /// Check that active vertex inputs can be fed by the vertex attributes of the same name.
+  ///
+  /// Inputs which type is not represented by a [`UniType`] (e.g. non-square matrices) cannot be fed by any vertex
+  /// attribute.
+  fn validate_vertex_attribs(
+    reflection: &ProgramReflection,
+    vertex_desc: Vec<VertexBufferDesc>,
+  ) -> Result<(), ShaderError> {
+    for desc in vertex_desc {
+      let attrib = match reflection.attribute(desc.name) {
+        Some(attrib) => attrib,
+        None => continue,
+      };
+
+      // a vertex attribute cannot feed an array of inputs
+      let compatible = !attrib.array
+        && attrib.ty.map_or(false, |ty| {
+          Self::is_compatible_vertex_attrib(&desc.attrib_desc, ty)
+        });
+
+      if !compatible {
+        return Err(ShaderError::VertexAttribTypeMismatch {
+          name: desc.name.to_owned(),
+          rust_type: desc.attrib_desc.to_string(),
+          glsl_type: Self::glsl_type(&attrib.glsl_type, attrib.array, attrib.len),
+        });
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Check that the active uniform, or uniform block, `name` can be set with `T`.
+  ///
+  /// Rust arrays of several elements must be bound to arrays, and other uniforms to non-array uniforms (or arrays of
+  /// which only the first element is used). Uniform blocks must have the size of the aligned Rust type. Uniforms which
+  /// type is not represented by a [`UniType`] (e.g. non-square matrices) cannot be set.
+  fn validate_uniform<T>(reflection: &ProgramReflection, name: &str) -> Result<(), ShaderError>
+  where
+    T: Uniform,
+  {
+    let mismatch = |glsl_type| ShaderError::UniTypeMismatch {
+      field: name.to_owned(),
+      rust_type: Self::uniform_rust_type::<T>(),
+      glsl_type,
+    };
+
+    if let Some(size) = T::BLOCK_SIZE {
+      return match reflection.uniform_block(name) {
+        Some(block) if block.size != size => {
+          Err(mismatch(format!("uniform block of {} bytes", block.size)))
+        }
+        _ => Ok(()),
+      };
+    }
+
+    let uniform = match reflection.uniform(name) {
+      Some(uniform) => uniform,
+      None => return Ok(()),
+    };
+
+    let compatible_len = if T::LEN > 1 {
+      uniform.array
+    } else {
+      !uniform.array || uniform.len == 1
+    };
+    let compatible_ty = uniform
+      .ty
+      .map_or(false, |ty| Self::is_compatible_uniform(T::uni_type(), ty));
+
+    if compatible_len && compatible_ty {
+      Ok(())
+    } else {
+      Err(mismatch(Self::glsl_type(
+        &uniform.glsl_type,
+        uniform.array,
+        uniform.len,
+      )))
+    }
+  }
+
+  /// Display the GLSL type of a uniform or vertex input, e.g. `vec3` or `float[4]`.
+  fn glsl_type(ty: &str, array: bool, len: usize) -> String {
+    if array {
+      format!("{ty}[{len}]")
+    } else {
+      ty.to_string()
+    }
+  }
+
+  /// Display the Rust type of a uniform, in the same form as the Rust type of vertex attributes (e.g. `[f32; 3]`).
+  fn uniform_rust_type<T>() -> String
+  where
+    T: Uniform,
+  {
+    let attrib = |ty, unit_size, dim, locations| VertexAttribDesc {
+      ty,
+      dim,
+      unit_size,
+      align: unit_size,
+      locations,
+    };
+    let vec_dim = |dim| match dim {
+      UniDim::Dim1 => VertexAttribDim::Dim1,
+      UniDim::Dim2 => VertexAttribDim::Dim2,
+      UniDim::Dim3 => VertexAttribDim::Dim3,
+      UniDim::Dim4 => VertexAttribDim::Dim4,
+    };
+    // matrices span one location per column
+    let mat_dim = |dim| match dim {
+      UniMatDim::Mat22 => (VertexAttribDim::Dim2, 2),
+      UniMatDim::Mat33 => (VertexAttribDim::Dim3, 3),
+      UniMatDim::Mat44 => (VertexAttribDim::Dim4, 4),
+    };
+
+    let desc = match T::uni_type() {
+      UniType::Integral(dim) => attrib(
+        VertexAttribType::Integral(Normalized::No),
+        4,
+        vec_dim(dim),
+        1,
+      ),
+      UniType::Unsigned(dim) => attrib(
+        VertexAttribType::Unsigned(Normalized::No),
+        4,
+        vec_dim(dim),
+        1,
+      ),
+      UniType::Floating(dim) => attrib(VertexAttribType::Floating, 4, vec_dim(dim), 1),
+      #[cfg(feature = "shader-f64")]
+      UniType::Floating64(dim) => attrib(VertexAttribType::Floating64, 8, vec_dim(dim), 1),
+      UniType::Boolean(dim) => attrib(VertexAttribType::Boolean, 1, vec_dim(dim), 1),
+
+      UniType::Matrix(dim) => {
+        let (dim, locations) = mat_dim(dim);
+        attrib(VertexAttribType::Floating, 4, dim, locations)
+      }
+
+      #[cfg(feature = "shader-f64")]
+      UniType::Matrix64(dim) => {
+        let (dim, locations) = mat_dim(dim);
+        attrib(VertexAttribType::Floating64, 8, dim, locations)
+      }
+
+      UniType::Sampler(ty, dim) => return format!("{dim:?} texture of {ty:?} pixels"),
+
+      UniType::Buffer => {
+        return format!("uniform block of {} bytes", T::BLOCK_SIZE.unwrap_or(0));
+      }
+    };
+
+    match T::LEN {
+      1 => desc.to_string(),
+      len => format!("[{desc}; {len}]"),
+    }
+  }
+
+  /// Bind a vertex entity for rendering with `connector`.
+  ///
+  /// Returns the index type of the vertex entity if it is rendered with indices, after having set primitive restart
+  /// up.
+  unsafe fn bind_vertex_entity(
+    &self,
+    handle: usize,
+    connector: Connector,
+  ) -> Result<Option<IndexType>, VertexEntityError> {
+    let mut st = self.state.borrow_mut();
+    let st = st.deref_mut();
+    let data = st
+      .vertex_entities
+      .get(&handle)
+      .ok_or_else(|| VertexEntityError::Render { cause: None })?;
+
+    let vao = handle as GLuint;
+    st.bound_vertex_array.set_if_invalid(vao, || {
+      gl::BindVertexArray(vao);
+    });
+
+    // patches can only be rendered by programs with tessellation stages, so the size of patches is only set when
+    // those are supported
+    if let Connector::Patch(size) = connector {
+      if st.tessellation_supported {
+        st.patch_vertices.set_if_invalid(size, || {
+          gl::PatchParameteri(gl::PATCH_VERTICES, size as _);
+        });
+      }
+    }
+
+    if data.index_buffer.is_none() && data.shared_index_buffer.is_none() {
+      return Ok(None);
+    }
+
+    let index_type = data.index_type;
+    let primitive_restart = GL33::should_use_primitive_restart(connector);
+
+    st.primitive_restart.set_if_invalid(primitive_restart, || {
+      if primitive_restart {
+        gl::Enable(gl::PRIMITIVE_RESTART);
+      } else {
+        gl::Disable(gl::PRIMITIVE_RESTART);
+      }
+    });
+
+    if primitive_restart {
+      // the restart index depends on the width of the indices
+      let restart_index = index_type.primitive_restart_index();
+      st.primitive_restart_index
+        .set_if_invalid(restart_index, || {
+          gl::PrimitiveRestartIndex(restart_index);
+        });
+    }
+
+    Ok(Some(index_type))
+  }
+
+  /// Fence the round-robin buffers of a vertex entity once its draws are issued, so that they are not updated while
+  /// the GPU still reads them.
+  fn fence_vertex_entity(&self, handle: usize) {
+    if let Some(data) = self.state.borrow_mut().vertex_entities.get_mut(&handle) {
+      data.fence();
+    }
+  }
+
+  fn opengl_usage(usage: Usage) -> GLenum {
+    match usage {
+      Usage::Static => gl::STATIC_DRAW,
+      Usage::Dynamic => gl::DYNAMIC_DRAW,
+      Usage::Stream(_) => gl::STREAM_DRAW,
+    }
+  }
+
+  fn opengl_index_type(index_type: IndexType) -> GLenum {
+    match index_type {
+      IndexType::U8 => gl::UNSIGNED_BYTE,
+      IndexType::U16 => gl::UNSIGNED_SHORT,
+      IndexType::U32 => gl::UNSIGNED_INT,
+    }
+  }
+
   fn opengl_connector(connector: Connector) -> GLenum {
     match connector {
       Connector::Point => gl::POINTS,
@@ -1725,12 +3305,20 @@ impl GL33 {
       Connector::TriangleFan => gl::TRIANGLE_FAN,
       Connector::TriangleStrip => gl::TRIANGLE_STRIP,
       Connector::Patch(_) => gl::PATCHES,
+      Connector::LineAdjacency => gl::LINES_ADJACENCY,
+      Connector::LineStripAdjacency => gl::LINE_STRIP_ADJACENCY,
+      Connector::TriangleAdjacency => gl::TRIANGLES_ADJACENCY,
+      Connector::TriangleStripAdjacency => gl::TRIANGLE_STRIP_ADJACENCY,
     }
   }
 
   fn should_use_primitive_restart(connector: Connector) -> bool {
     match connector {
-      Connector::LineStrip | Connector::TriangleStrip | Connector::TriangleFan => true,
+      Connector::LineStrip
+      | Connector::TriangleStrip
+      | Connector::TriangleFan
+      | Connector::LineStripAdjacency
+      | Connector::TriangleStripAdjacency => true,
       _ => false,
     }
   }
@@ -2068,20 +3656,23 @@ unsafe impl Backend for GL33 {
     st.framebuffers.clear();
     st.textures.clear();
     st.programs.clear();
+    st.indirect_buffers.clear();
+    st.buffers.clear();
   }
 }
 
 unsafe impl VertexEntityBackend for GL33 {
-  unsafe fn new_vertex_entity<V, P, VSF, W, WSF>(
+  unsafe fn new_vertex_entity<V, P, VSF, W, WSF, I>(
     &mut self,
-    mut builder: VertexEntityBuilder<VSF::Storage<V>, WSF::Storage<W>>,
-  ) -> Result<VertexEntity<V, P, VSF, W, WSF>, VertexEntityError>
+    mut builder: VertexEntityBuilder<VSF::Storage<V>, WSF::Storage<W>, I>,
+  ) -> Result<VertexEntity<V, P, VSF, W, WSF, I>, VertexEntityError>
   where
     V: Vertex,
     P: Primitive,
     VSF: VertexStorageFamily,
     W: Vertex,
     WSF: VertexStorageFamily,
+    I: Index,
   {
     let indices = builder.indices;
 
@@ -2091,23 +3682,35 @@ unsafe impl VertexEntityBackend for GL33 {
     gl::BindVertexArray(vao);
     self.state.borrow_mut().bound_vertex_array.set(vao);
 
-    let built_vertex_buffers = self.build_vertex_buffers(&mut builder.vertices, false)?;
+    let built_vertex_buffers =
+      self.build_vertex_buffers(&mut builder.vertices, false, builder.vertex_usage)?;
     let vertex_buffers = built_vertex_buffers.buffers;
-    let index_buffer = self.build_index_buffer(&indices);
-    let built_instance_buffers = self.build_vertex_buffers(&mut builder.instances, true)?;
+    let index_buffer = self.build_index_buffer(&indices, builder.index_usage);
+    let shared_index_buffer = builder
+      .index_buffer
+      .as_ref()
+      .map(|indices| self.bind_shared_index_buffer(indices));
+    let built_instance_buffers =
+      self.build_vertex_buffers(&mut builder.instances, true, builder.instance_usage)?;
     let instance_buffers = built_instance_buffers.buffers;
 
-    let vertex_count = if indices.is_empty() {
-      built_vertex_buffers.len
-    } else {
-      indices.len()
+    let vertex_count = match &builder.index_buffer {
+      Some(indices) => indices.len(),
+      None if indices.is_empty() => built_vertex_buffers.len,
+      None => indices.len(),
     };
 
     let data = VertexEntityData {
       vao,
       vertex_buffers,
+      vertex_len: built_vertex_buffers.len,
       index_buffer,
+      shared_index_buffer,
+      index_type: I::INDEX_TYPE,
       instance_buffers,
+      vertex_usage: builder.vertex_usage,
+      index_usage: builder.index_usage,
+      instance_usage: builder.instance_usage,
     };
 
     let vao = vao as usize;
@@ -2124,6 +3727,7 @@ unsafe impl VertexEntityBackend for GL33 {
       builder.vertices,
       builder.instances,
       indices,
+      builder.index_buffer,
       vertex_count,
       dropper,
     ))
@@ -2135,6 +3739,8 @@ unsafe impl VertexEntityBackend for GL33 {
     start_index: usize,
     vert_count: usize,
     inst_count: usize,
+    base_vertex: usize,
+    base_instance: usize,
   ) -> Result<(), VertexEntityError>
   where
     V: Vertex,
@@ -2145,198 +3751,258 @@ unsafe impl VertexEntityBackend for GL33 {
       return Ok(());
     }
 
-    let vao = handle as GLuint;
-
-    self
-      .state
-      .borrow_mut()
-      .bound_vertex_array
-      .set_if_invalid(vao, || {
-        gl::BindVertexArray(vao);
-      });
+    if base_instance != 0 && !self.state.borrow().base_instance_supported {
+      return Err(VertexEntityError::UnsupportedBaseInstance { base_instance });
+    }
 
-    let st = self.state.borrow();
-    let data = st
-      .vertex_entities
-      .get(&handle)
-      .ok_or_else(|| VertexEntityError::Render { cause: None })?;
+    let mode = GL33::opengl_connector(P::CONNECTOR);
 
-    if data.index_buffer.is_some() {
+    if let Some(index_type) = self.bind_vertex_entity(handle, P::CONNECTOR)? {
       // indexed render
-      let first = (mem::size_of::<u32>() * start_index) as *const c_void;
-      let primitive_restart = GL33::should_use_primitive_restart(P::CONNECTOR);
+      let first = (index_type.bytes() * start_index) as *const c_void;
+      let ty = GL33::opengl_index_type(index_type);
 
-      drop(st);
-      self
-        .state
-        .borrow_mut()
-        .primitive_restart
-        .set_if_invalid(primitive_restart, || {
-          if primitive_restart {
-            gl::Enable(gl::PRIMITIVE_RESTART);
-          } else {
-            gl::Disable(gl::PRIMITIVE_RESTART);
-          }
-        });
-
-      if inst_count == 1 {
-        // FIXME: bug here
-        gl::DrawElements(
-          GL33::opengl_connector(P::CONNECTOR),
+      if base_instance != 0 {
+        gl::DrawElementsInstancedBaseVertexBaseInstance(
+          mode,
           vert_count as _,
-          gl::UNSIGNED_INT,
+          ty,
           first,
+          inst_count as _,
+          base_vertex as _,
+          base_instance as _,
         );
+      } else if inst_count == 1 {
+        gl::DrawElementsBaseVertex(mode, vert_count as _, ty, first, base_vertex as _);
       } else {
-        gl::DrawElementsInstanced(
-          GL33::opengl_connector(P::CONNECTOR),
+        gl::DrawElementsInstancedBaseVertex(
+          mode,
           vert_count as _,
-          gl::UNSIGNED_INT,
+          ty,
           first,
           inst_count as _,
+          base_vertex as _,
         );
       }
     } else {
-      // direct render
+      // direct render; the base vertex simply offsets the first vertex
+      let first = start_index + base_vertex;
 
-      if inst_count == 1 {
-        gl::DrawArrays(
-          GL33::opengl_connector(P::CONNECTOR),
-          start_index as _,
-          vert_count as _,
-        );
-      } else {
-        gl::DrawArraysInstanced(
-          GL33::opengl_connector(P::CONNECTOR),
-          start_index as _,
+      if base_instance != 0 {
+        gl::DrawArraysInstancedBaseInstance(
+          mode,
+          first as _,
           vert_count as _,
           inst_count as _,
+          base_instance as _,
         );
+      } else if inst_count == 1 {
+        gl::DrawArrays(mode, first as _, vert_count as _);
+      } else {
+        gl::DrawArraysInstanced(mode, first as _, vert_count as _, inst_count as _);
       }
     }
 
+    self.fence_vertex_entity(handle);
+
     Ok(())
   }
 
-  unsafe fn vertex_entity_update_vertices<V, S>(
-    &mut self,
-    handle: usize,
-    storage: &mut S,
+  unsafe fn vertex_entity_render_batch<V, W, P>(
+    &self,
+    views: &[VertexEntityView<V, W, P>],
   ) -> Result<(), VertexEntityError>
   where
     V: Vertex,
-    S: AsVertexStorage<V>,
+    W: Vertex,
+    P: Primitive,
   {
-    // get the associated data with the handle first
-    let mut state = self.state.borrow_mut();
-    let state = state.deref_mut();
-    let data = state
-      .vertex_entities
-      .get(&handle)
-      .ok_or_else(|| VertexEntityError::UpdateVertexStorage { cause: None })?;
+    // views of the same vertex entity are submitted together with a single multi-draw call, unless they are instanced
+    let mut views = views;
+    while let Some(first) = views.first() {
+      let len = views
+        .iter()
+        .position(|view| view.handle() != first.handle())
+        .unwrap_or(views.len());
+      let (batch, rest) = views.split_at(len);
+      views = rest;
+
+      let single_instance = batch
+        .iter()
+        .all(|view| view.instance_count() == 1 && view.base_instance() == 0);
+
+      if !single_instance {
+        for view in batch {
+          self.vertex_entity_render::<V, P>(
+            view.handle(),
+            view.start_vertex(),
+            view.vertex_count(),
+            view.instance_count(),
+            view.base_vertex(),
+            view.base_instance(),
+          )?;
+        }
 
-    // allow updating only if the vertex storage has the same shape as the one used to create the vertex entity
-    // (interleaved/interleaved or deinterleaved/deinterleaved)
-    match (storage.as_vertex_storage(), &data.vertex_buffers) {
-      (VertexStorage::Interleaved(storage), Some(VertexEntityBuffers::Interleaved(ref buffer))) => {
-        let vertices = storage.vertices();
-        buffer
-          .update(&mut state.bound_array_buffer, &vertices, 0, vertices.len())
-          .map_err(|e| VertexEntityError::UpdateVertexStorage {
-            cause: Some(Box::new(e)),
-          })
+        continue;
       }
 
-      (
-        VertexStorage::Deinterleaved(storage),
-        Some(VertexEntityBuffers::Deinterleaved(buffers)),
-      ) => {
-        for (comp, buffer) in storage.components_list().iter().zip(buffers) {
-          buffer
-            .update(&mut state.bound_array_buffer, &comp, 0, comp.len())
-            .map_err(|e| VertexEntityError::UpdateVertexStorage {
-              cause: Some(Box::new(e)),
-            })?;
-        }
+      let mode = GL33::opengl_connector(P::CONNECTOR);
+      let counts: Vec<GLsizei> = batch.iter().map(|view| view.vertex_count() as _).collect();
+
+      if let Some(index_type) = self.bind_vertex_entity(batch[0].handle(), P::CONNECTOR)? {
+        let offsets: Vec<*const c_void> = batch
+          .iter()
+          .map(|view| (index_type.bytes() * view.start_vertex()) as *const c_void)
+          .collect();
+        let base_vertices: Vec<GLint> = batch.iter().map(|view| view.base_vertex() as _).collect();
+
+        gl::MultiDrawElementsBaseVertex(
+          mode,
+          counts.as_ptr(),
+          GL33::opengl_index_type(index_type),
+          offsets.as_ptr(),
+          batch.len() as _,
+          base_vertices.as_ptr(),
+        );
+      } else {
+        let firsts: Vec<GLint> = batch
+          .iter()
+          .map(|view| (view.start_vertex() + view.base_vertex()) as _)
+          .collect();
 
-        Ok(())
+        gl::MultiDrawArrays(mode, firsts.as_ptr(), counts.as_ptr(), batch.len() as _);
       }
 
-      _ => Err(VertexEntityError::UpdateVertexStorage { cause: None }),
+      self.fence_vertex_entity(batch[0].handle());
     }
+
+    Ok(())
   }
 
-  unsafe fn vertex_entity_update_indices(
+  unsafe fn vertex_entity_update_vertices<V, S>(
     &mut self,
     handle: usize,
-    indices: &mut Vec<u32>,
-  ) -> Result<(), VertexEntityError> {
-    // get the associated data with the handle first
-    let mut state = self.state.borrow_mut();
-    let state = state.deref_mut();
-    let data = state
-      .vertex_entities
-      .get(&handle)
-      .ok_or_else(|| VertexEntityError::UpdateIndices { cause: None })?;
+    storage: &mut S,
+    range: Range<usize>,
+  ) -> Result<(), VertexEntityError>
+  where
+    V: Vertex,
+    S: AsVertexStorage<V>,
+  {
+    GL33::update_vertex_buffers(
+      &mut self.state.borrow_mut(),
+      handle,
+      storage.as_vertex_storage(),
+      false,
+      range,
+    )
+  }
 
-    // update the index buffer if it exists
-    match &data.index_buffer {
-      Some(buffer) => buffer
-        .update(&mut state.bound_array_buffer, &indices, 0, indices.len())
-        .map_err(|e| VertexEntityError::UpdateIndices {
-          cause: Some(Box::new(e)),
-        }),
-      None => Err(VertexEntityError::UpdateIndices { cause: None }),
-    }
+  unsafe fn vertex_entity_update_vertex_attribute<V>(
+    &mut self,
+    handle: usize,
+    storage: &mut Deinterleaved<V>,
+    rank: usize,
+  ) -> Result<(), VertexEntityError>
+  where
+    V: Vertex,
+  {
+    GL33::update_vertex_attribute_buffer(&mut self.state.borrow_mut(), handle, storage, rank)
   }
 
-  unsafe fn vertex_entity_update_instance_data<W, WS>(
+  unsafe fn vertex_entity_update_indices<I>(
     &mut self,
     handle: usize,
-    storage: &mut WS,
+    indices: &mut Vec<I>,
+    range: Range<usize>,
   ) -> Result<(), VertexEntityError>
   where
-    W: Vertex,
-    WS: AsVertexStorage<W>,
+    I: Index,
   {
     // get the associated data with the handle first
     let mut state = self.state.borrow_mut();
     let state = state.deref_mut();
     let data = state
       .vertex_entities
-      .get(&handle)
-      .ok_or_else(|| VertexEntityError::UpdateVertexStorage { cause: None })?;
+      .get_mut(&handle)
+      .ok_or_else(|| VertexEntityError::UpdateIndices { cause: None })?;
 
-    // allow updating only if the instance data storage has the same shape as the one used to create the vertex entity
-    // (interleaved/interleaved or deinterleaved/deinterleaved)
-    match (storage.as_vertex_storage(), &data.instance_buffers) {
-      (VertexStorage::Interleaved(storage), Some(VertexEntityBuffers::Interleaved(ref buffer))) => {
-        let vertices = storage.vertices();
-        buffer
-          .update(&mut state.bound_array_buffer, &vertices, 0, vertices.len())
-          .map_err(|e| VertexEntityError::UpdateVertexStorage {
-            cause: Some(Box::new(e)),
-          })
+    if indices.is_empty() {
+      // switch to direct rendering by detaching the index buffer, if any
+      if let Some(buffer) = data.index_buffer.take() {
+        let vao = data.vao;
+        state.bound_vertex_array.set_if_invalid(vao, || {
+          gl::BindVertexArray(vao);
+        });
+        gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, 0);
+        state.bound_element_array_buffer.set(0);
+        drop(buffer);
       }
 
-      (
-        VertexStorage::Deinterleaved(storage),
-        Some(VertexEntityBuffers::Deinterleaved(buffers)),
-      ) => {
-        for (comp, buffer) in storage.components_list().iter().zip(buffers) {
-          buffer
-            .update(&mut state.bound_array_buffer, &comp, 0, comp.len())
-            .map_err(|e| VertexEntityError::UpdateVertexStorage {
-              cause: Some(Box::new(e)),
-            })?;
+      return Ok(());
+    }
+
+    let vao = data.vao;
+    match &mut data.index_buffer {
+      Some(buffer) => {
+        let changed = buffer
+          .upload(&mut state.bound_array_buffer, indices, range)
+          .map_err(|e| VertexEntityError::UpdateIndices {
+            cause: Some(Box::new(e)),
+          })?;
+
+        if changed {
+          // the index buffer binding is part of the vertex array state
+          state.bound_vertex_array.set_if_invalid(vao, || {
+            gl::BindVertexArray(vao);
+          });
+
+          let handle = buffer.handle();
+          gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, handle);
+          state.bound_element_array_buffer.set(handle);
         }
 
         Ok(())
       }
 
-      _ => Err(VertexEntityError::UpdateVertexStorage { cause: None }),
+      None => {
+        // switch to indexed rendering by attaching a new index buffer
+        state.bound_vertex_array.set_if_invalid(vao, || {
+          gl::BindVertexArray(vao);
+        });
+
+        let buffer = StorageBuffer::new(
+          &mut state.bound_array_buffer,
+          gl::ELEMENT_ARRAY_BUFFER,
+          indices,
+          data.index_usage,
+        );
+        state.bound_element_array_buffer.set(buffer.handle());
+        data.index_buffer = Some(buffer);
+
+        Ok(())
+      }
     }
   }
+
+  unsafe fn vertex_entity_update_instance_data<W, WS>(
+    &mut self,
+    handle: usize,
+    storage: &mut WS,
+    range: Range<usize>,
+  ) -> Result<(), VertexEntityError>
+  where
+    W: Vertex,
+    WS: AsVertexStorage<W>,
+  {
+    GL33::update_vertex_buffers(
+      &mut self.state.borrow_mut(),
+      handle,
+      storage.as_vertex_storage(),
+      true,
+      range,
+    )
+  }
 }
 
 unsafe impl FramebufferBackend for GL33 {
@@ -2504,22 +4170,36 @@ unsafe impl FramebufferBackend for GL33 {
 static mut BOOL_CACHE: Vec<u32> = Vec::new();
 
 unsafe impl ShaderBackend for GL33 {
-  unsafe fn new_program<V, W, P, S, E>(
+  unsafe fn new_program<V, W, P, S, E, F>(
     &mut self,
     vertex_code: String,
+    tess_control_code: String,
+    tess_eval_code: String,
     primitive_code: String,
     shading_code: String,
-  ) -> Result<Program<V, W, P, S, E>, ShaderError>
+  ) -> Result<Program<V, W, P, S, E, F>, ShaderError>
   where
     V: Vertex,
     W: Vertex,
     P: Primitive,
     S: RenderSlots,
     E: Uniforms,
+    F: Vertex,
   {
     // create the shader stages first
     let vertex_stage = StageHandle::new_stage(gl::VERTEX_SHADER, &vertex_code)?;
 
+    let tess_stages = if tess_control_code.is_empty() && tess_eval_code.is_empty() {
+      None
+    } else if !self.state.borrow().tessellation_supported {
+      return Err(StageError::UnsupportedType(gl::TESS_CONTROL_SHADER).into());
+    } else {
+      Some((
+        StageHandle::new_stage(gl::TESS_CONTROL_SHADER, &tess_control_code)?,
+        StageHandle::new_stage(gl::TESS_EVALUATION_SHADER, &tess_eval_code)?,
+      ))
+    };
+
     let primitive_stage = if primitive_code.is_empty() {
       None
     } else {
@@ -2536,32 +4216,100 @@ unsafe impl ShaderBackend for GL33 {
 
     gl::AttachShader(handle, vertex_stage.handle);
 
+    if let Some((tess_control_stage, tess_eval_stage)) = tess_stages {
+      gl::AttachShader(handle, tess_control_stage.handle);
+      gl::AttachShader(handle, tess_eval_stage.handle);
+    }
+
     if let Some(primitive_stage) = primitive_stage {
       gl::AttachShader(handle, primitive_stage.handle);
     }
 
     gl::AttachShader(handle, fragment_stage.handle);
 
-    let data = ProgramData { handle };
-    data.link()?;
-    data.bind_vertex_attribs(V::vertex_desc())?;
-    data.bind_vertex_attribs(W::vertex_desc())?;
+    // link errors are located in the code of the stages they are about
+    let sources = |stage| {
+      let (ty, code) = match stage {
+        Stage::Vertex => (gl::VERTEX_SHADER, &vertex_code),
+        Stage::TessControl => (gl::TESS_CONTROL_SHADER, &tess_control_code),
+        Stage::TessEval => (gl::TESS_EVALUATION_SHADER, &tess_eval_code),
+        Stage::Primitive => (gl::GEOMETRY_SHADER, &primitive_code),
+        Stage::Shading => (gl::FRAGMENT_SHADER, &shading_code),
+      };
+
+      (!code.is_empty()).then(|| (code.as_str(), StageHandle::prelude_lines(ty)))
+    };
+    let link_error = |e| match e {
+      ProgramError::LinkFailed { reason, .. } => ShaderError::Link {
+        diagnostics: parse_glsl_log(&reason, None, sources),
+      },
+
+      e => e.into(),
+    };
+
+    let mut data = ProgramData {
+      handle,
+      reflection: None,
+    };
+    data.capture_varyings(F::vertex_desc(), mem::size_of::<F>())?;
+    data.link().map_err(link_error)?;
+    data
+      .bind_vertex_attribs(V::vertex_desc())
+      .map_err(link_error)?;
+    data
+      .bind_vertex_attribs(W::vertex_desc())
+      .map_err(link_error)?;
+
+    // check the vertex attributes now, and keep the reflection around to check the uniforms when they are built
+    if cfg!(feature = "shader-validation") {
+      let reflection = data.reflect();
+      GL33::validate_vertex_attribs(&reflection, V::vertex_desc())?;
+      GL33::validate_vertex_attribs(&reflection, W::vertex_desc())?;
+      data.reflection = Some(reflection);
+    }
 
     // everything went okay, just track the program and let’s gooooooo
     let handle = handle as usize;
     self.state.borrow_mut().programs.insert(handle, data);
 
+    // build the uniforms; the program is deleted if they don’t match it
+    let uniforms = match E::build_uniforms(self, handle) {
+      Ok(uniforms) => uniforms,
+      Err(e) => {
+        self.state.borrow_mut().programs.remove(&handle);
+        return Err(e);
+      }
+    };
+
     let state = self.state.clone();
     let dropper = Box::new(move |handle| {
       state.borrow_mut().drop_program(handle);
     });
 
-    // build the uniforms
-    let uniforms = E::build_uniforms(self, handle)?;
-
     Ok(Program::new(handle, uniforms, dropper))
   }
 
+  unsafe fn program_reflection(
+    &mut self,
+    program_handle: usize,
+  ) -> Result<ProgramReflection, ShaderError> {
+    let st = self.state.borrow();
+    let program = st
+      .programs
+      .get(&program_handle)
+      .ok_or_else(|| ShaderError::Reflection {
+        handle: program_handle,
+        cause: None,
+      })?;
+
+    Ok(
+      program
+        .reflection
+        .clone()
+        .unwrap_or_else(|| program.reflect()),
+    )
+  }
+
   unsafe fn new_uni_buffer<T, Scheme>(
     &mut self,
     value: T::Aligned,
@@ -2569,7 +4317,7 @@ unsafe impl ShaderBackend for GL33 {
   where
     T: MemoryLayout<Scheme>,
   {
-    let buffer = Buffer::from_slice(&self.state, gl::UNIFORM_BUFFER, &[value]);
+    let buffer = Buffer::from_slice(&self.state, gl::UNIFORM_BUFFER, &[value], gl::STREAM_DRAW);
     let state = self.state.clone();
     let buffer_with_binding =
       BufferWithBinding::new(buffer, state.borrow().uni_buffer_bindings.clone());
@@ -2629,10 +4377,24 @@ unsafe impl ShaderBackend for GL33 {
     T: Uniform,
   {
     // TODO: pattern match for buffer bindings / that kind of stuff
-    ProgramData::ask_uniform(handle as GLuint, name).ok_or_else(|| ShaderError::UniCreation {
-      name: name.to_owned(),
-      cause: None,
-    })
+    let uni =
+      ProgramData::ask_uniform(handle as GLuint, name).ok_or_else(|| ShaderError::UniCreation {
+        name: name.to_owned(),
+        cause: None,
+      })?;
+
+    // the reflection is only cached when uniforms are validated
+    let st = self.state.borrow();
+    let reflection = st
+      .programs
+      .get(&handle)
+      .and_then(|program| program.reflection.as_ref());
+
+    if let Some(reflection) = reflection {
+      GL33::validate_uniform::<T>(reflection, name)?;
+    }
+
+    Ok(uni)
   }
 
   unsafe fn new_shader_uni_unbound<T>(&mut self, _: usize) -> Result<Uni<T>, ShaderError>
@@ -3363,10 +5125,12 @@ unsafe impl PipelineBackend for GL33 {
     f(WithFramebuffer::new(self))
   }
 
-  unsafe fn with_program<V, W, P, S, E, Err>(
+  unsafe fn with_program<V, W, P, S, E, F, Err>(
     &mut self,
-    program: &Program<V, W, P, S, E>,
-    f: impl for<'a> FnOnce(luminance::pipeline::WithProgram<'a, Self, V, W, P, S, E>) -> Result<(), Err>,
+    program: &Program<V, W, P, S, E, F>,
+    f: impl for<'a> FnOnce(
+      luminance::pipeline::WithProgram<'a, Self, V, W, P, S, E, F>,
+    ) -> Result<(), Err>,
   ) -> Result<(), Err>
   where
     V: Vertex,
@@ -3374,6 +5138,7 @@ unsafe impl PipelineBackend for GL33 {
     P: Primitive,
     S: RenderSlots,
     E: Uniforms,
+    F: Vertex,
     Err: From<PipelineError>,
   {
     let program_handle = program.handle() as GLuint;
@@ -3388,14 +5153,15 @@ unsafe impl PipelineBackend for GL33 {
     f(WithProgram::new(self, program))
   }
 
-  unsafe fn with_render_state<V, W, P, Err>(
+  unsafe fn with_render_state<V, W, P, F, Err>(
     &mut self,
     render_state: &luminance::render_state::RenderState,
-    f: impl for<'a> FnOnce(WithRenderState<'a, Self, V, W, P>) -> Result<(), Err>,
+    f: impl for<'a> FnOnce(WithRenderState<'a, Self, V, W, P, F>) -> Result<(), Err>,
   ) -> Result<(), Err>
   where
     V: Vertex,
     P: Primitive,
+    F: Vertex,
     Err: From<PipelineError>,
   {
     let mut st = self.state.borrow_mut();
@@ -3578,6 +5344,8 @@ unsafe impl PipelineBackend for GL33 {
         view.start_vertex(),
         view.vertex_count(),
         view.instance_count(),
+        view.base_vertex(),
+        view.base_instance(),
       )
       .map_err(|e| PipelineError::RenderVertexEntity {
         start_vertex: view.start_vertex(),
@@ -3586,6 +5354,375 @@ unsafe impl PipelineBackend for GL33 {
         cause: Some(Box::new(e)),
       })
   }
+  unsafe fn render_vertex_entity_batch<V, W, P>(
+    &mut self,
+    views: &[VertexEntityView<V, W, P>],
+  ) -> Result<(), PipelineError>
+  where
+    V: Vertex,
+    W: Vertex,
+    P: Primitive,
+  {
+    self
+      .vertex_entity_render_batch(views)
+      .map_err(|e| PipelineError::RenderVertexEntityBatch {
+        view_count: views.len(),
+        cause: Some(Box::new(e)),
+      })
+  }
+
+  unsafe fn render_vertex_entity_with_feedback<V, W, P, F>(
+    &mut self,
+    view: VertexEntityView<V, W, P>,
+    buffer_handle: usize,
+  ) -> Result<(), PipelineError>
+  where
+    V: Vertex,
+    W: Vertex,
+    P: Primitive,
+    F: Vertex,
+  {
+    // strips and fans are captured as lists, and adjacency primitives without their adjacent vertices
+    let mode = match P::CONNECTOR {
+      Connector::Point => gl::POINTS,
+      Connector::Line
+      | Connector::LineStrip
+      | Connector::LineAdjacency
+      | Connector::LineStripAdjacency => gl::LINES,
+      Connector::Triangle
+      | Connector::TriangleStrip
+      | Connector::TriangleFan
+      | Connector::TriangleAdjacency
+      | Connector::TriangleStripAdjacency => gl::TRIANGLES,
+      Connector::Patch(_) => {
+        return Err(PipelineError::UncapturablePrimitive {
+          connector: P::CONNECTOR,
+        })
+      }
+    };
+
+    // indirect buffers receive captured commands
+    let known_buffer = {
+      let st = self.state.borrow();
+      st.buffers.contains_key(&buffer_handle) || st.indirect_buffers.contains_key(&buffer_handle)
+    };
+
+    if !known_buffer {
+      return Err(PipelineError::TransformFeedback {
+        cause: Some(Box::new(backend::BufferError::NoData {
+          handle: buffer_handle,
+        })),
+      });
+    }
+
+    gl::BindBufferBase(gl::TRANSFORM_FEEDBACK_BUFFER, 0, buffer_handle as GLuint);
+    gl::BeginTransformFeedback(mode);
+
+    let rendered = self.render_vertex_entity(view);
+
+    gl::EndTransformFeedback();
+    gl::BindBufferBase(gl::TRANSFORM_FEEDBACK_BUFFER, 0, 0);
+
+    rendered
+  }
+}
+
+unsafe impl BufferBackend for GL33 {
+  unsafe fn new_buffer<T>(
+    &mut self,
+    values: &[T],
+    usage: Usage,
+  ) -> Result<buffer::Buffer<T>, backend::BufferError>
+  where
+    T: Copy,
+  {
+    let state = self.state.clone();
+    let mut st = self.state.borrow_mut();
+    let buffer = Buffer::new(
+      &mut st.bound_array_buffer,
+      gl::ARRAY_BUFFER,
+      values,
+      GL33::opengl_usage(usage),
+    );
+
+    let handle = buffer.handle as usize;
+    let buffer_with_binding = BufferWithBinding::new(buffer, st.uni_buffer_bindings.clone());
+    st.buffers.insert(handle, buffer_with_binding);
+
+    let dropper = Box::new(move |handle| {
+      state.borrow_mut().drop_buffer(handle);
+    });
+
+    Ok(buffer::Buffer::new(handle, values.len(), dropper))
+  }
+
+  unsafe fn buffer_update<T>(
+    &mut self,
+    handle: usize,
+    start: usize,
+    values: &[T],
+  ) -> Result<(), backend::BufferError>
+  where
+    T: Copy,
+  {
+    let mut st = self.state.borrow_mut();
+    let st = st.deref_mut();
+    let buffer = st
+      .buffers
+      .get(&handle)
+      .ok_or(backend::BufferError::NoData { handle })?;
+
+    buffer
+      .buffer
+      .update(&mut st.bound_array_buffer, values, start)
+      .map_err(|e| backend::BufferError::Update {
+        cause: Some(Box::new(e)),
+      })
+  }
+
+  unsafe fn buffer_read<T>(
+    &mut self,
+    handle: usize,
+    len: usize,
+  ) -> Result<Vec<T>, backend::BufferError>
+  where
+    T: Copy,
+  {
+    let mut st = self.state.borrow_mut();
+    let st = st.deref_mut();
+    let buffer = st
+      .buffers
+      .get(&handle)
+      .ok_or(backend::BufferError::NoData { handle })?;
+
+    buffer
+      .buffer
+      .read(&mut st.bound_array_buffer, 0, len)
+      .map_err(|e| backend::BufferError::Read {
+        cause: Some(Box::new(e)),
+      })
+  }
+
+  unsafe fn buffer_map<T>(
+    &mut self,
+    handle: usize,
+    len: usize,
+  ) -> Result<BufferSliceMut<'_, Self, T>, backend::BufferError>
+  where
+    T: Copy,
+  {
+    let ptr = {
+      let mut st = self.state.borrow_mut();
+      let st = st.deref_mut();
+      let capacity = st
+        .buffers
+        .get(&handle)
+        .ok_or(backend::BufferError::NoData { handle })?
+        .buffer
+        .capacity;
+
+      if capacity == 0 {
+        // empty buffers cannot be mapped
+        ptr::NonNull::dangling().as_ptr()
+      } else {
+        let gl_handle = handle as GLuint;
+        st.bound_array_buffer.set_if_invalid(gl_handle, || {
+          gl::BindBuffer(gl::ARRAY_BUFFER, gl_handle);
+        });
+
+        let ptr = gl::MapBufferRange(
+          gl::ARRAY_BUFFER,
+          0,
+          capacity as GLsizeiptr,
+          gl::MAP_READ_BIT | gl::MAP_WRITE_BIT,
+        ) as *mut T;
+
+        if ptr.is_null() {
+          return Err(backend::BufferError::Map { cause: None });
+        }
+
+        ptr
+      }
+    };
+
+    Ok(BufferSliceMut::new(self, handle, ptr, len))
+  }
+
+  unsafe fn buffer_unmap<T>(&mut self, handle: usize) -> Result<(), backend::BufferError> {
+    let mut st = self.state.borrow_mut();
+    let st = st.deref_mut();
+    let capacity = st
+      .buffers
+      .get(&handle)
+      .ok_or(backend::BufferError::NoData { handle })?
+      .buffer
+      .capacity;
+
+    if capacity == 0 {
+      return Ok(());
+    }
+
+    let gl_handle = handle as GLuint;
+    st.bound_array_buffer.set_if_invalid(gl_handle, || {
+      gl::BindBuffer(gl::ARRAY_BUFFER, gl_handle);
+    });
+
+    if gl::UnmapBuffer(gl::ARRAY_BUFFER) == gl::FALSE {
+      // the content of the buffer got corrupted while mapped
+      return Err(backend::BufferError::Map { cause: None });
+    }
+
+    Ok(())
+  }
+}
+
+unsafe impl IndirectBufferBackend for GL33 {
+  /// Commands must have a base instance of `0` if base instances are not supported.
+  unsafe fn new_indirect_buffer<C>(
+    &mut self,
+    commands: &[C],
+  ) -> Result<IndirectBuffer<C>, IndirectBufferError>
+  where
+    C: IndirectCommand,
+  {
+    let mut st = self.state.borrow_mut();
+    st.check_base_instances(commands)?;
+
+    let buffer = Buffer::new(
+      &mut st.bound_array_buffer,
+      gl::DRAW_INDIRECT_BUFFER,
+      commands,
+      gl::DYNAMIC_DRAW,
+    );
+
+    let handle = buffer.handle;
+    st.bound_draw_indirect_buffer.set(handle);
+    st.indirect_buffers.insert(handle as usize, buffer);
+
+    let state = self.state.clone();
+    let dropper = Box::new(move |handle| {
+      state.borrow_mut().drop_indirect_buffer(handle);
+    });
+
+    Ok(IndirectBuffer::new(
+      handle as usize,
+      commands.len(),
+      dropper,
+    ))
+  }
+
+  unsafe fn indirect_buffer_update<C>(
+    &mut self,
+    handle: usize,
+    start: usize,
+    commands: &[C],
+  ) -> Result<(), IndirectBufferError>
+  where
+    C: IndirectCommand,
+  {
+    let mut st = self.state.borrow_mut();
+    let st = st.deref_mut();
+    st.check_base_instances(commands)?;
+
+    let buffer = st
+      .indirect_buffers
+      .get(&handle)
+      .ok_or(IndirectBufferError::NoData { handle })?;
+
+    buffer
+      .update(&mut st.bound_array_buffer, commands, start)
+      .map_err(|e| IndirectBufferError::Update {
+        cause: Some(Box::new(e)),
+      })
+  }
+
+  unsafe fn indirect_buffer_read<C>(&mut self, handle: usize) -> Result<Vec<C>, IndirectBufferError>
+  where
+    C: IndirectCommand,
+  {
+    let mut st = self.state.borrow_mut();
+    let st = st.deref_mut();
+    let buffer = st
+      .indirect_buffers
+      .get(&handle)
+      .ok_or(IndirectBufferError::NoData { handle })?;
+    let len = buffer.capacity / mem::size_of::<C>();
+
+    buffer
+      .read(&mut st.bound_array_buffer, 0, len)
+      .map_err(|e| IndirectBufferError::Read {
+        cause: Some(Box::new(e)),
+      })
+  }
+
+  /// Vertex entities are rendered with `glDrawElementsIndirect` if they have indices, and `glDrawArraysIndirect`
+  /// otherwise, so that the commands never leave GPU memory.
+  ///
+  /// Commands written by shaders must have a base instance of `0` if base instances are not supported.
+  unsafe fn vertex_entity_render_indirect<V, P, C>(
+    &mut self,
+    entity_handle: usize,
+    indirect_handle: usize,
+    commands: Range<usize>,
+  ) -> Result<(), IndirectBufferError>
+  where
+    V: Vertex,
+    P: Primitive,
+    C: IndirectCommand,
+  {
+    if !self.state.borrow().draw_indirect_supported {
+      return Err(IndirectBufferError::Unsupported);
+    }
+
+    if !self
+      .state
+      .borrow()
+      .indirect_buffers
+      .contains_key(&indirect_handle)
+    {
+      return Err(IndirectBufferError::NoData {
+        handle: indirect_handle,
+      });
+    }
+
+    let index_type = self
+      .bind_vertex_entity(entity_handle, P::CONNECTOR)
+      .map_err(|e| IndirectBufferError::Render {
+        cause: Some(Box::new(e)),
+      })?;
+
+    if index_type.is_some() != C::INDEXED {
+      return Err(IndirectBufferError::MismatchingCommands {
+        indexed_commands: C::INDEXED,
+      });
+    }
+
+    let indirect_buffer = indirect_handle as GLuint;
+    self
+      .state
+      .borrow_mut()
+      .bound_draw_indirect_buffer
+      .set_if_invalid(indirect_buffer, || {
+        gl::BindBuffer(gl::DRAW_INDIRECT_BUFFER, indirect_buffer);
+      });
+
+    let mode = GL33::opengl_connector(P::CONNECTOR);
+
+    for command in commands {
+      let offset = (command * mem::size_of::<C>()) as *const c_void;
+
+      match index_type {
+        Some(index_type) => {
+          gl::DrawElementsIndirect(mode, GL33::opengl_index_type(index_type), offset)
+        }
+        None => gl::DrawArraysIndirect(mode, offset),
+      }
+    }
+
+    self.fence_vertex_entity(entity_handle);
+
+    Ok(())
+  }
 }
 
 unsafe impl QueryBackend for GL33 {
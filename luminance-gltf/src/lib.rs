@@ -0,0 +1,316 @@
+//! glTF 2.0 importer for luminance.
+//!
+//! [`Gltf`] imports glTF documents — `.gltf` files with their buffers and images, or binary `.glb` files — and turns
+//! their meshes into [`VertexEntityBuilder`]s for any [`Vertex`] type, their images into textures and their
+//! materials into [`Material`]s, whose [`MaterialParams`] can be uploaded to shaders with a uniform buffer.
+//!
+//! glTF attributes are written to the vertex fields selected by a [`VertexMapping`]. With deinterleaved storages, each
+//! mapped accessor becomes the components of a field.
+//!
+//! ```ignore
+//! let gltf = Gltf::load("helmet.gltf")?;
+//! let mapping = VertexMapping::<MyVertex>::new()
+//!   .position::<"pos">()
+//!   .normal::<"nor">()
+//!   .tex_coord::<"uv">(0);
+//!
+//! for mesh in gltf.deinterleaved_meshes(&mapping)? {
+//!   for primitive in mesh.primitives {
+//!     let material = gltf.material(primitive.material);
+//!     let params = ctx.new_uni_buffer::<MaterialParams, Std140>(material.params)?;
+//!     let entity = ctx.new_vertex_entity(primitive.builder)?;
+//!     // …
+//!   }
+//! }
+//! ```
+//!
+//! Skins, animations, morph targets and the scene graph are not imported; the [`gltf::Document`] is available to
+//! read them.
+
+#![allow(incomplete_features)]
+#![feature(adt_const_params)]
+
+pub mod mapping;
+pub mod material;
+pub mod texture;
+
+pub use gltf;
+pub use mapping::VertexMapping;
+pub use material::{Material, MaterialParams, TextureRef};
+pub use texture::ImagePixel;
+
+use gltf::{buffer, image, mesh::Mode, Document, Semantic};
+use luminance::{
+  backend::{Backend, TextureError},
+  context::Context,
+  dim::{Dim2, Size2},
+  texture::{Mipmaps, Texture, TextureSampling},
+  vertex::Vertex,
+  vertex_entity::VertexEntityBuilder,
+  vertex_storage::{Deinterleaved, Interleaved},
+};
+use mapping::ReadStorage;
+use std::{error::Error, fmt, path::Path};
+
+/// Errors that might happen when importing glTF documents.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum GltfError {
+  /// The document, or one of its buffers or images, couldn’t be read.
+  Import { cause: gltf::Error },
+
+  /// A primitive lacks an attribute of the vertex mapping.
+  MissingAttribute {
+    mesh: usize,
+    primitive: usize,
+    semantic: Semantic,
+  },
+
+  /// There is no texture with that index.
+  NoSuchTexture { texture: usize },
+
+  /// The format of an image cannot be converted to the requested pixel format.
+  UnsupportedImageFormat { image: usize, format: image::Format },
+
+  /// A texture couldn’t be created.
+  Texture { cause: TextureError },
+}
+
+impl fmt::Display for GltfError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      GltfError::Import { cause } => write!(f, "cannot import glTF document: {}", cause),
+
+      GltfError::MissingAttribute {
+        mesh,
+        primitive,
+        semantic,
+      } => write!(
+        f,
+        "primitive {} of mesh {} has no {} attribute",
+        primitive,
+        mesh,
+        semantic.to_string()
+      ),
+
+      GltfError::NoSuchTexture { texture } => write!(f, "no texture {}", texture),
+
+      GltfError::UnsupportedImageFormat { image, format } => {
+        write!(f, "image {} has unsupported format {:?}", image, format)
+      }
+
+      GltfError::Texture { cause } => write!(f, "cannot create texture: {}", cause),
+    }
+  }
+}
+
+impl Error for GltfError {
+  fn source(&self) -> Option<&(dyn Error + 'static)> {
+    match self {
+      GltfError::Import { cause } => Some(cause),
+      GltfError::Texture { cause } => Some(cause),
+      _ => None,
+    }
+  }
+}
+
+impl From<gltf::Error> for GltfError {
+  fn from(cause: gltf::Error) -> Self {
+    GltfError::Import { cause }
+  }
+}
+
+impl From<TextureError> for GltfError {
+  fn from(cause: TextureError) -> Self {
+    GltfError::Texture { cause }
+  }
+}
+
+/// An imported glTF document, with its buffers and decoded images.
+#[derive(Debug)]
+pub struct Gltf {
+  pub document: Document,
+  pub buffers: Vec<buffer::Data>,
+  pub images: Vec<image::Data>,
+}
+
+/// A mesh of a glTF document.
+#[derive(Debug)]
+pub struct Mesh<S> {
+  pub name: Option<String>,
+  pub primitives: Vec<Primitive<S>>,
+}
+
+/// A primitive of a mesh, ready to be turned into a vertex entity.
+#[derive(Debug)]
+pub struct Primitive<S> {
+  /// How vertices are connected; it must match the primitive type of the vertex entity.
+  pub mode: Mode,
+
+  /// Index of the material of the primitive, if any; see [`Gltf::material`].
+  pub material: Option<usize>,
+
+  /// Vertices and indices of the primitive; primitives without indices are rendered directly.
+  pub builder: VertexEntityBuilder<S, (), u32>,
+}
+
+impl Gltf {
+  /// Import a glTF document from a file, along with the buffers and images it references.
+  ///
+  /// Buffers and images are looked up relatively to the directory of the file.
+  pub fn load(path: impl AsRef<Path>) -> Result<Self, GltfError> {
+    let (document, buffers, images) = gltf::import(path)?;
+    Ok(Self {
+      document,
+      buffers,
+      images,
+    })
+  }
+
+  /// Import a glTF document from memory, either as JSON or binary glTF.
+  ///
+  /// Buffers and images must be embedded, as data URIs or in the binary chunk.
+  pub fn from_slice(bytes: &[u8]) -> Result<Self, GltfError> {
+    let (document, buffers, images) = gltf::import_slice(bytes)?;
+    Ok(Self {
+      document,
+      buffers,
+      images,
+    })
+  }
+
+  /// Build the meshes of the document, with interleaved vertices.
+  pub fn interleaved_meshes<V>(
+    &self,
+    mapping: &VertexMapping<V>,
+  ) -> Result<Vec<Mesh<Interleaved<V>>>, GltfError>
+  where
+    V: Vertex + Default,
+  {
+    self.meshes(mapping)
+  }
+
+  /// Build the meshes of the document, with deinterleaved vertices.
+  pub fn deinterleaved_meshes<V>(
+    &self,
+    mapping: &VertexMapping<V>,
+  ) -> Result<Vec<Mesh<Deinterleaved<V>>>, GltfError>
+  where
+    V: Vertex + Default,
+  {
+    self.meshes(mapping)
+  }
+
+  fn meshes<V, S>(&self, mapping: &VertexMapping<V>) -> Result<Vec<Mesh<S>>, GltfError>
+  where
+    V: Vertex + Default,
+    S: ReadStorage<V>,
+  {
+    self
+      .document
+      .meshes()
+      .map(|mesh| {
+        let primitives = mesh
+          .primitives()
+          .map(|primitive| {
+            let reader =
+              primitive.reader(|buffer| self.buffers.get(buffer.index()).map(|data| &data[..]));
+
+            // all the attributes of a primitive have the same count
+            let count = primitive
+              .attributes()
+              .map(|(_, accessor)| accessor.count())
+              .next()
+              .unwrap_or(0);
+
+            let vertices =
+              S::read(mapping, &reader, count).map_err(|semantic| GltfError::MissingAttribute {
+                mesh: mesh.index(),
+                primitive: primitive.index(),
+                semantic,
+              })?;
+            let indices = reader
+              .read_indices()
+              .map(|indices| indices.into_u32().collect::<Vec<_>>())
+              .unwrap_or_default();
+
+            Ok(Primitive {
+              mode: primitive.mode(),
+              material: primitive.material().index(),
+              builder: VertexEntityBuilder::new()
+                .add_vertices(vertices)
+                .add_indices(indices),
+            })
+          })
+          .collect::<Result<_, GltfError>>()?;
+
+        Ok(Mesh {
+          name: mesh.name().map(str::to_owned),
+          primitives,
+        })
+      })
+      .collect()
+  }
+
+  /// Materials of the document, by index.
+  pub fn materials(&self) -> Vec<Material> {
+    self.document.materials().map(Material::from).collect()
+  }
+
+  /// Get a material by index, or the default material if there is none.
+  pub fn material(&self, index: Option<usize>) -> Material {
+    index
+      .and_then(|index| self.document.materials().nth(index))
+      .map(Material::from)
+      .unwrap_or_default()
+  }
+
+  /// Sampling of a texture, as described by its sampler.
+  pub fn texture_sampling(&self, texture: usize) -> Result<TextureSampling, GltfError> {
+    let texture = self.texture(texture)?;
+    Ok(texture::texture_sampling(&texture.sampler()))
+  }
+
+  /// Texels of a texture, converted to the pixel format `P`.
+  pub fn texels<P>(&self, texture: usize) -> Result<Vec<P::RawEncoding>, GltfError>
+  where
+    P: ImagePixel,
+  {
+    let image = self.texture(texture)?.source().index();
+    let data = &self.images[image];
+
+    P::texels(data).ok_or(GltfError::UnsupportedImageFormat {
+      image,
+      format: data.format,
+    })
+  }
+
+  /// Create a 2D texture out of a texture of the document, with the pixel format `P` and the sampling of its sampler.
+  ///
+  /// Samplers filtering with mipmaps require `mipmaps` to be [`Mipmaps::Yes`].
+  pub fn new_texture<B, P>(
+    &self,
+    ctx: &mut Context<B>,
+    texture: usize,
+    mipmaps: Mipmaps,
+  ) -> Result<Texture<Dim2, P>, GltfError>
+  where
+    B: Backend,
+    P: ImagePixel,
+  {
+    let texels = self.texels::<P>(texture)?;
+    let sampling = self.texture_sampling(texture)?;
+    let image = &self.images[self.texture(texture)?.source().index()];
+    let size = Size2::new(image.width, image.height);
+
+    Ok(ctx.new_texture(size, mipmaps, &sampling, &texels)?)
+  }
+
+  fn texture(&self, texture: usize) -> Result<gltf::Texture<'_>, GltfError> {
+    self
+      .document
+      .textures()
+      .nth(texture)
+      .ok_or(GltfError::NoSuchTexture { texture })
+  }
+}
@@ -0,0 +1,298 @@
+//! Mapping of glTF vertex attributes onto vertex fields.
+
+use gltf::{mesh::Reader, Buffer, Semantic};
+use luminance::{
+  has_field::HasField,
+  vertex::{Deinterleave, FieldSetter, FromAttrib, Vertex},
+  vertex_storage::{Deinterleaved, Interleaved},
+};
+use std::marker::PhantomData;
+
+/// Values of glTF attributes, read from the accessors of a primitive.
+pub(crate) trait AttributeValue: Sized {
+  fn read<'a, 's, F>(reader: &Reader<'a, 's, F>, semantic: &Semantic) -> Option<Vec<Self>>
+  where
+    F: Clone + Fn(Buffer<'a>) -> Option<&'s [u8]>;
+}
+
+impl AttributeValue for [f32; 2] {
+  fn read<'a, 's, F>(reader: &Reader<'a, 's, F>, semantic: &Semantic) -> Option<Vec<Self>>
+  where
+    F: Clone + Fn(Buffer<'a>) -> Option<&'s [u8]>,
+  {
+    match *semantic {
+      Semantic::TexCoords(set) => Some(reader.read_tex_coords(set)?.into_f32().collect()),
+      _ => None,
+    }
+  }
+}
+
+impl AttributeValue for [f32; 3] {
+  fn read<'a, 's, F>(reader: &Reader<'a, 's, F>, semantic: &Semantic) -> Option<Vec<Self>>
+  where
+    F: Clone + Fn(Buffer<'a>) -> Option<&'s [u8]>,
+  {
+    match semantic {
+      Semantic::Positions => Some(reader.read_positions()?.collect()),
+      Semantic::Normals => Some(reader.read_normals()?.collect()),
+      _ => None,
+    }
+  }
+}
+
+impl AttributeValue for [f32; 4] {
+  fn read<'a, 's, F>(reader: &Reader<'a, 's, F>, semantic: &Semantic) -> Option<Vec<Self>>
+  where
+    F: Clone + Fn(Buffer<'a>) -> Option<&'s [u8]>,
+  {
+    match *semantic {
+      Semantic::Tangents => Some(reader.read_tangents()?.collect()),
+      Semantic::Colors(set) => Some(reader.read_colors(set)?.into_rgba_f32().collect()),
+      _ => None,
+    }
+  }
+}
+
+/// A glTF attribute mapped onto a vertex field.
+///
+/// Interleaved vertices get the attribute set in the field; deinterleaved storages get it as the components of the
+/// field.
+pub(crate) struct Attribute<V, T> {
+  semantic: Semantic,
+  setter: FieldSetter<V, T>,
+  set_components: fn(Deinterleaved<V>, Vec<T>) -> Deinterleaved<V>,
+}
+
+impl<V, T> Attribute<V, T>
+where
+  V: Vertex,
+{
+  fn new<const NAME: &'static str>(semantic: Semantic) -> Self
+  where
+    V: Deinterleave<NAME>,
+    <V as HasField<NAME>>::FieldType: FromAttrib<T>,
+  {
+    fn set_components<V, T, const NAME: &'static str>(
+      storage: Deinterleaved<V>,
+      values: Vec<T>,
+    ) -> Deinterleaved<V>
+    where
+      V: Vertex + Deinterleave<NAME>,
+      <V as HasField<NAME>>::FieldType: FromAttrib<T>,
+    {
+      storage.set_components::<NAME>(
+        values
+          .into_iter()
+          .map(FromAttrib::from_attrib)
+          .collect::<Vec<_>>(),
+      )
+    }
+
+    Self {
+      semantic,
+      setter: FieldSetter::new::<NAME>(),
+      set_components: set_components::<V, T, NAME>,
+    }
+  }
+}
+
+/// Fields of a vertex type the glTF attributes are written to.
+///
+/// Fields are selected by name, the same way [`Deinterleaved::set_components`] does, and attributes are converted to
+/// the type of the fields with [`FromAttrib`]. Every mapped attribute must be present in the imported primitives;
+/// fields that are not mapped keep their default value.
+///
+/// ```ignore
+/// let mapping = VertexMapping::<MyVertex>::new()
+///   .position::<"pos">()
+///   .normal::<"nor">()
+///   .tex_coord::<"uv">(0);
+/// ```
+pub struct VertexMapping<V> {
+  vec2: Vec<Attribute<V, [f32; 2]>>,
+  vec3: Vec<Attribute<V, [f32; 3]>>,
+  vec4: Vec<Attribute<V, [f32; 4]>>,
+  _phantom: PhantomData<*const V>,
+}
+
+impl<V> VertexMapping<V>
+where
+  V: Vertex,
+{
+  /// Map no attribute.
+  pub fn new() -> Self {
+    Self {
+      vec2: Vec::new(),
+      vec3: Vec::new(),
+      vec4: Vec::new(),
+      _phantom: PhantomData,
+    }
+  }
+
+  /// Write positions (`POSITION`) to the `NAME` field.
+  pub fn position<const NAME: &'static str>(mut self) -> Self
+  where
+    V: Deinterleave<NAME>,
+    <V as HasField<NAME>>::FieldType: FromAttrib<[f32; 3]>,
+  {
+    self.vec3.push(Attribute::new::<NAME>(Semantic::Positions));
+    self
+  }
+
+  /// Write normals (`NORMAL`) to the `NAME` field.
+  pub fn normal<const NAME: &'static str>(mut self) -> Self
+  where
+    V: Deinterleave<NAME>,
+    <V as HasField<NAME>>::FieldType: FromAttrib<[f32; 3]>,
+  {
+    self.vec3.push(Attribute::new::<NAME>(Semantic::Normals));
+    self
+  }
+
+  /// Write tangents (`TANGENT`), whose `w` is the handedness of the bitangent, to the `NAME` field.
+  pub fn tangent<const NAME: &'static str>(mut self) -> Self
+  where
+    V: Deinterleave<NAME>,
+    <V as HasField<NAME>>::FieldType: FromAttrib<[f32; 4]>,
+  {
+    self.vec4.push(Attribute::new::<NAME>(Semantic::Tangents));
+    self
+  }
+
+  /// Write the colors of the given set (`COLOR_n`) to the `NAME` field.
+  ///
+  /// RGB colors get an alpha of `1`.
+  pub fn color<const NAME: &'static str>(mut self, set: u32) -> Self
+  where
+    V: Deinterleave<NAME>,
+    <V as HasField<NAME>>::FieldType: FromAttrib<[f32; 4]>,
+  {
+    self
+      .vec4
+      .push(Attribute::new::<NAME>(Semantic::Colors(set)));
+    self
+  }
+
+  /// Write the texture coordinates of the given set (`TEXCOORD_n`) to the `NAME` field.
+  pub fn tex_coord<const NAME: &'static str>(mut self, set: u32) -> Self
+  where
+    V: Deinterleave<NAME>,
+    <V as HasField<NAME>>::FieldType: FromAttrib<[f32; 2]>,
+  {
+    self
+      .vec2
+      .push(Attribute::new::<NAME>(Semantic::TexCoords(set)));
+    self
+  }
+}
+
+impl<V> Default for VertexMapping<V>
+where
+  V: Vertex,
+{
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+fn read_attribute<'a, 's, F, V, T>(
+  attribute: &Attribute<V, T>,
+  reader: &Reader<'a, 's, F>,
+) -> Result<Vec<T>, Semantic>
+where
+  F: Clone + Fn(Buffer<'a>) -> Option<&'s [u8]>,
+  T: AttributeValue,
+{
+  T::read(reader, &attribute.semantic).ok_or_else(|| attribute.semantic.clone())
+}
+
+fn write_attributes<'a, 's, F, V, T>(
+  attributes: &[Attribute<V, T>],
+  reader: &Reader<'a, 's, F>,
+  vertices: &mut [V],
+) -> Result<(), Semantic>
+where
+  F: Clone + Fn(Buffer<'a>) -> Option<&'s [u8]>,
+  V: Vertex,
+  T: AttributeValue,
+{
+  for attribute in attributes {
+    attribute
+      .setter
+      .set_all(vertices, read_attribute(attribute, reader)?);
+  }
+
+  Ok(())
+}
+
+fn set_attributes<'a, 's, F, V, T>(
+  attributes: &[Attribute<V, T>],
+  reader: &Reader<'a, 's, F>,
+  mut storage: Deinterleaved<V>,
+) -> Result<Deinterleaved<V>, Semantic>
+where
+  F: Clone + Fn(Buffer<'a>) -> Option<&'s [u8]>,
+  V: Vertex,
+  T: AttributeValue,
+{
+  for attribute in attributes {
+    storage = (attribute.set_components)(storage, read_attribute(attribute, reader)?);
+  }
+
+  Ok(storage)
+}
+
+/// Vertex storages the mapped attributes of a primitive can be read into.
+pub(crate) trait ReadStorage<V>: Sized {
+  /// Read the mapped attributes into a storage of `count` vertices.
+  ///
+  /// Returns the semantic of the first missing attribute, if any.
+  fn read<'a, 's, F>(
+    mapping: &VertexMapping<V>,
+    reader: &Reader<'a, 's, F>,
+    count: usize,
+  ) -> Result<Self, Semantic>
+  where
+    F: Clone + Fn(Buffer<'a>) -> Option<&'s [u8]>;
+}
+
+impl<V> ReadStorage<V> for Interleaved<V>
+where
+  V: Vertex + Default,
+{
+  fn read<'a, 's, F>(
+    mapping: &VertexMapping<V>,
+    reader: &Reader<'a, 's, F>,
+    count: usize,
+  ) -> Result<Self, Semantic>
+  where
+    F: Clone + Fn(Buffer<'a>) -> Option<&'s [u8]>,
+  {
+    let mut vertices = vec![V::default(); count];
+
+    write_attributes(&mapping.vec2, reader, &mut vertices)?;
+    write_attributes(&mapping.vec3, reader, &mut vertices)?;
+    write_attributes(&mapping.vec4, reader, &mut vertices)?;
+
+    Ok(Interleaved::new().set_vertices(vertices))
+  }
+}
+
+impl<V> ReadStorage<V> for Deinterleaved<V>
+where
+  V: Vertex + Default,
+{
+  fn read<'a, 's, F>(
+    mapping: &VertexMapping<V>,
+    reader: &Reader<'a, 's, F>,
+    count: usize,
+  ) -> Result<Self, Semantic>
+  where
+    F: Clone + Fn(Buffer<'a>) -> Option<&'s [u8]>,
+  {
+    let storage = Deinterleaved::from_vertices(&vec![V::default(); count]);
+    let storage = set_attributes(&mapping.vec2, reader, storage)?;
+    let storage = set_attributes(&mapping.vec3, reader, storage)?;
+    set_attributes(&mapping.vec4, reader, storage)
+  }
+}
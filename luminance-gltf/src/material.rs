@@ -0,0 +1,171 @@
+//! glTF PBR materials.
+
+use gltf::material::AlphaMode;
+use luminance::shader::{MemoryLayout, Std140, Std430};
+
+/// Reference to a texture of a glTF document, along with the set of texture coordinates to sample it with.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct TextureRef {
+  /// Index of the texture; see [`Gltf::new_texture`](crate::Gltf::new_texture).
+  pub texture: usize,
+
+  /// Set of texture coordinates (`TEXCOORD_n`) the texture is sampled with.
+  pub tex_coord: u32,
+}
+
+impl TextureRef {
+  fn from_info(info: gltf::texture::Info) -> Self {
+    Self {
+      texture: info.texture().index(),
+      tex_coord: info.tex_coord(),
+    }
+  }
+}
+
+/// Scalar parameters of a material, to upload to shaders.
+///
+/// The fields are ordered so that the type has the same layout with `std140` and `std430`, without any padding; it
+/// can then be uploaded as is with [`Context::new_uni_buffer`](luminance::context::Context::new_uni_buffer). The
+/// matching GLSL block is:
+///
+/// ```glsl
+/// layout (std140) uniform Material {
+///   vec4 base_color_factor;
+///   vec3 emissive_factor;
+///   float metallic_factor;
+///   float roughness_factor;
+///   float normal_scale;
+///   float occlusion_strength;
+///   float alpha_cutoff;
+/// };
+/// ```
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MaterialParams {
+  /// Linear RGBA base color, multiplied with the base color texture.
+  pub base_color_factor: [f32; 4],
+
+  /// Linear RGB emissive color, multiplied with the emissive texture.
+  pub emissive_factor: [f32; 3],
+
+  /// Metalness, multiplied with the blue channel of the metallic-roughness texture.
+  pub metallic_factor: f32,
+
+  /// Roughness, multiplied with the green channel of the metallic-roughness texture.
+  pub roughness_factor: f32,
+
+  /// Scale of the X and Y components of the normals read from the normal texture.
+  pub normal_scale: f32,
+
+  /// Strength of the occlusion read from the occlusion texture.
+  pub occlusion_strength: f32,
+
+  /// Alpha below which fragments are discarded, when the alpha mode is [`AlphaMode::Mask`].
+  pub alpha_cutoff: f32,
+}
+
+impl Default for MaterialParams {
+  /// Parameters of the default glTF material.
+  fn default() -> Self {
+    Self {
+      base_color_factor: [1., 1., 1., 1.],
+      emissive_factor: [0., 0., 0.],
+      metallic_factor: 1.,
+      roughness_factor: 1.,
+      normal_scale: 1.,
+      occlusion_strength: 1.,
+      alpha_cutoff: 0.5,
+    }
+  }
+}
+
+unsafe impl MemoryLayout<Std140> for MaterialParams {
+  type Aligned = Self;
+}
+
+unsafe impl MemoryLayout<Std430> for MaterialParams {
+  type Aligned = Self;
+}
+
+/// A glTF metallic-roughness material.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Material {
+  pub name: Option<String>,
+
+  pub params: MaterialParams,
+
+  pub alpha_mode: AlphaMode,
+
+  /// Whether back faces must be rendered too (i.e. face culling must be disabled).
+  pub double_sided: bool,
+
+  /// sRGB base color texture.
+  pub base_color_texture: Option<TextureRef>,
+
+  /// Linear texture with the roughness in its green channel and the metalness in its blue channel.
+  pub metallic_roughness_texture: Option<TextureRef>,
+
+  /// Tangent-space normal texture.
+  pub normal_texture: Option<TextureRef>,
+
+  /// Linear texture with the occlusion in its red channel.
+  pub occlusion_texture: Option<TextureRef>,
+
+  /// sRGB emissive texture.
+  pub emissive_texture: Option<TextureRef>,
+}
+
+impl Default for Material {
+  /// The default glTF material, used by primitives without material.
+  fn default() -> Self {
+    Self {
+      name: None,
+      params: MaterialParams::default(),
+      alpha_mode: AlphaMode::Opaque,
+      double_sided: false,
+      base_color_texture: None,
+      metallic_roughness_texture: None,
+      normal_texture: None,
+      occlusion_texture: None,
+      emissive_texture: None,
+    }
+  }
+}
+
+impl<'a> From<gltf::Material<'a>> for Material {
+  fn from(material: gltf::Material<'a>) -> Self {
+    let pbr = material.pbr_metallic_roughness();
+    let normal = material.normal_texture();
+    let occlusion = material.occlusion_texture();
+
+    let params = MaterialParams {
+      base_color_factor: pbr.base_color_factor(),
+      emissive_factor: material.emissive_factor(),
+      metallic_factor: pbr.metallic_factor(),
+      roughness_factor: pbr.roughness_factor(),
+      normal_scale: normal.as_ref().map_or(1., |normal| normal.scale()),
+      occlusion_strength: occlusion
+        .as_ref()
+        .map_or(1., |occlusion| occlusion.strength()),
+      alpha_cutoff: material.alpha_cutoff().unwrap_or(0.5),
+    };
+
+    Self {
+      name: material.name().map(str::to_owned),
+      params,
+      alpha_mode: material.alpha_mode(),
+      double_sided: material.double_sided(),
+      base_color_texture: pbr.base_color_texture().map(TextureRef::from_info),
+      metallic_roughness_texture: pbr.metallic_roughness_texture().map(TextureRef::from_info),
+      normal_texture: normal.map(|normal| TextureRef {
+        texture: normal.texture().index(),
+        tex_coord: normal.tex_coord(),
+      }),
+      occlusion_texture: occlusion.map(|occlusion| TextureRef {
+        texture: occlusion.texture().index(),
+        tex_coord: occlusion.tex_coord(),
+      }),
+      emissive_texture: material.emissive_texture().map(TextureRef::from_info),
+    }
+  }
+}
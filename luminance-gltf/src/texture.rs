@@ -0,0 +1,140 @@
+//! glTF images and samplers.
+
+use gltf::{
+  image::{Data, Format},
+  texture::{MagFilter as GltfMagFilter, MinFilter as GltfMinFilter, Sampler, WrappingMode},
+};
+use luminance::{
+  pixel::{
+    NormR8UI, NormRG8UI, NormRGB8UI, NormRGBA8UI, Pixel, R8UI, RG8UI, RGB32F, RGB8UI, RGBA32F,
+    RGBA8UI, SRGB8UI, SRGBA8UI,
+  },
+  texture::{MagFilter, MinFilter, TextureSampling, Wrap},
+};
+
+/// Pixel formats glTF images can be converted to.
+///
+/// 8-bit images can be converted to any 8-bit unsigned format with the same number of channels, and 32-bit floating
+/// images to floating formats. RGB and RGBA images can be converted to one another; the alpha channel is then dropped,
+/// or set to its maximum value.
+pub trait ImagePixel: Pixel {
+  /// Convert the pixels of an image, if the format of the image is supported.
+  fn texels(image: &Data) -> Option<Vec<Self::RawEncoding>>;
+}
+
+macro_rules! impl_ImagePixel_u8 {
+  ($t:ty, $channels:expr) => {
+    impl ImagePixel for $t {
+      fn texels(image: &Data) -> Option<Vec<Self::RawEncoding>> {
+        let channels = match image.format {
+          Format::R8 => 1,
+          Format::R8G8 => 2,
+          Format::R8G8B8 => 3,
+          Format::R8G8B8A8 => 4,
+          _ => return None,
+        };
+
+        convert_channels(&image.pixels, channels, $channels, u8::MAX)
+      }
+    }
+  };
+}
+
+impl_ImagePixel_u8!(R8UI, 1);
+impl_ImagePixel_u8!(NormR8UI, 1);
+impl_ImagePixel_u8!(RG8UI, 2);
+impl_ImagePixel_u8!(NormRG8UI, 2);
+impl_ImagePixel_u8!(RGB8UI, 3);
+impl_ImagePixel_u8!(NormRGB8UI, 3);
+impl_ImagePixel_u8!(SRGB8UI, 3);
+impl_ImagePixel_u8!(RGBA8UI, 4);
+impl_ImagePixel_u8!(NormRGBA8UI, 4);
+impl_ImagePixel_u8!(SRGBA8UI, 4);
+
+macro_rules! impl_ImagePixel_f32 {
+  ($t:ty, $channels:expr) => {
+    impl ImagePixel for $t {
+      fn texels(image: &Data) -> Option<Vec<Self::RawEncoding>> {
+        let channels = match image.format {
+          Format::R32G32B32FLOAT => 3,
+          Format::R32G32B32A32FLOAT => 4,
+          _ => return None,
+        };
+
+        let floats = image
+          .pixels
+          .chunks_exact(4)
+          .map(|bytes| f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+          .collect::<Vec<_>>();
+        convert_channels(&floats, channels, $channels, 1.)
+      }
+    }
+  };
+}
+
+impl_ImagePixel_f32!(RGB32F, 3);
+impl_ImagePixel_f32!(RGBA32F, 4);
+
+/// Convert pixels of `from` channels to pixels of `to` channels.
+///
+/// Only RGB and RGBA pixels can be converted to one another, by dropping the alpha channel or adding one set to `alpha`.
+fn convert_channels<T>(pixels: &[T], from: usize, to: usize, alpha: T) -> Option<Vec<T>>
+where
+  T: Copy,
+{
+  match (from, to) {
+    _ if from == to => Some(pixels.to_vec()),
+
+    (4, 3) => Some(
+      pixels
+        .chunks_exact(4)
+        .flat_map(|pixel| &pixel[..3])
+        .copied()
+        .collect(),
+    ),
+
+    (3, 4) => Some(
+      pixels
+        .chunks_exact(3)
+        .flat_map(|pixel| [pixel[0], pixel[1], pixel[2], alpha])
+        .collect(),
+    ),
+
+    _ => None,
+  }
+}
+
+/// Convert a glTF sampler to texture sampling.
+///
+/// Filters that are not specified are linear; the `r` coordinate is clamped to the edge.
+pub fn texture_sampling(sampler: &Sampler) -> TextureSampling {
+  let min_filter = match sampler.min_filter() {
+    Some(GltfMinFilter::Nearest) => MinFilter::Nearest,
+    Some(GltfMinFilter::Linear) | None => MinFilter::Linear,
+    Some(GltfMinFilter::NearestMipmapNearest) => MinFilter::NearestMipmapNearest,
+    Some(GltfMinFilter::NearestMipmapLinear) => MinFilter::NearestMipmapLinear,
+    Some(GltfMinFilter::LinearMipmapNearest) => MinFilter::LinearMipmapNearest,
+    Some(GltfMinFilter::LinearMipmapLinear) => MinFilter::LinearMipmapLinear,
+  };
+
+  let mag_filter = match sampler.mag_filter() {
+    Some(GltfMagFilter::Nearest) => MagFilter::Nearest,
+    Some(GltfMagFilter::Linear) | None => MagFilter::Linear,
+  };
+
+  TextureSampling {
+    wrap_s: wrap(sampler.wrap_s()),
+    wrap_t: wrap(sampler.wrap_t()),
+    min_filter,
+    mag_filter,
+    ..TextureSampling::default()
+  }
+}
+
+fn wrap(mode: WrappingMode) -> Wrap {
+  match mode {
+    WrappingMode::ClampToEdge => Wrap::ClampToEdge,
+    WrappingMode::MirroredRepeat => Wrap::MirroredRepeat,
+    WrappingMode::Repeat => Wrap::Repeat,
+  }
+}
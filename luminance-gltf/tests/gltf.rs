@@ -0,0 +1,279 @@
+#![allow(incomplete_features)]
+#![feature(adt_const_params)]
+
+use luminance::{
+  backend::mock::MockBackend,
+  context::Context,
+  namespace,
+  pixel::{RGB32F, SRGB8UI, SRGBA8UI},
+  shader::{MemoryLayout, Std140},
+  texture::{MagFilter, MinFilter, Mipmaps, Wrap},
+  vertex_storage::Interleaved,
+  Vertex,
+};
+use luminance_gltf::{
+  gltf::{material::AlphaMode, mesh::Mode, Semantic},
+  Gltf, GltfError, Material, MaterialParams, TextureRef, VertexMapping,
+};
+use std::{io::Cursor, mem};
+
+namespace! {
+  Namespace = { "position", "normal", "tex_coord" }
+}
+
+// normals get a w of 1
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Vertex)]
+#[vertex(namespace = "Namespace")]
+struct Vertex {
+  position: [f32; 3],
+  normal: [f32; 4],
+  tex_coord: [f32; 2],
+}
+
+const POSITIONS: [[f32; 3]; 4] = [[0., 0., 0.], [1., 0., 0.], [1., 1., 0.], [0., 1., 0.]];
+const UVS: [[f32; 2]; 4] = [[0., 1.], [1., 1.], [1., 0.], [0., 0.]];
+const INDICES: [u16; 6] = [0, 1, 2, 0, 2, 3];
+
+/// A textured quad, and a mesh of points without texture coordinates.
+fn quads() -> Vec<u8> {
+  let mut png = Vec::new();
+  image::RgbImage::from_raw(2, 1, vec![255, 0, 0, 0, 255, 0])
+    .unwrap()
+    .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+    .unwrap();
+
+  let mut bin = Vec::new();
+  bin.extend(POSITIONS.iter().flatten().flat_map(|x| x.to_le_bytes()));
+  bin.extend(
+    [[0f32, 0., 1.]; 4]
+      .iter()
+      .flatten()
+      .flat_map(|x| x.to_le_bytes()),
+  );
+  bin.extend(UVS.iter().flatten().flat_map(|x| x.to_le_bytes()));
+  bin.extend(INDICES.iter().flat_map(|i| i.to_le_bytes()));
+  bin.extend([0; 4]);
+  bin.extend(&png);
+
+  let json = format!(
+    r#"{{
+      "asset": {{ "version": "2.0" }},
+      "buffers": [{{ "byteLength": {buffer_len} }}],
+      "bufferViews": [
+        {{ "buffer": 0, "byteOffset": 0, "byteLength": 48 }},
+        {{ "buffer": 0, "byteOffset": 48, "byteLength": 48 }},
+        {{ "buffer": 0, "byteOffset": 96, "byteLength": 32 }},
+        {{ "buffer": 0, "byteOffset": 128, "byteLength": 12 }},
+        {{ "buffer": 0, "byteOffset": 144, "byteLength": {png_len} }}
+      ],
+      "accessors": [
+        {{ "bufferView": 0, "componentType": 5126, "count": 4, "type": "VEC3", "min": [0, 0, 0], "max": [1, 1, 0] }},
+        {{ "bufferView": 1, "componentType": 5126, "count": 4, "type": "VEC3" }},
+        {{ "bufferView": 2, "componentType": 5126, "count": 4, "type": "VEC2" }},
+        {{ "bufferView": 3, "componentType": 5123, "count": 6, "type": "SCALAR" }}
+      ],
+      "meshes": [
+        {{
+          "name": "quad",
+          "primitives": [{{ "attributes": {{ "POSITION": 0, "NORMAL": 1, "TEXCOORD_0": 2 }}, "indices": 3, "material": 0 }}]
+        }},
+        {{
+          "name": "points",
+          "primitives": [{{ "attributes": {{ "POSITION": 0, "NORMAL": 1 }}, "mode": 0 }}]
+        }}
+      ],
+      "materials": [{{
+        "name": "red",
+        "pbrMetallicRoughness": {{
+          "baseColorFactor": [1, 0, 0, 1],
+          "metallicFactor": 0.5,
+          "baseColorTexture": {{ "index": 0 }}
+        }},
+        "alphaMode": "MASK",
+        "alphaCutoff": 0.25,
+        "doubleSided": true
+      }}],
+      "textures": [{{ "sampler": 0, "source": 0 }}],
+      "samplers": [{{ "magFilter": 9728, "minFilter": 9987, "wrapS": 33071, "wrapT": 33648 }}],
+      "images": [{{ "bufferView": 4, "mimeType": "image/png" }}]
+    }}"#,
+    buffer_len = bin.len(),
+    png_len = png.len()
+  );
+
+  glb(json.as_bytes(), &bin)
+}
+
+/// Pack a JSON document and its binary buffer into a binary glTF file.
+fn glb(json: &[u8], bin: &[u8]) -> Vec<u8> {
+  fn chunk(out: &mut Vec<u8>, ty: &[u8], data: &[u8], pad: u8) {
+    let len = (data.len() + 3) & !3;
+    out.extend((len as u32).to_le_bytes());
+    out.extend(ty);
+    out.extend(data);
+    out.resize(out.len() + len - data.len(), pad);
+  }
+
+  let mut chunks = Vec::new();
+  chunk(&mut chunks, b"JSON", json, b' ');
+  chunk(&mut chunks, b"BIN\0", bin, 0);
+
+  let mut out = Vec::new();
+  out.extend(b"glTF");
+  out.extend(2u32.to_le_bytes());
+  out.extend((12 + chunks.len() as u32).to_le_bytes());
+  out.extend(chunks);
+  out
+}
+
+fn mapping() -> VertexMapping<Vertex> {
+  VertexMapping::new()
+    .position::<"position">()
+    .normal::<"normal">()
+}
+
+#[test]
+fn interleaved_meshes() {
+  let gltf = Gltf::from_slice(&quads()).unwrap();
+  let meshes = gltf
+    .interleaved_meshes(&mapping().tex_coord::<"tex_coord">(0))
+    .map(|_| ());
+  assert!(matches!(
+    meshes,
+    Err(GltfError::MissingAttribute {
+      mesh: 1,
+      primitive: 0,
+      semantic: Semantic::TexCoords(0),
+    })
+  ));
+
+  let meshes = gltf.interleaved_meshes(&mapping()).unwrap();
+  assert_eq!(meshes.len(), 2);
+  assert_eq!(meshes[0].name.as_deref(), Some("quad"));
+
+  let quad = &meshes[0].primitives[0];
+  assert_eq!(quad.mode, Mode::Triangles);
+  assert_eq!(quad.material, Some(0));
+  assert_eq!(quad.builder.indices, INDICES.map(u32::from));
+  assert_eq!(
+    quad.builder.vertices.vertices()[1],
+    Vertex {
+      position: [1., 0., 0.],
+      normal: [0., 0., 1., 1.],
+      tex_coord: [0., 0.],
+    }
+  );
+
+  // primitives without indices are rendered directly
+  let points = &meshes[1].primitives[0];
+  let vertices: &Interleaved<Vertex> = &points.builder.vertices;
+  assert_eq!(points.mode, Mode::Points);
+  assert_eq!(points.material, None);
+  assert!(points.builder.indices.is_empty());
+  assert_eq!(vertices.vertices().len(), 4);
+}
+
+#[test]
+fn deinterleaved_meshes() {
+  let gltf = Gltf::from_slice(&quads()).unwrap();
+  let mapping = mapping();
+  let interleaved = gltf.interleaved_meshes(&mapping).unwrap();
+  let deinterleaved = gltf.deinterleaved_meshes(&mapping).unwrap();
+
+  for (interleaved, deinterleaved) in interleaved.iter().zip(&deinterleaved) {
+    let interleaved = &interleaved.primitives[0].builder;
+    let deinterleaved = &deinterleaved.primitives[0].builder;
+    let vertices = interleaved.vertices.vertices();
+    let components = deinterleaved.vertices.components_list();
+
+    assert_eq!(deinterleaved.indices, interleaved.indices);
+    assert_eq!(deinterleaved.vertices.len(), vertices.len());
+
+    let bytes = |f: fn(&Vertex) -> Vec<f32>| -> Vec<u8> {
+      vertices
+        .iter()
+        .flat_map(f)
+        .flat_map(|x| x.to_ne_bytes())
+        .collect()
+    };
+    assert_eq!(components[0], bytes(|v| v.position.to_vec()));
+    assert_eq!(components[1], bytes(|v| v.normal.to_vec()));
+    assert_eq!(components[2], vec![0; 8 * vertices.len()]);
+  }
+}
+
+#[test]
+fn materials() {
+  let gltf = Gltf::from_slice(&quads()).unwrap();
+  let materials = gltf.materials();
+  assert_eq!(materials.len(), 1);
+
+  let red = &materials[0];
+  assert_eq!(red.name.as_deref(), Some("red"));
+  assert_eq!(red.alpha_mode, AlphaMode::Mask);
+  assert!(red.double_sided);
+  assert_eq!(
+    red.params,
+    MaterialParams {
+      base_color_factor: [1., 0., 0., 1.],
+      metallic_factor: 0.5,
+      alpha_cutoff: 0.25,
+      ..MaterialParams::default()
+    }
+  );
+  assert_eq!(
+    red.base_color_texture,
+    Some(TextureRef {
+      texture: 0,
+      tex_coord: 0,
+    })
+  );
+  assert_eq!(red.normal_texture, None);
+
+  assert_eq!(&gltf.material(Some(0)), red);
+  assert_eq!(gltf.material(None), Material::default());
+
+  // the parameters have the same size with std140 as the GLSL block
+  assert_eq!(
+    mem::size_of::<<MaterialParams as MemoryLayout<Std140>>::Aligned>(),
+    48
+  );
+}
+
+#[test]
+fn textures() {
+  let gltf = Gltf::from_slice(&quads()).unwrap();
+
+  let sampling = gltf.texture_sampling(0).unwrap();
+  assert_eq!(sampling.wrap_s, Wrap::ClampToEdge);
+  assert_eq!(sampling.wrap_t, Wrap::MirroredRepeat);
+  assert_eq!(sampling.min_filter, MinFilter::LinearMipmapLinear);
+  assert_eq!(sampling.mag_filter, MagFilter::Nearest);
+
+  // RGB images get an opaque alpha channel
+  assert_eq!(
+    gltf.texels::<SRGBA8UI>(0).unwrap(),
+    [255, 0, 0, 255, 0, 255, 0, 255]
+  );
+  assert_eq!(gltf.texels::<SRGB8UI>(0).unwrap(), [255, 0, 0, 0, 255, 0]);
+  assert!(matches!(
+    gltf.texels::<RGB32F>(0),
+    Err(GltfError::UnsupportedImageFormat { image: 0, .. })
+  ));
+  assert!(matches!(
+    gltf.texture_sampling(1),
+    Err(GltfError::NoSuchTexture { texture: 1 })
+  ));
+
+  let mut ctx = Context::new(MockBackend::new).unwrap();
+  let texture = gltf
+    .new_texture::<_, SRGBA8UI>(&mut ctx, 0, Mipmaps::count(2))
+    .unwrap();
+  assert_eq!(texture.size().width, 2);
+  assert_eq!(texture.size().height, 1);
+  assert_eq!(
+    ctx.read_texture(&texture).unwrap(),
+    [255, 0, 0, 255, 0, 255, 0, 255]
+  );
+}
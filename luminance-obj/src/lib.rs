@@ -0,0 +1,386 @@
+//! Wavefront OBJ and MTL loader for luminance.
+//!
+//! [`Obj`] parses OBJ files (and the MTL libraries they reference) and turns their faces into
+//! [`VertexEntityBuilder`]s for any [`Vertex`] type. Positions, normals and texture coordinates are written to the
+//! vertex fields selected by a [`VertexMapping`], and vertices are deduplicated into indices. Faces are split by
+//! material, one builder per material.
+//!
+//! ```ignore
+//! let obj = Obj::load("suzanne.obj")?;
+//! let mapping = VertexMapping::<MyVertex>::new()
+//!   .position::<"pos">()
+//!   .normal::<"nor">();
+//!
+//! for mesh in obj.interleaved_meshes(&mapping) {
+//!   let material = mesh.material.as_deref().and_then(|name| obj.material(name));
+//!   let entity = ctx.new_vertex_entity(mesh.builder)?;
+//!   // …
+//! }
+//! ```
+//!
+//! Polygons are triangulated as fans; lines, points, curves and surfaces are ignored.
+
+#![allow(incomplete_features)]
+#![feature(adt_const_params)]
+
+pub mod mapping;
+pub mod mtl;
+
+pub use mapping::VertexMapping;
+pub use mtl::{parse_mtl, Material};
+
+use luminance::{
+  vertex::Vertex,
+  vertex_entity::VertexEntityBuilder,
+  vertex_storage::{Deinterleaved, Interleaved},
+};
+use std::{collections::HashMap, error::Error, fmt, fs, io, path::Path, str::SplitWhitespace};
+
+/// Errors that might happen when loading OBJ and MTL files.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ObjError {
+  /// A file couldn’t be read.
+  Io { path: String, cause: io::Error },
+
+  /// A statement is malformed.
+  InvalidStatement { line: usize, statement: String },
+
+  /// A face refers to a vertex attribute that is not defined (yet).
+  InvalidIndex { line: usize, index: i64 },
+}
+
+impl ObjError {
+  fn invalid_statement(line: usize, statement: &str) -> Self {
+    ObjError::InvalidStatement {
+      line,
+      statement: statement.to_owned(),
+    }
+  }
+}
+
+impl fmt::Display for ObjError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      ObjError::Io { path, cause } => write!(f, "cannot read {}: {}", path, cause),
+
+      ObjError::InvalidStatement { line, statement } => {
+        write!(f, "invalid statement at line {}: {}", line, statement)
+      }
+
+      ObjError::InvalidIndex { line, index } => {
+        write!(f, "invalid index {} at line {}", index, line)
+      }
+    }
+  }
+}
+
+impl Error for ObjError {
+  fn source(&self) -> Option<&(dyn Error + 'static)> {
+    match self {
+      ObjError::Io { cause, .. } => Some(cause),
+      _ => None,
+    }
+  }
+}
+
+/// A vertex of a face, made of indices into the attributes of an [`Obj`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct FaceVertex {
+  pub position: usize,
+  pub uv: Option<usize>,
+  pub normal: Option<usize>,
+}
+
+/// Triangles sharing the same material.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Group {
+  pub material: Option<String>,
+  pub triangles: Vec<[FaceVertex; 3]>,
+}
+
+/// A parsed OBJ file.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Obj {
+  pub positions: Vec<[f32; 3]>,
+  pub uvs: Vec<[f32; 2]>,
+  pub normals: Vec<[f32; 3]>,
+
+  /// Triangles, grouped by material in order of first use.
+  pub groups: Vec<Group>,
+
+  /// Paths of the MTL libraries referenced by the file.
+  pub material_libs: Vec<String>,
+
+  pub materials: Vec<Material>,
+}
+
+/// A mesh of an [`Obj`], ready to be turned into a vertex entity.
+#[derive(Debug)]
+pub struct Mesh<S> {
+  /// Name of the material of the mesh, if any; see [`Obj::material`].
+  pub material: Option<String>,
+
+  pub builder: VertexEntityBuilder<S, (), u32>,
+}
+
+impl Obj {
+  /// Parse an OBJ file.
+  ///
+  /// The MTL libraries are not loaded; see [`Obj::add_materials`] and [`Obj::load`].
+  pub fn parse(src: &str) -> Result<Self, ObjError> {
+    let mut obj = Obj::default();
+    let mut group = 0;
+
+    for (line_nb, line) in src.lines().enumerate() {
+      let line_nb = line_nb + 1;
+      let line = line.split('#').next().unwrap_or("").trim();
+      let mut words = line.split_whitespace();
+
+      let keyword = match words.next() {
+        Some(keyword) => keyword,
+        None => continue,
+      };
+
+      match keyword {
+        "v" => {
+          let [x, y, z, _] = parse_floats::<4>(words, 3)
+            .ok_or_else(|| ObjError::invalid_statement(line_nb, line))?;
+          obj.positions.push([x, y, z]);
+        }
+
+        "vt" => {
+          let [u, v, _] = parse_floats::<3>(words, 1)
+            .ok_or_else(|| ObjError::invalid_statement(line_nb, line))?;
+          obj.uvs.push([u, v]);
+        }
+
+        "vn" => {
+          let normal = parse_floats::<3>(words, 3)
+            .ok_or_else(|| ObjError::invalid_statement(line_nb, line))?;
+          obj.normals.push(normal);
+        }
+
+        "f" => {
+          let face = words
+            .map(|word| obj.parse_face_vertex(line_nb, line, word))
+            .collect::<Result<Vec<_>, _>>()?;
+
+          if face.len() < 3 {
+            return Err(ObjError::invalid_statement(line_nb, line));
+          }
+
+          if obj.groups.is_empty() {
+            obj.groups.push(Group::default());
+          }
+
+          let triangles = &mut obj.groups[group].triangles;
+          for i in 1..face.len() - 1 {
+            triangles.push([face[0], face[i], face[i + 1]]);
+          }
+        }
+
+        "usemtl" => {
+          let material = words.collect::<Vec<_>>().join(" ");
+
+          group = match obj
+            .groups
+            .iter()
+            .position(|group| group.material.as_deref() == Some(material.as_str()))
+          {
+            Some(group) => group,
+
+            // faces without material come first; reuse that group if nothing was put in it
+            None if obj.groups.len() == 1 && obj.groups[0].triangles.is_empty() => {
+              obj.groups[0].material = Some(material);
+              0
+            }
+
+            None => {
+              obj.groups.push(Group {
+                material: Some(material),
+                triangles: Vec::new(),
+              });
+              obj.groups.len() - 1
+            }
+          };
+        }
+
+        "mtllib" => obj.material_libs.extend(words.map(str::to_owned)),
+
+        // objects, groups, smoothing groups, lines, points, etc. are not used
+        _ => (),
+      }
+    }
+
+    obj.groups.retain(|group| !group.triangles.is_empty());
+    Ok(obj)
+  }
+
+  /// Load an OBJ file and the MTL libraries it references.
+  ///
+  /// The libraries are looked up relatively to the directory of the OBJ file.
+  pub fn load(path: impl AsRef<Path>) -> Result<Self, ObjError> {
+    let path = path.as_ref();
+    let mut obj = Self::parse(&read_file(path)?)?;
+
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+    for lib in obj.material_libs.clone() {
+      obj.add_materials(&read_file(&dir.join(lib))?)?;
+    }
+
+    Ok(obj)
+  }
+
+  /// Parse a MTL library and add its materials.
+  pub fn add_materials(&mut self, src: &str) -> Result<(), ObjError> {
+    self.materials.extend(parse_mtl(src)?);
+    Ok(())
+  }
+
+  /// Find a material by name.
+  pub fn material(&self, name: &str) -> Option<&Material> {
+    self.materials.iter().find(|material| material.name == name)
+  }
+
+  /// Build a mesh per material, with interleaved vertices.
+  pub fn interleaved_meshes<V>(&self, mapping: &VertexMapping<V>) -> Vec<Mesh<Interleaved<V>>>
+  where
+    V: Vertex + Default,
+  {
+    self
+      .groups
+      .iter()
+      .map(|group| {
+        let (vertices, indices) = self.deduplicate(group, mapping);
+
+        Mesh {
+          material: group.material.clone(),
+          builder: VertexEntityBuilder::new()
+            .add_vertices(Interleaved::new().set_vertices(vertices))
+            .add_indices(indices),
+        }
+      })
+      .collect()
+  }
+
+  /// Build a mesh per material, with deinterleaved vertices.
+  pub fn deinterleaved_meshes<V>(&self, mapping: &VertexMapping<V>) -> Vec<Mesh<Deinterleaved<V>>>
+  where
+    V: Vertex + Default,
+  {
+    self
+      .groups
+      .iter()
+      .map(|group| {
+        let (vertices, indices) = self.deduplicate(group, mapping);
+
+        Mesh {
+          material: group.material.clone(),
+          builder: VertexEntityBuilder::new()
+            .add_vertices(Deinterleaved::from_vertices(&vertices))
+            .add_indices(indices),
+        }
+      })
+      .collect()
+  }
+
+  /// Build the vertices of a group, each unique face vertex once, and the indices of its triangles.
+  fn deduplicate<V>(&self, group: &Group, mapping: &VertexMapping<V>) -> (Vec<V>, Vec<u32>)
+  where
+    V: Vertex + Default,
+  {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::with_capacity(group.triangles.len() * 3);
+    let mut known = HashMap::new();
+
+    for face_vertex in group.triangles.iter().flatten() {
+      let index = *known.entry(*face_vertex).or_insert_with(|| {
+        vertices.push(mapping.vertex(
+          self.positions[face_vertex.position],
+          face_vertex.normal.map(|normal| self.normals[normal]),
+          face_vertex.uv.map(|uv| self.uvs[uv]),
+        ));
+
+        vertices.len() as u32 - 1
+      });
+
+      indices.push(index);
+    }
+
+    (vertices, indices)
+  }
+
+  /// Parse a `position/uv/normal` face vertex; the texture coordinates and the normal are optional.
+  fn parse_face_vertex(
+    &self,
+    line_nb: usize,
+    line: &str,
+    word: &str,
+  ) -> Result<FaceVertex, ObjError> {
+    let mut parts = word.split('/');
+
+    let mut index = |len: usize, required: bool| -> Result<Option<usize>, ObjError> {
+      match parts.next() {
+        Some(part) if !part.is_empty() => {
+          let index: i64 = part
+            .parse()
+            .map_err(|_| ObjError::invalid_statement(line_nb, line))?;
+          resolve_index(index, len)
+            .map(Some)
+            .ok_or(ObjError::InvalidIndex {
+              line: line_nb,
+              index,
+            })
+        }
+
+        _ if required => Err(ObjError::invalid_statement(line_nb, line)),
+
+        _ => Ok(None),
+      }
+    };
+
+    let position = index(self.positions.len(), true)?.unwrap_or_default();
+    let uv = index(self.uvs.len(), false)?;
+    let normal = index(self.normals.len(), false)?;
+
+    Ok(FaceVertex {
+      position,
+      uv,
+      normal,
+    })
+  }
+}
+
+/// Resolve a 1-based index, or a negative index relative to the end, into a 0-based index.
+fn resolve_index(index: i64, len: usize) -> Option<usize> {
+  let resolved = if index < 0 {
+    len as i64 + index
+  } else {
+    index - 1
+  };
+
+  (0..len as i64)
+    .contains(&resolved)
+    .then_some(resolved as usize)
+}
+
+/// Parse at least `min` and at most `N` floats, the missing ones being `0`.
+pub(crate) fn parse_floats<const N: usize>(words: SplitWhitespace, min: usize) -> Option<[f32; N]> {
+  let mut floats = [0.; N];
+  let mut len = 0;
+
+  for word in words {
+    *floats.get_mut(len)? = word.parse().ok()?;
+    len += 1;
+  }
+
+  (len >= min).then_some(floats)
+}
+
+fn read_file(path: &Path) -> Result<String, ObjError> {
+  fs::read_to_string(path).map_err(|cause| ObjError::Io {
+    path: path.display().to_string(),
+    cause,
+  })
+}
@@ -0,0 +1,106 @@
+//! Mapping of OBJ attributes onto vertex fields.
+
+use luminance::{
+  has_field::HasField,
+  vertex::{FieldSetter, FromAttrib, Vertex},
+};
+
+/// Fields of a vertex type the OBJ attributes are written to.
+///
+/// Fields are selected by name, the same way [`Deinterleaved::set_components`] does, and attributes are converted to
+/// the type of the fields with [`FromAttrib`]. Fields that are not mapped keep their default value, as do mapped fields
+/// of vertices not providing the attribute (e.g. a face without normals).
+///
+/// ```ignore
+/// let mapping = VertexMapping::<MyVertex>::new()
+///   .position::<"pos">()
+///   .normal::<"nor">()
+///   .uv::<"uv">();
+/// ```
+///
+/// [`Deinterleaved::set_components`]: luminance::vertex_storage::Deinterleaved::set_components
+pub struct VertexMapping<V> {
+  position: Option<FieldSetter<V, [f32; 3]>>,
+  normal: Option<FieldSetter<V, [f32; 3]>>,
+  uv: Option<FieldSetter<V, [f32; 2]>>,
+}
+
+impl<V> VertexMapping<V>
+where
+  V: Vertex,
+{
+  /// Map no attribute.
+  pub fn new() -> Self {
+    Self {
+      position: None,
+      normal: None,
+      uv: None,
+    }
+  }
+
+  /// Write positions to the `NAME` field.
+  pub fn position<const NAME: &'static str>(mut self) -> Self
+  where
+    V: HasField<NAME>,
+    <V as HasField<NAME>>::FieldType: FromAttrib<[f32; 3]>,
+  {
+    self.position = Some(FieldSetter::new::<NAME>());
+    self
+  }
+
+  /// Write normals to the `NAME` field.
+  pub fn normal<const NAME: &'static str>(mut self) -> Self
+  where
+    V: HasField<NAME>,
+    <V as HasField<NAME>>::FieldType: FromAttrib<[f32; 3]>,
+  {
+    self.normal = Some(FieldSetter::new::<NAME>());
+    self
+  }
+
+  /// Write texture coordinates to the `NAME` field.
+  pub fn uv<const NAME: &'static str>(mut self) -> Self
+  where
+    V: HasField<NAME>,
+    <V as HasField<NAME>>::FieldType: FromAttrib<[f32; 2]>,
+  {
+    self.uv = Some(FieldSetter::new::<NAME>());
+    self
+  }
+
+  /// Build a vertex out of its OBJ attributes.
+  pub(crate) fn vertex(
+    &self,
+    position: [f32; 3],
+    normal: Option<[f32; 3]>,
+    uv: Option<[f32; 2]>,
+  ) -> V
+  where
+    V: Default,
+  {
+    let mut vertex = V::default();
+
+    if let Some(setter) = &self.position {
+      setter.set(&mut vertex, position);
+    }
+
+    if let (Some(setter), Some(normal)) = (&self.normal, normal) {
+      setter.set(&mut vertex, normal);
+    }
+
+    if let (Some(setter), Some(uv)) = (&self.uv, uv) {
+      setter.set(&mut vertex, uv);
+    }
+
+    vertex
+  }
+}
+
+impl<V> Default for VertexMapping<V>
+where
+  V: Vertex,
+{
+  fn default() -> Self {
+    Self::new()
+  }
+}
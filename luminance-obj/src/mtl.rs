@@ -0,0 +1,135 @@
+//! MTL material libraries.
+
+use crate::{parse_floats, ObjError};
+
+/// A material, as described in a MTL library.
+///
+/// Only the most common statements are supported; the others are ignored. Texture maps are kept as the paths found in
+/// the library, without their options.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Material {
+  pub name: String,
+
+  /// Ambient color (`Ka`).
+  pub ambient: [f32; 3],
+
+  /// Diffuse color (`Kd`).
+  pub diffuse: [f32; 3],
+
+  /// Specular color (`Ks`).
+  pub specular: [f32; 3],
+
+  /// Emissive color (`Ke`).
+  pub emissive: [f32; 3],
+
+  /// Specular exponent (`Ns`).
+  pub shininess: f32,
+
+  /// Opacity (`d`, or `1 - Tr`).
+  pub dissolve: f32,
+
+  /// Index of refraction (`Ni`).
+  pub optical_density: f32,
+
+  /// Illumination model (`illum`).
+  pub illumination: Option<u32>,
+
+  pub ambient_map: Option<String>,
+  pub diffuse_map: Option<String>,
+  pub specular_map: Option<String>,
+  pub normal_map: Option<String>,
+  pub dissolve_map: Option<String>,
+}
+
+impl Material {
+  pub fn new(name: impl Into<String>) -> Self {
+    Self {
+      name: name.into(),
+      ambient: [0., 0., 0.],
+      diffuse: [1., 1., 1.],
+      specular: [0., 0., 0.],
+      emissive: [0., 0., 0.],
+      shininess: 0.,
+      dissolve: 1.,
+      optical_density: 1.,
+      illumination: None,
+      ambient_map: None,
+      diffuse_map: None,
+      specular_map: None,
+      normal_map: None,
+      dissolve_map: None,
+    }
+  }
+}
+
+/// Parse a MTL library.
+pub fn parse_mtl(src: &str) -> Result<Vec<Material>, ObjError> {
+  let mut materials: Vec<Material> = Vec::new();
+
+  for (line_nb, line) in src.lines().enumerate() {
+    let line_nb = line_nb + 1;
+    let line = line.split('#').next().unwrap_or("").trim();
+    let mut words = line.split_whitespace();
+
+    let keyword = match words.next() {
+      Some(keyword) => keyword,
+      None => continue,
+    };
+
+    if keyword == "newmtl" {
+      let name = words.collect::<Vec<_>>().join(" ");
+
+      if name.is_empty() {
+        return Err(ObjError::invalid_statement(line_nb, line));
+      }
+
+      materials.push(Material::new(name));
+      continue;
+    }
+
+    let material = materials
+      .last_mut()
+      .ok_or_else(|| ObjError::invalid_statement(line_nb, line))?;
+    let color =
+      |words| parse_floats::<3>(words, 3).ok_or_else(|| ObjError::invalid_statement(line_nb, line));
+    let scalar = |words| {
+      parse_floats::<1>(words, 1)
+        .map(|[x]| x)
+        .ok_or_else(|| ObjError::invalid_statement(line_nb, line))
+    };
+    // texture maps can have options before the path, which is always last
+    let map = |words: std::str::SplitWhitespace| {
+      words
+        .last()
+        .map(str::to_owned)
+        .ok_or_else(|| ObjError::invalid_statement(line_nb, line))
+    };
+
+    match keyword {
+      "Ka" => material.ambient = color(words)?,
+      "Kd" => material.diffuse = color(words)?,
+      "Ks" => material.specular = color(words)?,
+      "Ke" => material.emissive = color(words)?,
+      "Ns" => material.shininess = scalar(words)?,
+      "d" => material.dissolve = scalar(words)?,
+      "Tr" => material.dissolve = 1. - scalar(words)?,
+      "Ni" => material.optical_density = scalar(words)?,
+      "illum" => {
+        material.illumination = Some(
+          words
+            .next()
+            .and_then(|word| word.parse().ok())
+            .ok_or_else(|| ObjError::invalid_statement(line_nb, line))?,
+        )
+      }
+      "map_Ka" => material.ambient_map = Some(map(words)?),
+      "map_Kd" => material.diffuse_map = Some(map(words)?),
+      "map_Ks" => material.specular_map = Some(map(words)?),
+      "map_Bump" | "map_bump" | "bump" | "norm" => material.normal_map = Some(map(words)?),
+      "map_d" => material.dissolve_map = Some(map(words)?),
+      _ => (),
+    }
+  }
+
+  Ok(materials)
+}
@@ -0,0 +1,201 @@
+#![allow(incomplete_features)]
+#![feature(adt_const_params)]
+
+use luminance::{namespace, vertex_storage::Interleaved, Vertex};
+use luminance_obj::{FaceVertex, Obj, ObjError, VertexMapping};
+
+namespace! {
+  Namespace = { "position", "tex_coord", "normal", "tint" }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Vertex)]
+#[vertex(namespace = "Namespace")]
+struct Vertex {
+  position: [f32; 3],
+  tex_coord: [f32; 2],
+  normal: [f32; 3],
+  tint: [u8; 4],
+}
+
+// fields that are not mapped keep their default value
+impl Default for Vertex {
+  fn default() -> Self {
+    Self {
+      position: [0.; 3],
+      tex_coord: [0.; 2],
+      normal: [0.; 3],
+      tint: [255; 4],
+    }
+  }
+}
+
+const QUADS: &str = "
+# two quads sharing an edge, with a material each
+mtllib quads.mtl
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+v 2 0 0
+v 2 1 0
+vt 0 0
+vt 1 0
+vt 1 1
+vt 0 1
+vn 0 0 1
+
+usemtl red
+f 1/1/1 2/2/1 3/3/1 4/4/1
+usemtl green
+f 2/1/1 5/2/1 6/3/1 -4/4/-1
+";
+
+const MTL: &str = "
+newmtl red
+Kd 1 0 0
+Ns 10
+map_Kd -o 1 1 red.png
+
+newmtl green
+Kd 0 1 0
+Tr 0.25
+";
+
+fn mapping() -> VertexMapping<Vertex> {
+  VertexMapping::new()
+    .position::<"position">()
+    .normal::<"normal">()
+    .uv::<"tex_coord">()
+}
+
+#[test]
+fn parse_quads() {
+  let obj = Obj::parse(QUADS).unwrap();
+
+  assert_eq!(obj.positions.len(), 6);
+  assert_eq!(obj.uvs.len(), 4);
+  assert_eq!(obj.normals, [[0., 0., 1.]]);
+  assert_eq!(obj.material_libs, ["quads.mtl"]);
+
+  // quads are triangulated and split by material
+  assert_eq!(obj.groups.len(), 2);
+  assert_eq!(obj.groups[0].material.as_deref(), Some("red"));
+  assert_eq!(obj.groups[0].triangles.len(), 2);
+  assert_eq!(obj.groups[1].material.as_deref(), Some("green"));
+
+  // negative indices are relative to the end
+  assert_eq!(
+    obj.groups[1].triangles[1][2],
+    FaceVertex {
+      position: 2,
+      uv: Some(3),
+      normal: Some(0),
+    }
+  );
+}
+
+#[test]
+fn interleaved_meshes() {
+  let obj = Obj::parse(QUADS).unwrap();
+  let mut meshes = obj.interleaved_meshes(&mapping());
+
+  assert_eq!(meshes.len(), 2);
+  assert_eq!(meshes[0].material.as_deref(), Some("red"));
+
+  // the vertices shared by both triangles of a quad are deduplicated
+  let red = &mut meshes[0].builder;
+  assert_eq!(red.indices, [0, 1, 2, 0, 2, 3]);
+  assert_eq!(
+    red.vertices.vertices()[..2],
+    [
+      Vertex {
+        position: [0., 0., 0.],
+        tex_coord: [0., 0.],
+        normal: [0., 0., 1.],
+        tint: [255; 4],
+      },
+      Vertex {
+        position: [1., 0., 0.],
+        tex_coord: [1., 0.],
+        normal: [0., 0., 1.],
+        tint: [255; 4],
+      },
+    ]
+  );
+
+  let green: &Interleaved<Vertex> = &meshes[1].builder.vertices;
+  assert_eq!(green.vertices().len(), 4);
+  assert_eq!(green.vertices()[3].position, [1., 1., 0.]);
+}
+
+#[test]
+fn deinterleaved_meshes() {
+  let obj = Obj::parse(QUADS).unwrap();
+  let interleaved = obj.interleaved_meshes(&mapping());
+  let deinterleaved = obj.deinterleaved_meshes(&mapping());
+
+  for (interleaved, deinterleaved) in interleaved.iter().zip(&deinterleaved) {
+    let vertices = interleaved.builder.vertices.vertices();
+    let components = deinterleaved.builder.vertices.components_list();
+
+    assert_eq!(deinterleaved.builder.indices, interleaved.builder.indices);
+    assert_eq!(deinterleaved.builder.vertices.len(), vertices.len());
+
+    let positions: Vec<f32> = vertices.iter().flat_map(|v| v.position).collect();
+    let position_bytes: Vec<u8> = positions.iter().flat_map(|x| x.to_ne_bytes()).collect();
+    assert_eq!(components[0], position_bytes);
+    assert_eq!(components[3], vec![255; 4 * vertices.len()]);
+  }
+}
+
+#[test]
+fn materials() {
+  let mut obj = Obj::parse(QUADS).unwrap();
+  obj.add_materials(MTL).unwrap();
+
+  let red = obj.material("red").unwrap();
+  assert_eq!(red.diffuse, [1., 0., 0.]);
+  assert_eq!(red.shininess, 10.);
+  assert_eq!(red.diffuse_map.as_deref(), Some("red.png"));
+
+  let green = obj.material("green").unwrap();
+  assert_eq!(green.diffuse, [0., 1., 0.]);
+  assert_eq!(green.dissolve, 0.75);
+  assert!(obj.material("blue").is_none());
+}
+
+#[test]
+fn load_with_materials() {
+  let dir = std::env::temp_dir().join(format!("luminance-obj-{}", std::process::id()));
+  std::fs::create_dir_all(&dir).unwrap();
+  std::fs::write(dir.join("quads.obj"), QUADS).unwrap();
+  std::fs::write(dir.join("quads.mtl"), MTL).unwrap();
+
+  let obj = Obj::load(dir.join("quads.obj")).unwrap();
+  assert_eq!(obj.materials.len(), 2);
+
+  std::fs::remove_file(dir.join("quads.mtl")).unwrap();
+  assert!(matches!(
+    Obj::load(dir.join("quads.obj")),
+    Err(ObjError::Io { .. })
+  ));
+
+  std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn invalid_files() {
+  assert!(matches!(
+    Obj::parse("v 0 0 0\nf 1 2 3"),
+    Err(ObjError::InvalidIndex { line: 2, index: 2 })
+  ));
+  assert!(matches!(
+    Obj::parse("v 0 0"),
+    Err(ObjError::InvalidStatement { line: 1, .. })
+  ));
+  assert!(matches!(
+    Obj::parse("v 0 0 0\nf 1 1"),
+    Err(ObjError::InvalidStatement { line: 2, .. })
+  ));
+}
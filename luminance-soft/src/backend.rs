@@ -0,0 +1,1970 @@
+//! The [`Soft`] backend.
+
+use crate::{
+  buffer::BufferData,
+  pixel,
+  raster::{DrawCall, DrawState, Target},
+  shader::{FragmentInput, FragmentShader, UniformValues, VertexInput, VertexOutput, VertexShader},
+  texture::{flat_offset, flat_size, TextureData},
+  vertex::{AttributeStorage, FeedbackLayout},
+};
+use luminance::{
+  backend::{
+    Backend, BufferBackend, BufferError, FramebufferBackend, FramebufferError,
+    IndirectBufferBackend, IndirectBufferError, PipelineBackend, PipelineError, QueryBackend,
+    QueryError, ShaderBackend, ShaderError, TextureBackend, TextureError, VertexEntityBackend,
+    VertexEntityError,
+  },
+  buffer::{Buffer, BufferSliceMut},
+  context::ContextActive,
+  dim::{Dim, Dimensionable},
+  framebuffer::{Back, Framebuffer},
+  indirect::{IndirectBuffer, IndirectCommand},
+  pipeline::{PipelineState, Viewport, WithFramebuffer, WithProgram, WithRenderState},
+  pixel::{Depth32F, Format, NormRGBA8UI, Pixel, PixelType},
+  primitive::{Connector, Primitive},
+  render_slots::{DepthChannel, DepthRenderSlot, RenderChannel, RenderSlots},
+  render_state::RenderState,
+  shader::{
+    reflection::ProgramReflection, InUseUniBuffer, MemoryLayout, Program, Uni, UniBuffer,
+    UniBufferRef, Uniform, UniformValue, Uniforms,
+  },
+  texture::{InUseTexture, Mipmaps, Texture, TextureSampling},
+  vertex::Vertex,
+  vertex_entity::{Index, IndexType, Usage, VertexEntity, VertexEntityBuilder, VertexEntityView},
+  vertex_storage::{AsVertexStorage, Deinterleaved, Shared, VertexStorageFamily},
+};
+use std::{
+  borrow::Cow, cell::RefCell, collections::HashMap, error::Error, fmt, mem, ops::Range, ptr, rc::Rc,
+};
+
+/// Errors specific to [`Soft`].
+///
+/// They are used as causes of the regular backend errors.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SoftError {
+  /// No resource is associated with the handle.
+  UnknownHandle { handle: usize },
+
+  /// A range of data goes past the end of the storage it targets.
+  OutOfBounds {
+    start: usize,
+    count: usize,
+    len: usize,
+  },
+
+  /// The vertex storage doesn’t have the same shape as the one the vertex entity was created with, or is longer.
+  IncompatibleStorage,
+
+  /// The components of a deinterleaved storage don’t all have the same length.
+  MismatchingComponents,
+
+  /// No shader was registered with this name.
+  UnknownShader { name: String },
+
+  /// Primitive shader stages are not supported.
+  UnsupportedPrimitiveStage,
+
+  /// Tessellation shader stages are not supported.
+  UnsupportedTessellationStages,
+
+  /// Primitives with that connector cannot be rasterized.
+  UnsupportedConnector(Connector),
+
+  /// Framebuffers with that dimension are not supported.
+  UnsupportedDimension(Dim),
+}
+
+impl fmt::Display for SoftError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      SoftError::UnknownHandle { handle } => {
+        write!(f, "no resource associated with handle {}", handle)
+      }
+
+      SoftError::OutOfBounds { start, count, len } => write!(
+        f,
+        "range {}..{} is out of bounds (len = {})",
+        start,
+        start + count,
+        len
+      ),
+
+      SoftError::IncompatibleStorage => f.write_str("incompatible vertex storage"),
+
+      SoftError::MismatchingComponents => {
+        f.write_str("deinterleaved components have different lengths")
+      }
+
+      SoftError::UnknownShader { name } => write!(f, "no shader registered as {}", name),
+
+      SoftError::UnsupportedPrimitiveStage => f.write_str("primitive stages are not supported"),
+
+      SoftError::UnsupportedTessellationStages => {
+        f.write_str("tessellation stages are not supported")
+      }
+
+      SoftError::UnsupportedConnector(connector) => {
+        write!(f, "unsupported connector: {:?}", connector)
+      }
+
+      SoftError::UnsupportedDimension(dim) => {
+        write!(f, "unsupported framebuffer dimension: {}", dim)
+      }
+    }
+  }
+}
+
+impl Error for SoftError {}
+
+struct VertexEntityData {
+  vertices: AttributeStorage,
+  // indices are widened to u32; the index type is kept to recognize primitive restarts
+  indices: Vec<u32>,
+  // buffer holding the indices instead, if any
+  shared_indices: Option<usize>,
+  index_type: IndexType,
+  instances: AttributeStorage,
+}
+
+impl VertexEntityData {
+  /// Indices of the vertex entity, read from their buffer if they are shared.
+  fn indices<'a>(
+    &'a self,
+    buffers: &HashMap<usize, BufferData>,
+  ) -> Result<Cow<'a, [u32]>, SoftError> {
+    let handle = match self.shared_indices {
+      Some(handle) => handle,
+      None => return Ok(Cow::Borrowed(&self.indices)),
+    };
+
+    let bytes = buffers
+      .get(&handle)
+      .ok_or(SoftError::UnknownHandle { handle })?
+      .as_bytes();
+
+    let indices = match self.index_type {
+      IndexType::U8 => bytes.iter().map(|&index| index as u32).collect(),
+      IndexType::U16 => bytes
+        .chunks_exact(2)
+        .map(|index| u16::from_ne_bytes([index[0], index[1]]) as u32)
+        .collect(),
+      IndexType::U32 => bytes
+        .chunks_exact(4)
+        .map(|index| u32::from_ne_bytes([index[0], index[1], index[2], index[3]]))
+        .collect(),
+    };
+
+    Ok(Cow::Owned(indices))
+  }
+}
+
+struct FramebufferData {
+  size: [u32; 2],
+  // texture handles of the color layers, by index; 0 if the layer is missing
+  colors: Vec<usize>,
+  depth: Option<usize>,
+  // stencil values, if the depth layer has a stencil part
+  stencil: Option<Vec<u8>>,
+}
+
+struct ProgramData {
+  vertex: Rc<VertexShader>,
+  fragment: Rc<FragmentShader>,
+  uniforms: HashMap<String, UniformValue>,
+  feedback: FeedbackLayout,
+}
+
+/// Reinterpret raw texels as bytes.
+fn texels_as_bytes<T>(texels: &[T]) -> &[u8] {
+  unsafe { std::slice::from_raw_parts(texels.as_ptr() as *const u8, mem::size_of_val(texels)) }
+}
+
+struct State {
+  // whether the associated context is still active
+  context_active: ContextActive,
+
+  // next handle to give away; 0 is reserved for the back buffer
+  next_handle: usize,
+
+  vertex_shaders: HashMap<String, Rc<VertexShader>>,
+  fragment_shaders: HashMap<String, Rc<FragmentShader>>,
+
+  vertex_entities: HashMap<usize, VertexEntityData>,
+  framebuffers: HashMap<usize, FramebufferData>,
+  programs: HashMap<usize, ProgramData>,
+  uniforms: HashMap<usize, (usize, String)>, // uniform handle -> (program handle, name)
+  textures: HashMap<usize, TextureData>,
+  uni_buffers: HashMap<usize, Vec<u128>>, // u128 to get an alignment suitable for any aligned type
+  indirect_buffers: HashMap<usize, BufferData>,
+  buffers: HashMap<usize, BufferData>,
+
+  // currently bound resources
+  framebuffer: usize,
+  program: usize,
+  // buffer capturing the rendered vertices with transform feedback, if any
+  feedback_buffer: Option<usize>,
+  draw_state: DrawState,
+}
+
+impl State {
+  fn new(context_active: ContextActive) -> Self {
+    Self {
+      context_active,
+      next_handle: 1,
+      vertex_shaders: HashMap::new(),
+      fragment_shaders: HashMap::new(),
+      vertex_entities: HashMap::new(),
+      framebuffers: HashMap::new(),
+      programs: HashMap::new(),
+      uniforms: HashMap::new(),
+      textures: HashMap::new(),
+      uni_buffers: HashMap::new(),
+      indirect_buffers: HashMap::new(),
+      buffers: HashMap::new(),
+      framebuffer: 0,
+      program: 0,
+      feedback_buffer: None,
+      draw_state: DrawState::default(),
+    }
+  }
+
+  fn next_handle(&mut self) -> usize {
+    let handle = self.next_handle;
+    self.next_handle += 1;
+    handle
+  }
+
+  fn is_context_active(&self) -> bool {
+    self.context_active.is_active()
+  }
+
+  /// Run a function with the buffers of a framebuffer.
+  ///
+  /// The textures of the framebuffer are taken out of the texture map while the function runs, so that shaders cannot
+  /// sample the textures they render to.
+  fn with_target<R>(
+    &mut self,
+    handle: usize,
+    f: impl FnOnce(&mut Target, &State) -> R,
+  ) -> Result<R, SoftError> {
+    let fb = self
+      .framebuffers
+      .get_mut(&handle)
+      .ok_or(SoftError::UnknownHandle { handle })?;
+    let [width, height] = fb.size;
+    let color_handles = fb.colors.clone();
+    let depth_handle = fb.depth;
+    let mut stencil = fb.stencil.take();
+
+    let mut colors: Vec<_> = color_handles
+      .iter()
+      .filter_map(|handle| self.textures.remove_entry(handle))
+      .collect();
+    let mut depth = depth_handle.and_then(|handle| self.textures.remove_entry(&handle));
+
+    let r = {
+      let mut target = Target {
+        width,
+        height,
+        colors: colors.iter_mut().map(|(_, data)| data).collect(),
+        depth: depth.as_mut().map(|(_, data)| data),
+        stencil: stencil.as_deref_mut(),
+      };
+
+      f(&mut target, self)
+    };
+
+    self.textures.extend(colors);
+    self.textures.extend(depth);
+
+    if let Some(fb) = self.framebuffers.get_mut(&handle) {
+      fb.stencil = stencil;
+    }
+
+    Ok(r)
+  }
+
+  fn drop_vertex_entity(&mut self, handle: usize) {
+    if self.is_context_active() {
+      self.vertex_entities.remove(&handle);
+    }
+  }
+
+  fn drop_framebuffer(&mut self, handle: usize) {
+    if self.is_context_active() {
+      self.framebuffers.remove(&handle);
+    }
+  }
+
+  fn drop_program(&mut self, handle: usize) {
+    if self.is_context_active() {
+      self.programs.remove(&handle);
+      self.uniforms.retain(|_, (program, _)| *program != handle);
+    }
+  }
+
+  fn drop_texture(&mut self, handle: usize) {
+    if self.is_context_active() {
+      self.textures.remove(&handle);
+    }
+  }
+
+  fn drop_uni_buffer(&mut self, handle: usize) {
+    if self.is_context_active() {
+      self.uni_buffers.remove(&handle);
+    }
+  }
+
+  fn drop_indirect_buffer(&mut self, handle: usize) {
+    if self.is_context_active() {
+      self.indirect_buffers.remove(&handle);
+    }
+  }
+
+  fn drop_buffer(&mut self, handle: usize) {
+    if self.is_context_active() {
+      self.buffers.remove(&handle);
+    }
+  }
+}
+
+/// Software backend.
+///
+/// See the [crate documentation](crate) for further details.
+pub struct Soft {
+  state: Rc<RefCell<State>>,
+}
+
+impl fmt::Debug for Soft {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("Soft").finish_non_exhaustive()
+  }
+}
+
+impl Soft {
+  pub fn new(context_active: ContextActive) -> Option<Self> {
+    let state = Rc::new(RefCell::new(State::new(context_active)));
+    Some(Self { state })
+  }
+
+  /// Register a vertex shader.
+  ///
+  /// Programs use it by passing `name` as vertex stage code. Registering a shader with a name already in use replaces
+  /// the previous shader for programs created afterwards.
+  pub fn add_vertex_shader(
+    &self,
+    name: impl Into<String>,
+    shader: impl Fn(&VertexInput, &UniformValues) -> VertexOutput + 'static,
+  ) {
+    self
+      .state
+      .borrow_mut()
+      .vertex_shaders
+      .insert(name.into(), Rc::new(shader));
+  }
+
+  /// Register a fragment shader.
+  ///
+  /// Programs use it by passing `name` as shading stage code. Registering a shader with a name already in use replaces
+  /// the previous shader for programs created afterwards.
+  pub fn add_fragment_shader(
+    &self,
+    name: impl Into<String>,
+    shader: impl Fn(&FragmentInput, &UniformValues) -> Option<Vec<[f32; 4]>> + 'static,
+  ) {
+    self
+      .state
+      .borrow_mut()
+      .fragment_shaders
+      .insert(name.into(), Rc::new(shader));
+  }
+
+  /// Read the color of the back buffer.
+  ///
+  /// Pixels are returned row by row, starting with the bottom row. If no back buffer was ever retrieved, an empty
+  /// vector is returned.
+  pub fn read_back_buffer(&self) -> Vec<[f32; 4]> {
+    let st = self.state.borrow();
+
+    let data = st
+      .framebuffers
+      .get(&0)
+      .and_then(|fb| fb.colors.first())
+      .and_then(|handle| st.textures.get(handle));
+
+    match data {
+      Some(data) => {
+        let [width, height, _] = data.size;
+        (0..height)
+          .flat_map(|y| (0..width).map(move |x| [x, y, 0]))
+          .map(|position| data.load(position))
+          .collect()
+      }
+
+      None => Vec::new(),
+    }
+  }
+
+  /// Create the texture of a render layer and attach it to a framebuffer.
+  unsafe fn new_layer<D, P>(
+    &mut self,
+    framebuffer_handle: usize,
+    size: D::Size,
+    mipmaps: Mipmaps,
+    sampling: &TextureSampling,
+    attach: impl FnOnce(&mut FramebufferData, usize, &TextureData),
+  ) -> Result<Texture<D, P>, TextureError>
+  where
+    D: Dimensionable,
+    P: Pixel,
+  {
+    let tex = self.reserve_texture::<D, P>(size, mipmaps, sampling)?;
+
+    let mut st = self.state.borrow_mut();
+    let st = &mut *st;
+
+    if let (Some(fb), Some(data)) = (
+      st.framebuffers.get_mut(&framebuffer_handle),
+      st.textures.get(&tex.handle()),
+    ) {
+      attach(fb, tex.handle(), data);
+    }
+
+    Ok(tex)
+  }
+}
+
+unsafe impl Backend for Soft {
+  unsafe fn unload(&mut self) {
+    let mut st = self.state.borrow_mut();
+
+    st.vertex_entities.clear();
+    st.framebuffers.clear();
+    st.programs.clear();
+    st.uniforms.clear();
+    st.textures.clear();
+    st.uni_buffers.clear();
+    st.indirect_buffers.clear();
+    st.buffers.clear();
+  }
+}
+
+unsafe impl VertexEntityBackend for Soft {
+  unsafe fn new_vertex_entity<V, P, VSF, W, WSF, I>(
+    &mut self,
+    mut builder: VertexEntityBuilder<VSF::Storage<V>, WSF::Storage<W>, I>,
+  ) -> Result<VertexEntity<V, P, VSF, W, WSF, I>, VertexEntityError>
+  where
+    V: Vertex,
+    P: Primitive,
+    VSF: VertexStorageFamily,
+    W: Vertex,
+    WSF: VertexStorageFamily,
+    I: Index,
+  {
+    let vertices =
+      AttributeStorage::new(&mut builder.vertices).ok_or_else(|| VertexEntityError::Creation {
+        cause: Some(Box::new(SoftError::MismatchingComponents)),
+      })?;
+    let instances =
+      AttributeStorage::new(&mut builder.instances).ok_or_else(|| VertexEntityError::Creation {
+        cause: Some(Box::new(SoftError::MismatchingComponents)),
+      })?;
+    let indices = builder.indices;
+    let index_len = builder
+      .index_buffer
+      .as_ref()
+      .map_or(indices.len(), Shared::len);
+
+    let vertex_count = if index_len == 0 {
+      vertices.len()
+    } else {
+      index_len
+    };
+
+    let data = VertexEntityData {
+      vertices,
+      indices: indices.iter().map(|&index| index.into()).collect(),
+      shared_indices: builder.index_buffer.as_ref().map(Shared::buffer_handle),
+      index_type: I::INDEX_TYPE,
+      instances,
+    };
+
+    let mut st = self.state.borrow_mut();
+    let handle = st.next_handle();
+    st.vertex_entities.insert(handle, data);
+
+    let state = self.state.clone();
+    let dropper = Box::new(move |handle| {
+      state.borrow_mut().drop_vertex_entity(handle);
+    });
+
+    Ok(VertexEntity::new(
+      handle,
+      builder.vertices,
+      builder.instances,
+      indices,
+      builder.index_buffer,
+      vertex_count,
+      dropper,
+    ))
+  }
+
+  unsafe fn vertex_entity_render<V, P>(
+    &self,
+    handle: usize,
+    start_index: usize,
+    vert_count: usize,
+    inst_count: usize,
+    base_vertex: usize,
+    base_instance: usize,
+  ) -> Result<(), VertexEntityError>
+  where
+    V: Vertex,
+    P: Primitive,
+  {
+    let render_error = |e: SoftError| VertexEntityError::Render {
+      cause: Some(Box::new(e)),
+    };
+
+    if inst_count == 0 {
+      return Ok(());
+    }
+
+    let mut st = self.state.borrow_mut();
+    let entity = st
+      .vertex_entities
+      .get(&handle)
+      .ok_or_else(|| render_error(SoftError::UnknownHandle { handle }))?;
+
+    if let Connector::Patch(_) = P::CONNECTOR {
+      return Err(render_error(SoftError::UnsupportedConnector(P::CONNECTOR)));
+    }
+
+    let indices = entity.indices(&st.buffers).map_err(render_error)?;
+
+    // attribute-less vertex entities can render any amount of vertices
+    let len = if !indices.is_empty() {
+      Some(indices.len())
+    } else if !entity.vertices.is_empty_storage() {
+      Some(entity.vertices.len())
+    } else {
+      None
+    };
+
+    if let Some(len) = len {
+      if start_index + vert_count > len {
+        return Err(render_error(SoftError::OutOfBounds {
+          start: start_index,
+          count: vert_count,
+          len,
+        }));
+      }
+    }
+
+    let instance_data_len = entity.instances.instance_data_len(inst_count);
+    if !entity.instances.is_empty_storage()
+      && base_instance + instance_data_len > entity.instances.len()
+    {
+      return Err(render_error(SoftError::OutOfBounds {
+        start: base_instance,
+        count: instance_data_len,
+        len: entity.instances.len(),
+      }));
+    }
+
+    // base vertices of indirect commands can be negative, and wrap around
+    let base_vertex = base_vertex as u32;
+    let elements: Vec<_> = if indices.is_empty() {
+      (start_index..start_index + vert_count)
+        .map(|i| Some(base_vertex.wrapping_add(i as u32)))
+        .collect()
+    } else {
+      let primitive_restart = matches!(
+        P::CONNECTOR,
+        Connector::LineStrip
+          | Connector::TriangleStrip
+          | Connector::TriangleFan
+          | Connector::LineStripAdjacency
+          | Connector::TriangleStripAdjacency
+      );
+
+      let restart_index = entity.index_type.primitive_restart_index();
+      indices[start_index..start_index + vert_count]
+        .iter()
+        .map(|&index| {
+          (!primitive_restart || index != restart_index).then_some(base_vertex.wrapping_add(index))
+        })
+        .collect()
+    };
+
+    if !entity.vertices.is_empty_storage() {
+      let len = entity.vertices.len();
+
+      if let Some(index) = elements
+        .iter()
+        .flatten()
+        .find(|&&index| index as usize >= len)
+      {
+        return Err(render_error(SoftError::OutOfBounds {
+          start: *index as usize,
+          count: 1,
+          len,
+        }));
+      }
+    }
+
+    let program_handle = st.program;
+    if !st.programs.contains_key(&program_handle) {
+      return Err(render_error(SoftError::UnknownHandle {
+        handle: program_handle,
+      }));
+    }
+
+    let framebuffer_handle = st.framebuffer;
+    let feedback_buffer = st.feedback_buffer;
+    let draw_state = st.draw_state.clone();
+
+    let captured = st
+      .with_target(framebuffer_handle, |target, st| {
+        let entity = &st.vertex_entities[&handle];
+        let program = &st.programs[&program_handle];
+        let vertices = entity.vertices.resolve(&st.buffers)?;
+        let instances = entity.instances.resolve(&st.buffers)?;
+
+        let draw_call = DrawCall {
+          connector: P::CONNECTOR,
+          vertices: &vertices,
+          instances: &instances,
+          elements,
+          instance_count: inst_count,
+          base_instance,
+          vertex_shader: &*program.vertex,
+          fragment_shader: &*program.fragment,
+          uniforms: UniformValues {
+            values: &program.uniforms,
+            textures: &st.textures,
+            uni_buffers: &st.uni_buffers,
+            buffers: &st.buffers,
+          },
+        };
+
+        let mut captured = Vec::new();
+        draw_call.run(
+          &draw_state,
+          target,
+          feedback_buffer.is_some().then_some(&mut captured),
+        );
+        Ok(captured)
+      })
+      .and_then(|r| r)
+      .map_err(render_error)?;
+
+    // write the captured vertices to the buffer, dropping the ones that don’t fit
+    if let Some(buffer_handle) = feedback_buffer {
+      let State {
+        programs,
+        buffers,
+        indirect_buffers,
+        ..
+      } = &mut *st;
+      let layout = &programs[&program_handle].feedback;
+      let buffer = buffers
+        .get_mut(&buffer_handle)
+        .or_else(|| indirect_buffers.get_mut(&buffer_handle))
+        .ok_or_else(|| {
+          render_error(SoftError::UnknownHandle {
+            handle: buffer_handle,
+          })
+        })?;
+
+      if layout.stride() > 0 {
+        for (bytes, output) in buffer
+          .as_bytes_mut()
+          .chunks_exact_mut(layout.stride())
+          .zip(&captured)
+        {
+          layout.encode(&output.varyings, bytes);
+        }
+      }
+    }
+
+    Ok(())
+  }
+
+  unsafe fn vertex_entity_render_batch<V, W, P>(
+    &self,
+    views: &[VertexEntityView<V, W, P>],
+  ) -> Result<(), VertexEntityError>
+  where
+    V: Vertex,
+    W: Vertex,
+    P: Primitive,
+  {
+    for view in views {
+      self.vertex_entity_render::<V, P>(
+        view.handle(),
+        view.start_vertex(),
+        view.vertex_count(),
+        view.instance_count(),
+        view.base_vertex(),
+        view.base_instance(),
+      )?;
+    }
+
+    Ok(())
+  }
+
+  unsafe fn vertex_entity_update_vertices<V, S>(
+    &mut self,
+    handle: usize,
+    storage: &mut S,
+    range: Range<usize>,
+  ) -> Result<(), VertexEntityError>
+  where
+    V: Vertex,
+    S: AsVertexStorage<V>,
+  {
+    let mut st = self.state.borrow_mut();
+    let data = st.vertex_entities.get_mut(&handle).ok_or_else(|| {
+      VertexEntityError::UpdateVertexStorage {
+        cause: Some(Box::new(SoftError::UnknownHandle { handle })),
+      }
+    })?;
+
+    data
+      .vertices
+      .update(storage, range)
+      .map_err(|_| VertexEntityError::UpdateVertexStorage {
+        cause: Some(Box::new(SoftError::IncompatibleStorage)),
+      })
+  }
+
+  unsafe fn vertex_entity_update_vertex_attribute<V>(
+    &mut self,
+    handle: usize,
+    storage: &mut Deinterleaved<V>,
+    rank: usize,
+  ) -> Result<(), VertexEntityError>
+  where
+    V: Vertex,
+  {
+    let mut st = self.state.borrow_mut();
+    let data = st.vertex_entities.get_mut(&handle).ok_or_else(|| {
+      VertexEntityError::UpdateVertexStorage {
+        cause: Some(Box::new(SoftError::UnknownHandle { handle })),
+      }
+    })?;
+
+    data.vertices.update_attribute(storage, rank).map_err(|_| {
+      VertexEntityError::UpdateVertexStorage {
+        cause: Some(Box::new(SoftError::IncompatibleStorage)),
+      }
+    })
+  }
+
+  unsafe fn vertex_entity_update_indices<I>(
+    &mut self,
+    handle: usize,
+    indices: &mut Vec<I>,
+    range: Range<usize>,
+  ) -> Result<(), VertexEntityError>
+  where
+    I: Index,
+  {
+    let mut st = self.state.borrow_mut();
+    let data =
+      st.vertex_entities
+        .get_mut(&handle)
+        .ok_or_else(|| VertexEntityError::UpdateIndices {
+          cause: Some(Box::new(SoftError::UnknownHandle { handle })),
+        })?;
+
+    if range.end > indices.len() {
+      return Err(VertexEntityError::UpdateIndices {
+        cause: Some(Box::new(SoftError::OutOfBounds {
+          start: range.start,
+          count: range.len(),
+          len: indices.len(),
+        })),
+      });
+    }
+
+    // indices can be added, removed or resized between updates; copy them all again in that case
+    if data.indices.len() != indices.len() {
+      data.indices = indices.iter().map(|&index| index.into()).collect();
+    } else {
+      for (dst, &src) in data.indices[range.clone()].iter_mut().zip(&indices[range]) {
+        *dst = src.into();
+      }
+    }
+
+    Ok(())
+  }
+
+  unsafe fn vertex_entity_update_instance_data<W, WS>(
+    &mut self,
+    handle: usize,
+    storage: &mut WS,
+    range: Range<usize>,
+  ) -> Result<(), VertexEntityError>
+  where
+    W: Vertex,
+    WS: AsVertexStorage<W>,
+  {
+    let mut st = self.state.borrow_mut();
+    let data = st.vertex_entities.get_mut(&handle).ok_or_else(|| {
+      VertexEntityError::UpdateVertexStorage {
+        cause: Some(Box::new(SoftError::UnknownHandle { handle })),
+      }
+    })?;
+
+    data
+      .instances
+      .update(storage, range)
+      .map_err(|_| VertexEntityError::UpdateVertexStorage {
+        cause: Some(Box::new(SoftError::IncompatibleStorage)),
+      })
+  }
+}
+
+unsafe impl FramebufferBackend for Soft {
+  unsafe fn new_render_layer<D, RC>(
+    &mut self,
+    framebuffer_handle: usize,
+    size: D::Size,
+    mipmaps: Mipmaps,
+    sampling: &TextureSampling,
+    index: usize,
+  ) -> Result<Texture<D, RC>, FramebufferError>
+  where
+    D: Dimensionable,
+    RC: RenderChannel,
+  {
+    self
+      .new_layer(
+        framebuffer_handle,
+        size,
+        mipmaps,
+        sampling,
+        |fb, handle, _| {
+          if fb.colors.len() <= index {
+            fb.colors.resize(index + 1, 0);
+          }
+
+          fb.colors[index] = handle;
+        },
+      )
+      .map_err(|e| FramebufferError::RenderLayerCreation {
+        cause: Some(Box::new(e)),
+      })
+  }
+
+  unsafe fn new_depth_render_layer<D, DC>(
+    &mut self,
+    framebuffer_handle: usize,
+    size: D::Size,
+    mipmaps: Mipmaps,
+    sampling: &TextureSampling,
+  ) -> Result<Texture<D, DC>, FramebufferError>
+  where
+    D: Dimensionable,
+    DC: DepthChannel,
+  {
+    self
+      .new_layer(
+        framebuffer_handle,
+        size,
+        mipmaps,
+        sampling,
+        |fb, handle, data| {
+          fb.depth = Some(handle);
+
+          if let Format::DepthStencil(..) = data.format.format {
+            fb.stencil = Some(vec![0; fb.size[0] as usize * fb.size[1] as usize]);
+          }
+        },
+      )
+      .map_err(|e| FramebufferError::DepthRenderLayerCreation {
+        cause: Some(Box::new(e)),
+      })
+  }
+
+  unsafe fn new_framebuffer<D, RS, DS>(
+    &mut self,
+    size: D::Size,
+    mipmaps: Mipmaps,
+    sampling: &TextureSampling,
+  ) -> Result<Framebuffer<D, RS, DS>, FramebufferError>
+  where
+    D: Dimensionable,
+    RS: RenderSlots,
+    DS: DepthRenderSlot,
+  {
+    if D::dim() != Dim::Dim2 {
+      return Err(FramebufferError::Creation {
+        cause: Some(Box::new(SoftError::UnsupportedDimension(D::dim()))),
+      });
+    }
+
+    let handle = {
+      let mut st = self.state.borrow_mut();
+      let handle = st.next_handle();
+      st.framebuffers.insert(
+        handle,
+        FramebufferData {
+          size: [D::width(&size), D::height(&size)],
+          colors: Vec::new(),
+          depth: None,
+          stencil: None,
+        },
+      );
+      handle
+    };
+
+    let layers = RS::new_render_layers::<_, D>(self, handle, size, mipmaps, sampling)?;
+    let depth_layer = DS::new_depth_render_layer::<_, D>(self, handle, size, mipmaps, sampling)?;
+
+    let state = self.state.clone();
+    let dropper = Box::new(move |handle| {
+      state.borrow_mut().drop_framebuffer(handle);
+    });
+
+    Ok(Framebuffer::new(handle, size, layers, depth_layer, dropper))
+  }
+
+  unsafe fn back_buffer<D, RS, DS>(
+    &mut self,
+    size: D::Size,
+  ) -> Result<Framebuffer<D, Back<RS>, Back<DS>>, FramebufferError>
+  where
+    D: Dimensionable,
+    RS: RenderSlots,
+    DS: DepthRenderSlot,
+  {
+    if D::dim() != Dim::Dim2 {
+      return Err(FramebufferError::RetrieveBackBuffer {
+        cause: Some(Box::new(SoftError::UnsupportedDimension(D::dim()))),
+      });
+    }
+
+    // the back buffer owns its buffers: an 8-bit normalized RGBA color buffer, a 32-bit floating depth buffer and a
+    // stencil buffer
+    let mut st = self.state.borrow_mut();
+    let [width, height] = [D::width(&size), D::height(&size)];
+    let sampling = TextureSampling::default();
+
+    if let Some(fb) = st.framebuffers.remove(&0) {
+      for handle in fb.colors.iter().chain(&fb.depth) {
+        st.textures.remove(handle);
+      }
+    }
+
+    let color = st.next_handle();
+    st.textures.insert(
+      color,
+      TextureData::new(
+        Dim::Dim2,
+        [width, height, 1],
+        NormRGBA8UI::PIXEL_FMT,
+        sampling,
+      ),
+    );
+
+    let depth = st.next_handle();
+    st.textures.insert(
+      depth,
+      TextureData::new(Dim::Dim2, [width, height, 1], Depth32F::PIXEL_FMT, sampling),
+    );
+
+    st.framebuffers.insert(
+      0,
+      FramebufferData {
+        size: [width, height],
+        colors: vec![color],
+        depth: Some(depth),
+        stencil: Some(vec![0; width as usize * height as usize]),
+      },
+    );
+
+    Ok(Framebuffer::new(0, size, (), (), Box::new(|_| {})))
+  }
+}
+
+macro_rules! impl_uniform_visit {
+  ($( $name:ident, $t:ty, $v:ident, )*) => {
+    $(
+      fn $name(&mut self, uni: &Uni<$t>, value: &$t) -> Result<(), ShaderError> {
+        self.set_uniform(uni.handle(), UniformValue::$v(vec![*value]));
+        Ok(())
+      }
+    )*
+  };
+
+  ($( array $name:ident, $t:ty, $v:ident, )*) => {
+    $(
+      fn $name<const N: usize>(&mut self, uni: &Uni<[$t; N]>, value: &[$t; N]) -> Result<(), ShaderError> {
+        self.set_uniform(uni.handle(), UniformValue::$v(value.to_vec()));
+        Ok(())
+      }
+    )*
+  };
+
+  ($( vec $name:ident, $t:ty, $n:literal, $v:ident, )*) => {
+    $(
+      fn $name<T>(&mut self, uni: &Uni<T>, value: &[$t; $n]) -> Result<(), ShaderError> where T: AsRef<[$t; $n]> {
+        self.set_uniform(uni.handle(), UniformValue::$v(value.to_vec()));
+        Ok(())
+      }
+    )*
+  };
+
+  ($( mat $name:ident, $t:ty, $n:literal, $v:ident, )*) => {
+    $(
+      fn $name<T>(&mut self, uni: &Uni<T>, value: &[[$t; $n]; $n]) -> Result<(), ShaderError> where T: AsRef<[[$t; $n]; $n]> {
+        self.set_uniform(uni.handle(), UniformValue::$v(value.iter().flatten().copied().collect()));
+        Ok(())
+      }
+    )*
+  };
+}
+
+impl Soft {
+  /// Set the value of a uniform; unbound uniforms are ignored.
+  fn set_uniform(&mut self, handle: usize, value: UniformValue) {
+    let mut st = self.state.borrow_mut();
+    let st = &mut *st;
+
+    if let Some((program, name)) = st.uniforms.get(&handle) {
+      if let Some(program) = st.programs.get_mut(program) {
+        program.uniforms.insert(name.clone(), value);
+      }
+    }
+  }
+}
+
+unsafe impl ShaderBackend for Soft {
+  unsafe fn new_program<V, W, P, S, E, F>(
+    &mut self,
+    vertex_code: String,
+    tess_control_code: String,
+    tess_eval_code: String,
+    primitive_code: String,
+    shading_code: String,
+  ) -> Result<Program<V, W, P, S, E, F>, ShaderError>
+  where
+    V: Vertex,
+    W: Vertex,
+    P: Primitive,
+    S: RenderSlots,
+    E: Uniforms,
+    F: Vertex,
+  {
+    let creation_error = |e| ShaderError::Creation {
+      cause: Some(Box::new(e)),
+    };
+
+    if !primitive_code.is_empty() {
+      return Err(creation_error(SoftError::UnsupportedPrimitiveStage));
+    }
+
+    if !tess_control_code.is_empty() || !tess_eval_code.is_empty() {
+      return Err(creation_error(SoftError::UnsupportedTessellationStages));
+    }
+
+    if let Connector::Patch(_) = P::CONNECTOR {
+      return Err(creation_error(SoftError::UnsupportedConnector(
+        P::CONNECTOR,
+      )));
+    }
+
+    let handle = {
+      let mut st = self.state.borrow_mut();
+
+      let vertex = st
+        .vertex_shaders
+        .get(&vertex_code)
+        .cloned()
+        .ok_or_else(|| creation_error(SoftError::UnknownShader { name: vertex_code }))?;
+      let fragment = st
+        .fragment_shaders
+        .get(&shading_code)
+        .cloned()
+        .ok_or_else(|| creation_error(SoftError::UnknownShader { name: shading_code }))?;
+
+      let handle = st.next_handle();
+      st.programs.insert(
+        handle,
+        ProgramData {
+          vertex,
+          fragment,
+          uniforms: HashMap::new(),
+          feedback: FeedbackLayout::new::<F>(),
+        },
+      );
+      handle
+    };
+
+    let state = self.state.clone();
+    let dropper = Box::new(move |handle| {
+      state.borrow_mut().drop_program(handle);
+    });
+
+    let uniforms = E::build_uniforms(self, handle)?;
+
+    Ok(Program::new(handle, uniforms, dropper))
+  }
+
+  unsafe fn program_reflection(
+    &mut self,
+    program_handle: usize,
+  ) -> Result<ProgramReflection, ShaderError> {
+    if !self.state.borrow().programs.contains_key(&program_handle) {
+      return Err(ShaderError::Reflection {
+        handle: program_handle,
+        cause: Some(Box::new(SoftError::UnknownHandle {
+          handle: program_handle,
+        })),
+      });
+    }
+
+    // shaders are closures looking their inputs up by name when they run, so they don’t declare any
+    Ok(ProgramReflection::default())
+  }
+
+  unsafe fn new_uni_buffer<T, Scheme>(
+    &mut self,
+    value: T::Aligned,
+  ) -> Result<UniBuffer<T, Scheme>, ShaderError>
+  where
+    T: MemoryLayout<Scheme>,
+  {
+    let len = (mem::size_of::<T::Aligned>() + mem::size_of::<u128>() - 1) / mem::size_of::<u128>();
+    let mut storage = vec![0u128; len.max(1)];
+    ptr::write(storage.as_mut_ptr() as *mut T::Aligned, value);
+
+    let mut st = self.state.borrow_mut();
+    let handle = st.next_handle();
+    st.uni_buffers.insert(handle, storage);
+
+    let state = self.state.clone();
+    let dropper = Box::new(move |handle| {
+      state.borrow_mut().drop_uni_buffer(handle);
+    });
+
+    Ok(UniBuffer::new(handle, dropper))
+  }
+
+  unsafe fn new_shader_uni<T>(&mut self, handle: usize, name: &str) -> Result<Uni<T>, ShaderError>
+  where
+    T: Uniform,
+  {
+    let mut st = self.state.borrow_mut();
+
+    if !st.programs.contains_key(&handle) {
+      return Err(ShaderError::UniCreation {
+        name: name.to_owned(),
+        cause: Some(Box::new(SoftError::UnknownHandle { handle })),
+      });
+    }
+
+    // asking several times the same uniform yields the same handle
+    let existing = st
+      .uniforms
+      .iter()
+      .find(|(_, (program, uni_name))| *program == handle && uni_name == name)
+      .map(|(uni_handle, _)| *uni_handle);
+
+    let uni_handle = match existing {
+      Some(uni_handle) => uni_handle,
+
+      None => {
+        let uni_handle = st.next_handle();
+        st.uniforms.insert(uni_handle, (handle, name.to_owned()));
+        uni_handle
+      }
+    };
+
+    Ok(Uni::new(uni_handle))
+  }
+
+  unsafe fn sync_uni_buffer<T, Scheme>(
+    &mut self,
+    uni_buffer_handle: usize,
+  ) -> Result<UniBufferRef<'_, Self, T, Scheme>, ShaderError>
+  where
+    T: MemoryLayout<Scheme>,
+  {
+    let ptr = self
+      .state
+      .borrow_mut()
+      .uni_buffers
+      .get_mut(&uni_buffer_handle)
+      .ok_or_else(|| ShaderError::NoData {
+        handle: uni_buffer_handle,
+      })?
+      .as_mut_ptr() as *mut T;
+
+    Ok(UniBufferRef::new(self, uni_buffer_handle, ptr))
+  }
+
+  unsafe fn unsync_uni_buffer<T, Scheme>(&mut self, _: usize) -> Result<(), ShaderError>
+  where
+    T: MemoryLayout<Scheme>,
+  {
+    Ok(())
+  }
+
+  unsafe fn new_shader_uni_unbound<T>(&mut self, _: usize) -> Result<Uni<T>, ShaderError>
+  where
+    T: Uniform,
+  {
+    // 0 is never given away as a handle, so setting that uniform is ignored
+    Ok(Uni::new(0))
+  }
+
+  unsafe fn set_shader_uni<T>(
+    &mut self,
+    _: usize,
+    uni: &Uni<T>,
+    value: &T::Value,
+  ) -> Result<(), ShaderError>
+  where
+    T: Uniform,
+  {
+    T::set(self, uni, value)
+  }
+
+  impl_uniform_visit! {
+    visit_i32, i32, Integral,
+    visit_u32, u32, Unsigned,
+    visit_f32, f32, Floating,
+    visit_bool, bool, Boolean,
+  }
+
+  impl_uniform_visit! {
+    array visit_i32_array, i32, Integral,
+    array visit_u32_array, u32, Unsigned,
+    array visit_f32_array, f32, Floating,
+    array visit_bool_array, bool, Boolean,
+  }
+
+  impl_uniform_visit! {
+    vec visit_ivec2, i32, 2, Integral,
+    vec visit_uvec2, u32, 2, Unsigned,
+    vec visit_vec2, f32, 2, Floating,
+    vec visit_bvec2, bool, 2, Boolean,
+
+    vec visit_ivec3, i32, 3, Integral,
+    vec visit_uvec3, u32, 3, Unsigned,
+    vec visit_vec3, f32, 3, Floating,
+    vec visit_bvec3, bool, 3, Boolean,
+
+    vec visit_ivec4, i32, 4, Integral,
+    vec visit_uvec4, u32, 4, Unsigned,
+    vec visit_vec4, f32, 4, Floating,
+    vec visit_bvec4, bool, 4, Boolean,
+  }
+
+  impl_uniform_visit! {
+    mat visit_mat22, f32, 2, Floating,
+    mat visit_mat33, f32, 3, Floating,
+    mat visit_mat44, f32, 4, Floating,
+  }
+
+  #[cfg(feature = "shader-f64")]
+  impl_uniform_visit! {
+    visit_f64, f64, Floating64,
+  }
+
+  #[cfg(feature = "shader-f64")]
+  impl_uniform_visit! {
+    array visit_f64_array, f64, Floating64,
+  }
+
+  #[cfg(feature = "shader-f64")]
+  impl_uniform_visit! {
+    vec visit_dvec2, f64, 2, Floating64,
+    vec visit_dvec3, f64, 3, Floating64,
+    vec visit_dvec4, f64, 4, Floating64,
+  }
+
+  #[cfg(feature = "shader-f64")]
+  impl_uniform_visit! {
+    mat visit_dmat22, f64, 2, Floating64,
+    mat visit_dmat33, f64, 3, Floating64,
+    mat visit_dmat44, f64, 4, Floating64,
+  }
+
+  fn visit_texture<D, P>(
+    &mut self,
+    uni: &Uni<InUseTexture<D, P>>,
+    value: &InUseTexture<D, P>,
+  ) -> Result<(), ShaderError>
+  where
+    D: Dimensionable,
+    P: PixelType,
+  {
+    self.set_uniform(uni.handle(), UniformValue::Texture(value.handle()));
+    Ok(())
+  }
+
+  fn visit_uni_buffer<T, Scheme>(
+    &mut self,
+    uni: &Uni<UniBuffer<T, Scheme>>,
+    value: &InUseUniBuffer<T, Scheme>,
+  ) -> Result<(), ShaderError>
+  where
+    T: MemoryLayout<Scheme>,
+  {
+    self.set_uniform(uni.handle(), UniformValue::UniBuffer(value.handle()));
+    Ok(())
+  }
+
+  unsafe fn use_uni_buffer<T, Scheme>(
+    &mut self,
+    handle: usize,
+  ) -> Result<InUseUniBuffer<T, Scheme>, ShaderError>
+  where
+    T: MemoryLayout<Scheme>,
+  {
+    // buffers can be used as uniform buffers as well
+    let st = self.state.borrow();
+    if !st.uni_buffers.contains_key(&handle) && !st.buffers.contains_key(&handle) {
+      return Err(ShaderError::NoData { handle });
+    }
+
+    // there is no binding point; the handle of the uniform buffer is used instead
+    Ok(InUseUniBuffer::new(handle, Box::new(|_| {})))
+  }
+}
+
+unsafe impl TextureBackend for Soft {
+  unsafe fn reserve_texture<D, P>(
+    &mut self,
+    size: D::Size,
+    _: Mipmaps,
+    sampling: &TextureSampling,
+  ) -> Result<Texture<D, P>, TextureError>
+  where
+    D: Dimensionable,
+    P: Pixel,
+  {
+    if !pixel::is_supported(P::PIXEL_FMT) {
+      return Err(TextureError::UnsupportedPixelFormat(P::PIXEL_FMT));
+    }
+
+    let data = TextureData::new(D::dim(), flat_size::<D>(&size), P::PIXEL_FMT, *sampling);
+
+    let mut st = self.state.borrow_mut();
+    let handle = st.next_handle();
+    st.textures.insert(handle, data);
+
+    let state = self.state.clone();
+    let dropper = Box::new(move |handle| {
+      state.borrow_mut().drop_texture(handle);
+    });
+
+    Ok(Texture::new(handle, dropper, size))
+  }
+
+  unsafe fn new_texture<D, P>(
+    &mut self,
+    size: D::Size,
+    mipmaps: Mipmaps,
+    sampling: &TextureSampling,
+    texels: &[P::RawEncoding],
+  ) -> Result<Texture<D, P>, TextureError>
+  where
+    D: Dimensionable,
+    P: Pixel,
+  {
+    let tex = self.reserve_texture(size, mipmaps, sampling)?;
+    self.set_texture_data::<D, P>(tex.handle(), D::ZERO_OFFSET, size, false, texels, 0)?;
+
+    Ok(tex)
+  }
+
+  unsafe fn resize_texture<D, P>(
+    &mut self,
+    handle: usize,
+    size: D::Size,
+    _: Mipmaps,
+  ) -> Result<(), TextureError>
+  where
+    D: Dimensionable,
+    P: Pixel,
+  {
+    let mut st = self.state.borrow_mut();
+    let data = st
+      .textures
+      .get_mut(&handle)
+      .ok_or(TextureError::NoData { handle })?;
+
+    // resizing a texture loses its content
+    *data = TextureData::new(data.dim, flat_size::<D>(&size), data.format, data.sampling);
+
+    Ok(())
+  }
+
+  unsafe fn set_texture_data<D, P>(
+    &mut self,
+    handle: usize,
+    offset: D::Offset,
+    size: D::Size,
+    _: bool,
+    texels: &[P::RawEncoding],
+    level: usize,
+  ) -> Result<(), TextureError>
+  where
+    D: Dimensionable,
+    P: Pixel,
+  {
+    let flat_offset = flat_offset::<D>(&offset);
+    let flat_size = flat_size::<D>(&size);
+
+    let mut st = self.state.borrow_mut();
+    let data = st
+      .textures
+      .get_mut(&handle)
+      .ok_or(TextureError::NoData { handle })?;
+
+    let bytes = texels_as_bytes(texels);
+    let expected_bytes = TextureData::bytes_len(flat_size, data.format);
+    if bytes.len() < expected_bytes {
+      return Err(TextureError::NotEnoughPixels {
+        expected_bytes,
+        provided_bytes: bytes.len(),
+        cause: None,
+      });
+    }
+
+    // only the base level is kept around
+    if level == 0 {
+      if !data.contains(flat_offset, flat_size) {
+        return Err(TextureError::CannotUploadTexels {
+          cause: Some(Box::new(SoftError::OutOfBounds {
+            start: 0,
+            count: expected_bytes,
+            len: data.texels.len(),
+          })),
+        });
+      }
+
+      data.write(flat_offset, flat_size, bytes);
+    }
+
+    Ok(())
+  }
+
+  unsafe fn clear_texture_data<D, P>(
+    &mut self,
+    handle: usize,
+    offset: D::Offset,
+    size: D::Size,
+    _: bool,
+    clear_value: P::RawEncoding,
+  ) -> Result<(), TextureError>
+  where
+    D: Dimensionable,
+    P: Pixel,
+  {
+    let flat_offset = flat_offset::<D>(&offset);
+    let flat_size = flat_size::<D>(&size);
+
+    let mut st = self.state.borrow_mut();
+    let data = st
+      .textures
+      .get_mut(&handle)
+      .ok_or(TextureError::NoData { handle })?;
+
+    let len = TextureData::bytes_len(flat_size, data.format);
+    if !data.contains(flat_offset, flat_size) {
+      return Err(TextureError::CannotUploadTexels {
+        cause: Some(Box::new(SoftError::OutOfBounds {
+          start: 0,
+          count: len,
+          len: data.texels.len(),
+        })),
+      });
+    }
+
+    let clear_value = texels_as_bytes(std::slice::from_ref(&clear_value));
+    let texels: Vec<u8> = clear_value.iter().copied().cycle().take(len).collect();
+    data.write(flat_offset, flat_size, &texels);
+
+    Ok(())
+  }
+
+  unsafe fn read_texture<D, P>(
+    &mut self,
+    handle: usize,
+  ) -> Result<Vec<P::RawEncoding>, TextureError>
+  where
+    D: Dimensionable,
+    P: Pixel,
+  {
+    let st = self.state.borrow();
+    let data = st
+      .textures
+      .get(&handle)
+      .ok_or(TextureError::NoData { handle })?;
+
+    let len = data.texels.len() / mem::size_of::<P::RawEncoding>().max(1);
+    let mut texels = vec![P::RawEncoding::default(); len];
+    ptr::copy_nonoverlapping(
+      data.texels.as_ptr(),
+      texels.as_mut_ptr() as *mut u8,
+      len * mem::size_of::<P::RawEncoding>(),
+    );
+
+    Ok(texels)
+  }
+
+  unsafe fn use_texture<D, P>(&mut self, handle: usize) -> Result<InUseTexture<D, P>, TextureError>
+  where
+    D: Dimensionable,
+    P: PixelType,
+  {
+    if !self.state.borrow().textures.contains_key(&handle) {
+      return Err(TextureError::NoData { handle });
+    }
+
+    // there is no texture unit; the handle of the texture is used instead
+    Ok(InUseTexture::new(handle, Box::new(|_| {})))
+  }
+}
+
+unsafe impl PipelineBackend for Soft {
+  unsafe fn with_framebuffer<D, CS, DS, Err>(
+    &mut self,
+    framebuffer: &Framebuffer<D, CS, DS>,
+    pipeline_state: &PipelineState,
+    f: impl for<'a> FnOnce(WithFramebuffer<'a, Self, CS>) -> Result<(), Err>,
+  ) -> Result<(), Err>
+  where
+    D: Dimensionable,
+    CS: RenderSlots,
+    DS: DepthRenderSlot,
+    Err: From<PipelineError>,
+  {
+    let handle = framebuffer.handle();
+
+    {
+      let mut st = self.state.borrow_mut();
+
+      st.draw_state.viewport = match pipeline_state.viewport {
+        Viewport::Whole => {
+          let size = framebuffer.size();
+          [0, 0, D::width(size) as i32, D::height(size) as i32]
+        }
+
+        Viewport::Specific {
+          x,
+          y,
+          width,
+          height,
+        } => [x as i32, y as i32, width as i32, height as i32],
+      };
+      st.draw_state.srgb_enabled = pipeline_state.srgb_enabled;
+      st.framebuffer = handle;
+
+      st.with_target(handle, |target, _| {
+        target.clear(
+          pipeline_state.scissor(),
+          pipeline_state.srgb_enabled,
+          pipeline_state.clear_color().copied(),
+          pipeline_state.clear_depth(),
+          pipeline_state.clear_stencil(),
+        );
+      })
+      .map_err(|e| PipelineError::WithFramebuffer {
+        pipeline_state: pipeline_state.clone(),
+        cause: Some(Box::new(e)),
+      })?;
+    }
+
+    f(WithFramebuffer::new(self))
+  }
+
+  unsafe fn with_program<V, W, P, S, E, F, Err>(
+    &mut self,
+    program: &Program<V, W, P, S, E, F>,
+    f: impl for<'a> FnOnce(WithProgram<'a, Self, V, W, P, S, E, F>) -> Result<(), Err>,
+  ) -> Result<(), Err>
+  where
+    V: Vertex,
+    W: Vertex,
+    P: Primitive,
+    S: RenderSlots,
+    E: Uniforms,
+    F: Vertex,
+    Err: From<PipelineError>,
+  {
+    let handle = program.handle();
+
+    {
+      let mut st = self.state.borrow_mut();
+
+      if !st.programs.contains_key(&handle) {
+        return Err(
+          PipelineError::WithProgram {
+            cause: Some(Box::new(SoftError::UnknownHandle { handle })),
+          }
+          .into(),
+        );
+      }
+
+      st.program = handle;
+    }
+
+    f(WithProgram::new(self, program))
+  }
+
+  unsafe fn with_render_state<V, W, P, F, Err>(
+    &mut self,
+    render_state: &RenderState,
+    f: impl for<'a> FnOnce(WithRenderState<'a, Self, V, W, P, F>) -> Result<(), Err>,
+  ) -> Result<(), Err>
+  where
+    V: Vertex,
+    W: Vertex,
+    P: Primitive,
+    F: Vertex,
+    Err: From<PipelineError>,
+  {
+    self.state.borrow_mut().draw_state.render_state = render_state.clone();
+    f(WithRenderState::new(self))
+  }
+
+  unsafe fn render_vertex_entity<V, W, P>(
+    &mut self,
+    view: VertexEntityView<V, W, P>,
+  ) -> Result<(), PipelineError>
+  where
+    V: Vertex,
+    W: Vertex,
+    P: Primitive,
+  {
+    self
+      .vertex_entity_render::<V, P>(
+        view.handle(),
+        view.start_vertex(),
+        view.vertex_count(),
+        view.instance_count(),
+        view.base_vertex(),
+        view.base_instance(),
+      )
+      .map_err(|e| PipelineError::RenderVertexEntity {
+        start_vertex: view.start_vertex(),
+        vertex_count: view.vertex_count(),
+        instance_count: view.instance_count(),
+        cause: Some(Box::new(e)),
+      })
+  }
+
+  unsafe fn render_vertex_entity_batch<V, W, P>(
+    &mut self,
+    views: &[VertexEntityView<V, W, P>],
+  ) -> Result<(), PipelineError>
+  where
+    V: Vertex,
+    W: Vertex,
+    P: Primitive,
+  {
+    self
+      .vertex_entity_render_batch(views)
+      .map_err(|e| PipelineError::RenderVertexEntityBatch {
+        view_count: views.len(),
+        cause: Some(Box::new(e)),
+      })
+  }
+
+  unsafe fn render_vertex_entity_with_feedback<V, W, P, F>(
+    &mut self,
+    view: VertexEntityView<V, W, P>,
+    buffer_handle: usize,
+  ) -> Result<(), PipelineError>
+  where
+    V: Vertex,
+    W: Vertex,
+    P: Primitive,
+    F: Vertex,
+  {
+    // indirect buffers receive captured commands
+    let known_buffer = {
+      let st = self.state.borrow();
+      st.buffers.contains_key(&buffer_handle) || st.indirect_buffers.contains_key(&buffer_handle)
+    };
+
+    if !known_buffer {
+      return Err(PipelineError::TransformFeedback {
+        cause: Some(Box::new(SoftError::UnknownHandle {
+          handle: buffer_handle,
+        })),
+      });
+    }
+
+    self.state.borrow_mut().feedback_buffer = Some(buffer_handle);
+    let rendered = self.render_vertex_entity(view);
+    self.state.borrow_mut().feedback_buffer = None;
+
+    rendered
+  }
+}
+
+unsafe impl BufferBackend for Soft {
+  unsafe fn new_buffer<T>(&mut self, values: &[T], _: Usage) -> Result<Buffer<T>, BufferError>
+  where
+    T: Copy,
+  {
+    let mut st = self.state.borrow_mut();
+    let handle = st.next_handle();
+    st.buffers.insert(handle, BufferData::new(values));
+
+    let state = self.state.clone();
+    let dropper = Box::new(move |handle| {
+      state.borrow_mut().drop_buffer(handle);
+    });
+
+    Ok(Buffer::new(handle, values.len(), dropper))
+  }
+
+  unsafe fn buffer_update<T>(
+    &mut self,
+    handle: usize,
+    start: usize,
+    values: &[T],
+  ) -> Result<(), BufferError>
+  where
+    T: Copy,
+  {
+    let mut st = self.state.borrow_mut();
+    let buffer = st
+      .buffers
+      .get_mut(&handle)
+      .ok_or(BufferError::NoData { handle })?;
+
+    if start + values.len() > buffer.len() {
+      return Err(BufferError::Update {
+        cause: Some(Box::new(SoftError::OutOfBounds {
+          start,
+          count: values.len(),
+          len: buffer.len(),
+        })),
+      });
+    }
+
+    ptr::copy_nonoverlapping(
+      values.as_ptr(),
+      buffer.as_mut_ptr::<T>().add(start),
+      values.len(),
+    );
+    Ok(())
+  }
+
+  unsafe fn buffer_read<T>(&mut self, handle: usize, len: usize) -> Result<Vec<T>, BufferError>
+  where
+    T: Copy,
+  {
+    let st = self.state.borrow();
+    let buffer = st
+      .buffers
+      .get(&handle)
+      .ok_or(BufferError::NoData { handle })?;
+
+    if len > buffer.len() {
+      return Err(BufferError::Read {
+        cause: Some(Box::new(SoftError::OutOfBounds {
+          start: 0,
+          count: len,
+          len: buffer.len(),
+        })),
+      });
+    }
+
+    Ok(std::slice::from_raw_parts(buffer.as_ptr::<T>(), len).to_vec())
+  }
+
+  unsafe fn buffer_map<T>(
+    &mut self,
+    handle: usize,
+    len: usize,
+  ) -> Result<BufferSliceMut<'_, Self, T>, BufferError>
+  where
+    T: Copy,
+  {
+    let ptr = {
+      let mut st = self.state.borrow_mut();
+      let buffer = st
+        .buffers
+        .get_mut(&handle)
+        .ok_or(BufferError::NoData { handle })?;
+
+      if len > buffer.len() {
+        return Err(BufferError::Map {
+          cause: Some(Box::new(SoftError::OutOfBounds {
+            start: 0,
+            count: len,
+            len: buffer.len(),
+          })),
+        });
+      }
+
+      buffer.as_mut_ptr::<T>()
+    };
+
+    Ok(BufferSliceMut::new(self, handle, ptr, len))
+  }
+
+  unsafe fn buffer_unmap<T>(&mut self, handle: usize) -> Result<(), BufferError> {
+    // the buffer is mapped in place; there is nothing to write back
+    if !self.state.borrow().buffers.contains_key(&handle) {
+      return Err(BufferError::NoData { handle });
+    }
+
+    Ok(())
+  }
+}
+
+unsafe impl IndirectBufferBackend for Soft {
+  unsafe fn new_indirect_buffer<C>(
+    &mut self,
+    commands: &[C],
+  ) -> Result<IndirectBuffer<C>, IndirectBufferError>
+  where
+    C: IndirectCommand,
+  {
+    let mut st = self.state.borrow_mut();
+    let handle = st.next_handle();
+    st.indirect_buffers
+      .insert(handle, BufferData::new(commands));
+
+    let state = self.state.clone();
+    let dropper = Box::new(move |handle| {
+      state.borrow_mut().drop_indirect_buffer(handle);
+    });
+
+    Ok(IndirectBuffer::new(handle, commands.len(), dropper))
+  }
+
+  unsafe fn indirect_buffer_update<C>(
+    &mut self,
+    handle: usize,
+    start: usize,
+    commands: &[C],
+  ) -> Result<(), IndirectBufferError>
+  where
+    C: IndirectCommand,
+  {
+    let mut st = self.state.borrow_mut();
+    let buffer = st
+      .indirect_buffers
+      .get_mut(&handle)
+      .ok_or(IndirectBufferError::NoData { handle })?;
+    let len = buffer.len();
+
+    match buffer
+      .as_mut_slice::<C>()
+      .get_mut(start..start + commands.len())
+    {
+      Some(slice) => {
+        slice.copy_from_slice(commands);
+        Ok(())
+      }
+
+      None => Err(IndirectBufferError::Update {
+        cause: Some(Box::new(SoftError::OutOfBounds {
+          start,
+          count: commands.len(),
+          len,
+        })),
+      }),
+    }
+  }
+
+  unsafe fn indirect_buffer_read<C>(&mut self, handle: usize) -> Result<Vec<C>, IndirectBufferError>
+  where
+    C: IndirectCommand,
+  {
+    self
+      .state
+      .borrow()
+      .indirect_buffers
+      .get(&handle)
+      .map(|buffer| buffer.as_slice::<C>().to_vec())
+      .ok_or(IndirectBufferError::NoData { handle })
+  }
+
+  unsafe fn vertex_entity_render_indirect<V, P, C>(
+    &mut self,
+    entity_handle: usize,
+    indirect_handle: usize,
+    commands: Range<usize>,
+  ) -> Result<(), IndirectBufferError>
+  where
+    V: Vertex,
+    P: Primitive,
+    C: IndirectCommand,
+  {
+    let draw_commands = {
+      let st = self.state.borrow();
+      let buffer =
+        st.indirect_buffers
+          .get(&indirect_handle)
+          .ok_or(IndirectBufferError::NoData {
+            handle: indirect_handle,
+          })?;
+
+      buffer
+        .as_slice::<C>()
+        .get(commands.clone())
+        .ok_or_else(|| IndirectBufferError::Render {
+          cause: Some(Box::new(SoftError::OutOfBounds {
+            start: commands.start,
+            count: commands.len(),
+            len: buffer.len(),
+          })),
+        })?
+        .to_vec()
+    };
+
+    for command in draw_commands {
+      self
+        .vertex_entity_render::<V, P>(
+          entity_handle,
+          command.first() as _,
+          command.count() as _,
+          command.instance_count() as _,
+          command.base_vertex() as _,
+          command.base_instance() as _,
+        )
+        .map_err(|e| IndirectBufferError::Render {
+          cause: Some(Box::new(e)),
+        })?;
+    }
+
+    Ok(())
+  }
+}
+
+unsafe impl QueryBackend for Soft {
+  fn backend_author(&self) -> Result<String, QueryError> {
+    Ok("luminance".to_owned())
+  }
+
+  fn backend_name(&self) -> Result<String, QueryError> {
+    Ok("soft".to_owned())
+  }
+
+  fn backend_version(&self) -> Result<String, QueryError> {
+    Ok(env!("CARGO_PKG_VERSION").to_owned())
+  }
+
+  fn backend_shading_lang_version(&self) -> Result<String, QueryError> {
+    // shaders are Rust closures
+    Err(QueryError::NoBackendShadingLanguageVersion)
+  }
+}
@@ -0,0 +1,63 @@
+//! Buffer storage.
+
+use std::{mem, ptr, slice};
+
+/// Content of a buffer, kept as raw bytes.
+#[derive(Debug)]
+pub(crate) struct BufferData {
+  storage: Vec<u128>, // u128 to get an alignment suitable for any type
+  bytes: usize,
+  len: usize,
+}
+
+impl BufferData {
+  pub(crate) fn new<T>(values: &[T]) -> Self {
+    let bytes = mem::size_of_val(values);
+    let mut storage =
+      vec![0u128; ((bytes + mem::size_of::<u128>() - 1) / mem::size_of::<u128>()).max(1)];
+
+    unsafe {
+      ptr::copy_nonoverlapping(
+        values.as_ptr(),
+        storage.as_mut_ptr() as *mut T,
+        values.len(),
+      );
+    }
+
+    Self {
+      storage,
+      bytes,
+      len: values.len(),
+    }
+  }
+
+  /// Number of values in the buffer.
+  pub(crate) fn len(&self) -> usize {
+    self.len
+  }
+
+  pub(crate) fn as_bytes(&self) -> &[u8] {
+    unsafe { slice::from_raw_parts(self.storage.as_ptr() as *const u8, self.bytes) }
+  }
+
+  pub(crate) fn as_bytes_mut(&mut self) -> &mut [u8] {
+    unsafe { slice::from_raw_parts_mut(self.storage.as_mut_ptr() as *mut u8, self.bytes) }
+  }
+
+  pub(crate) fn as_ptr<T>(&self) -> *const T {
+    self.storage.as_ptr() as *const T
+  }
+
+  pub(crate) fn as_mut_ptr<T>(&mut self) -> *mut T {
+    self.storage.as_mut_ptr() as *mut T
+  }
+
+  /// The values of the buffer; `T` must be the type the buffer was created with.
+  pub(crate) unsafe fn as_slice<T>(&self) -> &[T] {
+    slice::from_raw_parts(self.as_ptr(), self.len)
+  }
+
+  pub(crate) unsafe fn as_mut_slice<T>(&mut self) -> &mut [T] {
+    slice::from_raw_parts_mut(self.as_mut_ptr(), self.len)
+  }
+}
@@ -0,0 +1,45 @@
+//! CPU software rasterizer backend for luminance.
+//!
+//! [`Soft`] implements all the backend traits without any GPU, following the OpenGL 3.3 semantics as closely as
+//! possible (clipping, top-left fill rule, depth and stencil tests, blending, sRGB conversions, etc.). It is meant to
+//! run rendering code in headless environments (CI, tests) and to compare rendered images against golden images.
+//!
+//! Because no shading language is compiled, shaders are Rust closures registered by name on the backend; see the
+//! [`shader`] module for further details.
+//!
+//! ```ignore
+//! let mut ctx = Context::new(Soft::new).unwrap();
+//!
+//! ctx.backend().add_vertex_shader("vs", |input, _| {
+//!   let [x, y, ..] = input.attrib("pos");
+//!   VertexOutput::new([x, y, 0., 1.], [])
+//! });
+//! ctx.backend().add_fragment_shader("fs", |_, _| Some(vec![[1., 0., 0., 1.]]));
+//!
+//! let program = ctx.new_program(
+//!   ProgramBuilder::new()
+//!     .add_vertex_stage("vs")
+//!     .no_primitive_stage()
+//!     .add_shading_stage("fs"),
+//! )?;
+//!
+//! // … render to the back buffer …
+//!
+//! let pixels = ctx.backend().read_back_buffer();
+//! ```
+//!
+//! Limitations:
+//!
+//! - Primitive and tessellation stages are not supported.
+//! - Only 2D framebuffers can be rendered to.
+//! - Textures don’t have mipmaps and packed pixel formats are not supported.
+
+mod backend;
+mod buffer;
+mod pixel;
+mod raster;
+pub mod shader;
+mod texture;
+mod vertex;
+
+pub use backend::{Soft, SoftError};
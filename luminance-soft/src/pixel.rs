@@ -0,0 +1,193 @@
+//! Conversions between stored texels and colors.
+//!
+//! Texels are stored as raw bytes, laid out exactly as the [`Pixel::RawEncoding`] of their pixel format (depth-stencil
+//! texels only store the depth part; stencil values live in the framebuffer). Colors are always manipulated as
+//! `[f32; 4]`, following the OpenGL rules to convert from and to normalized and integral formats.
+//!
+//! [`Pixel::RawEncoding`]: luminance::pixel::Pixel::RawEncoding
+
+use luminance::pixel::{Format, PixelFormat, Size, Type};
+
+/// Number of bytes a single texel takes in texture storage.
+pub(crate) fn texel_len(fmt: PixelFormat) -> usize {
+  match fmt.format {
+    Format::DepthStencil(depth, _) => depth.bits_len() / 8,
+    format => format.bytes_len(),
+  }
+}
+
+/// Whether the pixel format can be stored and converted by the software backend.
+///
+/// Packed formats (i.e. using [`Size::Ten`] or [`Size::Eleven`]) are not supported; neither are 8-bit and 16-bit
+/// floating formats.
+pub(crate) fn is_supported(fmt: PixelFormat) -> bool {
+  let (sizes, count) = channel_sizes(fmt.format);
+
+  sizes[..count]
+    .iter()
+    .all(|size| match (fmt.encoding, size) {
+      (_, Size::Ten) | (_, Size::Eleven) => false,
+      (Type::Floating, size) => *size == Size::ThirtyTwo,
+      _ => true,
+    })
+}
+
+/// Whether the pixel format is sRGB-encoded.
+pub(crate) fn is_srgb(fmt: PixelFormat) -> bool {
+  matches!(fmt.format, Format::SRGB(..) | Format::SRGBA(..))
+}
+
+/// Whether the pixel format stores integers that are not normalized.
+///
+/// Such formats don’t support blending.
+pub(crate) fn is_integral(fmt: PixelFormat) -> bool {
+  matches!(fmt.encoding, Type::Integral | Type::Unsigned)
+}
+
+/// Clamp a color to the range of representable values of a pixel format, as done before blending.
+pub(crate) fn clamp(fmt: PixelFormat, color: [f32; 4]) -> [f32; 4] {
+  match fmt.encoding {
+    Type::NormUnsigned => color.map(|c| c.clamp(0., 1.)),
+    Type::NormIntegral => color.map(|c| c.clamp(-1., 1.)),
+    _ => color,
+  }
+}
+
+fn channel_sizes(format: Format) -> ([Size; 4], usize) {
+  match format {
+    Format::R(r) | Format::Depth(r) | Format::DepthStencil(r, _) => ([r; 4], 1),
+    Format::RG(r, g) => ([r, g, r, r], 2),
+    Format::RGB(r, g, b) | Format::SRGB(r, g, b) => ([r, g, b, r], 3),
+    Format::RGBA(r, g, b, a) | Format::SRGBA(r, g, b, a) => ([r, g, b, a], 4),
+  }
+}
+
+/// Decode a texel into a color.
+///
+/// Missing channels are set to `0`, but alpha, which is set to `1`. sRGB texels are not converted; see
+/// [`srgb_to_linear`].
+pub(crate) fn decode(fmt: PixelFormat, bytes: &[u8]) -> [f32; 4] {
+  let (sizes, count) = channel_sizes(fmt.format);
+  let mut color = [0., 0., 0., 1.];
+  let mut offset = 0;
+
+  for (c, size) in color.iter_mut().zip(&sizes[..count]) {
+    let len = size.bits_len() / 8;
+    *c = decode_channel(fmt.encoding, &bytes[offset..offset + len]);
+    offset += len;
+  }
+
+  color
+}
+
+/// Encode a color into a texel.
+///
+/// sRGB pixel formats expect the color to be already sRGB-encoded; see [`linear_to_srgb`].
+pub(crate) fn encode(fmt: PixelFormat, color: [f32; 4], bytes: &mut [u8]) {
+  let (sizes, count) = channel_sizes(fmt.format);
+  let mut offset = 0;
+
+  for (c, size) in color.iter().zip(&sizes[..count]) {
+    let len = size.bits_len() / 8;
+    encode_channel(fmt.encoding, *c, &mut bytes[offset..offset + len]);
+    offset += len;
+  }
+}
+
+fn decode_channel(encoding: Type, bytes: &[u8]) -> f32 {
+  match (encoding, bytes.len()) {
+    (Type::NormUnsigned, 1) => bytes[0] as f32 / u8::MAX as f32,
+    (Type::NormUnsigned, 2) => u16::from_ne_bytes([bytes[0], bytes[1]]) as f32 / u16::MAX as f32,
+    (Type::NormUnsigned, 4) => (u32_from(bytes) as f64 / u32::MAX as f64) as f32,
+
+    (Type::NormIntegral, 1) => (bytes[0] as i8 as f32 / i8::MAX as f32).max(-1.),
+    (Type::NormIntegral, 2) => {
+      (i16::from_ne_bytes([bytes[0], bytes[1]]) as f32 / i16::MAX as f32).max(-1.)
+    }
+    (Type::NormIntegral, 4) => ((u32_from(bytes) as i32 as f64 / i32::MAX as f64) as f32).max(-1.),
+
+    (Type::Unsigned, 1) => bytes[0] as f32,
+    (Type::Unsigned, 2) => u16::from_ne_bytes([bytes[0], bytes[1]]) as f32,
+    (Type::Unsigned, 4) => u32_from(bytes) as f32,
+
+    (Type::Integral, 1) => bytes[0] as i8 as f32,
+    (Type::Integral, 2) => i16::from_ne_bytes([bytes[0], bytes[1]]) as f32,
+    (Type::Integral, 4) => u32_from(bytes) as i32 as f32,
+
+    (Type::Floating, 4) => f32::from_bits(u32_from(bytes)),
+
+    _ => 0.,
+  }
+}
+
+fn encode_channel(encoding: Type, c: f32, bytes: &mut [u8]) {
+  match (encoding, bytes.len()) {
+    (Type::NormUnsigned, 1) => bytes[0] = (c.clamp(0., 1.) * u8::MAX as f32).round() as u8,
+    (Type::NormUnsigned, 2) => {
+      bytes.copy_from_slice(&((c.clamp(0., 1.) * u16::MAX as f32).round() as u16).to_ne_bytes())
+    }
+    (Type::NormUnsigned, 4) => bytes
+      .copy_from_slice(&((c.clamp(0., 1.) as f64 * u32::MAX as f64).round() as u32).to_ne_bytes()),
+
+    (Type::NormIntegral, 1) => bytes[0] = (c.clamp(-1., 1.) * i8::MAX as f32).round() as i8 as u8,
+    (Type::NormIntegral, 2) => {
+      bytes.copy_from_slice(&((c.clamp(-1., 1.) * i16::MAX as f32).round() as i16).to_ne_bytes())
+    }
+    (Type::NormIntegral, 4) => bytes
+      .copy_from_slice(&((c.clamp(-1., 1.) as f64 * i32::MAX as f64).round() as i32).to_ne_bytes()),
+
+    (Type::Unsigned, 1) => bytes[0] = c as u8,
+    (Type::Unsigned, 2) => bytes.copy_from_slice(&(c as u16).to_ne_bytes()),
+    (Type::Unsigned, 4) => bytes.copy_from_slice(&(c as u32).to_ne_bytes()),
+
+    (Type::Integral, 1) => bytes[0] = c as i8 as u8,
+    (Type::Integral, 2) => bytes.copy_from_slice(&(c as i16).to_ne_bytes()),
+    (Type::Integral, 4) => bytes.copy_from_slice(&(c as i32).to_ne_bytes()),
+
+    (Type::Floating, 4) => bytes.copy_from_slice(&c.to_ne_bytes()),
+
+    _ => (),
+  }
+}
+
+fn u32_from(bytes: &[u8]) -> u32 {
+  u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+/// Convert the color channels of an sRGB-encoded color to linear; alpha is left untouched.
+pub(crate) fn srgb_to_linear([r, g, b, a]: [f32; 4]) -> [f32; 4] {
+  [
+    srgb_channel_to_linear(r),
+    srgb_channel_to_linear(g),
+    srgb_channel_to_linear(b),
+    a,
+  ]
+}
+
+/// Convert the color channels of a linear color to sRGB; alpha is left untouched.
+pub(crate) fn linear_to_srgb([r, g, b, a]: [f32; 4]) -> [f32; 4] {
+  [
+    linear_channel_to_srgb(r),
+    linear_channel_to_srgb(g),
+    linear_channel_to_srgb(b),
+    a,
+  ]
+}
+
+fn srgb_channel_to_linear(c: f32) -> f32 {
+  if c <= 0.04045 {
+    c / 12.92
+  } else {
+    ((c + 0.055) / 1.055).powf(2.4)
+  }
+}
+
+fn linear_channel_to_srgb(c: f32) -> f32 {
+  let c = c.clamp(0., 1.);
+
+  if c <= 0.0031308 {
+    c * 12.92
+  } else {
+    1.055 * c.powf(1. / 2.4) - 0.055
+  }
+}
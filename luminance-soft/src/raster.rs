@@ -0,0 +1,772 @@
+//! Primitive assembly, clipping, rasterization and per-fragment operations.
+//!
+//! This follows the OpenGL 3.3 pipeline:
+//!
+//! 1. Vertices are processed by the vertex shader, once per instance.
+//! 2. Primitives are assembled according to the [`Connector`], honoring primitive restart for strips and fans. Their
+//!    vertices are captured at this point when rendering with transform feedback.
+//! 3. Primitives are clipped against the near and far planes and mapped to the viewport. Triangles are then culled
+//!    according to the [`FaceCulling`] of the render state.
+//! 4. Primitives are rasterized (pixel centers, top-left rule for triangles, half-open lines); fragments outside of
+//!    the viewport, the framebuffer or the scissor region are dropped.
+//! 5. Fragments are shaded and go through the stencil test, the depth test, blending and sRGB conversion before being
+//!    written to the render layers.
+
+use crate::{
+  pixel,
+  shader::{FragmentInput, FragmentShader, UniformValues, VertexInput, VertexOutput, VertexShader},
+  texture::TextureData,
+  vertex::AttributeStorage,
+};
+use luminance::{
+  blending::{Blending, BlendingMode, Equation, Factor},
+  depth_stencil::{Comparison, DepthTest, DepthWrite, StencilOp, StencilTest},
+  face_culling::{FaceCulling, FaceCullingFace, FaceCullingOrder},
+  primitive::Connector,
+  render_state::RenderState,
+  scissor::Scissor,
+};
+use std::collections::HashMap;
+
+/// Pipeline state that is relevant when drawing.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct DrawState {
+  /// Viewport, as `[x, y, width, height]`.
+  pub(crate) viewport: [i32; 4],
+  pub(crate) srgb_enabled: bool,
+  pub(crate) render_state: RenderState,
+}
+
+/// Buffers to draw to.
+pub(crate) struct Target<'a> {
+  pub(crate) width: u32,
+  pub(crate) height: u32,
+  pub(crate) colors: Vec<&'a mut TextureData>,
+  pub(crate) depth: Option<&'a mut TextureData>,
+  pub(crate) stencil: Option<&'a mut [u8]>,
+}
+
+impl<'a> Target<'a> {
+  /// Region of the target affected by a scissor, as `[x_min, y_min, x_max, y_max]` (max excluded).
+  fn region(&self, scissor: &Scissor) -> [i32; 4] {
+    let region = [0, 0, self.width as i32, self.height as i32];
+
+    match *scissor {
+      Scissor::Off => region,
+
+      Scissor::On {
+        x,
+        y,
+        width,
+        height,
+      } => intersect(
+        region,
+        [x as i32, y as i32, (x + width) as i32, (y + height) as i32],
+      ),
+    }
+  }
+
+  /// Clear the buffers of the target.
+  pub(crate) fn clear(
+    &mut self,
+    scissor: &Scissor,
+    srgb_enabled: bool,
+    color: Option<[f32; 4]>,
+    depth: Option<f32>,
+    stencil: Option<i32>,
+  ) {
+    let [x_min, y_min, x_max, y_max] = self.region(scissor);
+
+    for y in y_min..y_max {
+      for x in x_min..x_max {
+        let position = [x as u32, y as u32, 0];
+
+        if let Some(color) = color {
+          for layer in &mut self.colors {
+            let color = if srgb_enabled && pixel::is_srgb(layer.format) {
+              pixel::linear_to_srgb(color)
+            } else {
+              color
+            };
+
+            layer.store(position, color);
+          }
+        }
+
+        if let (Some(depth), Some(layer)) = (depth, self.depth.as_deref_mut()) {
+          layer.store(position, [depth.clamp(0., 1.), 0., 0., 0.]);
+        }
+
+        if let (Some(value), Some(buffer)) = (stencil, self.stencil.as_deref_mut()) {
+          buffer[(x + y * self.width as i32) as usize] = value as u8;
+        }
+      }
+    }
+  }
+}
+
+/// A single draw call.
+pub(crate) struct DrawCall<'a> {
+  pub(crate) connector: Connector,
+  pub(crate) vertices: &'a AttributeStorage,
+  pub(crate) instances: &'a AttributeStorage,
+  /// Vertex IDs to process, in order; `None` restarts the primitive.
+  pub(crate) elements: Vec<Option<u32>>,
+  pub(crate) instance_count: usize,
+  /// Instance to fetch the instance data of the first instance from.
+  pub(crate) base_instance: usize,
+  pub(crate) vertex_shader: &'a VertexShader,
+  pub(crate) fragment_shader: &'a FragmentShader,
+  pub(crate) uniforms: UniformValues<'a>,
+}
+
+impl<'a> DrawCall<'a> {
+  /// Run the draw call.
+  ///
+  /// If `feedback` is set, the vertices of the assembled primitives are appended to it, primitive by primitive.
+  pub(crate) fn run(
+    &self,
+    state: &DrawState,
+    target: &mut Target,
+    mut feedback: Option<&mut Vec<VertexOutput>>,
+  ) {
+    let mut rasterizer = Rasterizer::new(state, target, self);
+    let mut attributes = Vec::new();
+
+    for instance_id in 0..self.instance_count {
+      let mut cache: HashMap<u32, usize> = HashMap::new();
+      let mut outputs = Vec::new();
+
+      let slots: Vec<_> = self
+        .elements
+        .iter()
+        .map(|element| {
+          element.map(|vertex_id| {
+            *cache.entry(vertex_id).or_insert_with(|| {
+              attributes.clear();
+              self.vertices.fetch(vertex_id as usize, &mut attributes);
+
+              if !self.instances.is_empty_storage() {
+                self
+                  .instances
+                  .fetch_instance(self.base_instance, instance_id, &mut attributes);
+              }
+
+              let input = VertexInput {
+                vertex_id,
+                instance_id: instance_id as u32,
+                attributes: &attributes,
+              };
+
+              outputs.push((self.vertex_shader)(&input, &self.uniforms));
+              outputs.len() - 1
+            })
+          })
+        })
+        .collect();
+
+      assemble(self.connector, &slots, |primitive| {
+        if let Some(feedback) = feedback.as_deref_mut() {
+          let vertices = match primitive {
+            Primitive::Point(a) => vec![a],
+            Primitive::Line(a, b) => vec![a, b],
+            Primitive::Triangle(a, b, c) => vec![a, b, c],
+          };
+
+          feedback.extend(vertices.into_iter().map(|v| outputs[v].clone()));
+        }
+
+        match primitive {
+          Primitive::Point(a) => rasterizer.point(&outputs[a]),
+          Primitive::Line(a, b) => rasterizer.line(&outputs[a], &outputs[b]),
+          Primitive::Triangle(a, b, c) => {
+            rasterizer.triangle(&outputs[a], &outputs[b], &outputs[c])
+          }
+        }
+      });
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Primitive {
+  Point(usize),
+  Line(usize, usize),
+  Triangle(usize, usize, usize),
+}
+
+/// Assemble primitives out of processed vertices.
+///
+/// `None` restarts the current primitive.
+fn assemble(connector: Connector, slots: &[Option<usize>], mut f: impl FnMut(Primitive)) {
+  for run in slots.split(Option::is_none) {
+    let run: Vec<usize> = run.iter().flatten().copied().collect();
+
+    match connector {
+      Connector::Point => run.iter().for_each(|&a| f(Primitive::Point(a))),
+
+      Connector::Line => run
+        .chunks_exact(2)
+        .for_each(|v| f(Primitive::Line(v[0], v[1]))),
+
+      Connector::LineStrip => run.windows(2).for_each(|v| f(Primitive::Line(v[0], v[1]))),
+
+      Connector::Triangle => run
+        .chunks_exact(3)
+        .for_each(|v| f(Primitive::Triangle(v[0], v[1], v[2]))),
+
+      Connector::TriangleStrip => {
+        for (i, v) in run.windows(3).enumerate() {
+          if i % 2 == 0 {
+            f(Primitive::Triangle(v[0], v[1], v[2]));
+          } else {
+            f(Primitive::Triangle(v[1], v[0], v[2]));
+          }
+        }
+      }
+
+      Connector::TriangleFan => {
+        for v in run.windows(2).skip(1) {
+          f(Primitive::Triangle(run[0], v[0], v[1]));
+        }
+      }
+
+      // adjacent vertices are only read by primitive stages, which aren’t supported
+      Connector::LineAdjacency => run
+        .chunks_exact(4)
+        .for_each(|v| f(Primitive::Line(v[1], v[2]))),
+
+      Connector::LineStripAdjacency => run.windows(4).for_each(|v| f(Primitive::Line(v[1], v[2]))),
+
+      Connector::TriangleAdjacency => run
+        .chunks_exact(6)
+        .for_each(|v| f(Primitive::Triangle(v[0], v[2], v[4]))),
+
+      Connector::TriangleStripAdjacency => {
+        // the strip is made of the even vertices, its odd triangles being reversed
+        for i in 0..run.len().saturating_sub(4) / 2 {
+          let v = &run[2 * i..2 * i + 5];
+
+          if i % 2 == 0 {
+            f(Primitive::Triangle(v[0], v[2], v[4]));
+          } else {
+            f(Primitive::Triangle(v[2], v[0], v[4]));
+          }
+        }
+      }
+
+      // rejected before drawing
+      Connector::Patch(_) => (),
+    }
+  }
+}
+
+/// Vertex in window space.
+#[derive(Clone, Debug)]
+struct WindowVertex {
+  x: f32,
+  y: f32,
+  z: f32,
+  inv_w: f32,
+  // varyings divided by w, for perspective-correct interpolation
+  varyings: Vec<f32>,
+}
+
+/// Linear interpolation between two clip-space vertices.
+fn lerp_output(a: &VertexOutput, b: &VertexOutput, t: f32) -> VertexOutput {
+  let mut position = a.position;
+  for (p, b) in position.iter_mut().zip(b.position) {
+    *p += t * (b - *p);
+  }
+
+  let varyings = a
+    .varyings
+    .iter()
+    .zip(&b.varyings)
+    .map(|(a, b)| a + t * (b - a))
+    .collect();
+
+  VertexOutput { position, varyings }
+}
+
+/// Clipping planes, as signed distance functions of clip-space positions; a vertex is inside if the distance is
+/// positive.
+const CLIP_PLANES: [fn(&[f32; 4]) -> f32; 3] = [
+  |p| p[3] - 1e-6, // w > 0
+  |p| p[2] + p[3], // near
+  |p| p[3] - p[2], // far
+];
+
+/// Clip a convex polygon against all the clipping planes.
+fn clip_polygon(mut polygon: Vec<VertexOutput>) -> Vec<VertexOutput> {
+  for plane in CLIP_PLANES {
+    if polygon.is_empty() {
+      break;
+    }
+
+    let mut clipped = Vec::with_capacity(polygon.len() + 1);
+
+    for (i, a) in polygon.iter().enumerate() {
+      let b = &polygon[(i + 1) % polygon.len()];
+      let (da, db) = (plane(&a.position), plane(&b.position));
+
+      if da >= 0. {
+        clipped.push(a.clone());
+      }
+
+      if (da >= 0.) != (db >= 0.) {
+        clipped.push(lerp_output(a, b, da / (da - db)));
+      }
+    }
+
+    polygon = clipped;
+  }
+
+  polygon
+}
+
+/// Clip a line against all the clipping planes.
+fn clip_line(a: &VertexOutput, b: &VertexOutput) -> Option<(VertexOutput, VertexOutput)> {
+  let (mut t0, mut t1) = (0f32, 1f32);
+
+  for plane in CLIP_PLANES {
+    let (da, db) = (plane(&a.position), plane(&b.position));
+
+    if da < 0. && db < 0. {
+      return None;
+    }
+
+    if da < 0. {
+      t0 = t0.max(da / (da - db));
+    } else if db < 0. {
+      t1 = t1.min(da / (da - db));
+    }
+  }
+
+  if t0 > t1 {
+    None
+  } else {
+    Some((lerp_output(a, b, t0), lerp_output(a, b, t1)))
+  }
+}
+
+fn intersect(a: [i32; 4], b: [i32; 4]) -> [i32; 4] {
+  [
+    a[0].max(b[0]),
+    a[1].max(b[1]),
+    a[2].min(b[2]),
+    a[3].min(b[3]),
+  ]
+}
+
+/// Edge function; positive if `p` is on the left of the `a -> b` edge.
+fn edge(a: &WindowVertex, b: &WindowVertex, [x, y]: [f32; 2]) -> f32 {
+  (b.x - a.x) * (y - a.y) - (b.y - a.y) * (x - a.x)
+}
+
+/// Whether an edge of a counter-clockwise triangle is a top or a left edge.
+fn is_top_left(a: &WindowVertex, b: &WindowVertex) -> bool {
+  let (dx, dy) = (b.x - a.x, b.y - a.y);
+  dy < 0. || (dy == 0. && dx < 0.)
+}
+
+struct Rasterizer<'a, 'b, 'c> {
+  state: &'a DrawState,
+  target: &'a mut Target<'b>,
+  draw_call: &'a DrawCall<'c>,
+  /// Region fragments can be written to, as `[x_min, y_min, x_max, y_max]` (max excluded).
+  region: [i32; 4],
+}
+
+impl<'a, 'b, 'c> Rasterizer<'a, 'b, 'c> {
+  fn new(state: &'a DrawState, target: &'a mut Target<'b>, draw_call: &'a DrawCall<'c>) -> Self {
+    let [x, y, width, height] = state.viewport;
+    let region = intersect(
+      target.region(&state.render_state.scissor),
+      [x, y, x + width, y + height],
+    );
+
+    Self {
+      state,
+      target,
+      draw_call,
+      region,
+    }
+  }
+
+  /// Map a clipped vertex to window space.
+  fn to_window(&self, v: &VertexOutput) -> WindowVertex {
+    let [vx, vy, vw, vh] = self.state.viewport;
+    let inv_w = 1. / v.position[3];
+    let [x, y, z] = [
+      v.position[0] * inv_w,
+      v.position[1] * inv_w,
+      v.position[2] * inv_w,
+    ];
+
+    WindowVertex {
+      x: vx as f32 + (x + 1.) * vw as f32 * 0.5,
+      y: vy as f32 + (y + 1.) * vh as f32 * 0.5,
+      z: ((z + 1.) * 0.5).clamp(0., 1.),
+      inv_w,
+      varyings: v.varyings.iter().map(|v| v * inv_w).collect(),
+    }
+  }
+
+  fn contains(&self, x: i32, y: i32) -> bool {
+    let [x_min, y_min, x_max, y_max] = self.region;
+    x >= x_min && x < x_max && y >= y_min && y < y_max
+  }
+
+  fn point(&mut self, v: &VertexOutput) {
+    let [x, y, z, w] = v.position;
+
+    // points are clipped if their center is outside of the clip volume
+    if w <= 0. || x.abs() > w || y.abs() > w || z.abs() > w {
+      return;
+    }
+
+    let v = self.to_window(v);
+    let (px, py) = (v.x.floor() as i32, v.y.floor() as i32);
+
+    if self.contains(px, py) {
+      let varyings: Vec<f32> = v.varyings.iter().map(|a| a / v.inv_w).collect();
+      self.fragment(px, py, v.z, v.inv_w, &varyings, true);
+    }
+  }
+
+  fn line(&mut self, a: &VertexOutput, b: &VertexOutput) {
+    let (a, b) = match clip_line(a, b) {
+      Some((a, b)) => (self.to_window(&a), self.to_window(&b)),
+      None => return,
+    };
+
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let x_major = dx.abs() >= dy.abs();
+    let (start, end) = if x_major { (a.x, b.x) } else { (a.y, b.y) };
+
+    if start == end {
+      return;
+    }
+
+    // pixel centers in [start, end) (or (end, start] when going backwards)
+    let range = if end > start {
+      (start - 0.5).ceil() as i32..(end - 0.5).ceil() as i32
+    } else {
+      (end - 0.5).floor() as i32 + 1..(start - 0.5).floor() as i32 + 1
+    };
+
+    let mut varyings = vec![0.; a.varyings.len().min(b.varyings.len())];
+
+    for p in range {
+      let t = ((p as f32 + 0.5 - start) / (end - start)).clamp(0., 1.);
+      let (px, py) = if x_major {
+        (p, (a.y + t * dy).floor() as i32)
+      } else {
+        ((a.x + t * dx).floor() as i32, p)
+      };
+
+      if !self.contains(px, py) {
+        continue;
+      }
+
+      let z = a.z + t * (b.z - a.z);
+      let inv_w = a.inv_w + t * (b.inv_w - a.inv_w);
+
+      for (i, v) in varyings.iter_mut().enumerate() {
+        *v = (a.varyings[i] + t * (b.varyings[i] - a.varyings[i])) / inv_w;
+      }
+
+      self.fragment(px, py, z, inv_w, &varyings, true);
+    }
+  }
+
+  fn triangle(&mut self, a: &VertexOutput, b: &VertexOutput, c: &VertexOutput) {
+    let polygon = clip_polygon(vec![a.clone(), b.clone(), c.clone()]);
+
+    if polygon.len() < 3 {
+      return;
+    }
+
+    let polygon: Vec<_> = polygon.iter().map(|v| self.to_window(v)).collect();
+
+    // the facing is determined with the whole (clipped) polygon
+    let area: f32 = (0..polygon.len())
+      .map(|i| {
+        let (a, b) = (&polygon[i], &polygon[(i + 1) % polygon.len()]);
+        a.x * b.y - b.x * a.y
+      })
+      .sum();
+
+    if area == 0. {
+      return;
+    }
+
+    let front_facing = match self.state.render_state.face_culling {
+      FaceCulling::On {
+        order: FaceCullingOrder::CW,
+        ..
+      } => area < 0.,
+      _ => area > 0.,
+    };
+
+    match self.state.render_state.face_culling {
+      FaceCulling::On { face, .. } => match face {
+        FaceCullingFace::Front if front_facing => return,
+        FaceCullingFace::Back if !front_facing => return,
+        FaceCullingFace::Both => return,
+        _ => (),
+      },
+
+      FaceCulling::Off => {
+        // GL_CCW is the default front face order
+      }
+    }
+
+    for i in 1..polygon.len() - 1 {
+      if area > 0. {
+        self.fill_triangle(&polygon[0], &polygon[i], &polygon[i + 1], front_facing);
+      } else {
+        self.fill_triangle(&polygon[0], &polygon[i + 1], &polygon[i], front_facing);
+      }
+    }
+  }
+
+  /// Rasterize a counter-clockwise triangle.
+  fn fill_triangle(
+    &mut self,
+    v0: &WindowVertex,
+    v1: &WindowVertex,
+    v2: &WindowVertex,
+    front_facing: bool,
+  ) {
+    let area = edge(v0, v1, [v2.x, v2.y]);
+
+    if area <= 0. {
+      return;
+    }
+
+    let [x_min, y_min, x_max, y_max] = intersect(
+      self.region,
+      [
+        v0.x.min(v1.x).min(v2.x).floor() as i32,
+        v0.y.min(v1.y).min(v2.y).floor() as i32,
+        v0.x.max(v1.x).max(v2.x).ceil() as i32 + 1,
+        v0.y.max(v1.y).max(v2.y).ceil() as i32 + 1,
+      ],
+    );
+
+    let top_left = [
+      is_top_left(v1, v2),
+      is_top_left(v2, v0),
+      is_top_left(v0, v1),
+    ];
+    let varyings_len = v0
+      .varyings
+      .len()
+      .min(v1.varyings.len())
+      .min(v2.varyings.len());
+    let mut varyings = vec![0.; varyings_len];
+
+    for py in y_min..y_max {
+      for px in x_min..x_max {
+        let p = [px as f32 + 0.5, py as f32 + 0.5];
+        let w = [edge(v1, v2, p), edge(v2, v0, p), edge(v0, v1, p)];
+
+        let inside = w
+          .iter()
+          .zip(top_left)
+          .all(|(w, top_left)| *w > 0. || (*w == 0. && top_left));
+
+        if !inside {
+          continue;
+        }
+
+        let l = w.map(|w| w / area);
+        let z = l[0] * v0.z + l[1] * v1.z + l[2] * v2.z;
+        let inv_w = l[0] * v0.inv_w + l[1] * v1.inv_w + l[2] * v2.inv_w;
+
+        for (i, v) in varyings.iter_mut().enumerate() {
+          *v = (l[0] * v0.varyings[i] + l[1] * v1.varyings[i] + l[2] * v2.varyings[i]) / inv_w;
+        }
+
+        self.fragment(px, py, z, inv_w, &varyings, front_facing);
+      }
+    }
+  }
+
+  /// Shade a fragment and run the per-fragment operations.
+  fn fragment(&mut self, x: i32, y: i32, z: f32, inv_w: f32, varyings: &[f32], front_facing: bool) {
+    let input = FragmentInput {
+      frag_coord: [x as f32 + 0.5, y as f32 + 0.5, z, inv_w],
+      front_facing,
+      varyings,
+    };
+
+    let colors = match (self.draw_call.fragment_shader)(&input, &self.draw_call.uniforms) {
+      Some(colors) => colors,
+      None => return,
+    };
+
+    let render_state = &self.state.render_state;
+    let position = [x as u32, y as u32, 0];
+    let index = x as usize + y as usize * self.target.width as usize;
+
+    // stencil test
+    let stencil = match (
+      render_state.stencil_test,
+      self.target.stencil.as_deref_mut(),
+    ) {
+      (
+        StencilTest::On {
+          comparison,
+          reference,
+          mask,
+          depth_passes_stencil_fails,
+          depth_fails_stencil_passes,
+          depth_stencil_pass,
+        },
+        Some(buffer),
+      ) => {
+        let value = &mut buffer[index];
+
+        if !compare(comparison, reference & mask, *value & mask) {
+          *value = stencil_op(depth_passes_stencil_fails, *value, reference);
+          return;
+        }
+
+        Some((
+          value,
+          reference,
+          depth_fails_stencil_passes,
+          depth_stencil_pass,
+        ))
+      }
+
+      _ => None,
+    };
+
+    // depth test; the depth buffer is only updated if the depth test is enabled
+    if let (DepthTest::On(comparison), Some(depth)) =
+      (render_state.depth_test, self.target.depth.as_deref_mut())
+    {
+      if !compare(comparison, z, depth.load(position)[0]) {
+        if let Some((value, reference, depth_fails_stencil_passes, _)) = stencil {
+          *value = stencil_op(depth_fails_stencil_passes, *value, reference);
+        }
+
+        return;
+      }
+
+      if render_state.depth_write == DepthWrite::On {
+        depth.store(position, [z, 0., 0., 0.]);
+      }
+    }
+
+    if let Some((value, reference, _, depth_stencil_pass)) = stencil {
+      *value = stencil_op(depth_stencil_pass, *value, reference);
+    }
+
+    // blending and writing
+    for (layer, color) in self.target.colors.iter_mut().zip(colors) {
+      let fmt = layer.format;
+      let srgb = self.state.srgb_enabled && pixel::is_srgb(fmt);
+
+      let color = match render_state.blending {
+        BlendingMode::Off => color,
+        _ if pixel::is_integral(fmt) => color,
+
+        blending => {
+          let src = pixel::clamp(fmt, color);
+          let dst = layer.load(position);
+          let dst = if srgb {
+            pixel::srgb_to_linear(dst)
+          } else {
+            dst
+          };
+
+          match blending {
+            BlendingMode::Combined(blending) => blend(&blending, &blending, src, dst),
+            BlendingMode::Separate { rgb, alpha } => blend(&rgb, &alpha, src, dst),
+            BlendingMode::Off => unreachable!(),
+          }
+        }
+      };
+
+      let color = if srgb {
+        pixel::linear_to_srgb(color)
+      } else {
+        color
+      };
+
+      layer.store(position, color);
+    }
+  }
+}
+
+/// Compare an incoming value to a stored one.
+fn compare<T>(comparison: Comparison, incoming: T, stored: T) -> bool
+where
+  T: PartialOrd,
+{
+  match comparison {
+    Comparison::Never => false,
+    Comparison::Always => true,
+    Comparison::Equal => incoming == stored,
+    Comparison::NotEqual => incoming != stored,
+    Comparison::Less => incoming < stored,
+    Comparison::LessOrEqual => incoming <= stored,
+    Comparison::Greater => incoming > stored,
+    Comparison::GreaterOrEqual => incoming >= stored,
+  }
+}
+
+fn stencil_op(op: StencilOp, value: u8, reference: u8) -> u8 {
+  match op {
+    StencilOp::Keep => value,
+    StencilOp::Zero => 0,
+    StencilOp::Replace => reference,
+    StencilOp::Increment => value.saturating_add(1),
+    StencilOp::IncrementWrap => value.wrapping_add(1),
+    StencilOp::Decrement => value.saturating_sub(1),
+    StencilOp::DecrementWrap => value.wrapping_sub(1),
+    StencilOp::Invert => !value,
+  }
+}
+
+fn blend(rgb: &Blending, alpha: &Blending, src: [f32; 4], dst: [f32; 4]) -> [f32; 4] {
+  let mut color = [0.; 4];
+
+  for (i, c) in color.iter_mut().enumerate() {
+    let blending = if i == 3 { alpha } else { rgb };
+    let s = src[i] * factor(blending.src, i, src, dst);
+    let d = dst[i] * factor(blending.dst, i, src, dst);
+
+    *c = match blending.equation {
+      Equation::Additive => s + d,
+      Equation::Subtract => s - d,
+      Equation::ReverseSubtract => d - s,
+      Equation::Min => src[i].min(dst[i]),
+      Equation::Max => src[i].max(dst[i]),
+    };
+  }
+
+  color
+}
+
+fn factor(factor: Factor, channel: usize, src: [f32; 4], dst: [f32; 4]) -> f32 {
+  match factor {
+    Factor::One => 1.,
+    Factor::Zero => 0.,
+    Factor::SrcColor => src[channel],
+    Factor::SrcColorComplement => 1. - src[channel],
+    Factor::DestColor => dst[channel],
+    Factor::DestColorComplement => 1. - dst[channel],
+    Factor::SrcAlpha => src[3],
+    Factor::SrcAlphaComplement => 1. - src[3],
+    Factor::DstAlpha => dst[3],
+    Factor::DstAlphaComplement => 1. - dst[3],
+    Factor::SrcAlphaSaturate if channel == 3 => 1.,
+    Factor::SrcAlphaSaturate => src[3].min(1. - dst[3]),
+  }
+}
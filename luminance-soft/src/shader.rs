@@ -0,0 +1,244 @@
+//! Shading with Rust closures.
+//!
+//! The software backend doesn’t compile any shading language. Instead, vertex and fragment shaders are Rust closures
+//! registered by name with [`Soft::add_vertex_shader`] and [`Soft::add_fragment_shader`]. When a program is created,
+//! the code passed to [`ProgramBuilder::add_vertex_stage`] and [`ProgramBuilder::add_shading_stage`] is used as the
+//! name of the shaders to use.
+//!
+//! Vertex attributes are looked up by the names of the fields of the vertex types, and uniforms by the names used to
+//! create them. Everything is converted to `f32`, as if all attributes were declared as `vec4` in GLSL: normalized
+//! integral attributes are normalized, and non-normalized ones are simply converted. Matrix attributes are read with
+//! [`VertexInput::attrib_mat44`].
+//!
+//! When rendering with transform feedback, the [`VertexOutput::varyings`] of the captured vertices fill the fields of
+//! the captured vertex type, in order, and are converted back to the types of the fields.
+//!
+//! [`Soft::add_vertex_shader`]: crate::Soft::add_vertex_shader
+//! [`Soft::add_fragment_shader`]: crate::Soft::add_fragment_shader
+//! [`ProgramBuilder::add_vertex_stage`]: luminance::shader::ProgramBuilder::add_vertex_stage
+//! [`ProgramBuilder::add_shading_stage`]: luminance::shader::ProgramBuilder::add_shading_stage
+
+use crate::{buffer::BufferData, texture::TextureData};
+use luminance::shader::UniformValue;
+use std::collections::HashMap;
+
+pub use crate::texture::Sampler;
+
+/// Vertex shader.
+pub type VertexShader = dyn Fn(&VertexInput, &UniformValues) -> VertexOutput;
+
+/// Fragment shader.
+///
+/// It returns one color per render slot, in the order of declaration of the render slots, or `None` to discard the
+/// fragment.
+pub type FragmentShader = dyn Fn(&FragmentInput, &UniformValues) -> Option<Vec<[f32; 4]>>;
+
+/// Input of a vertex shader.
+#[derive(Debug)]
+pub struct VertexInput<'a> {
+  pub(crate) vertex_id: u32,
+  pub(crate) instance_id: u32,
+  pub(crate) attributes: &'a [(&'static str, [f32; 4])],
+}
+
+impl<'a> VertexInput<'a> {
+  /// Index of the vertex being processed (`gl_VertexID`).
+  pub fn vertex_id(&self) -> u32 {
+    self.vertex_id
+  }
+
+  /// Index of the instance being processed (`gl_InstanceID`).
+  pub fn instance_id(&self) -> u32 {
+    self.instance_id
+  }
+
+  /// Value of a vertex or instance attribute.
+  ///
+  /// Missing components are set to `0`, but the last one, which is set to `1`. Unknown attributes are read as
+  /// `[0., 0., 0., 1.]`.
+  pub fn attrib(&self, name: &str) -> [f32; 4] {
+    self
+      .attributes
+      .iter()
+      .find(|(attr_name, _)| *attr_name == name)
+      .map(|(_, value)| *value)
+      .unwrap_or([0., 0., 0., 1.])
+  }
+
+  /// Value of a matrix vertex or instance attribute, as a column-major 4×4 matrix.
+  ///
+  /// Columns are read as with [`VertexInput::attrib`]; missing columns are set to the ones of the identity matrix.
+  pub fn attrib_mat44(&self, name: &str) -> [[f32; 4]; 4] {
+    let mut columns = self
+      .attributes
+      .iter()
+      .filter(|(attr_name, _)| *attr_name == name)
+      .map(|(_, value)| *value);
+
+    let mut m = [[0.; 4]; 4];
+    for (i, column) in m.iter_mut().enumerate() {
+      *column = columns.next().unwrap_or_else(|| {
+        let mut identity = [0.; 4];
+        identity[i] = 1.;
+        identity
+      });
+    }
+
+    m
+  }
+}
+
+/// Output of a vertex shader.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct VertexOutput {
+  /// Clip-space position (`gl_Position`).
+  pub position: [f32; 4],
+
+  /// Values to interpolate and pass to the fragment shader.
+  pub varyings: Vec<f32>,
+}
+
+impl VertexOutput {
+  pub fn new(position: [f32; 4], varyings: impl Into<Vec<f32>>) -> Self {
+    Self {
+      position,
+      varyings: varyings.into(),
+    }
+  }
+}
+
+/// Input of a fragment shader.
+#[derive(Debug)]
+pub struct FragmentInput<'a> {
+  pub(crate) frag_coord: [f32; 4],
+  pub(crate) front_facing: bool,
+  pub(crate) varyings: &'a [f32],
+}
+
+impl<'a> FragmentInput<'a> {
+  /// Window-space coordinates of the fragment (`gl_FragCoord`).
+  ///
+  /// `x` and `y` are at the center of the pixel, `z` is the depth of the fragment and `w` is the interpolated inverse
+  /// of the clip-space `w`.
+  pub fn frag_coord(&self) -> [f32; 4] {
+    self.frag_coord
+  }
+
+  /// Whether the fragment belongs to a front-facing primitive (`gl_FrontFacing`).
+  ///
+  /// Points and lines are always front-facing.
+  pub fn front_facing(&self) -> bool {
+    self.front_facing
+  }
+
+  /// Perspective-correct interpolated varyings, as output by the vertex shader.
+  pub fn varyings(&self) -> &[f32] {
+    self.varyings
+  }
+}
+
+/// Uniforms available to shaders.
+///
+/// Uniforms that were never set are read as zero, as in GLSL.
+#[derive(Debug)]
+pub struct UniformValues<'a> {
+  pub(crate) values: &'a HashMap<String, UniformValue>,
+  pub(crate) textures: &'a HashMap<usize, TextureData>,
+  pub(crate) uni_buffers: &'a HashMap<usize, Vec<u128>>,
+  pub(crate) buffers: &'a HashMap<usize, BufferData>,
+}
+
+impl<'a> UniformValues<'a> {
+  /// Raw value of a uniform, if it was set.
+  pub fn get(&self, name: &str) -> Option<&'a UniformValue> {
+    self.values.get(name)
+  }
+
+  fn floats<const N: usize>(&self, name: &str) -> [f32; N] {
+    let mut value = [0.; N];
+
+    if let Some(UniformValue::Floating(floats)) = self.get(name) {
+      for (v, f) in value.iter_mut().zip(floats) {
+        *v = *f;
+      }
+    }
+
+    value
+  }
+
+  pub fn float(&self, name: &str) -> f32 {
+    self.floats::<1>(name)[0]
+  }
+
+  pub fn vec2(&self, name: &str) -> [f32; 2] {
+    self.floats(name)
+  }
+
+  pub fn vec3(&self, name: &str) -> [f32; 3] {
+    self.floats(name)
+  }
+
+  pub fn vec4(&self, name: &str) -> [f32; 4] {
+    self.floats(name)
+  }
+
+  /// Column-major 4×4 matrix.
+  pub fn mat44(&self, name: &str) -> [[f32; 4]; 4] {
+    let m = self.floats::<16>(name);
+    [
+      [m[0], m[1], m[2], m[3]],
+      [m[4], m[5], m[6], m[7]],
+      [m[8], m[9], m[10], m[11]],
+      [m[12], m[13], m[14], m[15]],
+    ]
+  }
+
+  pub fn int(&self, name: &str) -> i32 {
+    match self.get(name) {
+      Some(UniformValue::Integral(ints)) => ints.first().copied().unwrap_or_default(),
+      _ => 0,
+    }
+  }
+
+  pub fn uint(&self, name: &str) -> u32 {
+    match self.get(name) {
+      Some(UniformValue::Unsigned(uints)) => uints.first().copied().unwrap_or_default(),
+      _ => 0,
+    }
+  }
+
+  pub fn boolean(&self, name: &str) -> bool {
+    match self.get(name) {
+      Some(UniformValue::Boolean(bools)) => bools.first().copied().unwrap_or_default(),
+      _ => false,
+    }
+  }
+
+  /// Sampler of a texture uniform.
+  pub fn texture(&self, name: &str) -> Option<Sampler<'a>> {
+    match self.get(name) {
+      Some(UniformValue::Texture(handle)) => self.textures.get(handle).map(Sampler::new),
+      _ => None,
+    }
+  }
+
+  /// Raw content of a uniform buffer uniform.
+  ///
+  /// The content is laid out according to the memory layout scheme the uniform buffer was created with. Buffers used
+  /// as uniform buffers are returned whole.
+  pub fn uni_buffer(&self, name: &str) -> Option<&'a [u8]> {
+    match self.get(name) {
+      Some(UniformValue::UniBuffer(handle)) => self
+        .uni_buffers
+        .get(handle)
+        .map(|storage| unsafe {
+          std::slice::from_raw_parts(
+            storage.as_ptr() as *const u8,
+            std::mem::size_of_val(storage.as_slice()),
+          )
+        })
+        .or_else(|| self.buffers.get(handle).map(BufferData::as_bytes)),
+      _ => None,
+    }
+  }
+}
@@ -0,0 +1,238 @@
+//! Texture storage and sampling.
+
+use crate::pixel;
+use luminance::{
+  dim::{Dim, Dimensionable},
+  pixel::PixelFormat,
+  texture::{MagFilter, TextureSampling, Wrap},
+};
+use std::fmt;
+
+/// CPU-side texture storage.
+///
+/// Only the base level is stored; mipmaps are never generated.
+pub(crate) struct TextureData {
+  pub(crate) dim: Dim,
+  pub(crate) size: [u32; 3],
+  pub(crate) format: PixelFormat,
+  pub(crate) sampling: TextureSampling,
+  pub(crate) texels: Vec<u8>,
+}
+
+impl fmt::Debug for TextureData {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("TextureData")
+      .field("dim", &self.dim)
+      .field("size", &self.size)
+      .field("format", &self.format)
+      .finish()
+  }
+}
+
+impl TextureData {
+  pub(crate) fn new(
+    dim: Dim,
+    size: [u32; 3],
+    format: PixelFormat,
+    sampling: TextureSampling,
+  ) -> Self {
+    let texels = vec![0; Self::bytes_len(size, format)];
+
+    Self {
+      dim,
+      size,
+      format,
+      sampling,
+      texels,
+    }
+  }
+
+  pub(crate) fn bytes_len([width, height, depth]: [u32; 3], format: PixelFormat) -> usize {
+    width as usize * height as usize * depth as usize * pixel::texel_len(format)
+  }
+
+  fn texel_offset(&self, [x, y, z]: [u32; 3]) -> usize {
+    let [width, height, _] = self.size;
+    (x as usize + (y as usize + z as usize * height as usize) * width as usize)
+      * pixel::texel_len(self.format)
+  }
+
+  /// Check that a region fits in the texture.
+  pub(crate) fn contains(&self, [x, y, z]: [u32; 3], [width, height, depth]: [u32; 3]) -> bool {
+    let [tex_width, tex_height, tex_depth] = self.size;
+    x + width <= tex_width && y + height <= tex_height && z + depth <= tex_depth
+  }
+
+  /// Copy `texels` in the region defined by `offset` and `size`, row by row.
+  ///
+  /// The region must fit in the texture.
+  pub(crate) fn write(
+    &mut self,
+    offset: [u32; 3],
+    [width, height, depth]: [u32; 3],
+    texels: &[u8],
+  ) {
+    let row_len = width as usize * pixel::texel_len(self.format);
+
+    for k in 0..depth {
+      for j in 0..height {
+        let src = (j + k * height) as usize * row_len;
+        let dst = self.texel_offset([offset[0], offset[1] + j, offset[2] + k]);
+        self.texels[dst..dst + row_len].copy_from_slice(&texels[src..src + row_len]);
+      }
+    }
+  }
+
+  /// Decode the texel at the given position.
+  ///
+  /// sRGB texels are not converted to linear.
+  pub(crate) fn load(&self, position: [u32; 3]) -> [f32; 4] {
+    let offset = self.texel_offset(position);
+    let len = pixel::texel_len(self.format);
+    pixel::decode(self.format, &self.texels[offset..offset + len])
+  }
+
+  /// Encode a color at the given position.
+  pub(crate) fn store(&mut self, position: [u32; 3], color: [f32; 4]) {
+    let offset = self.texel_offset(position);
+    let len = pixel::texel_len(self.format);
+    pixel::encode(self.format, color, &mut self.texels[offset..offset + len]);
+  }
+}
+
+/// Flatten a size into `[width, height, depth]`.
+pub(crate) fn flat_size<D>(size: &D::Size) -> [u32; 3]
+where
+  D: Dimensionable,
+{
+  [D::width(size), D::height(size), D::depth(size)]
+}
+
+/// Flatten an offset into `[x, y, z]`, ignoring the axes the dimension doesn’t have.
+pub(crate) fn flat_offset<D>(offset: &D::Offset) -> [u32; 3]
+where
+  D: Dimensionable,
+{
+  match D::dim() {
+    Dim::Dim1 => [D::x_offset(offset), 0, 0],
+    Dim::Dim2 | Dim::Dim1Array => [D::x_offset(offset), D::y_offset(offset), 0],
+    Dim::Dim3 | Dim::Cubemap | Dim::Dim2Array => [
+      D::x_offset(offset),
+      D::y_offset(offset),
+      D::z_offset(offset),
+    ],
+  }
+}
+
+/// Read-only access to a texture from within shaders.
+///
+/// Samplers are obtained from [`UniformValues::texture`](crate::shader::UniformValues::texture).
+#[derive(Clone, Copy, Debug)]
+pub struct Sampler<'a> {
+  data: &'a TextureData,
+}
+
+impl<'a> Sampler<'a> {
+  pub(crate) fn new(data: &'a TextureData) -> Self {
+    Self { data }
+  }
+
+  /// Size of the texture, as `[width, height, depth]`.
+  ///
+  /// Missing axes are set to `1`; cubemaps have a depth of `6` (one layer per face) and arrays store their layers
+  /// along the last axis.
+  pub fn size(&self) -> [u32; 3] {
+    self.data.size
+  }
+
+  /// Decode a texel, converting sRGB texels to linear.
+  fn load(&self, position: [u32; 3]) -> [f32; 4] {
+    let color = self.data.load(position);
+
+    if pixel::is_srgb(self.data.format) {
+      pixel::srgb_to_linear(color)
+    } else {
+      color
+    }
+  }
+
+  /// Fetch a texel, without any filtering.
+  ///
+  /// Out of bounds fetches return `[0., 0., 0., 0.]`.
+  pub fn fetch(&self, [x, y, z]: [u32; 3]) -> [f32; 4] {
+    let [width, height, depth] = self.data.size;
+
+    if x >= width || y >= height || z >= depth {
+      [0.; 4]
+    } else {
+      self.load([x, y, z])
+    }
+  }
+
+  /// Sample the first layer of the texture with normalized coordinates.
+  ///
+  /// Because there is no mipmapping, the magnification filter is always used, as well as the wrapping modes of the
+  /// texture on the S and T axes.
+  pub fn sample(&self, [u, v]: [f32; 2]) -> [f32; 4] {
+    let [width, height, _] = self.data.size;
+    let sampling = &self.data.sampling;
+
+    if width == 0 || height == 0 {
+      return [0.; 4];
+    }
+
+    match sampling.mag_filter {
+      MagFilter::Nearest => {
+        let x = wrap(sampling.wrap_s, (u * width as f32).floor() as i64, width);
+        let y = wrap(sampling.wrap_t, (v * height as f32).floor() as i64, height);
+        self.load([x, y, 0])
+      }
+
+      MagFilter::Linear => {
+        let u = u * width as f32 - 0.5;
+        let v = v * height as f32 - 0.5;
+        let (x0, y0) = (u.floor(), v.floor());
+        let (a, b) = (u - x0, v - y0);
+
+        let x = [x0 as i64, x0 as i64 + 1].map(|x| wrap(sampling.wrap_s, x, width));
+        let y = [y0 as i64, y0 as i64 + 1].map(|y| wrap(sampling.wrap_t, y, height));
+
+        let t00 = self.load([x[0], y[0], 0]);
+        let t10 = self.load([x[1], y[0], 0]);
+        let t01 = self.load([x[0], y[1], 0]);
+        let t11 = self.load([x[1], y[1], 0]);
+
+        let mut color = [0.; 4];
+        for i in 0..4 {
+          color[i] = (1. - a) * (1. - b) * t00[i]
+            + a * (1. - b) * t10[i]
+            + (1. - a) * b * t01[i]
+            + a * b * t11[i];
+        }
+
+        color
+      }
+    }
+  }
+}
+
+/// Apply a wrapping mode to a texel coordinate.
+fn wrap(mode: Wrap, i: i64, len: u32) -> u32 {
+  let len = len as i64;
+
+  let i = match mode {
+    Wrap::ClampToEdge => i.clamp(0, len - 1),
+    Wrap::Repeat => i.rem_euclid(len),
+    Wrap::MirroredRepeat => {
+      let m = i.rem_euclid(2 * len);
+
+      if m >= len {
+        2 * len - 1 - m
+      } else {
+        m
+      }
+    }
+  };
+
+  i as u32
+}
@@ -0,0 +1,512 @@
+//! Vertex storage and attribute fetching.
+
+use crate::{backend::SoftError, buffer::BufferData};
+use luminance::{
+  vertex::{self, Normalized, Vertex, VertexAttribDesc, VertexAttribType, VertexBufferDesc},
+  vertex_storage::{AlignedBytes, AsVertexStorage, Deinterleaved, VertexStorage},
+};
+use std::{borrow::Cow, collections::HashMap, mem, ops::Range};
+
+/// Raw vertex data, as it would be stored in GPU buffers.
+#[derive(Clone, Debug)]
+pub(crate) enum StorageData {
+  None,
+  Interleaved { bytes: Vec<u8>, stride: usize },
+  Deinterleaved { components: Vec<AlignedBytes> },
+  // interleaved vertices living in a buffer
+  Shared { buffer: usize, stride: usize },
+}
+
+/// Copy of a vertex storage, along with the information required to fetch its attributes.
+#[derive(Clone, Debug)]
+pub(crate) struct AttributeStorage {
+  descs: Vec<VertexBufferDesc>,
+  offsets: Vec<usize>,
+  data: StorageData,
+  len: usize,
+}
+
+impl AttributeStorage {
+  pub(crate) fn new<V>(storage: &mut impl AsVertexStorage<V>) -> Option<Self>
+  where
+    V: Vertex,
+  {
+    let descs = V::vertex_desc();
+    let offsets = aligned_offsets(&descs);
+    let (data, len) = Self::copy_data(&descs, storage)?;
+
+    Some(Self {
+      descs,
+      offsets,
+      data,
+      len,
+    })
+  }
+
+  /// Copy the data out of a vertex storage, returning the number of vertices.
+  ///
+  /// Returns `None` if deinterleaved components don’t all have the same length.
+  fn copy_data<V>(
+    descs: &[VertexBufferDesc],
+    storage: &mut impl AsVertexStorage<V>,
+  ) -> Option<(StorageData, usize)>
+  where
+    V: Vertex,
+  {
+    match storage.as_vertex_storage() {
+      VertexStorage::NoStorage => Some((StorageData::None, 0)),
+
+      VertexStorage::Interleaved(storage) => {
+        let len = storage.vertices().len();
+        let bytes = storage.vertices_as_bytes().to_owned();
+        let stride = bytes.len().checked_div(len).unwrap_or(0);
+
+        Some((StorageData::Interleaved { bytes, stride }, len))
+      }
+
+      VertexStorage::Deinterleaved(storage) => {
+        let components = storage.components_list().clone();
+        let mut len = None;
+
+        for (component, desc) in components.iter().zip(descs) {
+          let component_len = component.len() / component_weight(&desc.attrib_desc);
+
+          match len {
+            None => len = Some(component_len),
+            Some(len) if len != component_len => return None,
+            _ => (),
+          }
+        }
+
+        Some((StorageData::Deinterleaved { components }, len.unwrap_or(0)))
+      }
+
+      VertexStorage::Shared(storage) => Some((
+        StorageData::Shared {
+          buffer: storage.buffer_handle(),
+          stride: mem::size_of::<V>(),
+        },
+        storage.len(),
+      )),
+    }
+  }
+
+  /// Number of vertices in the storage.
+  pub(crate) fn len(&self) -> usize {
+    self.len
+  }
+
+  pub(crate) fn is_empty_storage(&self) -> bool {
+    matches!(self.data, StorageData::None)
+  }
+
+  /// Replace the vertices in `range` with the ones from a storage.
+  ///
+  /// The new storage must have the same shape. If its length changed, the whole storage is copied again; otherwise,
+  /// only `range` is.
+  pub(crate) fn update<V>(
+    &mut self,
+    storage: &mut impl AsVertexStorage<V>,
+    range: Range<usize>,
+  ) -> Result<(), ()>
+  where
+    V: Vertex,
+  {
+    let storage_len = match storage.as_vertex_storage() {
+      VertexStorage::Interleaved(storage) => storage.vertices().len(),
+      VertexStorage::Deinterleaved(storage) => storage.len(),
+      VertexStorage::NoStorage | VertexStorage::Shared(_) => return Err(()),
+    };
+
+    if storage_len != self.len {
+      let (data, len) = Self::copy_data(&self.descs, storage).ok_or(())?;
+
+      return match (&self.data, &data) {
+        (StorageData::Interleaved { .. }, StorageData::Interleaved { .. })
+        | (StorageData::Deinterleaved { .. }, StorageData::Deinterleaved { .. }) => {
+          self.data = data;
+          self.len = len;
+          Ok(())
+        }
+
+        _ => Err(()),
+      };
+    }
+
+    match (&mut self.data, storage.as_vertex_storage()) {
+      (StorageData::Interleaved { bytes, stride }, VertexStorage::Interleaved(storage)) => {
+        let bytes_range = range.start * *stride..range.end * *stride;
+        bytes[bytes_range.clone()].copy_from_slice(&storage.vertices_as_bytes()[bytes_range]);
+        Ok(())
+      }
+
+      (StorageData::Deinterleaved { components }, VertexStorage::Deinterleaved(storage)) => {
+        let new_components = storage.components_list();
+
+        for ((component, new_component), desc) in
+          components.iter_mut().zip(new_components).zip(&self.descs)
+        {
+          let weight = component_weight(&desc.attrib_desc);
+          let bytes_range = range.start * weight..range.end * weight;
+          component[bytes_range.clone()].copy_from_slice(&new_component[bytes_range]);
+        }
+
+        Ok(())
+      }
+
+      _ => Err(()),
+    }
+  }
+
+  /// Replace a single attribute of deinterleaved vertices with the one at index `rank` in a storage.
+  ///
+  /// The number of vertices must not have changed.
+  pub(crate) fn update_attribute<V>(
+    &mut self,
+    storage: &Deinterleaved<V>,
+    rank: usize,
+  ) -> Result<(), ()>
+  where
+    V: Vertex,
+  {
+    let len = self.len * component_weight(&self.descs[rank].attrib_desc);
+    let new_component = &storage.components_list()[rank];
+
+    match &mut self.data {
+      StorageData::Deinterleaved { components } if new_component.len() == len => {
+        components[rank].copy_from_slice(new_component);
+        Ok(())
+      }
+
+      _ => Err(()),
+    }
+  }
+
+  /// Copy the vertices of a shared storage out of its buffer, so that they can be fetched.
+  ///
+  /// Other storages are returned as-is.
+  pub(crate) fn resolve<'a>(
+    &'a self,
+    buffers: &HashMap<usize, BufferData>,
+  ) -> Result<Cow<'a, Self>, SoftError> {
+    match self.data {
+      StorageData::Shared { buffer, stride } => {
+        let bytes = buffers
+          .get(&buffer)
+          .ok_or(SoftError::UnknownHandle { handle: buffer })?
+          .as_bytes()
+          .to_owned();
+
+        Ok(Cow::Owned(Self {
+          descs: self.descs.clone(),
+          offsets: self.offsets.clone(),
+          data: StorageData::Interleaved { bytes, stride },
+          len: self.len,
+        }))
+      }
+
+      _ => Ok(Cow::Borrowed(self)),
+    }
+  }
+
+  /// Fetch all the attributes of a vertex, appending them to `attributes`.
+  pub(crate) fn fetch(&self, index: usize, attributes: &mut Vec<(&'static str, [f32; 4])>) {
+    self.fetch_with(|_| index, attributes);
+  }
+
+  /// Fetch all the attributes of an instance, appending them to `attributes`.
+  ///
+  /// Each attribute is read at `base_instance + instance_id / divisor`.
+  pub(crate) fn fetch_instance(
+    &self,
+    base_instance: usize,
+    instance_id: usize,
+    attributes: &mut Vec<(&'static str, [f32; 4])>,
+  ) {
+    self.fetch_with(
+      |desc| base_instance + instance_id / desc.divisor.max(1),
+      attributes,
+    );
+  }
+
+  /// Number of values read to render `instance_count` instances.
+  pub(crate) fn instance_data_len(&self, instance_count: usize) -> usize {
+    vertex::instance_data_len(&self.descs, instance_count)
+  }
+
+  /// Fetch all the attributes, at the index computed for each of them, appending them to `attributes`.
+  ///
+  /// Attributes spanning several locations, such as matrices, are appended once per location.
+  fn fetch_with(
+    &self,
+    index: impl Fn(&VertexBufferDesc) -> usize,
+    attributes: &mut Vec<(&'static str, [f32; 4])>,
+  ) {
+    for (k, desc) in self.descs.iter().enumerate() {
+      let index = index(desc);
+      let weight = component_weight(&desc.attrib_desc);
+
+      let bytes = match &self.data {
+        // shared storages are resolved before fetching
+        StorageData::None | StorageData::Shared { .. } => return,
+
+        StorageData::Interleaved { bytes, stride } => {
+          let start = index * stride + self.offsets[k];
+          &bytes[start..start + weight]
+        }
+
+        StorageData::Deinterleaved { components } => {
+          let start = index * weight;
+          &components[k][start..start + weight]
+        }
+      };
+
+      // attributes spanning several locations are fetched as one value per location
+      let location_weight = weight / desc.attrib_desc.locations;
+      for location in bytes.chunks_exact(location_weight) {
+        attributes.push((desc.name, decode_attrib(&desc.attrib_desc, location)));
+      }
+    }
+  }
+}
+
+/// Layout of the vertices captured with transform feedback.
+#[derive(Clone, Debug)]
+pub(crate) struct FeedbackLayout {
+  descs: Vec<VertexBufferDesc>,
+  offsets: Vec<usize>,
+  stride: usize,
+}
+
+impl FeedbackLayout {
+  pub(crate) fn new<F>() -> Self
+  where
+    F: Vertex,
+  {
+    let descs = F::vertex_desc();
+    let offsets = aligned_offsets(&descs);
+
+    Self {
+      descs,
+      offsets,
+      stride: mem::size_of::<F>(),
+    }
+  }
+
+  /// Size in bytes of a captured vertex.
+  pub(crate) fn stride(&self) -> usize {
+    self.stride
+  }
+
+  /// Encode the varyings of a vertex into `bytes`, filling the fields of the vertex in order.
+  ///
+  /// Missing varyings are read as `0`.
+  pub(crate) fn encode(&self, varyings: &[f32], bytes: &mut [u8]) {
+    let mut varyings = varyings.iter().copied();
+
+    for (desc, &offset) in self.descs.iter().zip(&self.offsets) {
+      let desc = &desc.attrib_desc;
+      let bytes = &mut bytes[offset..offset + component_weight(desc)];
+
+      for unit in bytes.chunks_exact_mut(desc.unit_size) {
+        encode_unit(desc.ty, varyings.next().unwrap_or_default(), unit);
+      }
+    }
+  }
+}
+
+/// Compute offsets for all the vertex components according to the alignments provided.
+fn aligned_offsets(descs: &[VertexBufferDesc]) -> Vec<usize> {
+  let mut offsets = Vec::with_capacity(descs.len());
+  let mut off = 0;
+
+  for desc in descs {
+    let desc = &desc.attrib_desc;
+    let a = desc.align - 1;
+    off = (off + a) & !a;
+    offsets.push(off);
+    off += component_weight(desc);
+  }
+
+  offsets
+}
+
+/// Weight in bytes of a vertex component.
+fn component_weight(desc: &VertexAttribDesc) -> usize {
+  desc.size()
+}
+
+/// Decode a vertex attribute into a `vec4`.
+fn decode_attrib(desc: &VertexAttribDesc, bytes: &[u8]) -> [f32; 4] {
+  if desc.ty.is_packed() {
+    return decode_packed(desc.ty, bytes);
+  }
+
+  let mut value = [0., 0., 0., 1.];
+
+  for (v, unit) in value.iter_mut().zip(bytes.chunks_exact(desc.unit_size)) {
+    *v = match (desc.ty, unit.len()) {
+      (VertexAttribType::Floating, 2) => decode_half(u16::from_ne_bytes([unit[0], unit[1]])),
+      (VertexAttribType::Floating, 4) => f32::from_ne_bytes([unit[0], unit[1], unit[2], unit[3]]),
+      (VertexAttribType::Floating, 8) => f64::from_ne_bytes([
+        unit[0], unit[1], unit[2], unit[3], unit[4], unit[5], unit[6], unit[7],
+      ]) as f32,
+      #[cfg(feature = "shader-f64")]
+      (VertexAttribType::Floating64, 8) => f64::from_ne_bytes([
+        unit[0], unit[1], unit[2], unit[3], unit[4], unit[5], unit[6], unit[7],
+      ]) as f32,
+
+      (VertexAttribType::Integral(normalized), _) => {
+        let (x, max) = match unit.len() {
+          1 => (unit[0] as i8 as f32, i8::MAX as f32),
+          2 => (
+            i16::from_ne_bytes([unit[0], unit[1]]) as f32,
+            i16::MAX as f32,
+          ),
+          _ => (
+            i32::from_ne_bytes([unit[0], unit[1], unit[2], unit[3]]) as f32,
+            i32::MAX as f32,
+          ),
+        };
+
+        match normalized {
+          Normalized::Yes => (x / max).max(-1.),
+          Normalized::No => x,
+        }
+      }
+
+      (VertexAttribType::Unsigned(normalized), _) => {
+        let (x, max) = match unit.len() {
+          1 => (unit[0] as f32, u8::MAX as f32),
+          2 => (
+            u16::from_ne_bytes([unit[0], unit[1]]) as f32,
+            u16::MAX as f32,
+          ),
+          _ => (
+            u32::from_ne_bytes([unit[0], unit[1], unit[2], unit[3]]) as f32,
+            u32::MAX as f32,
+          ),
+        };
+
+        match normalized {
+          Normalized::Yes => x / max,
+          Normalized::No => x,
+        }
+      }
+
+      (VertexAttribType::Boolean, _) => (unit[0] != 0) as u8 as f32,
+
+      _ => 0.,
+    };
+  }
+
+  value
+}
+
+/// Decode a half-precision floating-point value.
+fn decode_half(bits: u16) -> f32 {
+  let sign = if bits & 0x8000 == 0 { 1. } else { -1. };
+  let exponent = (bits >> 10 & 0x1F) as i32;
+  let mantissa = (bits & 0x3FF) as f32;
+
+  let x = match exponent {
+    // subnormals
+    0 => mantissa * 2f32.powi(-24),
+    0x1F if mantissa == 0. => f32::INFINITY,
+    0x1F => f32::NAN,
+    _ => (1. + mantissa / 1024.) * 2f32.powi(exponent - 15),
+  };
+
+  sign * x
+}
+
+/// Decode a vertex attribute packed in a 32-bit value (2_10_10_10 reversed layouts) into a `vec4`.
+fn decode_packed(ty: VertexAttribType, bytes: &[u8]) -> [f32; 4] {
+  let packed = u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+  let mut value = [0.; 4];
+
+  for (i, v) in value.iter_mut().enumerate() {
+    let (shift, bits) = (10 * i as u32, if i < 3 { 10 } else { 2 });
+
+    *v = match ty {
+      VertexAttribType::Integral2_10_10_10Rev(normalized) => {
+        // sign-extend the field
+        let x = ((packed << (32 - shift - bits)) as i32 >> (32 - bits)) as f32;
+
+        match normalized {
+          Normalized::Yes => (x / ((1 << (bits - 1)) - 1) as f32).max(-1.),
+          Normalized::No => x,
+        }
+      }
+
+      VertexAttribType::Unsigned2_10_10_10Rev(normalized) => {
+        let x = (packed >> shift & ((1 << bits) - 1)) as f32;
+
+        match normalized {
+          Normalized::Yes => x / ((1 << bits) - 1) as f32,
+          Normalized::No => x,
+        }
+      }
+
+      _ => 0.,
+    };
+  }
+
+  value
+}
+
+/// Encode a single unit of a vertex attribute; the reverse of [`decode_attrib`].
+fn encode_unit(ty: VertexAttribType, x: f32, unit: &mut [u8]) {
+  match (ty, unit.len()) {
+    (VertexAttribType::Floating, 4) => unit.copy_from_slice(&x.to_ne_bytes()),
+    (VertexAttribType::Floating, 8) => unit.copy_from_slice(&(x as f64).to_ne_bytes()),
+    #[cfg(feature = "shader-f64")]
+    (VertexAttribType::Floating64, 8) => unit.copy_from_slice(&(x as f64).to_ne_bytes()),
+
+    (VertexAttribType::Integral(normalized), len) => {
+      let x = match normalized {
+        Normalized::Yes => x.clamp(-1., 1.),
+        Normalized::No => x,
+      };
+
+      match len {
+        1 => unit[0] = (x * norm_scale(normalized, i8::MAX as f32)).round() as i8 as u8,
+        2 => unit.copy_from_slice(
+          &((x * norm_scale(normalized, i16::MAX as f32)).round() as i16).to_ne_bytes(),
+        ),
+        _ => unit.copy_from_slice(
+          &((x * norm_scale(normalized, i32::MAX as f32)).round() as i32).to_ne_bytes(),
+        ),
+      }
+    }
+
+    (VertexAttribType::Unsigned(normalized), len) => {
+      let x = match normalized {
+        Normalized::Yes => x.clamp(0., 1.),
+        Normalized::No => x,
+      };
+
+      match len {
+        1 => unit[0] = (x * norm_scale(normalized, u8::MAX as f32)).round() as u8,
+        2 => unit.copy_from_slice(
+          &((x * norm_scale(normalized, u16::MAX as f32)).round() as u16).to_ne_bytes(),
+        ),
+        _ => unit.copy_from_slice(
+          &((x * norm_scale(normalized, u32::MAX as f32)).round() as u32).to_ne_bytes(),
+        ),
+      }
+    }
+
+    (VertexAttribType::Boolean, _) => unit[0] = (x != 0.) as u8,
+
+    _ => unit.fill(0),
+  }
+}
+
+/// Scale to apply to a value before converting it to an integer.
+fn norm_scale(normalized: Normalized, max: f32) -> f32 {
+  match normalized {
+    Normalized::Yes => max,
+    Normalized::No => 1.,
+  }
+}
@@ -0,0 +1,785 @@
+#![allow(incomplete_features)]
+#![feature(adt_const_params)]
+
+use luminance::{
+  backend::PipelineError,
+  blending::{Blending, BlendingMode, Equation, Factor},
+  context::Context,
+  depth_stencil::{Comparison, DepthTest},
+  dim::{Dim2, Size2},
+  namespace,
+  pipeline::PipelineState,
+  pixel::{Depth32F, NormRGBA8UI, NormUnsigned, RGBA32F},
+  primitive::{
+    self, Point, Primitive, Triangle, TriangleAdjacency, TriangleStrip, TriangleStripAdjacency,
+  },
+  render_state::RenderState,
+  scissor::Scissor,
+  shader::{Program, ProgramBuilder, Uni},
+  texture::{InUseTexture, MagFilter, MinFilter, Mipmaps, TextureSampling},
+  vertex::{Int2_10_10_10Rev, UInt2_10_10_10Rev},
+  vertex_entity::{Usage, VertexEntity, VertexEntityBuilder, View},
+  vertex_storage::{Interleaved, Interleaving, Shared, Sharing},
+  RenderSlots, Uniforms, Vertex,
+};
+use luminance_soft::{shader::VertexOutput, Soft};
+
+namespace! {
+  VertexNamespace = { "pos", "offset", "shade", "model": 4, "color", "nor" }
+}
+
+namespace! {
+  RenderSlotNamespace = { "frag" }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Vertex)]
+#[vertex(namespace = "VertexNamespace")]
+struct Vertex {
+  pos: [f32; 2],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Vertex)]
+#[vertex(namespace = "VertexNamespace")]
+struct Instance {
+  offset: f32,
+  #[vertex(divisor = 2)]
+  shade: f32,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Vertex)]
+#[vertex(namespace = "VertexNamespace")]
+struct Transform {
+  model: [[f32; 4]; 4],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Vertex)]
+#[vertex(namespace = "VertexNamespace")]
+struct Compressed {
+  pos: [half::f16; 2],
+  #[vertex(normalized = true)]
+  color: UInt2_10_10_10Rev,
+  #[vertex(normalized = true)]
+  nor: Int2_10_10_10Rev,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, RenderSlots)]
+#[slot(namespace = "RenderSlotNamespace")]
+struct Slots {
+  frag: RGBA32F,
+}
+
+#[derive(Debug, Uniforms)]
+struct ShaderUniforms {
+  color: Uni<[f32; 4]>,
+  z: Uni<f32>,
+}
+
+#[derive(Uniforms)]
+struct TextureUniforms {
+  tex: Uni<InUseTexture<Dim2, NormUnsigned>>,
+}
+
+/// Quad covering the whole viewport, made of two triangles sharing a diagonal.
+fn new_quad(ctx: &mut Context<Soft>) -> VertexEntity<Vertex, Triangle, Interleaving> {
+  let vertices = [
+    Vertex { pos: [-1., -1.] },
+    Vertex { pos: [1., -1.] },
+    Vertex { pos: [1., 1.] },
+    Vertex { pos: [-1., 1.] },
+  ];
+
+  ctx
+    .new_vertex_entity(
+      VertexEntityBuilder::new()
+        .add_vertices(Interleaved::new().set_vertices(vertices))
+        .add_indices([0, 1, 2, 0, 2, 3]),
+    )
+    .unwrap()
+}
+
+/// Register shaders outputting the `color` uniform at the depth given by the `z` uniform.
+fn new_color_program(
+  ctx: &mut Context<Soft>,
+) -> Program<Vertex, (), Triangle, Slots, ShaderUniforms> {
+  ctx.backend().add_vertex_shader("vs", |input, unis| {
+    let [x, y, ..] = input.attrib("pos");
+    VertexOutput::new([x, y, unis.float("z"), 1.], [])
+  });
+  ctx
+    .backend()
+    .add_fragment_shader("fs", |_, unis| Some(vec![unis.vec4("color")]));
+
+  ctx
+    .new_program(
+      ProgramBuilder::new()
+        .add_vertex_stage("vs")
+        .no_primitive_stage()
+        .add_shading_stage("fs"),
+    )
+    .unwrap()
+}
+
+fn assert_color_eq(a: [f32; 4], b: [f32; 4]) {
+  assert!(
+    a.iter().zip(b).all(|(a, b)| (a - b).abs() <= 1. / 255.),
+    "{:?} != {:?}",
+    a,
+    b
+  );
+}
+
+#[test]
+fn shared_edges_are_rasterized_once() {
+  let mut ctx = Context::new(Soft::new).unwrap();
+  let quad = new_quad(&mut ctx);
+  let program = new_color_program(&mut ctx);
+  let fb = ctx
+    .new_framebuffer::<Dim2, Slots, ()>(Size2::new(8, 8), Mipmaps::No, &TextureSampling::default())
+    .unwrap();
+
+  let additive = BlendingMode::Combined(Blending {
+    equation: Equation::Additive,
+    src: Factor::One,
+    dst: Factor::One,
+  });
+
+  ctx
+    .with_framebuffer(
+      &fb,
+      &PipelineState::default().set_clear_color([0., 0., 0., 0.]),
+      |mut with_framebuffer| {
+        with_framebuffer.with_program(&program, |mut with_program| {
+          with_program.update(|mut program, unis| program.set(&unis.color, &[0.25; 4]))?;
+          with_program.with_render_state(
+            &RenderState::default().set_blending(additive),
+            |mut with_render_state| with_render_state.render_vertex_entity(quad.view(..)),
+          )
+        })
+      },
+    )
+    .unwrap();
+
+  let texels = ctx.read_texture(&fb.layers().frag).unwrap();
+  assert_eq!(texels.len(), 8 * 8 * 4);
+  assert!(texels.iter().all(|&t| t == 0.25));
+}
+
+#[test]
+fn depth_test() {
+  let mut ctx = Context::new(Soft::new).unwrap();
+  let quad = new_quad(&mut ctx);
+  let program = new_color_program(&mut ctx);
+  let fb = ctx
+    .new_framebuffer::<Dim2, Slots, Depth32F>(
+      Size2::new(4, 4),
+      Mipmaps::No,
+      &TextureSampling::default(),
+    )
+    .unwrap();
+
+  let red = [1., 0., 0., 1.];
+  let blue = [0., 0., 1., 1.];
+
+  ctx
+    .with_framebuffer(
+      &fb,
+      &PipelineState::default()
+        .set_clear_color([0., 0., 0., 1.])
+        .set_clear_depth(1.),
+      |mut with_framebuffer| {
+        with_framebuffer.with_program(&program, |mut with_program| {
+          let render_state = RenderState::default().set_depth_test(DepthTest::On(Comparison::Less));
+
+          // near quad first, then far quad, which must be rejected
+          for (color, z) in [(red, -0.5), (blue, 0.5)] {
+            with_program.update(|mut program, unis| {
+              program.set(&unis.color, &color)?;
+              program.set(&unis.z, &z)
+            })?;
+            with_program.with_render_state(&render_state, |mut with_render_state| {
+              with_render_state.render_vertex_entity(quad.view(..))
+            })?;
+          }
+
+          Ok::<_, PipelineError>(())
+        })
+      },
+    )
+    .unwrap();
+
+  let texels = ctx.read_texture(&fb.layers().frag).unwrap();
+  for texel in texels.chunks(4) {
+    assert_eq!(texel, red);
+  }
+
+  // depth is mapped from [-1, 1] to [0, 1]
+  let depths = ctx.read_texture(fb.depth_layer()).unwrap();
+  assert!(depths.iter().all(|&d| d == 0.25));
+}
+
+#[test]
+fn sample_texture() {
+  let mut ctx = Context::new(Soft::new).unwrap();
+  let quad = new_quad(&mut ctx);
+  let fb = ctx
+    .new_framebuffer::<Dim2, Slots, ()>(Size2::new(4, 4), Mipmaps::No, &TextureSampling::default())
+    .unwrap();
+
+  let sampling = TextureSampling {
+    min_filter: MinFilter::Nearest,
+    mag_filter: MagFilter::Nearest,
+    ..TextureSampling::default()
+  };
+  let texels = [
+    255, 0, 0, 255, // bottom left
+    0, 255, 0, 255, // bottom right
+    0, 0, 255, 255, // top left
+    255, 255, 255, 255, // top right
+  ];
+  let texture = ctx
+    .new_texture::<Dim2, NormRGBA8UI>(Size2::new(2, 2), Mipmaps::No, &sampling, &texels)
+    .unwrap();
+
+  ctx.backend().add_vertex_shader("vs", |input, _| {
+    let [x, y, ..] = input.attrib("pos");
+    VertexOutput::new([x, y, 0., 1.], [(x + 1.) * 0.5, (y + 1.) * 0.5])
+  });
+  ctx.backend().add_fragment_shader("fs", |input, unis| {
+    let uv = input.varyings();
+    let tex = unis.texture("tex")?;
+    Some(vec![tex.sample([uv[0], uv[1]])])
+  });
+
+  let program: Program<Vertex, (), Triangle, Slots, TextureUniforms> = ctx
+    .new_program(
+      ProgramBuilder::new()
+        .add_vertex_stage("vs")
+        .no_primitive_stage()
+        .add_shading_stage("fs"),
+    )
+    .unwrap();
+
+  ctx
+    .with_framebuffer(&fb, &PipelineState::default(), |mut with_framebuffer| {
+      let in_use = with_framebuffer.use_texture(&texture)?;
+
+      with_framebuffer.with_program(&program, |mut with_program| {
+        with_program.update(|mut program, unis| program.set(&unis.tex, &in_use))?;
+        with_program.with_render_state(&RenderState::default(), |mut with_render_state| {
+          with_render_state.render_vertex_entity(quad.view(..))
+        })
+      })
+    })
+    .unwrap();
+
+  let rendered = ctx.read_texture(&fb.layers().frag).unwrap();
+  let expected = |x: usize, y: usize| {
+    let i = (x / 2 + (y / 2) * 2) * 4;
+    [texels[i], texels[i + 1], texels[i + 2], texels[i + 3]].map(|c| c as f32 / 255.)
+  };
+
+  for y in 0..4 {
+    for x in 0..4 {
+      let i = (x + y * 4) * 4;
+      assert_eq!(rendered[i..i + 4], expected(x, y), "texel ({}, {})", x, y);
+    }
+  }
+}
+
+#[test]
+fn back_buffer_scissor() {
+  let mut ctx = Context::new(Soft::new).unwrap();
+  let quad = new_quad(&mut ctx);
+  let program = new_color_program(&mut ctx);
+  let back_buffer = ctx
+    .back_buffer::<Dim2, Slots, ()>(Size2::new(4, 4))
+    .unwrap();
+
+  let green = [0., 1., 0., 1.];
+  let red = [1., 0., 0., 1.];
+  let scissor = Scissor::On {
+    x: 1,
+    y: 1,
+    width: 2,
+    height: 2,
+  };
+
+  ctx
+    .with_framebuffer(
+      &back_buffer,
+      &PipelineState::default().set_clear_color(green),
+      |mut with_framebuffer| {
+        with_framebuffer.with_program(&program, |mut with_program| {
+          with_program.update(|mut program, unis| program.set(&unis.color, &red))?;
+          with_program.with_render_state(
+            &RenderState::default().set_scissor(scissor),
+            |mut with_render_state| with_render_state.render_vertex_entity(quad.view(..)),
+          )
+        })
+      },
+    )
+    .unwrap();
+
+  let pixels = ctx.backend().read_back_buffer();
+  assert_eq!(pixels.len(), 16);
+
+  for y in 0..4 {
+    for x in 0..4 {
+      let inside = (1..3).contains(&x) && (1..3).contains(&y);
+      let expected = if inside { red } else { green };
+      assert_color_eq(pixels[x + y * 4], expected);
+    }
+  }
+}
+
+#[test]
+fn render_shared_buffers() {
+  let mut ctx = Context::new(Soft::new).unwrap();
+  let program = new_color_program(&mut ctx);
+  let back_buffer = ctx
+    .back_buffer::<Dim2, Slots, ()>(Size2::new(4, 4))
+    .unwrap();
+
+  let vertices = ctx
+    .new_buffer(
+      &[
+        Vertex { pos: [-1., -1.] },
+        Vertex { pos: [1., -1.] },
+        Vertex { pos: [1., 1.] },
+        Vertex { pos: [-1., 1.] },
+      ],
+      Usage::Static,
+    )
+    .unwrap();
+  // only the lower-right triangle, twice
+  let indices = ctx
+    .new_buffer(&[0u16, 1, 2, 0, 1, 2], Usage::Dynamic)
+    .unwrap();
+  let quad: VertexEntity<Vertex, Triangle, Sharing, (), (), u16> = ctx
+    .new_vertex_entity(
+      VertexEntityBuilder::new()
+        .add_vertices(Shared::new(&vertices))
+        .add_index_buffer(&indices),
+    )
+    .unwrap();
+
+  let green = [0., 1., 0., 1.];
+  let red = [1., 0., 0., 1.];
+  let render = |ctx: &mut Context<Soft>| {
+    ctx
+      .with_framebuffer(
+        &back_buffer,
+        &PipelineState::default().set_clear_color(green),
+        |mut with_framebuffer| {
+          with_framebuffer.with_program(&program, |mut with_program| {
+            with_program.update(|mut program, unis| program.set(&unis.color, &red))?;
+            with_program.with_render_state(&RenderState::default(), |mut with_render_state| {
+              with_render_state.render_vertex_entity(quad.view(..))
+            })
+          })
+        },
+      )
+      .unwrap();
+
+    ctx.backend().read_back_buffer()
+  };
+
+  let pixels = render(&mut ctx);
+  assert_color_eq(pixels[3], red);
+  assert_color_eq(pixels[12], green);
+
+  // the vertex entity reads its indices from the buffer
+  ctx.update_buffer(&indices, 3, &[0, 2, 3]).unwrap();
+
+  let pixels = render(&mut ctx);
+  for pixel in pixels {
+    assert_color_eq(pixel, red);
+  }
+
+  // the vertex entity keeps the buffers alive
+  drop(vertices);
+  drop(indices);
+
+  let pixels = render(&mut ctx);
+  for pixel in pixels {
+    assert_color_eq(pixel, red);
+  }
+}
+
+#[test]
+fn render_with_feedback() {
+  let mut ctx = Context::new(Soft::new).unwrap();
+  let back_buffer = ctx
+    .back_buffer::<Dim2, Slots, ()>(Size2::new(4, 4))
+    .unwrap();
+
+  // varyings are captured as the fields of the captured vertices, in order
+  ctx.backend().add_vertex_shader("advance", |input, _| {
+    let [x, y, ..] = input.attrib("pos");
+    VertexOutput::new([x, y, 0., 1.], [x + 1., y * 2.])
+  });
+  ctx
+    .backend()
+    .add_fragment_shader("white", |_, _| Some(vec![[1.; 4]]));
+
+  fn new_program<P>(ctx: &mut Context<Soft>) -> Program<Vertex, (), P, Slots, (), Vertex>
+  where
+    P: Primitive,
+  {
+    ctx
+      .new_program(
+        ProgramBuilder::new()
+          .add_vertex_stage("advance")
+          .no_primitive_stage()
+          .add_shading_stage("white")
+          .capture_varyings(),
+      )
+      .unwrap()
+  }
+
+  let positions = [[0., 0.], [1., 0.], [0., 1.], [1., 1.]].map(|pos| Vertex { pos });
+  let buffers = [
+    ctx.new_buffer(&positions, Usage::Dynamic).unwrap(),
+    ctx.new_buffer(&positions, Usage::Dynamic).unwrap(),
+  ];
+  let particles: Vec<VertexEntity<Vertex, Point, Sharing>> = buffers
+    .iter()
+    .map(|buffer| {
+      ctx
+        .new_vertex_entity(VertexEntityBuilder::new().add_vertices(Shared::new(buffer)))
+        .unwrap()
+    })
+    .collect();
+  let points = new_program::<Point>(&mut ctx);
+
+  // ping-pong between the two buffers
+  for step in 0..2 {
+    let (source, target) = (&particles[step % 2], &particles[1 - step % 2]);
+
+    ctx
+      .with_framebuffer(
+        &back_buffer,
+        &PipelineState::default(),
+        |mut with_framebuffer| {
+          with_framebuffer.with_program(&points, |mut with_program| {
+            with_program.with_render_state(&RenderState::default(), |mut with_render_state| {
+              with_render_state.render_vertex_entity_with_feedback(source.view(..), target)
+            })
+          })
+        },
+      )
+      .unwrap();
+  }
+
+  let advanced: Vec<_> = ctx
+    .read_buffer(&buffers[0])
+    .unwrap()
+    .into_iter()
+    .map(|vertex| vertex.pos)
+    .collect();
+  assert_eq!(advanced, [[2., 0.], [3., 0.], [2., 4.], [3., 4.]]);
+
+  // strips are unrolled (every other triangle starting with its second vertex), and vertices that don’t fit are
+  // dropped
+  let strip: VertexEntity<Vertex, TriangleStrip, Interleaving> = ctx
+    .new_vertex_entity(
+      VertexEntityBuilder::new().add_vertices(Interleaved::new().set_vertices(positions)),
+    )
+    .unwrap();
+  let triangles = new_program::<TriangleStrip>(&mut ctx);
+  let captured = ctx
+    .new_buffer(&[Vertex { pos: [0.; 2] }; 5], Usage::Dynamic)
+    .unwrap();
+
+  ctx
+    .with_framebuffer(
+      &back_buffer,
+      &PipelineState::default(),
+      |mut with_framebuffer| {
+        with_framebuffer.with_program(&triangles, |mut with_program| {
+          with_program.with_render_state(&RenderState::default(), |mut with_render_state| {
+            with_render_state.render_vertex_entity_with_feedback(strip.view(..), &captured)
+          })
+        })
+      },
+    )
+    .unwrap();
+
+  let captured: Vec<_> = ctx
+    .read_buffer(&captured)
+    .unwrap()
+    .into_iter()
+    .map(|vertex| vertex.pos)
+    .collect();
+  assert_eq!(captured, [[1., 0.], [2., 0.], [1., 2.], [1., 2.], [2., 0.]]);
+}
+
+#[test]
+fn instance_divisor() {
+  let mut ctx = Context::new(Soft::new).unwrap();
+  let back_buffer = ctx
+    .back_buffer::<Dim2, Slots, ()>(Size2::new(4, 1))
+    .unwrap();
+
+  ctx.backend().add_vertex_shader("instanced", |input, _| {
+    let [x, y, ..] = input.attrib("pos");
+    let [offset, ..] = input.attrib("offset");
+    let [shade, ..] = input.attrib("shade");
+    VertexOutput::new([x + offset, y, 0., 1.], [shade])
+  });
+  ctx.backend().add_fragment_shader("shade", |input, _| {
+    let shade = input.varyings()[0];
+    Some(vec![[shade, shade, shade, 1.]])
+  });
+
+  let program: Program<Vertex, Instance, Point, Slots, ()> = ctx
+    .new_program(
+      ProgramBuilder::new()
+        .add_vertex_stage("instanced")
+        .no_primitive_stage()
+        .add_shading_stage("shade"),
+    )
+    .unwrap();
+
+  // one point per pixel; the shade only advances every other instance
+  let instances = [(-0.75, 0.25), (-0.25, 0.5), (0.25, 0.), (0.75, 0.)]
+    .map(|(offset, shade)| Instance { offset, shade });
+  let points: VertexEntity<Vertex, Point, Interleaving, Instance, Interleaving> = ctx
+    .new_vertex_entity(
+      VertexEntityBuilder::new()
+        .add_vertices(Interleaved::new().set_vertices([Vertex { pos: [0., 0.] }]))
+        .add_instances(Interleaved::new().set_vertices(instances)),
+    )
+    .unwrap();
+
+  ctx
+    .with_framebuffer(
+      &back_buffer,
+      &PipelineState::default(),
+      |mut with_framebuffer| {
+        with_framebuffer.with_program(&program, |mut with_program| {
+          with_program.with_render_state(&RenderState::default(), |mut with_render_state| {
+            with_render_state.render_vertex_entity(points.view(..).set_instance_count(4))
+          })
+        })
+      },
+    )
+    .unwrap();
+
+  let pixels = ctx.backend().read_back_buffer();
+  for (pixel, shade) in pixels.into_iter().zip([0.25, 0.25, 0.5, 0.5]) {
+    assert_color_eq(pixel, [shade, shade, shade, 1.]);
+  }
+}
+
+#[test]
+fn instance_matrix() {
+  let mut ctx = Context::new(Soft::new).unwrap();
+  let back_buffer = ctx
+    .back_buffer::<Dim2, Slots, ()>(Size2::new(4, 1))
+    .unwrap();
+
+  ctx.backend().add_vertex_shader("transformed", |input, _| {
+    let [x, y, ..] = input.attrib("pos");
+    let model = input.attrib_mat44("model");
+    let mut position = [0.; 4];
+
+    for (column, v) in model.iter().zip([x, y, 0., 1.]) {
+      for (p, c) in position.iter_mut().zip(column) {
+        *p += c * v;
+      }
+    }
+
+    // the scale of the model is used as the shade
+    VertexOutput::new(position, [model[0][0]])
+  });
+  ctx.backend().add_fragment_shader("shade", |input, _| {
+    let shade = input.varyings()[0];
+    Some(vec![[shade, shade, shade, 1.]])
+  });
+
+  let program: Program<Vertex, Transform, Point, Slots, ()> = ctx
+    .new_program(
+      ProgramBuilder::new()
+        .add_vertex_stage("transformed")
+        .no_primitive_stage()
+        .add_shading_stage("shade"),
+    )
+    .unwrap();
+
+  // one point per pixel, each one scaled and translated by its model matrix
+  let shades = [0.25, 0.5, 0.75, 1.];
+  let transforms = [-0.75, -0.25, 0.25, 0.75]
+    .into_iter()
+    .zip(shades)
+    .map(|(tx, scale)| Transform {
+      model: [
+        [scale, 0., 0., 0.],
+        [0., 1., 0., 0.],
+        [0., 0., 1., 0.],
+        [tx, 0., 0., 1.],
+      ],
+    })
+    .collect::<Vec<_>>();
+  let points: VertexEntity<Vertex, Point, Interleaving, Transform, Interleaving> = ctx
+    .new_vertex_entity(
+      VertexEntityBuilder::new()
+        .add_vertices(Interleaved::new().set_vertices([Vertex { pos: [0., 0.] }]))
+        .add_instances(Interleaved::new().set_vertices(transforms)),
+    )
+    .unwrap();
+
+  ctx
+    .with_framebuffer(
+      &back_buffer,
+      &PipelineState::default(),
+      |mut with_framebuffer| {
+        with_framebuffer.with_program(&program, |mut with_program| {
+          with_program.with_render_state(&RenderState::default(), |mut with_render_state| {
+            with_render_state.render_vertex_entity(points.view(..).set_instance_count(4))
+          })
+        })
+      },
+    )
+    .unwrap();
+
+  let pixels = ctx.backend().read_back_buffer();
+  for (pixel, shade) in pixels.into_iter().zip(shades) {
+    assert_color_eq(pixel, [shade, shade, shade, 1.]);
+  }
+}
+
+#[test]
+fn compressed_attributes() {
+  let mut ctx = Context::new(Soft::new).unwrap();
+  let back_buffer = ctx
+    .back_buffer::<Dim2, Slots, ()>(Size2::new(2, 2))
+    .unwrap();
+
+  ctx.backend().add_vertex_shader("compressed", |input, _| {
+    let [x, y, ..] = input.attrib("pos");
+    let [r, g, ..] = input.attrib("color");
+    let [nx, ..] = input.attrib("nor");
+    VertexOutput::new([x, y, 0., 1.], [r, g, -nx * 0.25])
+  });
+  ctx.backend().add_fragment_shader("compressed", |input, _| {
+    let [r, g, b] = [0, 1, 2].map(|i| input.varyings()[i]);
+    Some(vec![[r, g, b, 1.]])
+  });
+
+  let program: Program<Compressed, (), Triangle, Slots, ()> = ctx
+    .new_program(
+      ProgramBuilder::new()
+        .add_vertex_stage("compressed")
+        .no_primitive_stage()
+        .add_shading_stage("compressed"),
+    )
+    .unwrap();
+
+  let vertices = [[-1., -1.], [1., -1.], [1., 1.], [-1., 1.]].map(|pos| Compressed {
+    pos: pos.map(half::f16::from_f32),
+    color: UInt2_10_10_10Rev::from_normalized([1., 0.5, 0., 1.]),
+    nor: Int2_10_10_10Rev::from_normalized([-1., 0., 0., 0.]),
+  });
+  let quad: VertexEntity<Compressed, Triangle, Interleaving> = ctx
+    .new_vertex_entity(
+      VertexEntityBuilder::new()
+        .add_vertices(Interleaved::new().set_vertices(vertices))
+        .add_indices([0, 1, 2, 0, 2, 3]),
+    )
+    .unwrap();
+
+  ctx
+    .with_framebuffer(
+      &back_buffer,
+      &PipelineState::default(),
+      |mut with_framebuffer| {
+        with_framebuffer.with_program(&program, |mut with_program| {
+          with_program.with_render_state(&RenderState::default(), |mut with_render_state| {
+            with_render_state.render_vertex_entity(quad.view(..))
+          })
+        })
+      },
+    )
+    .unwrap();
+
+  for pixel in ctx.backend().read_back_buffer() {
+    assert_color_eq(pixel, [1., 0.5, 0.25, 1.]);
+  }
+}
+
+#[test]
+fn adjacency_primitives() {
+  let mut ctx = Context::new(Soft::new).unwrap();
+
+  ctx.backend().add_vertex_shader("pass", |input, _| {
+    let [x, y, ..] = input.attrib("pos");
+    VertexOutput::new([x, y, 0., 1.], [])
+  });
+  ctx
+    .backend()
+    .add_fragment_shader("white", |_, _| Some(vec![[1.; 4]]));
+
+  fn new_program<P>(ctx: &mut Context<Soft>) -> Program<Vertex, (), P, Slots, ()>
+  where
+    P: Primitive,
+  {
+    ctx
+      .new_program(
+        ProgramBuilder::new()
+          .add_vertex_stage("pass")
+          .no_primitive_stage()
+          .add_shading_stage("white"),
+      )
+      .unwrap()
+  }
+
+  fn render<P>(ctx: &mut Context<Soft>, indices: Vec<u32>) -> Vec<[f32; 4]>
+  where
+    P: Primitive,
+  {
+    let back_buffer = ctx
+      .back_buffer::<Dim2, Slots, ()>(Size2::new(4, 4))
+      .unwrap();
+    let vertices = [[-1., -1.], [1., -1.], [1., 1.], [-1., 1.]].map(|pos| Vertex { pos });
+    let quad: VertexEntity<Vertex, P, Interleaving> = ctx
+      .new_vertex_entity(
+        VertexEntityBuilder::new()
+          .add_vertices(Interleaved::new().set_vertices(vertices))
+          .add_indices(indices),
+      )
+      .unwrap();
+    let program = new_program::<P>(ctx);
+
+    ctx
+      .with_framebuffer(
+        &back_buffer,
+        &PipelineState::default().set_clear_color([0., 0., 0., 1.]),
+        |mut with_framebuffer| {
+          with_framebuffer.with_program(&program, |mut with_program| {
+            with_program.with_render_state(&RenderState::default(), |mut with_render_state| {
+              with_render_state.render_vertex_entity(quad.view(..))
+            })
+          })
+        },
+      )
+      .unwrap();
+
+    ctx.backend().read_back_buffer()
+  }
+
+  // adjacent vertices are skipped, so both quads cover the whole viewport
+  let triangles = render::<TriangleAdjacency>(
+    &mut ctx,
+    primitive::triangle_adjacency_indices(&[0, 1, 2, 0, 2, 3]),
+  );
+  let strip = render::<TriangleStripAdjacency>(&mut ctx, vec![0, 3, 1, 3, 3, 0, 2, 1]);
+
+  for pixel in triangles.into_iter().chain(strip) {
+    assert_color_eq(pixel, [1.; 4]);
+  }
+}
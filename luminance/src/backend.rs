@@ -1,20 +1,26 @@
+pub mod mock;
+
 use crate::{
+  buffer::{Buffer, BufferSliceMut},
   dim::Dimensionable,
   framebuffer::{Back, Framebuffer},
+  indirect::{IndirectBuffer, IndirectCommand},
   pipeline::{PipelineState, WithFramebuffer, WithProgram, WithRenderState},
   pixel::{Pixel, PixelFormat, PixelType},
-  primitive::Primitive,
+  primitive::{Connector, Primitive},
   render_slots::{DepthChannel, DepthRenderSlot, RenderChannel, RenderSlots},
   render_state::RenderState,
   shader::{
+    diagnostic::{Diagnostic, Stage},
+    reflection::ProgramReflection,
     InUseUniBuffer, MemoryLayout, Program, Uni, UniBuffer, UniBufferRef, Uniform, Uniforms,
   },
   texture::{InUseTexture, Mipmaps, Texture, TextureSampling},
   vertex::Vertex,
-  vertex_entity::{VertexEntity, VertexEntityBuilder, VertexEntityView},
-  vertex_storage::{AsVertexStorage, VertexStorageFamily},
+  vertex_entity::{Index, Usage, VertexEntity, VertexEntityBuilder, VertexEntityView},
+  vertex_storage::{AsVertexStorage, Deinterleaved, VertexStorageFamily},
 };
-use std::{collections::HashMap, error::Error as ErrorTrait, fmt};
+use std::{collections::HashMap, error::Error as ErrorTrait, fmt, ops::Range};
 
 /// Cached value.
 ///
@@ -152,10 +158,28 @@ impl ResourceMapper {
 #[derive(Debug)]
 #[non_exhaustive]
 pub enum VertexEntityError {
-  Creation { cause: Option<Box<dyn ErrorTrait>> },
-  Render { cause: Option<Box<dyn ErrorTrait>> },
-  UpdateVertexStorage { cause: Option<Box<dyn ErrorTrait>> },
-  UpdateIndices { cause: Option<Box<dyn ErrorTrait>> },
+  Creation {
+    cause: Option<Box<dyn ErrorTrait>>,
+  },
+  Render {
+    cause: Option<Box<dyn ErrorTrait>>,
+  },
+  UpdateVertexStorage {
+    cause: Option<Box<dyn ErrorTrait>>,
+  },
+  UpdateIndices {
+    cause: Option<Box<dyn ErrorTrait>>,
+  },
+  UpdateOutOfRange {
+    start: usize,
+    end: usize,
+    len: usize,
+  },
+  UnsupportedBaseInstance {
+    base_instance: usize,
+  },
+  /// The storage to update is a shared buffer, which must be updated as a [`Buffer`] instead.
+  SharedBuffer,
 }
 
 impl fmt::Display for VertexEntityError {
@@ -197,12 +221,231 @@ impl fmt::Display for VertexEntityError {
           .map(|cause| cause.to_string())
           .unwrap_or_else(|| "unknown cause".to_string())
       ),
+
+      VertexEntityError::UpdateOutOfRange { start, end, len } => write!(
+        f,
+        "cannot update range {}..{} of a storage of length {}",
+        start, end, len
+      ),
+
+      VertexEntityError::UnsupportedBaseInstance { base_instance } => write!(
+        f,
+        "cannot render from base instance {}: unsupported by the backend",
+        base_instance
+      ),
+
+      VertexEntityError::SharedBuffer => {
+        f.write_str("cannot update a shared buffer through a vertex entity")
+      }
     }
   }
 }
 
 impl ErrorTrait for VertexEntityError {}
 
+/// Errors that might happen when working with buffers.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum BufferError {
+  /// The buffer handle has no data associated with.
+  NoData {
+    handle: usize,
+  },
+
+  Creation {
+    cause: Option<Box<dyn ErrorTrait>>,
+  },
+
+  Update {
+    cause: Option<Box<dyn ErrorTrait>>,
+  },
+
+  Read {
+    cause: Option<Box<dyn ErrorTrait>>,
+  },
+
+  Map {
+    cause: Option<Box<dyn ErrorTrait>>,
+  },
+
+  /// The values to update are out of the buffer.
+  OutOfRange {
+    start: usize,
+    end: usize,
+    len: usize,
+  },
+}
+
+impl fmt::Display for BufferError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      BufferError::NoData { handle } => write!(f, "buffer {handle} has no associated data"),
+
+      BufferError::Creation { cause } => write!(
+        f,
+        "cannot create buffer: {}",
+        cause
+          .as_ref()
+          .map(|cause| cause.to_string())
+          .unwrap_or_else(|| "unknown cause".to_string())
+      ),
+
+      BufferError::Update { cause } => write!(
+        f,
+        "cannot update buffer: {}",
+        cause
+          .as_ref()
+          .map(|cause| cause.to_string())
+          .unwrap_or_else(|| "unknown cause".to_string())
+      ),
+
+      BufferError::Read { cause } => write!(
+        f,
+        "cannot read buffer: {}",
+        cause
+          .as_ref()
+          .map(|cause| cause.to_string())
+          .unwrap_or_else(|| "unknown cause".to_string())
+      ),
+
+      BufferError::Map { cause } => write!(
+        f,
+        "cannot map buffer: {}",
+        cause
+          .as_ref()
+          .map(|cause| cause.to_string())
+          .unwrap_or_else(|| "unknown cause".to_string())
+      ),
+
+      BufferError::OutOfRange { start, end, len } => write!(
+        f,
+        "values {}..{} are out of a buffer of {} values",
+        start, end, len
+      ),
+    }
+  }
+}
+
+impl ErrorTrait for BufferError {}
+
+/// Errors that might happen when working with indirect buffers.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum IndirectBufferError {
+  /// The indirect buffer handle has no data associated with.
+  NoData {
+    handle: usize,
+  },
+
+  Creation {
+    cause: Option<Box<dyn ErrorTrait>>,
+  },
+
+  Update {
+    cause: Option<Box<dyn ErrorTrait>>,
+  },
+
+  Read {
+    cause: Option<Box<dyn ErrorTrait>>,
+  },
+
+  Render {
+    cause: Option<Box<dyn ErrorTrait>>,
+  },
+
+  /// The commands to update or render are out of the buffer.
+  OutOfRange {
+    start: usize,
+    end: usize,
+    len: usize,
+  },
+
+  /// Indirect rendering is not supported by the backend.
+  Unsupported,
+
+  /// Commands with a base instance are not supported by the backend.
+  UnsupportedBaseInstance {
+    base_instance: u32,
+  },
+
+  /// The commands render indices but the vertex entity has none, or the other way around.
+  MismatchingCommands {
+    indexed_commands: bool,
+  },
+}
+
+impl fmt::Display for IndirectBufferError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      IndirectBufferError::NoData { handle } => {
+        write!(f, "indirect buffer {handle} has no associated data")
+      }
+
+      IndirectBufferError::Creation { cause } => write!(
+        f,
+        "cannot create indirect buffer: {}",
+        cause
+          .as_ref()
+          .map(|cause| cause.to_string())
+          .unwrap_or_else(|| "unknown cause".to_string())
+      ),
+
+      IndirectBufferError::Update { cause } => write!(
+        f,
+        "cannot update indirect buffer: {}",
+        cause
+          .as_ref()
+          .map(|cause| cause.to_string())
+          .unwrap_or_else(|| "unknown cause".to_string())
+      ),
+
+      IndirectBufferError::Read { cause } => write!(
+        f,
+        "cannot read indirect buffer: {}",
+        cause
+          .as_ref()
+          .map(|cause| cause.to_string())
+          .unwrap_or_else(|| "unknown cause".to_string())
+      ),
+
+      IndirectBufferError::Render { cause } => write!(
+        f,
+        "cannot render indirectly: {}",
+        cause
+          .as_ref()
+          .map(|cause| cause.to_string())
+          .unwrap_or_else(|| "unknown cause".to_string())
+      ),
+
+      IndirectBufferError::OutOfRange { start, end, len } => write!(
+        f,
+        "commands {}..{} are out of an indirect buffer of {} commands",
+        start, end, len
+      ),
+
+      IndirectBufferError::Unsupported => {
+        f.write_str("indirect rendering is not supported by the backend")
+      }
+
+      IndirectBufferError::UnsupportedBaseInstance { base_instance } => write!(
+        f,
+        "commands with a base instance ({}) are not supported by the backend",
+        base_instance
+      ),
+
+      IndirectBufferError::MismatchingCommands { indexed_commands } => {
+        if *indexed_commands {
+          f.write_str("cannot render a vertex entity without indices with indexed draw commands")
+        } else {
+          f.write_str("cannot render a vertex entity with indices with non-indexed draw commands")
+        }
+      }
+    }
+  }
+}
+
+impl ErrorTrait for IndirectBufferError {}
+
 #[derive(Debug)]
 #[non_exhaustive]
 pub enum FramebufferError {
@@ -301,6 +544,39 @@ pub enum ShaderError {
     name: String,
     cause: Option<Box<dyn ErrorTrait>>,
   },
+
+  /// The active resources of a program cannot be reflected.
+  Reflection {
+    handle: usize,
+    cause: Option<Box<dyn ErrorTrait>>,
+  },
+
+  /// A stage failed to compile.
+  Compilation {
+    stage: Stage,
+    diagnostics: Vec<Diagnostic>,
+  },
+
+  /// A program failed to link.
+  Link {
+    diagnostics: Vec<Diagnostic>,
+  },
+
+  /// A uniform doesn’t have the same type in Rust and in the program.
+  UniTypeMismatch {
+    /// Field of the [`Uniforms`](crate::shader::Uniforms) type the uniform is bound to, or name of the uniform when
+    /// it’s asked for directly with [`ShaderBackend::new_shader_uni`].
+    field: String,
+    rust_type: String,
+    glsl_type: String,
+  },
+
+  /// A vertex attribute cannot feed the input of the same name in the program.
+  VertexAttribTypeMismatch {
+    name: String,
+    rust_type: String,
+    glsl_type: String,
+  },
 }
 
 impl fmt::Display for ShaderError {
@@ -349,6 +625,54 @@ impl fmt::Display for ShaderError {
           .map(|cause| cause.to_string())
           .unwrap_or_else(|| "unknown cause".to_string())
       ),
+
+      ShaderError::Reflection { handle, cause } => write!(
+        f,
+        "cannot reflect program {}: {}",
+        handle,
+        cause
+          .as_ref()
+          .map(|cause| cause.to_string())
+          .unwrap_or_else(|| "unknown cause".to_string())
+      ),
+
+      ShaderError::Compilation { stage, diagnostics } => {
+        write!(f, "cannot compile {stage}")?;
+
+        for diagnostic in diagnostics {
+          write!(f, "\n{diagnostic}")?;
+        }
+
+        Ok(())
+      }
+
+      ShaderError::Link { diagnostics } => {
+        f.write_str("cannot link program")?;
+
+        for diagnostic in diagnostics {
+          write!(f, "\n{diagnostic}")?;
+        }
+
+        Ok(())
+      }
+
+      ShaderError::UniTypeMismatch {
+        field,
+        rust_type,
+        glsl_type,
+      } => write!(
+        f,
+        "uniform field \"{field}\" is declared as {rust_type} in Rust but as {glsl_type} in the program"
+      ),
+
+      ShaderError::VertexAttribTypeMismatch {
+        name,
+        rust_type,
+        glsl_type,
+      } => write!(
+        f,
+        "vertex attribute \"{name}\" is declared as {rust_type} in Rust but as {glsl_type} in the program"
+      ),
     }
   }
 }
@@ -385,6 +709,30 @@ pub enum PipelineError {
     cause: Option<Box<dyn ErrorTrait>>,
   },
 
+  RenderVertexEntityBatch {
+    view_count: usize,
+    cause: Option<Box<dyn ErrorTrait>>,
+  },
+
+  RenderIndirect {
+    start_command: usize,
+    command_count: usize,
+    cause: Option<Box<dyn ErrorTrait>>,
+  },
+
+  /// The program doesn’t capture any varying, so it cannot be used with transform feedback.
+  NoCapturedVaryings,
+
+  /// Primitives of this kind cannot be captured with transform feedback.
+  UncapturablePrimitive {
+    connector: Connector,
+  },
+
+  /// Transform feedback couldn’t be set up, e.g. because the target buffer doesn’t exist.
+  TransformFeedback {
+    cause: Option<Box<dyn ErrorTrait>>,
+  },
+
   ShaderError(ShaderError),
 
   FramebufferError(FramebufferError),
@@ -460,6 +808,38 @@ impl fmt::Display for PipelineError {
         start_vertex, vertex_count, instance_count,
       ),
 
+      PipelineError::RenderVertexEntityBatch { view_count, cause } => write!(
+        f,
+        "error in render vertex entity batch pipeline: {}; view_count={}",
+        cause.as_ref().map(|cause| cause.to_string()).unwrap_or_else(|| "unknown cause".to_string()),
+        view_count,
+      ),
+
+      PipelineError::RenderIndirect {
+        start_command,
+        command_count,
+        cause,
+      } => write!(
+        f,
+        "error in render indirect pipeline: {}; start_command={}, command_count={}",
+        cause.as_ref().map(|cause| cause.to_string()).unwrap_or_else(|| "unknown cause".to_string()),
+        start_command, command_count,
+      ),
+
+      PipelineError::NoCapturedVaryings => f.write_str("the program doesn’t capture any varying"),
+
+      PipelineError::UncapturablePrimitive { connector } => write!(
+        f,
+        "{:?} primitives cannot be captured with transform feedback",
+        connector
+      ),
+
+      PipelineError::TransformFeedback { cause } => write!(
+        f,
+        "error in transform feedback pipeline: {}",
+        cause.as_ref().map(|cause| cause.to_string()).unwrap_or_else(|| "unknown cause".to_string()),
+      ),
+
       PipelineError::ShaderError(e) => write!(f, "shader error in pipeline: {}", e),
 
       PipelineError::FramebufferError(e) => write!(f, "framebuffer error in pipeline: {}", e),
@@ -625,6 +1005,8 @@ pub enum Error {
   Texture(TextureError),
   Pipeline(PipelineError),
   Query(QueryError),
+  IndirectBuffer(IndirectBufferError),
+  Buffer(BufferError),
 }
 
 impl fmt::Display for Error {
@@ -636,6 +1018,8 @@ impl fmt::Display for Error {
       Error::Pipeline(e) => write!(f, "pipeline error: {}", e),
       Error::Texture(e) => write!(f, "texture error: {}", e),
       Error::Query(e) => write!(f, "query error: {}", e),
+      Error::IndirectBuffer(e) => write!(f, "indirect buffer error: {}", e),
+      Error::Buffer(e) => write!(f, "buffer error: {}", e),
     }
   }
 }
@@ -670,59 +1054,118 @@ impl From<PipelineError> for Error {
   }
 }
 
+impl From<IndirectBufferError> for Error {
+  fn from(e: IndirectBufferError) -> Self {
+    Error::IndirectBuffer(e)
+  }
+}
+
+impl From<BufferError> for Error {
+  fn from(e: BufferError) -> Self {
+    Error::Buffer(e)
+  }
+}
+
 pub unsafe trait Backend:
   VertexEntityBackend
   + FramebufferBackend
   + ShaderBackend
   + TextureBackend
   + PipelineBackend
+  + IndirectBufferBackend
+  + BufferBackend
   + QueryBackend
 {
   unsafe fn unload(&mut self);
 }
 
 pub unsafe trait VertexEntityBackend {
-  unsafe fn new_vertex_entity<V, P, VSF, W, WSF>(
+  unsafe fn new_vertex_entity<V, P, VSF, W, WSF, I>(
     &mut self,
-    builder: VertexEntityBuilder<VSF::Storage<V>, WSF::Storage<W>>,
-  ) -> Result<VertexEntity<V, P, VSF, W, WSF>, VertexEntityError>
+    builder: VertexEntityBuilder<VSF::Storage<V>, WSF::Storage<W>, I>,
+  ) -> Result<VertexEntity<V, P, VSF, W, WSF, I>, VertexEntityError>
   where
     V: Vertex,
     P: Primitive,
     VSF: VertexStorageFamily,
     W: Vertex,
-    WSF: VertexStorageFamily;
+    WSF: VertexStorageFamily,
+    I: Index;
 
+  /// Render a vertex entity.
+  ///
+  /// `base_vertex` is added to every index before fetching vertices (or to `start_index` for direct rendering), and
+  /// `base_instance` is the first instance to fetch instance data from.
   unsafe fn vertex_entity_render<V, P>(
     &self,
     handle: usize,
     start_index: usize,
     vert_count: usize,
     inst_count: usize,
+    base_vertex: usize,
+    base_instance: usize,
   ) -> Result<(), VertexEntityError>
   where
     V: Vertex,
     P: Primitive;
 
+  /// Render a batch of views, in order, in as few draw calls as possible.
+  ///
+  /// The views can come from different vertex entities.
+  unsafe fn vertex_entity_render_batch<V, W, P>(
+    &self,
+    views: &[VertexEntityView<V, W, P>],
+  ) -> Result<(), VertexEntityError>
+  where
+    V: Vertex,
+    W: Vertex,
+    P: Primitive;
+
+  /// Update the vertices in `range` with the ones from `storage`.
+  ///
+  /// `range` is expressed in vertices and is always contained in `storage`.
   unsafe fn vertex_entity_update_vertices<V, S>(
     &mut self,
     handle: usize,
     storage: &mut S,
+    range: Range<usize>,
   ) -> Result<(), VertexEntityError>
   where
     V: Vertex,
     S: AsVertexStorage<V>;
 
-  unsafe fn vertex_entity_update_indices(
+  /// Update a single attribute of deinterleaved vertices with the components of `storage` at index `rank`.
+  ///
+  /// The other attributes are left untouched.
+  unsafe fn vertex_entity_update_vertex_attribute<V>(
     &mut self,
     handle: usize,
-    indices: &mut Vec<u32>,
-  ) -> Result<(), VertexEntityError>;
+    storage: &mut Deinterleaved<V>,
+    rank: usize,
+  ) -> Result<(), VertexEntityError>
+  where
+    V: Vertex;
 
+  /// Update the indices in `range` with the ones from `indices`.
+  ///
+  /// `range` is always contained in `indices`.
+  unsafe fn vertex_entity_update_indices<I>(
+    &mut self,
+    handle: usize,
+    indices: &mut Vec<I>,
+    range: Range<usize>,
+  ) -> Result<(), VertexEntityError>
+  where
+    I: Index;
+
+  /// Update the instances in `range` with the ones from `storage`.
+  ///
+  /// `range` is expressed in instances and is always contained in `storage`.
   unsafe fn vertex_entity_update_instance_data<W, WS>(
     &mut self,
     handle: usize,
     storage: &mut WS,
+    range: Range<usize>,
   ) -> Result<(), VertexEntityError>
   where
     W: Vertex,
@@ -789,18 +1232,34 @@ macro_rules! mk_uniform_visit {
 }
 
 pub unsafe trait ShaderBackend {
-  unsafe fn new_program<V, W, P, S, E>(
+  /// Create a program out of the code of its stages.
+  ///
+  /// Optional stages (tessellation control and evaluation, and primitive) have empty code when absent. Tessellation
+  /// stages are either both present or both absent, and programs with them render
+  /// [`Patch`](crate::primitive::Patch) primitives.
+  ///
+  /// The outputs named after the fields of `F` must be captured, interleaved, when rendering with transform feedback.
+  unsafe fn new_program<V, W, P, S, E, F>(
     &mut self,
     vertex_code: String,
+    tess_control_code: String,
+    tess_eval_code: String,
     primitive_code: String,
     shading_code: String,
-  ) -> Result<Program<V, W, P, S, E>, ShaderError>
+  ) -> Result<Program<V, W, P, S, E, F>, ShaderError>
   where
     V: Vertex,
     W: Vertex,
     P: Primitive,
     S: RenderSlots,
-    E: Uniforms;
+    E: Uniforms,
+    F: Vertex;
+
+  /// Reflect the active resources of a linked program.
+  unsafe fn program_reflection(
+    &mut self,
+    program_handle: usize,
+  ) -> Result<ProgramReflection, ShaderError>;
 
   unsafe fn new_uni_buffer<T, Scheme>(
     &mut self,
@@ -993,7 +1452,7 @@ pub unsafe trait TextureBackend {
 }
 
 pub unsafe trait PipelineBackend:
-  FramebufferBackend + ShaderBackend + VertexEntityBackend
+  FramebufferBackend + ShaderBackend + VertexEntityBackend + IndirectBufferBackend
 {
   unsafe fn with_framebuffer<D, CS, DS, Err>(
     &mut self,
@@ -1007,10 +1466,10 @@ pub unsafe trait PipelineBackend:
     DS: DepthRenderSlot,
     Err: From<PipelineError>;
 
-  unsafe fn with_program<V, W, P, S, E, Err>(
+  unsafe fn with_program<V, W, P, S, E, F, Err>(
     &mut self,
-    program: &Program<V, W, P, S, E>,
-    f: impl for<'a> FnOnce(WithProgram<'a, Self, V, W, P, S, E>) -> Result<(), Err>,
+    program: &Program<V, W, P, S, E, F>,
+    f: impl for<'a> FnOnce(WithProgram<'a, Self, V, W, P, S, E, F>) -> Result<(), Err>,
   ) -> Result<(), Err>
   where
     V: Vertex,
@@ -1018,17 +1477,19 @@ pub unsafe trait PipelineBackend:
     P: Primitive,
     S: RenderSlots,
     E: Uniforms,
+    F: Vertex,
     Err: From<PipelineError>;
 
-  unsafe fn with_render_state<V, W, P, Err>(
+  unsafe fn with_render_state<V, W, P, F, Err>(
     &mut self,
     render_state: &RenderState,
-    f: impl for<'a> FnOnce(WithRenderState<'a, Self, V, W, P>) -> Result<(), Err>,
+    f: impl for<'a> FnOnce(WithRenderState<'a, Self, V, W, P, F>) -> Result<(), Err>,
   ) -> Result<(), Err>
   where
     V: Vertex,
     W: Vertex,
     P: Primitive,
+    F: Vertex,
     Err: From<PipelineError>;
 
   unsafe fn render_vertex_entity<V, W, P>(
@@ -1039,6 +1500,111 @@ pub unsafe trait PipelineBackend:
     V: Vertex,
     W: Vertex,
     P: Primitive;
+
+  unsafe fn render_vertex_entity_batch<V, W, P>(
+    &mut self,
+    views: &[VertexEntityView<V, W, P>],
+  ) -> Result<(), PipelineError>
+  where
+    V: Vertex,
+    W: Vertex,
+    P: Primitive;
+
+  /// Render a view while capturing the varyings of the current program, as vertices of type `F`, into a buffer.
+  ///
+  /// Vertices are captured primitive by primitive, strips and fans being unrolled into lists. Vertices that don’t fit
+  /// in the buffer are dropped.
+  unsafe fn render_vertex_entity_with_feedback<V, W, P, F>(
+    &mut self,
+    view: VertexEntityView<V, W, P>,
+    buffer_handle: usize,
+  ) -> Result<(), PipelineError>
+  where
+    V: Vertex,
+    W: Vertex,
+    P: Primitive,
+    F: Vertex;
+}
+
+pub unsafe trait IndirectBufferBackend {
+  unsafe fn new_indirect_buffer<C>(
+    &mut self,
+    commands: &[C],
+  ) -> Result<IndirectBuffer<C>, IndirectBufferError>
+  where
+    C: IndirectCommand;
+
+  /// Update the commands of an indirect buffer, starting at the command `start`.
+  unsafe fn indirect_buffer_update<C>(
+    &mut self,
+    handle: usize,
+    start: usize,
+    commands: &[C],
+  ) -> Result<(), IndirectBufferError>
+  where
+    C: IndirectCommand;
+
+  /// Read back all the commands of an indirect buffer.
+  unsafe fn indirect_buffer_read<C>(
+    &mut self,
+    handle: usize,
+  ) -> Result<Vec<C>, IndirectBufferError>
+  where
+    C: IndirectCommand;
+
+  /// Render a vertex entity with the commands in `commands` read from an indirect buffer.
+  ///
+  /// The vertex entity has indices if and only if `C` renders indices.
+  unsafe fn vertex_entity_render_indirect<V, P, C>(
+    &mut self,
+    entity_handle: usize,
+    indirect_handle: usize,
+    commands: Range<usize>,
+  ) -> Result<(), IndirectBufferError>
+  where
+    V: Vertex,
+    P: Primitive,
+    C: IndirectCommand;
+}
+
+pub unsafe trait BufferBackend {
+  /// Create a buffer holding a copy of `values`.
+  ///
+  /// `usage` is a hint of how often the buffer is updated.
+  unsafe fn new_buffer<T>(&mut self, values: &[T], usage: Usage) -> Result<Buffer<T>, BufferError>
+  where
+    T: Copy;
+
+  /// Update the values of a buffer, starting at the value `start`.
+  ///
+  /// The updated values are always contained in the buffer.
+  unsafe fn buffer_update<T>(
+    &mut self,
+    handle: usize,
+    start: usize,
+    values: &[T],
+  ) -> Result<(), BufferError>
+  where
+    T: Copy;
+
+  /// Read back the `len` values of a buffer.
+  unsafe fn buffer_read<T>(&mut self, handle: usize, len: usize) -> Result<Vec<T>, BufferError>
+  where
+    T: Copy;
+
+  /// Map the `len` values of a buffer, to read and write them from the CPU.
+  ///
+  /// The pointer of the returned slice must be aligned and non-null, even if the buffer is empty.
+  unsafe fn buffer_map<T>(
+    &mut self,
+    handle: usize,
+    len: usize,
+  ) -> Result<BufferSliceMut<'_, Self, T>, BufferError>
+  where
+    T: Copy;
+
+  /// Unmap a buffer mapped with [`BufferBackend::buffer_map`].
+  unsafe fn buffer_unmap<T>(&mut self, handle: usize) -> Result<(), BufferError>;
 }
 
 pub unsafe trait QueryBackend {
@@ -0,0 +1,2122 @@
+//! In-memory backend recording every command it receives.
+//!
+//! [`MockBackend`] implements all the backend traits without talking to any GPU, which allows to drive a [`Context`]
+//! in plain tests. Resources are tracked in memory (texels of textures and content of uniform buffers are kept around
+//! so that they can be read back) and every call made to the backend is appended to a command log that can be
+//! inspected with [`MockBackend::commands`].
+//!
+//! Nothing is actually rasterized: render commands are validated against the vertex entity they target (so that
+//! out-of-bounds draws are reported as errors) and then recorded.
+//!
+//! ```ignore
+//! let mut ctx = Context::new(MockBackend::new).unwrap();
+//! // … run your frame code with ctx …
+//! let commands = ctx.backend().commands();
+//! ```
+//!
+//! [`Context`]: crate::context::Context
+
+use crate::{
+  backend::{
+    Backend, BufferBackend, BufferError, FramebufferBackend, FramebufferError,
+    IndirectBufferBackend, IndirectBufferError, PipelineBackend, PipelineError, QueryBackend,
+    QueryError, ShaderBackend, ShaderError, TextureBackend, TextureError, VertexEntityBackend,
+    VertexEntityError,
+  },
+  buffer::{Buffer, BufferSliceMut},
+  context::ContextActive,
+  dim::{Dim, Dimensionable},
+  framebuffer::{Back, Framebuffer},
+  indirect::{IndirectBuffer, IndirectCommand},
+  pipeline::{PipelineState, WithFramebuffer, WithProgram, WithRenderState},
+  pixel::{Pixel, PixelFormat, PixelType},
+  primitive::{Connector, Primitive},
+  render_slots::{DepthChannel, DepthRenderSlot, RenderChannel, RenderSlots},
+  render_state::RenderState,
+  shader::{
+    reflection::ProgramReflection, InUseUniBuffer, MemoryLayout, Program, Uni, UniBuffer,
+    UniBufferRef, Uniform, UniformValue, Uniforms,
+  },
+  texture::{InUseTexture, Mipmaps, Texture, TextureSampling},
+  vertex::{self, Vertex, VertexBufferDesc},
+  vertex_entity::{Index, IndexType, Usage, VertexEntity, VertexEntityBuilder, VertexEntityView},
+  vertex_storage::{AsVertexStorage, Deinterleaved, Shared, VertexStorage, VertexStorageFamily},
+};
+use std::{
+  cell::RefCell,
+  collections::{HashMap, HashSet},
+  fmt, mem,
+  ops::Range,
+  ptr,
+  rc::Rc,
+};
+
+/// A command recorded by [`MockBackend`].
+///
+/// Sizes and offsets of textures and framebuffers are flattened to `[width, height, depth]` and `[x, y, z]`, whatever
+/// their dimension.
+#[derive(Clone, Debug)]
+pub enum Command {
+  NewVertexEntity {
+    handle: usize,
+    connector: Connector,
+    vertex_count: usize,
+    index_count: usize,
+    index_type: IndexType,
+    instance_count: usize,
+    vertex_usage: Usage,
+    index_usage: Usage,
+    instance_usage: Usage,
+  },
+
+  UpdateVertices {
+    handle: usize,
+    range: Range<usize>,
+  },
+
+  UpdateVertexAttribute {
+    handle: usize,
+    rank: usize,
+  },
+
+  UpdateIndices {
+    handle: usize,
+    range: Range<usize>,
+  },
+
+  UpdateInstanceData {
+    handle: usize,
+    range: Range<usize>,
+  },
+
+  DropVertexEntity {
+    handle: usize,
+  },
+
+  NewFramebuffer {
+    handle: usize,
+    size: [u32; 3],
+  },
+
+  NewRenderLayer {
+    framebuffer: usize,
+    texture: usize,
+    index: usize,
+  },
+
+  NewDepthRenderLayer {
+    framebuffer: usize,
+    texture: usize,
+  },
+
+  BackBuffer {
+    size: [u32; 3],
+  },
+
+  DropFramebuffer {
+    handle: usize,
+  },
+
+  NewProgram {
+    handle: usize,
+    vertex_code: String,
+    tess_control_code: String,
+    tess_eval_code: String,
+    primitive_code: String,
+    shading_code: String,
+    captured_varyings: Vec<&'static str>,
+  },
+
+  DropProgram {
+    handle: usize,
+  },
+
+  NewUniform {
+    program: usize,
+    handle: usize,
+    name: String,
+  },
+
+  SetUniform {
+    program: usize,
+    name: String,
+    value: UniformValue,
+  },
+
+  NewUniBuffer {
+    handle: usize,
+  },
+
+  SyncUniBuffer {
+    handle: usize,
+  },
+
+  UnsyncUniBuffer {
+    handle: usize,
+  },
+
+  UseUniBuffer {
+    handle: usize,
+  },
+
+  DropUniBuffer {
+    handle: usize,
+  },
+
+  NewTexture {
+    handle: usize,
+    dim: Dim,
+    size: [u32; 3],
+    format: PixelFormat,
+    mipmaps: Mipmaps,
+  },
+
+  ResizeTexture {
+    handle: usize,
+    size: [u32; 3],
+    mipmaps: Mipmaps,
+  },
+
+  SetTextureData {
+    handle: usize,
+    offset: [u32; 3],
+    size: [u32; 3],
+    level: usize,
+    gen_mipmaps: bool,
+  },
+
+  ClearTextureData {
+    handle: usize,
+    offset: [u32; 3],
+    size: [u32; 3],
+  },
+
+  ReadTexture {
+    handle: usize,
+  },
+
+  UseTexture {
+    handle: usize,
+  },
+
+  DropTexture {
+    handle: usize,
+  },
+
+  WithFramebuffer {
+    handle: usize,
+    pipeline_state: PipelineState,
+  },
+
+  WithProgram {
+    handle: usize,
+  },
+
+  WithRenderState {
+    render_state: RenderState,
+  },
+
+  RenderVertexEntity {
+    handle: usize,
+    start_vertex: usize,
+    vertex_count: usize,
+    instance_count: usize,
+    base_vertex: usize,
+    base_instance: usize,
+  },
+
+  /// A batch of views was rendered; the rendering of each view is recorded right after.
+  RenderVertexEntityBatch {
+    view_count: usize,
+  },
+
+  /// A view was rendered with transform feedback into a buffer; the rendering of the view is recorded right after.
+  RenderFeedback {
+    buffer: usize,
+  },
+
+  NewIndirectBuffer {
+    handle: usize,
+    len: usize,
+  },
+
+  UpdateIndirectBuffer {
+    handle: usize,
+    range: Range<usize>,
+  },
+
+  ReadIndirectBuffer {
+    handle: usize,
+  },
+
+  DropIndirectBuffer {
+    handle: usize,
+  },
+
+  /// A vertex entity was rendered indirectly; the rendering of each command is recorded right after.
+  RenderIndirect {
+    handle: usize,
+    indirect_buffer: usize,
+    range: Range<usize>,
+  },
+
+  NewBuffer {
+    handle: usize,
+    len: usize,
+    usage: Usage,
+  },
+
+  UpdateBuffer {
+    handle: usize,
+    range: Range<usize>,
+  },
+
+  ReadBuffer {
+    handle: usize,
+  },
+
+  MapBuffer {
+    handle: usize,
+  },
+
+  UnmapBuffer {
+    handle: usize,
+  },
+
+  DropBuffer {
+    handle: usize,
+  },
+
+  Unload,
+}
+
+/// Errors specific to [`MockBackend`].
+///
+/// They are used as causes of the regular backend errors.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum MockError {
+  /// No resource is associated with the handle.
+  UnknownHandle { handle: usize },
+
+  /// A range of data goes past the end of the storage it targets.
+  OutOfBounds {
+    start: usize,
+    count: usize,
+    len: usize,
+  },
+
+  /// The vertex storage doesn’t have the same shape as the one the vertex entity was created with.
+  IncompatibleStorage,
+
+  /// The components of a deinterleaved storage don’t all have the same length.
+  MismatchingComponents,
+}
+
+impl fmt::Display for MockError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      MockError::UnknownHandle { handle } => {
+        write!(f, "no resource associated with handle {}", handle)
+      }
+
+      MockError::OutOfBounds { start, count, len } => write!(
+        f,
+        "range {}..{} is out of bounds (len = {})",
+        start,
+        start + count,
+        len
+      ),
+
+      MockError::IncompatibleStorage => f.write_str("incompatible vertex storage"),
+
+      MockError::MismatchingComponents => {
+        f.write_str("deinterleaved components have different lengths")
+      }
+    }
+  }
+}
+
+impl std::error::Error for MockError {}
+
+/// Shape of a vertex storage, as seen at vertex entity creation.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum StorageShape {
+  None,
+  Interleaved,
+  Deinterleaved,
+  Shared { buffer: usize },
+}
+
+#[derive(Debug)]
+struct VertexEntityData {
+  vertex_shape: StorageShape,
+  vertex_len: usize,
+  index_len: usize,
+  instance_shape: StorageShape,
+  instance_len: usize,
+  instance_desc: Vec<VertexBufferDesc>,
+}
+
+#[derive(Debug)]
+struct BufferData {
+  storage: Vec<u128>, // u128 to get an alignment suitable for any type
+  len: usize,
+}
+
+impl BufferData {
+  fn new<T>(values: &[T]) -> Self {
+    let len = (mem::size_of_val(values) + mem::size_of::<u128>() - 1) / mem::size_of::<u128>();
+    let mut storage = vec![0u128; len.max(1)];
+
+    unsafe {
+      ptr::copy_nonoverlapping(
+        values.as_ptr(),
+        storage.as_mut_ptr() as *mut T,
+        values.len(),
+      );
+    }
+
+    Self {
+      storage,
+      len: values.len(),
+    }
+  }
+
+  fn as_ptr<T>(&self) -> *const T {
+    self.storage.as_ptr() as *const T
+  }
+
+  fn as_mut_ptr<T>(&mut self) -> *mut T {
+    self.storage.as_mut_ptr() as *mut T
+  }
+
+  /// The values of the buffer; `T` must be the type the buffer was created with.
+  unsafe fn as_slice<T>(&self) -> &[T] {
+    std::slice::from_raw_parts(self.as_ptr(), self.len)
+  }
+
+  unsafe fn as_mut_slice<T>(&mut self) -> &mut [T] {
+    std::slice::from_raw_parts_mut(self.as_mut_ptr(), self.len)
+  }
+}
+
+#[derive(Debug)]
+struct TextureData {
+  dim: Dim,
+  size: [u32; 3],
+  format: PixelFormat,
+  texels: Vec<u8>,
+}
+
+impl TextureData {
+  fn new(dim: Dim, size: [u32; 3], format: PixelFormat) -> Self {
+    let texels = vec![0; Self::bytes_len(size, format)];
+
+    Self {
+      dim,
+      size,
+      format,
+      texels,
+    }
+  }
+
+  fn bytes_len([width, height, depth]: [u32; 3], format: PixelFormat) -> usize {
+    width as usize * height as usize * depth as usize * format.format.bytes_len()
+  }
+
+  /// Copy `texels` in the region defined by `offset` and `size`, row by row.
+  fn write(
+    &mut self,
+    [x, y, z]: [u32; 3],
+    [width, height, depth]: [u32; 3],
+    texels: &[u8],
+  ) -> Result<(), MockError> {
+    let [tex_width, tex_height, tex_depth] = self.size;
+    let pixel_len = self.format.format.bytes_len();
+
+    if x + width > tex_width || y + height > tex_height || z + depth > tex_depth {
+      return Err(MockError::OutOfBounds {
+        start: (x + (y + z * tex_height) * tex_width) as usize,
+        count: (width * height * depth) as usize,
+        len: (tex_width * tex_height * tex_depth) as usize,
+      });
+    }
+
+    let row_len = width as usize * pixel_len;
+    for k in 0..depth {
+      for j in 0..height {
+        let src = (j + k * height) as usize * row_len;
+        let dst = (x + (y + j + (z + k) * tex_height) * tex_width) as usize * pixel_len;
+        self.texels[dst..dst + row_len].copy_from_slice(&texels[src..src + row_len]);
+      }
+    }
+
+    Ok(())
+  }
+}
+
+/// Flatten a size into `[width, height, depth]`.
+fn flat_size<D>(size: &D::Size) -> [u32; 3]
+where
+  D: Dimensionable,
+{
+  [D::width(size), D::height(size), D::depth(size)]
+}
+
+/// Flatten an offset into `[x, y, z]`, ignoring the axes the dimension doesn’t have.
+fn flat_offset<D>(offset: &D::Offset) -> [u32; 3]
+where
+  D: Dimensionable,
+{
+  match D::dim() {
+    Dim::Dim1 => [D::x_offset(offset), 0, 0],
+    Dim::Dim2 | Dim::Dim1Array => [D::x_offset(offset), D::y_offset(offset), 0],
+    Dim::Dim3 | Dim::Cubemap | Dim::Dim2Array => [
+      D::x_offset(offset),
+      D::y_offset(offset),
+      D::z_offset(offset),
+    ],
+  }
+}
+
+/// Reinterpret raw texels as bytes.
+fn texels_as_bytes<T>(texels: &[T]) -> &[u8] {
+  unsafe { std::slice::from_raw_parts(texels.as_ptr() as *const u8, mem::size_of_val(texels)) }
+}
+
+#[derive(Debug)]
+struct State {
+  // whether the associated context is still active
+  context_active: ContextActive,
+
+  // next handle to give away; 0 is reserved for the back buffer
+  next_handle: usize,
+
+  commands: Vec<Command>,
+
+  vertex_entities: HashMap<usize, VertexEntityData>,
+  framebuffers: HashSet<usize>,
+  programs: HashSet<usize>,
+  uniforms: HashMap<usize, (usize, String)>, // uniform handle -> (program handle, name)
+  textures: HashMap<usize, TextureData>,
+  uni_buffers: HashMap<usize, Vec<u128>>, // u128 to get an alignment suitable for any aligned type
+  indirect_buffers: HashMap<usize, BufferData>,
+  buffers: HashMap<usize, BufferData>,
+}
+
+impl State {
+  fn new(context_active: ContextActive) -> Self {
+    Self {
+      context_active,
+      next_handle: 1,
+      commands: Vec::new(),
+      vertex_entities: HashMap::new(),
+      framebuffers: HashSet::new(),
+      programs: HashSet::new(),
+      uniforms: HashMap::new(),
+      textures: HashMap::new(),
+      uni_buffers: HashMap::new(),
+      indirect_buffers: HashMap::new(),
+      buffers: HashMap::new(),
+    }
+  }
+
+  fn next_handle(&mut self) -> usize {
+    let handle = self.next_handle;
+    self.next_handle += 1;
+    handle
+  }
+
+  fn record(&mut self, command: Command) {
+    self.commands.push(command);
+  }
+
+  fn is_context_active(&self) -> bool {
+    self.context_active.is_active()
+  }
+
+  fn drop_vertex_entity(&mut self, handle: usize) {
+    if self.is_context_active() {
+      self.vertex_entities.remove(&handle);
+      self.record(Command::DropVertexEntity { handle });
+    }
+  }
+
+  fn drop_framebuffer(&mut self, handle: usize) {
+    if self.is_context_active() {
+      self.framebuffers.remove(&handle);
+      self.record(Command::DropFramebuffer { handle });
+    }
+  }
+
+  fn drop_program(&mut self, handle: usize) {
+    if self.is_context_active() {
+      self.programs.remove(&handle);
+      self.uniforms.retain(|_, (program, _)| *program != handle);
+      self.record(Command::DropProgram { handle });
+    }
+  }
+
+  fn drop_texture(&mut self, handle: usize) {
+    if self.is_context_active() {
+      self.textures.remove(&handle);
+      self.record(Command::DropTexture { handle });
+    }
+  }
+
+  fn drop_uni_buffer(&mut self, handle: usize) {
+    if self.is_context_active() {
+      self.uni_buffers.remove(&handle);
+      self.record(Command::DropUniBuffer { handle });
+    }
+  }
+
+  fn drop_indirect_buffer(&mut self, handle: usize) {
+    if self.is_context_active() {
+      self.indirect_buffers.remove(&handle);
+      self.record(Command::DropIndirectBuffer { handle });
+    }
+  }
+
+  fn drop_buffer(&mut self, handle: usize) {
+    if self.is_context_active() {
+      self.buffers.remove(&handle);
+      self.record(Command::DropBuffer { handle });
+    }
+  }
+}
+
+/// In-memory backend.
+///
+/// See the [module documentation](crate::backend::mock) for further details.
+#[derive(Debug)]
+pub struct MockBackend {
+  state: Rc<RefCell<State>>,
+}
+
+impl MockBackend {
+  pub fn new(context_active: ContextActive) -> Option<Self> {
+    let state = Rc::new(RefCell::new(State::new(context_active)));
+    Some(Self { state })
+  }
+
+  /// All the commands recorded so far, in order.
+  pub fn commands(&self) -> Vec<Command> {
+    self.state.borrow().commands.clone()
+  }
+
+  /// Take all the commands recorded so far, leaving the command log empty.
+  pub fn take_commands(&self) -> Vec<Command> {
+    mem::take(&mut self.state.borrow_mut().commands)
+  }
+
+  /// Clear the command log.
+  pub fn clear_commands(&self) {
+    self.state.borrow_mut().commands.clear();
+  }
+
+  fn record(&self, command: Command) {
+    self.state.borrow_mut().record(command);
+  }
+
+  /// Shape and length of a vertex storage.
+  fn storage_len<V>(
+    storage: &mut impl AsVertexStorage<V>,
+  ) -> Result<(StorageShape, usize), MockError>
+  where
+    V: Vertex,
+  {
+    match storage.as_vertex_storage() {
+      VertexStorage::NoStorage => Ok((StorageShape::None, 0)),
+
+      VertexStorage::Interleaved(storage) => {
+        Ok((StorageShape::Interleaved, storage.vertices().len()))
+      }
+
+      VertexStorage::Deinterleaved(storage) => {
+        let mut len = None;
+
+        for (components, desc) in storage.components_list().iter().zip(V::vertex_desc()) {
+          let field_len = desc.attrib_desc.size();
+          let components_len = components.len() / field_len;
+
+          match len {
+            None => len = Some(components_len),
+            Some(len) if len != components_len => return Err(MockError::MismatchingComponents),
+            _ => (),
+          }
+        }
+
+        Ok((StorageShape::Deinterleaved, len.unwrap_or(0)))
+      }
+
+      VertexStorage::Shared(storage) => Ok((
+        StorageShape::Shared {
+          buffer: storage.buffer_handle(),
+        },
+        storage.len(),
+      )),
+    }
+  }
+
+  /// Check that an updated storage has the same shape as the one the vertex entity was created with, and that the
+  /// updated range fits in it.
+  ///
+  /// Shared storages are updated through their buffer, never through the vertex entity.
+  fn check_update(
+    (shape, len): (StorageShape, usize),
+    range: &Range<usize>,
+    expected_shape: StorageShape,
+  ) -> Result<usize, MockError> {
+    if shape != expected_shape || matches!(shape, StorageShape::None | StorageShape::Shared { .. })
+    {
+      return Err(MockError::IncompatibleStorage);
+    }
+
+    if range.end > len {
+      return Err(MockError::OutOfBounds {
+        start: range.start,
+        count: range.len(),
+        len,
+      });
+    }
+
+    Ok(len)
+  }
+}
+
+unsafe impl Backend for MockBackend {
+  unsafe fn unload(&mut self) {
+    let mut st = self.state.borrow_mut();
+
+    st.vertex_entities.clear();
+    st.framebuffers.clear();
+    st.programs.clear();
+    st.uniforms.clear();
+    st.textures.clear();
+    st.uni_buffers.clear();
+    st.indirect_buffers.clear();
+    st.buffers.clear();
+    st.record(Command::Unload);
+  }
+}
+
+unsafe impl VertexEntityBackend for MockBackend {
+  unsafe fn new_vertex_entity<V, P, VSF, W, WSF, I>(
+    &mut self,
+    mut builder: VertexEntityBuilder<VSF::Storage<V>, WSF::Storage<W>, I>,
+  ) -> Result<VertexEntity<V, P, VSF, W, WSF, I>, VertexEntityError>
+  where
+    V: Vertex,
+    P: Primitive,
+    VSF: VertexStorageFamily,
+    W: Vertex,
+    WSF: VertexStorageFamily,
+    I: Index,
+  {
+    let (vertex_shape, vertex_len) =
+      Self::storage_len(&mut builder.vertices).map_err(|e| VertexEntityError::Creation {
+        cause: Some(Box::new(e)),
+      })?;
+    let (instance_shape, instance_len) =
+      Self::storage_len(&mut builder.instances).map_err(|e| VertexEntityError::Creation {
+        cause: Some(Box::new(e)),
+      })?;
+    let indices = builder.indices;
+    let index_len = builder
+      .index_buffer
+      .as_ref()
+      .map_or(indices.len(), Shared::len);
+
+    let vertex_count = if index_len == 0 {
+      vertex_len
+    } else {
+      index_len
+    };
+
+    let data = VertexEntityData {
+      vertex_shape,
+      vertex_len,
+      index_len,
+      instance_shape,
+      instance_len,
+      instance_desc: W::vertex_desc(),
+    };
+
+    let mut st = self.state.borrow_mut();
+
+    // shared storages must reference live buffers
+    let shared_buffers = [vertex_shape, instance_shape]
+      .into_iter()
+      .filter_map(|shape| match shape {
+        StorageShape::Shared { buffer } => Some(buffer),
+        _ => None,
+      })
+      .chain(builder.index_buffer.as_ref().map(Shared::buffer_handle));
+
+    for handle in shared_buffers {
+      if !st.buffers.contains_key(&handle) {
+        return Err(VertexEntityError::Creation {
+          cause: Some(Box::new(MockError::UnknownHandle { handle })),
+        });
+      }
+    }
+
+    let handle = st.next_handle();
+    st.vertex_entities.insert(handle, data);
+    st.record(Command::NewVertexEntity {
+      handle,
+      connector: P::CONNECTOR,
+      vertex_count,
+      index_count: index_len,
+      index_type: I::INDEX_TYPE,
+      instance_count: instance_len,
+      vertex_usage: builder.vertex_usage,
+      index_usage: builder.index_usage,
+      instance_usage: builder.instance_usage,
+    });
+
+    let state = self.state.clone();
+    let dropper = Box::new(move |handle| {
+      state.borrow_mut().drop_vertex_entity(handle);
+    });
+
+    Ok(VertexEntity::new(
+      handle,
+      builder.vertices,
+      builder.instances,
+      indices,
+      builder.index_buffer,
+      vertex_count,
+      dropper,
+    ))
+  }
+
+  unsafe fn vertex_entity_render<V, P>(
+    &self,
+    handle: usize,
+    start_index: usize,
+    vert_count: usize,
+    inst_count: usize,
+    base_vertex: usize,
+    base_instance: usize,
+  ) -> Result<(), VertexEntityError>
+  where
+    V: Vertex,
+    P: Primitive,
+  {
+    let mut st = self.state.borrow_mut();
+    let data = st
+      .vertex_entities
+      .get(&handle)
+      .ok_or_else(|| VertexEntityError::Render {
+        cause: Some(Box::new(MockError::UnknownHandle { handle })),
+      })?;
+
+    // attribute-less vertex entities can render any amount of vertices; the base vertex offsets direct rendering
+    let bounds = if data.index_len > 0 {
+      Some((start_index, data.index_len))
+    } else if data.vertex_shape != StorageShape::None {
+      Some((start_index + base_vertex, data.vertex_len))
+    } else {
+      None
+    };
+
+    if let Some((start, len)) = bounds {
+      if start + vert_count > len {
+        return Err(VertexEntityError::Render {
+          cause: Some(Box::new(MockError::OutOfBounds {
+            start,
+            count: vert_count,
+            len,
+          })),
+        });
+      }
+    }
+
+    let instance_data_len = vertex::instance_data_len(&data.instance_desc, inst_count);
+    if data.instance_shape != StorageShape::None
+      && base_instance + instance_data_len > data.instance_len
+    {
+      return Err(VertexEntityError::Render {
+        cause: Some(Box::new(MockError::OutOfBounds {
+          start: base_instance,
+          count: instance_data_len,
+          len: data.instance_len,
+        })),
+      });
+    }
+
+    st.record(Command::RenderVertexEntity {
+      handle,
+      start_vertex: start_index,
+      vertex_count: vert_count,
+      instance_count: inst_count,
+      base_vertex,
+      base_instance,
+    });
+
+    Ok(())
+  }
+
+  unsafe fn vertex_entity_render_batch<V, W, P>(
+    &self,
+    views: &[VertexEntityView<V, W, P>],
+  ) -> Result<(), VertexEntityError>
+  where
+    V: Vertex,
+    W: Vertex,
+    P: Primitive,
+  {
+    self.record(Command::RenderVertexEntityBatch {
+      view_count: views.len(),
+    });
+
+    for view in views {
+      self.vertex_entity_render::<V, P>(
+        view.handle(),
+        view.start_vertex(),
+        view.vertex_count(),
+        view.instance_count(),
+        view.base_vertex(),
+        view.base_instance(),
+      )?;
+    }
+
+    Ok(())
+  }
+
+  unsafe fn vertex_entity_update_vertices<V, S>(
+    &mut self,
+    handle: usize,
+    storage: &mut S,
+    range: Range<usize>,
+  ) -> Result<(), VertexEntityError>
+  where
+    V: Vertex,
+    S: AsVertexStorage<V>,
+  {
+    let mut st = self.state.borrow_mut();
+    let data = st.vertex_entities.get_mut(&handle).ok_or_else(|| {
+      VertexEntityError::UpdateVertexStorage {
+        cause: Some(Box::new(MockError::UnknownHandle { handle })),
+      }
+    })?;
+
+    // storages can grow or shrink between updates
+    data.vertex_len = Self::storage_len(storage)
+      .and_then(|storage_len| Self::check_update(storage_len, &range, data.vertex_shape))
+      .map_err(|e| VertexEntityError::UpdateVertexStorage {
+        cause: Some(Box::new(e)),
+      })?;
+
+    st.record(Command::UpdateVertices { handle, range });
+    Ok(())
+  }
+
+  unsafe fn vertex_entity_update_vertex_attribute<V>(
+    &mut self,
+    handle: usize,
+    storage: &mut Deinterleaved<V>,
+    rank: usize,
+  ) -> Result<(), VertexEntityError>
+  where
+    V: Vertex,
+  {
+    let mut st = self.state.borrow_mut();
+    let data = st.vertex_entities.get_mut(&handle).ok_or_else(|| {
+      VertexEntityError::UpdateVertexStorage {
+        cause: Some(Box::new(MockError::UnknownHandle { handle })),
+      }
+    })?;
+
+    // the other attributes are not updated, so the number of vertices cannot change
+    let vertex_len = data.vertex_len;
+    Self::storage_len(storage)
+      .and_then(|storage_len| Self::check_update(storage_len, &(0..0), data.vertex_shape))
+      .and_then(|len| {
+        if len == vertex_len {
+          Ok(())
+        } else {
+          Err(MockError::MismatchingComponents)
+        }
+      })
+      .map_err(|e| VertexEntityError::UpdateVertexStorage {
+        cause: Some(Box::new(e)),
+      })?;
+
+    st.record(Command::UpdateVertexAttribute { handle, rank });
+    Ok(())
+  }
+
+  unsafe fn vertex_entity_update_indices<I>(
+    &mut self,
+    handle: usize,
+    indices: &mut Vec<I>,
+    range: Range<usize>,
+  ) -> Result<(), VertexEntityError>
+  where
+    I: Index,
+  {
+    let mut st = self.state.borrow_mut();
+    let data =
+      st.vertex_entities
+        .get_mut(&handle)
+        .ok_or_else(|| VertexEntityError::UpdateIndices {
+          cause: Some(Box::new(MockError::UnknownHandle { handle })),
+        })?;
+
+    if range.end > indices.len() {
+      return Err(VertexEntityError::UpdateIndices {
+        cause: Some(Box::new(MockError::OutOfBounds {
+          start: range.start,
+          count: range.len(),
+          len: indices.len(),
+        })),
+      });
+    }
+
+    // indices can be added or removed between updates
+    data.index_len = indices.len();
+
+    st.record(Command::UpdateIndices { handle, range });
+    Ok(())
+  }
+
+  unsafe fn vertex_entity_update_instance_data<W, WS>(
+    &mut self,
+    handle: usize,
+    storage: &mut WS,
+    range: Range<usize>,
+  ) -> Result<(), VertexEntityError>
+  where
+    W: Vertex,
+    WS: AsVertexStorage<W>,
+  {
+    let mut st = self.state.borrow_mut();
+    let data = st.vertex_entities.get_mut(&handle).ok_or_else(|| {
+      VertexEntityError::UpdateVertexStorage {
+        cause: Some(Box::new(MockError::UnknownHandle { handle })),
+      }
+    })?;
+
+    data.instance_len = Self::storage_len(storage)
+      .and_then(|storage_len| Self::check_update(storage_len, &range, data.instance_shape))
+      .map_err(|e| VertexEntityError::UpdateVertexStorage {
+        cause: Some(Box::new(e)),
+      })?;
+
+    st.record(Command::UpdateInstanceData { handle, range });
+    Ok(())
+  }
+}
+
+unsafe impl FramebufferBackend for MockBackend {
+  unsafe fn new_render_layer<D, RC>(
+    &mut self,
+    framebuffer_handle: usize,
+    size: D::Size,
+    mipmaps: Mipmaps,
+    sampling: &TextureSampling,
+    index: usize,
+  ) -> Result<Texture<D, RC>, FramebufferError>
+  where
+    D: Dimensionable,
+    RC: RenderChannel,
+  {
+    let tex = self.reserve_texture(size, mipmaps, sampling).map_err(|e| {
+      FramebufferError::RenderLayerCreation {
+        cause: Some(Box::new(e)),
+      }
+    })?;
+
+    self.record(Command::NewRenderLayer {
+      framebuffer: framebuffer_handle,
+      texture: tex.handle(),
+      index,
+    });
+
+    Ok(tex)
+  }
+
+  unsafe fn new_depth_render_layer<D, DC>(
+    &mut self,
+    framebuffer_handle: usize,
+    size: D::Size,
+    mipmaps: Mipmaps,
+    sampling: &TextureSampling,
+  ) -> Result<Texture<D, DC>, FramebufferError>
+  where
+    D: Dimensionable,
+    DC: DepthChannel,
+  {
+    let tex = self.reserve_texture(size, mipmaps, sampling).map_err(|e| {
+      FramebufferError::DepthRenderLayerCreation {
+        cause: Some(Box::new(e)),
+      }
+    })?;
+
+    self.record(Command::NewDepthRenderLayer {
+      framebuffer: framebuffer_handle,
+      texture: tex.handle(),
+    });
+
+    Ok(tex)
+  }
+
+  unsafe fn new_framebuffer<D, RS, DS>(
+    &mut self,
+    size: D::Size,
+    mipmaps: Mipmaps,
+    sampling: &TextureSampling,
+  ) -> Result<Framebuffer<D, RS, DS>, FramebufferError>
+  where
+    D: Dimensionable,
+    RS: RenderSlots,
+    DS: DepthRenderSlot,
+  {
+    let handle = {
+      let mut st = self.state.borrow_mut();
+      let handle = st.next_handle();
+      st.framebuffers.insert(handle);
+      st.record(Command::NewFramebuffer {
+        handle,
+        size: flat_size::<D>(&size),
+      });
+      handle
+    };
+
+    let layers = RS::new_render_layers::<_, D>(self, handle, size, mipmaps, sampling)?;
+    let depth_layer = DS::new_depth_render_layer::<_, D>(self, handle, size, mipmaps, sampling)?;
+
+    let state = self.state.clone();
+    let dropper = Box::new(move |handle| {
+      state.borrow_mut().drop_framebuffer(handle);
+    });
+
+    Ok(Framebuffer::new(handle, size, layers, depth_layer, dropper))
+  }
+
+  unsafe fn back_buffer<D, RS, DS>(
+    &mut self,
+    size: D::Size,
+  ) -> Result<Framebuffer<D, Back<RS>, Back<DS>>, FramebufferError>
+  where
+    D: Dimensionable,
+    RS: RenderSlots,
+    DS: DepthRenderSlot,
+  {
+    self.record(Command::BackBuffer {
+      size: flat_size::<D>(&size),
+    });
+
+    Ok(Framebuffer::new(0, size, (), (), Box::new(|_| {})))
+  }
+}
+
+macro_rules! impl_uniform_visit {
+  ($( $name:ident, $t:ty, $v:ident, )*) => {
+    $(
+      fn $name(&mut self, uni: &Uni<$t>, value: &$t) -> Result<(), ShaderError> {
+        self.set_uniform(uni.handle(), UniformValue::$v(vec![*value]));
+        Ok(())
+      }
+    )*
+  };
+
+  ($( array $name:ident, $t:ty, $v:ident, )*) => {
+    $(
+      fn $name<const N: usize>(&mut self, uni: &Uni<[$t; N]>, value: &[$t; N]) -> Result<(), ShaderError> {
+        self.set_uniform(uni.handle(), UniformValue::$v(value.to_vec()));
+        Ok(())
+      }
+    )*
+  };
+
+  ($( vec $name:ident, $t:ty, $n:literal, $v:ident, )*) => {
+    $(
+      fn $name<T>(&mut self, uni: &Uni<T>, value: &[$t; $n]) -> Result<(), ShaderError> where T: AsRef<[$t; $n]> {
+        self.set_uniform(uni.handle(), UniformValue::$v(value.to_vec()));
+        Ok(())
+      }
+    )*
+  };
+
+  ($( mat $name:ident, $t:ty, $n:literal, $v:ident, )*) => {
+    $(
+      fn $name<T>(&mut self, uni: &Uni<T>, value: &[[$t; $n]; $n]) -> Result<(), ShaderError> where T: AsRef<[[$t; $n]; $n]> {
+        self.set_uniform(uni.handle(), UniformValue::$v(value.iter().flatten().copied().collect()));
+        Ok(())
+      }
+    )*
+  };
+}
+
+impl MockBackend {
+  /// Record a uniform being set; unbound uniforms are ignored.
+  fn set_uniform(&mut self, handle: usize, value: UniformValue) {
+    let mut st = self.state.borrow_mut();
+
+    if let Some((program, name)) = st.uniforms.get(&handle).cloned() {
+      st.record(Command::SetUniform {
+        program,
+        name,
+        value,
+      });
+    }
+  }
+}
+
+unsafe impl ShaderBackend for MockBackend {
+  unsafe fn new_program<V, W, P, S, E, F>(
+    &mut self,
+    vertex_code: String,
+    tess_control_code: String,
+    tess_eval_code: String,
+    primitive_code: String,
+    shading_code: String,
+  ) -> Result<Program<V, W, P, S, E, F>, ShaderError>
+  where
+    V: Vertex,
+    W: Vertex,
+    P: Primitive,
+    S: RenderSlots,
+    E: Uniforms,
+    F: Vertex,
+  {
+    let handle = {
+      let mut st = self.state.borrow_mut();
+      let handle = st.next_handle();
+      st.programs.insert(handle);
+      st.record(Command::NewProgram {
+        handle,
+        vertex_code,
+        tess_control_code,
+        tess_eval_code,
+        primitive_code,
+        shading_code,
+        captured_varyings: F::vertex_desc().iter().map(|desc| desc.name).collect(),
+      });
+      handle
+    };
+
+    let state = self.state.clone();
+    let dropper = Box::new(move |handle| {
+      state.borrow_mut().drop_program(handle);
+    });
+
+    let uniforms = E::build_uniforms(self, handle)?;
+
+    Ok(Program::new(handle, uniforms, dropper))
+  }
+
+  unsafe fn program_reflection(
+    &mut self,
+    program_handle: usize,
+  ) -> Result<ProgramReflection, ShaderError> {
+    if !self.state.borrow().programs.contains(&program_handle) {
+      return Err(ShaderError::Reflection {
+        handle: program_handle,
+        cause: Some(Box::new(MockError::UnknownHandle {
+          handle: program_handle,
+        })),
+      });
+    }
+
+    // shaders are never compiled, so nothing is known about what they use
+    Ok(ProgramReflection::default())
+  }
+
+  unsafe fn new_uni_buffer<T, Scheme>(
+    &mut self,
+    value: T::Aligned,
+  ) -> Result<UniBuffer<T, Scheme>, ShaderError>
+  where
+    T: MemoryLayout<Scheme>,
+  {
+    let len = (mem::size_of::<T::Aligned>() + mem::size_of::<u128>() - 1) / mem::size_of::<u128>();
+    let mut storage = vec![0u128; len.max(1)];
+    ptr::write(storage.as_mut_ptr() as *mut T::Aligned, value);
+
+    let mut st = self.state.borrow_mut();
+    let handle = st.next_handle();
+    st.uni_buffers.insert(handle, storage);
+    st.record(Command::NewUniBuffer { handle });
+
+    let state = self.state.clone();
+    let dropper = Box::new(move |handle| {
+      state.borrow_mut().drop_uni_buffer(handle);
+    });
+
+    Ok(UniBuffer::new(handle, dropper))
+  }
+
+  unsafe fn new_shader_uni<T>(&mut self, handle: usize, name: &str) -> Result<Uni<T>, ShaderError>
+  where
+    T: Uniform,
+  {
+    let mut st = self.state.borrow_mut();
+
+    if !st.programs.contains(&handle) {
+      return Err(ShaderError::UniCreation {
+        name: name.to_owned(),
+        cause: Some(Box::new(MockError::UnknownHandle { handle })),
+      });
+    }
+
+    // asking several times the same uniform yields the same handle
+    let existing = st
+      .uniforms
+      .iter()
+      .find(|(_, (program, uni_name))| *program == handle && uni_name == name)
+      .map(|(uni_handle, _)| *uni_handle);
+
+    let uni_handle = match existing {
+      Some(uni_handle) => uni_handle,
+
+      None => {
+        let uni_handle = st.next_handle();
+        st.uniforms.insert(uni_handle, (handle, name.to_owned()));
+        st.record(Command::NewUniform {
+          program: handle,
+          handle: uni_handle,
+          name: name.to_owned(),
+        });
+        uni_handle
+      }
+    };
+
+    Ok(Uni::new(uni_handle))
+  }
+
+  unsafe fn sync_uni_buffer<T, Scheme>(
+    &mut self,
+    uni_buffer_handle: usize,
+  ) -> Result<UniBufferRef<'_, Self, T, Scheme>, ShaderError>
+  where
+    T: MemoryLayout<Scheme>,
+  {
+    let ptr = {
+      let mut st = self.state.borrow_mut();
+      let ptr = st
+        .uni_buffers
+        .get_mut(&uni_buffer_handle)
+        .ok_or_else(|| ShaderError::NoData {
+          handle: uni_buffer_handle,
+        })?
+        .as_mut_ptr() as *mut T;
+
+      st.record(Command::SyncUniBuffer {
+        handle: uni_buffer_handle,
+      });
+      ptr
+    };
+
+    Ok(UniBufferRef::new(self, uni_buffer_handle, ptr))
+  }
+
+  unsafe fn unsync_uni_buffer<T, Scheme>(
+    &mut self,
+    uni_buffer_handle: usize,
+  ) -> Result<(), ShaderError>
+  where
+    T: MemoryLayout<Scheme>,
+  {
+    self.record(Command::UnsyncUniBuffer {
+      handle: uni_buffer_handle,
+    });
+    Ok(())
+  }
+
+  unsafe fn new_shader_uni_unbound<T>(&mut self, _: usize) -> Result<Uni<T>, ShaderError>
+  where
+    T: Uniform,
+  {
+    // 0 is never given away as a handle, so setting that uniform is ignored
+    Ok(Uni::new(0))
+  }
+
+  unsafe fn set_shader_uni<T>(
+    &mut self,
+    _: usize,
+    uni: &Uni<T>,
+    value: &T::Value,
+  ) -> Result<(), ShaderError>
+  where
+    T: Uniform,
+  {
+    T::set(self, uni, value)
+  }
+
+  impl_uniform_visit! {
+    visit_i32, i32, Integral,
+    visit_u32, u32, Unsigned,
+    visit_f32, f32, Floating,
+    visit_bool, bool, Boolean,
+  }
+
+  impl_uniform_visit! {
+    array visit_i32_array, i32, Integral,
+    array visit_u32_array, u32, Unsigned,
+    array visit_f32_array, f32, Floating,
+    array visit_bool_array, bool, Boolean,
+  }
+
+  impl_uniform_visit! {
+    vec visit_ivec2, i32, 2, Integral,
+    vec visit_uvec2, u32, 2, Unsigned,
+    vec visit_vec2, f32, 2, Floating,
+    vec visit_bvec2, bool, 2, Boolean,
+
+    vec visit_ivec3, i32, 3, Integral,
+    vec visit_uvec3, u32, 3, Unsigned,
+    vec visit_vec3, f32, 3, Floating,
+    vec visit_bvec3, bool, 3, Boolean,
+
+    vec visit_ivec4, i32, 4, Integral,
+    vec visit_uvec4, u32, 4, Unsigned,
+    vec visit_vec4, f32, 4, Floating,
+    vec visit_bvec4, bool, 4, Boolean,
+  }
+
+  impl_uniform_visit! {
+    mat visit_mat22, f32, 2, Floating,
+    mat visit_mat33, f32, 3, Floating,
+    mat visit_mat44, f32, 4, Floating,
+  }
+
+  #[cfg(feature = "shader-f64")]
+  impl_uniform_visit! {
+    visit_f64, f64, Floating64,
+  }
+
+  #[cfg(feature = "shader-f64")]
+  impl_uniform_visit! {
+    array visit_f64_array, f64, Floating64,
+  }
+
+  #[cfg(feature = "shader-f64")]
+  impl_uniform_visit! {
+    vec visit_dvec2, f64, 2, Floating64,
+    vec visit_dvec3, f64, 3, Floating64,
+    vec visit_dvec4, f64, 4, Floating64,
+  }
+
+  #[cfg(feature = "shader-f64")]
+  impl_uniform_visit! {
+    mat visit_dmat22, f64, 2, Floating64,
+    mat visit_dmat33, f64, 3, Floating64,
+    mat visit_dmat44, f64, 4, Floating64,
+  }
+
+  fn visit_texture<D, P>(
+    &mut self,
+    uni: &Uni<InUseTexture<D, P>>,
+    value: &InUseTexture<D, P>,
+  ) -> Result<(), ShaderError>
+  where
+    D: Dimensionable,
+    P: PixelType,
+  {
+    self.set_uniform(uni.handle(), UniformValue::Texture(value.handle()));
+    Ok(())
+  }
+
+  fn visit_uni_buffer<T, Scheme>(
+    &mut self,
+    uni: &Uni<UniBuffer<T, Scheme>>,
+    value: &InUseUniBuffer<T, Scheme>,
+  ) -> Result<(), ShaderError>
+  where
+    T: MemoryLayout<Scheme>,
+  {
+    self.set_uniform(uni.handle(), UniformValue::UniBuffer(value.handle()));
+    Ok(())
+  }
+
+  unsafe fn use_uni_buffer<T, Scheme>(
+    &mut self,
+    handle: usize,
+  ) -> Result<InUseUniBuffer<T, Scheme>, ShaderError>
+  where
+    T: MemoryLayout<Scheme>,
+  {
+    let mut st = self.state.borrow_mut();
+
+    // buffers can be used as uniform buffers as well
+    if !st.uni_buffers.contains_key(&handle) && !st.buffers.contains_key(&handle) {
+      return Err(ShaderError::NoData { handle });
+    }
+
+    st.record(Command::UseUniBuffer { handle });
+
+    // there is no binding point; the handle of the uniform buffer is used instead
+    Ok(InUseUniBuffer::new(handle, Box::new(|_| {})))
+  }
+}
+
+unsafe impl TextureBackend for MockBackend {
+  unsafe fn reserve_texture<D, P>(
+    &mut self,
+    size: D::Size,
+    mipmaps: Mipmaps,
+    _: &TextureSampling,
+  ) -> Result<Texture<D, P>, TextureError>
+  where
+    D: Dimensionable,
+    P: Pixel,
+  {
+    let flat_size = flat_size::<D>(&size);
+
+    let mut st = self.state.borrow_mut();
+    let handle = st.next_handle();
+    st.textures
+      .insert(handle, TextureData::new(D::dim(), flat_size, P::PIXEL_FMT));
+    st.record(Command::NewTexture {
+      handle,
+      dim: D::dim(),
+      size: flat_size,
+      format: P::PIXEL_FMT,
+      mipmaps,
+    });
+
+    let state = self.state.clone();
+    let dropper = Box::new(move |handle| {
+      state.borrow_mut().drop_texture(handle);
+    });
+
+    Ok(Texture::new(handle, dropper, size))
+  }
+
+  unsafe fn new_texture<D, P>(
+    &mut self,
+    size: D::Size,
+    mipmaps: Mipmaps,
+    sampling: &TextureSampling,
+    texels: &[P::RawEncoding],
+  ) -> Result<Texture<D, P>, TextureError>
+  where
+    D: Dimensionable,
+    P: Pixel,
+  {
+    let tex = self.reserve_texture(size, mipmaps, sampling)?;
+    let gen_mipmaps = match mipmaps {
+      Mipmaps::No => false,
+      Mipmaps::Yes { .. } => true,
+    };
+    self.set_texture_data::<D, P>(tex.handle(), D::ZERO_OFFSET, size, gen_mipmaps, texels, 0)?;
+
+    Ok(tex)
+  }
+
+  unsafe fn resize_texture<D, P>(
+    &mut self,
+    handle: usize,
+    size: D::Size,
+    mipmaps: Mipmaps,
+  ) -> Result<(), TextureError>
+  where
+    D: Dimensionable,
+    P: Pixel,
+  {
+    let flat_size = flat_size::<D>(&size);
+
+    let mut st = self.state.borrow_mut();
+    let data = st
+      .textures
+      .get_mut(&handle)
+      .ok_or_else(|| TextureError::NoData { handle })?;
+
+    // resizing a texture loses its content
+    *data = TextureData::new(data.dim, flat_size, data.format);
+    st.record(Command::ResizeTexture {
+      handle,
+      size: flat_size,
+      mipmaps,
+    });
+
+    Ok(())
+  }
+
+  unsafe fn set_texture_data<D, P>(
+    &mut self,
+    handle: usize,
+    offset: D::Offset,
+    size: D::Size,
+    gen_mipmaps: bool,
+    texels: &[P::RawEncoding],
+    level: usize,
+  ) -> Result<(), TextureError>
+  where
+    D: Dimensionable,
+    P: Pixel,
+  {
+    let flat_offset = flat_offset::<D>(&offset);
+    let flat_size = flat_size::<D>(&size);
+
+    let mut st = self.state.borrow_mut();
+    let data = st
+      .textures
+      .get_mut(&handle)
+      .ok_or_else(|| TextureError::NoData { handle })?;
+
+    let bytes = texels_as_bytes(texels);
+    let expected_bytes = TextureData::bytes_len(flat_size, data.format);
+    if bytes.len() < expected_bytes {
+      return Err(TextureError::NotEnoughPixels {
+        expected_bytes,
+        provided_bytes: bytes.len(),
+        cause: None,
+      });
+    }
+
+    // only the base level is kept around
+    if level == 0 {
+      data
+        .write(flat_offset, flat_size, bytes)
+        .map_err(|e| TextureError::CannotUploadTexels {
+          cause: Some(Box::new(e)),
+        })?;
+    }
+
+    st.record(Command::SetTextureData {
+      handle,
+      offset: flat_offset,
+      size: flat_size,
+      level,
+      gen_mipmaps,
+    });
+
+    Ok(())
+  }
+
+  unsafe fn clear_texture_data<D, P>(
+    &mut self,
+    handle: usize,
+    offset: D::Offset,
+    size: D::Size,
+    _: bool,
+    clear_value: P::RawEncoding,
+  ) -> Result<(), TextureError>
+  where
+    D: Dimensionable,
+    P: Pixel,
+  {
+    let flat_offset = flat_offset::<D>(&offset);
+    let flat_size = flat_size::<D>(&size);
+
+    let mut st = self.state.borrow_mut();
+    let data = st
+      .textures
+      .get_mut(&handle)
+      .ok_or_else(|| TextureError::NoData { handle })?;
+
+    let pixel_len = data.format.format.bytes_len();
+    let clear_value = texels_as_bytes(std::slice::from_ref(&clear_value));
+    let [width, height, depth] = flat_size;
+    let texels: Vec<u8> = clear_value
+      .iter()
+      .copied()
+      .cycle()
+      .take(width as usize * height as usize * depth as usize * pixel_len)
+      .collect();
+
+    data
+      .write(flat_offset, flat_size, &texels)
+      .map_err(|e| TextureError::CannotUploadTexels {
+        cause: Some(Box::new(e)),
+      })?;
+
+    st.record(Command::ClearTextureData {
+      handle,
+      offset: flat_offset,
+      size: flat_size,
+    });
+
+    Ok(())
+  }
+
+  unsafe fn read_texture<D, P>(
+    &mut self,
+    handle: usize,
+  ) -> Result<Vec<P::RawEncoding>, TextureError>
+  where
+    D: Dimensionable,
+    P: Pixel,
+  {
+    let mut st = self.state.borrow_mut();
+    let data = st
+      .textures
+      .get(&handle)
+      .ok_or_else(|| TextureError::NoData { handle })?;
+
+    let len = data.texels.len() / mem::size_of::<P::RawEncoding>().max(1);
+    let mut texels = vec![P::RawEncoding::default(); len];
+    ptr::copy_nonoverlapping(
+      data.texels.as_ptr(),
+      texels.as_mut_ptr() as *mut u8,
+      len * mem::size_of::<P::RawEncoding>(),
+    );
+
+    st.record(Command::ReadTexture { handle });
+
+    Ok(texels)
+  }
+
+  unsafe fn use_texture<D, P>(&mut self, handle: usize) -> Result<InUseTexture<D, P>, TextureError>
+  where
+    D: Dimensionable,
+    P: PixelType,
+  {
+    let mut st = self.state.borrow_mut();
+
+    if !st.textures.contains_key(&handle) {
+      return Err(TextureError::NoData { handle });
+    }
+
+    st.record(Command::UseTexture { handle });
+
+    // there is no texture unit; the handle of the texture is used instead
+    Ok(InUseTexture::new(handle, Box::new(|_| {})))
+  }
+}
+
+unsafe impl PipelineBackend for MockBackend {
+  unsafe fn with_framebuffer<D, CS, DS, Err>(
+    &mut self,
+    framebuffer: &Framebuffer<D, CS, DS>,
+    pipeline_state: &PipelineState,
+    f: impl for<'a> FnOnce(WithFramebuffer<'a, Self, CS>) -> Result<(), Err>,
+  ) -> Result<(), Err>
+  where
+    D: Dimensionable,
+    CS: RenderSlots,
+    DS: DepthRenderSlot,
+    Err: From<PipelineError>,
+  {
+    let handle = framebuffer.handle();
+
+    {
+      let mut st = self.state.borrow_mut();
+
+      if handle != 0 && !st.framebuffers.contains(&handle) {
+        return Err(
+          PipelineError::WithFramebuffer {
+            pipeline_state: pipeline_state.clone(),
+            cause: Some(Box::new(MockError::UnknownHandle { handle })),
+          }
+          .into(),
+        );
+      }
+
+      st.record(Command::WithFramebuffer {
+        handle,
+        pipeline_state: pipeline_state.clone(),
+      });
+    }
+
+    f(WithFramebuffer::new(self))
+  }
+
+  unsafe fn with_program<V, W, P, S, E, F, Err>(
+    &mut self,
+    program: &Program<V, W, P, S, E, F>,
+    f: impl for<'a> FnOnce(WithProgram<'a, Self, V, W, P, S, E, F>) -> Result<(), Err>,
+  ) -> Result<(), Err>
+  where
+    V: Vertex,
+    W: Vertex,
+    P: Primitive,
+    S: RenderSlots,
+    E: Uniforms,
+    F: Vertex,
+    Err: From<PipelineError>,
+  {
+    let handle = program.handle();
+
+    {
+      let mut st = self.state.borrow_mut();
+
+      if !st.programs.contains(&handle) {
+        return Err(
+          PipelineError::WithProgram {
+            cause: Some(Box::new(MockError::UnknownHandle { handle })),
+          }
+          .into(),
+        );
+      }
+
+      st.record(Command::WithProgram { handle });
+    }
+
+    f(WithProgram::new(self, program))
+  }
+
+  unsafe fn with_render_state<V, W, P, F, Err>(
+    &mut self,
+    render_state: &RenderState,
+    f: impl for<'a> FnOnce(WithRenderState<'a, Self, V, W, P, F>) -> Result<(), Err>,
+  ) -> Result<(), Err>
+  where
+    V: Vertex,
+    W: Vertex,
+    P: Primitive,
+    F: Vertex,
+    Err: From<PipelineError>,
+  {
+    self.record(Command::WithRenderState {
+      render_state: render_state.clone(),
+    });
+
+    f(WithRenderState::new(self))
+  }
+
+  unsafe fn render_vertex_entity<V, W, P>(
+    &mut self,
+    view: VertexEntityView<V, W, P>,
+  ) -> Result<(), PipelineError>
+  where
+    V: Vertex,
+    W: Vertex,
+    P: Primitive,
+  {
+    self
+      .vertex_entity_render::<V, P>(
+        view.handle(),
+        view.start_vertex(),
+        view.vertex_count(),
+        view.instance_count(),
+        view.base_vertex(),
+        view.base_instance(),
+      )
+      .map_err(|e| PipelineError::RenderVertexEntity {
+        start_vertex: view.start_vertex(),
+        vertex_count: view.vertex_count(),
+        instance_count: view.instance_count(),
+        cause: Some(Box::new(e)),
+      })
+  }
+  unsafe fn render_vertex_entity_batch<V, W, P>(
+    &mut self,
+    views: &[VertexEntityView<V, W, P>],
+  ) -> Result<(), PipelineError>
+  where
+    V: Vertex,
+    W: Vertex,
+    P: Primitive,
+  {
+    self
+      .vertex_entity_render_batch(views)
+      .map_err(|e| PipelineError::RenderVertexEntityBatch {
+        view_count: views.len(),
+        cause: Some(Box::new(e)),
+      })
+  }
+
+  unsafe fn render_vertex_entity_with_feedback<V, W, P, F>(
+    &mut self,
+    view: VertexEntityView<V, W, P>,
+    buffer_handle: usize,
+  ) -> Result<(), PipelineError>
+  where
+    V: Vertex,
+    W: Vertex,
+    P: Primitive,
+    F: Vertex,
+  {
+    {
+      let mut st = self.state.borrow_mut();
+
+      // indirect buffers receive captured commands
+      if !st.buffers.contains_key(&buffer_handle)
+        && !st.indirect_buffers.contains_key(&buffer_handle)
+      {
+        return Err(PipelineError::TransformFeedback {
+          cause: Some(Box::new(MockError::UnknownHandle {
+            handle: buffer_handle,
+          })),
+        });
+      }
+
+      st.record(Command::RenderFeedback {
+        buffer: buffer_handle,
+      });
+    }
+
+    self.render_vertex_entity(view)
+  }
+}
+
+unsafe impl BufferBackend for MockBackend {
+  unsafe fn new_buffer<T>(&mut self, values: &[T], usage: Usage) -> Result<Buffer<T>, BufferError>
+  where
+    T: Copy,
+  {
+    let mut st = self.state.borrow_mut();
+    let handle = st.next_handle();
+    st.buffers.insert(handle, BufferData::new(values));
+    st.record(Command::NewBuffer {
+      handle,
+      len: values.len(),
+      usage,
+    });
+
+    let state = self.state.clone();
+    let dropper = Box::new(move |handle| state.borrow_mut().drop_buffer(handle));
+
+    Ok(Buffer::new(handle, values.len(), dropper))
+  }
+
+  unsafe fn buffer_update<T>(
+    &mut self,
+    handle: usize,
+    start: usize,
+    values: &[T],
+  ) -> Result<(), BufferError>
+  where
+    T: Copy,
+  {
+    let mut st = self.state.borrow_mut();
+    let buffer = st
+      .buffers
+      .get_mut(&handle)
+      .ok_or(BufferError::NoData { handle })?;
+    let range = start..start + values.len();
+
+    if range.end > buffer.len {
+      return Err(BufferError::Update {
+        cause: Some(Box::new(MockError::OutOfBounds {
+          start,
+          count: values.len(),
+          len: buffer.len,
+        })),
+      });
+    }
+
+    ptr::copy_nonoverlapping(
+      values.as_ptr(),
+      buffer.as_mut_ptr::<T>().add(start),
+      values.len(),
+    );
+    st.record(Command::UpdateBuffer { handle, range });
+    Ok(())
+  }
+
+  unsafe fn buffer_read<T>(&mut self, handle: usize, len: usize) -> Result<Vec<T>, BufferError>
+  where
+    T: Copy,
+  {
+    let mut st = self.state.borrow_mut();
+    let buffer = st
+      .buffers
+      .get(&handle)
+      .ok_or(BufferError::NoData { handle })?;
+
+    if len > buffer.len {
+      return Err(BufferError::Read {
+        cause: Some(Box::new(MockError::OutOfBounds {
+          start: 0,
+          count: len,
+          len: buffer.len,
+        })),
+      });
+    }
+
+    let values = std::slice::from_raw_parts(buffer.as_ptr::<T>(), len).to_vec();
+    st.record(Command::ReadBuffer { handle });
+    Ok(values)
+  }
+
+  unsafe fn buffer_map<T>(
+    &mut self,
+    handle: usize,
+    len: usize,
+  ) -> Result<BufferSliceMut<'_, Self, T>, BufferError>
+  where
+    T: Copy,
+  {
+    let ptr = {
+      let mut st = self.state.borrow_mut();
+      let buffer = st
+        .buffers
+        .get_mut(&handle)
+        .ok_or(BufferError::NoData { handle })?;
+
+      if len > buffer.len {
+        return Err(BufferError::Map {
+          cause: Some(Box::new(MockError::OutOfBounds {
+            start: 0,
+            count: len,
+            len: buffer.len,
+          })),
+        });
+      }
+
+      let ptr = buffer.as_mut_ptr::<T>();
+      st.record(Command::MapBuffer { handle });
+      ptr
+    };
+
+    Ok(BufferSliceMut::new(self, handle, ptr, len))
+  }
+
+  unsafe fn buffer_unmap<T>(&mut self, handle: usize) -> Result<(), BufferError> {
+    let mut st = self.state.borrow_mut();
+
+    if !st.buffers.contains_key(&handle) {
+      return Err(BufferError::NoData { handle });
+    }
+
+    st.record(Command::UnmapBuffer { handle });
+    Ok(())
+  }
+}
+
+unsafe impl IndirectBufferBackend for MockBackend {
+  unsafe fn new_indirect_buffer<C>(
+    &mut self,
+    commands: &[C],
+  ) -> Result<IndirectBuffer<C>, IndirectBufferError>
+  where
+    C: IndirectCommand,
+  {
+    let mut st = self.state.borrow_mut();
+    let handle = st.next_handle();
+    st.indirect_buffers
+      .insert(handle, BufferData::new(commands));
+    st.record(Command::NewIndirectBuffer {
+      handle,
+      len: commands.len(),
+    });
+
+    let state = self.state.clone();
+    let dropper = Box::new(move |handle| state.borrow_mut().drop_indirect_buffer(handle));
+
+    Ok(IndirectBuffer::new(handle, commands.len(), dropper))
+  }
+
+  unsafe fn indirect_buffer_update<C>(
+    &mut self,
+    handle: usize,
+    start: usize,
+    commands: &[C],
+  ) -> Result<(), IndirectBufferError>
+  where
+    C: IndirectCommand,
+  {
+    let mut st = self.state.borrow_mut();
+    let buffer = st
+      .indirect_buffers
+      .get_mut(&handle)
+      .ok_or(IndirectBufferError::NoData { handle })?;
+    let range = start..start + commands.len();
+
+    if range.end > buffer.len {
+      return Err(IndirectBufferError::Update {
+        cause: Some(Box::new(MockError::OutOfBounds {
+          start,
+          count: commands.len(),
+          len: buffer.len,
+        })),
+      });
+    }
+
+    buffer.as_mut_slice::<C>()[range.clone()].copy_from_slice(commands);
+    st.record(Command::UpdateIndirectBuffer { handle, range });
+    Ok(())
+  }
+
+  unsafe fn indirect_buffer_read<C>(&mut self, handle: usize) -> Result<Vec<C>, IndirectBufferError>
+  where
+    C: IndirectCommand,
+  {
+    let mut st = self.state.borrow_mut();
+    let commands = st
+      .indirect_buffers
+      .get(&handle)
+      .ok_or(IndirectBufferError::NoData { handle })?
+      .as_slice::<C>()
+      .to_vec();
+
+    st.record(Command::ReadIndirectBuffer { handle });
+    Ok(commands)
+  }
+
+  unsafe fn vertex_entity_render_indirect<V, P, C>(
+    &mut self,
+    entity_handle: usize,
+    indirect_handle: usize,
+    commands: Range<usize>,
+  ) -> Result<(), IndirectBufferError>
+  where
+    V: Vertex,
+    P: Primitive,
+    C: IndirectCommand,
+  {
+    let draw_commands = {
+      let mut st = self.state.borrow_mut();
+      let buffer =
+        st.indirect_buffers
+          .get(&indirect_handle)
+          .ok_or(IndirectBufferError::NoData {
+            handle: indirect_handle,
+          })?;
+      let draw_commands = buffer
+        .as_slice::<C>()
+        .get(commands.clone())
+        .ok_or_else(|| IndirectBufferError::Render {
+          cause: Some(Box::new(MockError::OutOfBounds {
+            start: commands.start,
+            count: commands.len(),
+            len: buffer.len,
+          })),
+        })?
+        .to_vec();
+
+      st.record(Command::RenderIndirect {
+        handle: entity_handle,
+        indirect_buffer: indirect_handle,
+        range: commands,
+      });
+
+      draw_commands
+    };
+
+    for command in draw_commands {
+      self
+        .vertex_entity_render::<V, P>(
+          entity_handle,
+          command.first() as _,
+          command.count() as _,
+          command.instance_count() as _,
+          command.base_vertex() as _,
+          command.base_instance() as _,
+        )
+        .map_err(|e| IndirectBufferError::Render {
+          cause: Some(Box::new(e)),
+        })?;
+    }
+
+    Ok(())
+  }
+}
+
+unsafe impl QueryBackend for MockBackend {
+  fn backend_author(&self) -> Result<String, QueryError> {
+    Ok("luminance".to_owned())
+  }
+
+  fn backend_name(&self) -> Result<String, QueryError> {
+    Ok("mock".to_owned())
+  }
+
+  fn backend_version(&self) -> Result<String, QueryError> {
+    Ok(env!("CARGO_PKG_VERSION").to_owned())
+  }
+
+  fn backend_shading_lang_version(&self) -> Result<String, QueryError> {
+    // no shader is ever compiled
+    Err(QueryError::NoBackendShadingLanguageVersion)
+  }
+}
@@ -0,0 +1,138 @@
+//! GPU buffers.
+//!
+//! A [`Buffer`] is a typed array living in GPU memory. Its content can be updated, mapped and read back from the CPU,
+//! and the same buffer can be used by several vertex entities at once — as vertices, instance data or indices, see
+//! [`Shared`] — or as the content of a uniform buffer, with [`Context::use_buffer_as_uniform_buffer`]. Buffers can also
+//! receive vertices captured with transform feedback, see [`WithRenderState::render_vertex_entity_with_feedback`].
+//!
+//! The length of a buffer is set at creation and never changes, so that vertex entities referencing it remain valid.
+//! Vertex entities referencing a buffer also keep it alive: the GPU buffer is deleted when the [`Buffer`] and all the
+//! vertex entities using it are dropped.
+//!
+//! [`Shared`]: crate::vertex_storage::Shared
+//! [`Context::use_buffer_as_uniform_buffer`]: crate::context::Context::use_buffer_as_uniform_buffer
+//! [`WithRenderState::render_vertex_entity_with_feedback`]: crate::pipeline::WithRenderState::render_vertex_entity_with_feedback
+
+use crate::{backend::BufferBackend, pipeline::FeedbackTarget};
+use std::{
+  marker::PhantomData,
+  ops::{Deref, DerefMut},
+  rc::Rc,
+  slice,
+};
+
+/// A typed array living in GPU memory.
+pub struct Buffer<T> {
+  handle: usize,
+  len: usize,
+  resource: Rc<BufferResource>,
+  _phantom: PhantomData<*const T>,
+}
+
+impl<T> Buffer<T> {
+  pub unsafe fn new(handle: usize, len: usize, dropper: Box<dyn FnMut(usize)>) -> Self {
+    Self {
+      handle,
+      len,
+      resource: Rc::new(BufferResource { handle, dropper }),
+      _phantom: PhantomData,
+    }
+  }
+
+  pub fn handle(&self) -> usize {
+    self.handle
+  }
+
+  /// Number of values in the buffer.
+  pub fn len(&self) -> usize {
+    self.len
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+}
+
+impl<T> FeedbackTarget<T> for Buffer<T> {
+  fn feedback_buffer_handle(&self) -> usize {
+    self.handle
+  }
+}
+
+/// GPU resource of a [`Buffer`], deleted once the buffer and all the vertex entities using it are dropped.
+pub(crate) struct BufferResource {
+  handle: usize,
+  dropper: Box<dyn FnMut(usize)>,
+}
+
+impl BufferResource {
+  /// Share the resource of a buffer.
+  pub(crate) fn share<T>(buffer: &Buffer<T>) -> Rc<Self> {
+    buffer.resource.clone()
+  }
+}
+
+impl Drop for BufferResource {
+  fn drop(&mut self) {
+    (self.dropper)(self.handle)
+  }
+}
+
+/// Mapped content of a [`Buffer`].
+///
+/// The buffer is unmapped when dropped, making the changes visible to the GPU.
+#[derive(Debug)]
+pub struct BufferSliceMut<'a, B, T>
+where
+  B: ?Sized + BufferBackend,
+{
+  backend: &'a mut B,
+  buffer_handle: usize,
+  ptr: *mut T,
+  len: usize,
+}
+
+impl<'a, B, T> BufferSliceMut<'a, B, T>
+where
+  B: ?Sized + BufferBackend,
+{
+  pub unsafe fn new(backend: &'a mut B, buffer_handle: usize, ptr: *mut T, len: usize) -> Self {
+    Self {
+      backend,
+      buffer_handle,
+      ptr,
+      len,
+    }
+  }
+}
+
+impl<'a, B, T> Drop for BufferSliceMut<'a, B, T>
+where
+  B: ?Sized + BufferBackend,
+{
+  fn drop(&mut self) {
+    unsafe {
+      let _ = self.backend.buffer_unmap::<T>(self.buffer_handle);
+    }
+  }
+}
+
+impl<'a, B, T> Deref for BufferSliceMut<'a, B, T>
+where
+  B: ?Sized + BufferBackend,
+{
+  type Target = [T];
+
+  fn deref(&self) -> &Self::Target {
+    unsafe { slice::from_raw_parts(self.ptr, self.len) }
+  }
+}
+
+impl<'a, B, T> DerefMut for BufferSliceMut<'a, B, T>
+where
+  B: ?Sized + BufferBackend,
+{
+  fn deref_mut(&mut self) -> &mut Self::Target {
+    unsafe { slice::from_raw_parts_mut(self.ptr, self.len) }
+  }
+}
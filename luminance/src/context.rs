@@ -1,24 +1,30 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{
+  cell::RefCell,
+  ops::{Bound, Range, RangeBounds},
+  rc::Rc,
+};
 
 use crate::{
   backend::{
-    Backend, FramebufferError, PipelineError, QueryError, ShaderBackend, ShaderError, TextureError,
-    VertexEntityError,
+    Backend, BufferError, FramebufferError, IndirectBufferError, PipelineError, QueryError,
+    ShaderBackend, ShaderError, TextureError, VertexEntityError,
   },
+  buffer::{Buffer, BufferSliceMut},
   dim::Dimensionable,
   framebuffer::{Back, Framebuffer},
+  indirect::{IndirectBuffer, IndirectCommand},
   pipeline::{PipelineState, WithFramebuffer},
   pixel::Pixel,
   primitive::Primitive,
   render_slots::{DepthRenderSlot, RenderSlots},
   shader::{
-    InUseUniBuffer, MemoryLayout, Program, ProgramBuilder, ProgramUpdate, UniBuffer, UniBufferRef,
-    Uniforms,
+    reflection::ProgramReflection, InUseUniBuffer, MemoryLayout, Program, ProgramBuilder,
+    ProgramUpdate, UniBuffer, UniBufferRef, Uniforms,
   },
   texture::{InUseTexture, Mipmaps, Texture, TextureSampling},
-  vertex::Vertex,
-  vertex_entity::{VertexEntity, VertexEntityBuilder},
-  vertex_storage::VertexStorageFamily,
+  vertex::{Deinterleave, Vertex},
+  vertex_entity::{BuilderPrimitive, Index, Usage, VertexEntity, VertexEntityBuilder},
+  vertex_storage::{AsVertexStorage, Deinterleaving, VertexStorage, VertexStorageFamily},
 };
 
 #[derive(Clone, Debug)]
@@ -57,6 +63,11 @@ where
     })
   }
 
+  /// Access the underlying backend.
+  pub fn backend(&self) -> &B {
+    &self.backend
+  }
+
   pub fn backend_author(&self) -> Result<String, QueryError> {
     self.backend.backend_author()
   }
@@ -73,23 +84,28 @@ where
     self.backend.backend_shading_lang_version()
   }
 
-  pub fn new_vertex_entity<V, P, VSF, W, WSF>(
+  /// Create a vertex entity of primitive `P`.
+  ///
+  /// The builder must be able to build vertex entities of `P` (see [`BuilderPrimitive`]).
+  pub fn new_vertex_entity<V, P, VSF, W, WSF, I, BP>(
     &mut self,
-    builder: VertexEntityBuilder<VSF::Storage<V>, WSF::Storage<W>>,
-  ) -> Result<VertexEntity<V, P, VSF, W, WSF>, VertexEntityError>
+    builder: VertexEntityBuilder<VSF::Storage<V>, WSF::Storage<W>, I, BP>,
+  ) -> Result<VertexEntity<V, P, VSF, W, WSF, I>, VertexEntityError>
   where
     V: Vertex,
     P: Primitive,
     VSF: VertexStorageFamily,
     W: Vertex,
     WSF: VertexStorageFamily,
+    I: Index,
+    BP: BuilderPrimitive<P>,
   {
-    unsafe { self.backend.new_vertex_entity(builder) }
+    unsafe { self.backend.new_vertex_entity(builder.any_primitive()) }
   }
 
-  pub fn update_vertices<V, P, VSF, W, WSF>(
+  pub fn update_vertices<V, P, VSF, W, WSF, I>(
     &mut self,
-    entity: &mut VertexEntity<V, P, VSF, W, WSF>,
+    entity: &mut VertexEntity<V, P, VSF, W, WSF, I>,
   ) -> Result<(), VertexEntityError>
   where
     V: Vertex,
@@ -97,17 +113,82 @@ where
     VSF: VertexStorageFamily,
     W: Vertex,
     WSF: VertexStorageFamily,
+    I: Index,
   {
+    self.update_vertices_range(entity, ..)
+  }
+
+  /// Update only the vertices in `range`.
+  ///
+  /// This is useful to stream a few changes out of large vertex entities, as only the updated vertices are sent to the
+  /// backend.
+  ///
+  /// The vertices can be added or removed from the storage before updating. If the storage has outgrown the memory
+  /// allocated by the backend, the memory is reallocated and the whole storage is sent to the backend, whatever
+  /// `range`.
+  pub fn update_vertices_range<V, P, VSF, W, WSF, I>(
+    &mut self,
+    entity: &mut VertexEntity<V, P, VSF, W, WSF, I>,
+    range: impl RangeBounds<usize>,
+  ) -> Result<(), VertexEntityError>
+  where
+    V: Vertex,
+    P: Primitive,
+    VSF: VertexStorageFamily,
+    W: Vertex,
+    WSF: VertexStorageFamily,
+    I: Index,
+  {
+    let handle = entity.handle();
+    let storage = entity.vertices();
+    let range = resolve_shared_range(storage.as_vertex_storage(), range)?;
+
     unsafe {
       self
         .backend
-        .vertex_entity_update_vertices(entity.handle(), entity.vertices())
+        .vertex_entity_update_vertices(handle, storage, range)?;
     }
+
+    entity.update_vertex_count();
+    Ok(())
   }
 
-  pub fn update_indices<V, P, VSF, W, WSF>(
+  /// Update a single attribute of a vertex entity with deinterleaved vertices.
+  ///
+  /// Only the buffer of the `NAME` attribute is sent to the backend, which is useful when some attributes change often
+  /// while others remain static, such as animated colors of static positions. Components are typically edited with
+  /// [`Deinterleaved::components_mut`] beforehand.
+  ///
+  /// The number of vertices must not change, as the other attributes are not updated; use [`Context::update_vertices`]
+  /// to resize the storage.
+  ///
+  /// [`Deinterleaved::components_mut`]: crate::vertex_storage::Deinterleaved::components_mut
+  pub fn update_vertex_attribute<const NAME: &'static str, V, P, W, WSF, I>(
     &mut self,
-    entity: &mut VertexEntity<V, P, VSF, W, WSF>,
+    entity: &mut VertexEntity<V, P, Deinterleaving, W, WSF, I>,
+  ) -> Result<(), VertexEntityError>
+  where
+    V: Vertex + Deinterleave<NAME>,
+    P: Primitive,
+    W: Vertex,
+    WSF: VertexStorageFamily,
+    I: Index,
+  {
+    let handle = entity.handle();
+    let storage = entity.vertices();
+
+    unsafe {
+      self.backend.vertex_entity_update_vertex_attribute(
+        handle,
+        storage,
+        <V as Deinterleave<NAME>>::RANK,
+      )
+    }
+  }
+
+  pub fn update_indices<V, P, VSF, W, WSF, I>(
+    &mut self,
+    entity: &mut VertexEntity<V, P, VSF, W, WSF, I>,
   ) -> Result<(), VertexEntityError>
   where
     V: Vertex,
@@ -115,17 +196,69 @@ where
     VSF: VertexStorageFamily,
     W: Vertex,
     WSF: VertexStorageFamily,
+    I: Index,
   {
+    self.update_indices_range(entity, ..)
+  }
+
+  /// Update only the indices in `range`.
+  ///
+  /// As with [`Context::update_vertices_range`], indices can be added or removed before updating. Removing all the
+  /// indices switches the vertex entity to direct (non-indexed) rendering, and adding indices to a vertex entity
+  /// created without any switches it to indexed rendering.
+  pub fn update_indices_range<V, P, VSF, W, WSF, I>(
+    &mut self,
+    entity: &mut VertexEntity<V, P, VSF, W, WSF, I>,
+    range: impl RangeBounds<usize>,
+  ) -> Result<(), VertexEntityError>
+  where
+    V: Vertex,
+    P: Primitive,
+    VSF: VertexStorageFamily,
+    W: Vertex,
+    WSF: VertexStorageFamily,
+    I: Index,
+  {
+    if entity.index_buffer().is_some() {
+      return Err(VertexEntityError::SharedBuffer);
+    }
+
+    let handle = entity.handle();
+    let indices = entity.indices();
+    let range = resolve_range(range, indices.len())?;
+
     unsafe {
       self
         .backend
-        .vertex_entity_update_indices(entity.handle(), entity.indices())
+        .vertex_entity_update_indices(handle, indices, range)?;
     }
+
+    entity.update_vertex_count();
+    Ok(())
+  }
+
+  pub fn update_instance_data<V, P, VSF, W, WSF, I>(
+    &mut self,
+    entity: &mut VertexEntity<V, P, VSF, W, WSF, I>,
+  ) -> Result<(), VertexEntityError>
+  where
+    V: Vertex,
+    P: Primitive,
+    VSF: VertexStorageFamily,
+    W: Vertex,
+    WSF: VertexStorageFamily,
+    I: Index,
+  {
+    self.update_instance_data_range(entity, ..)
   }
 
-  pub fn update_instance_data<V, P, VSF, W, WSF>(
+  /// Update only the instances in `range`.
+  ///
+  /// As with [`Context::update_vertices_range`], instances can be added or removed before updating.
+  pub fn update_instance_data_range<V, P, VSF, W, WSF, I>(
     &mut self,
-    entity: &mut VertexEntity<V, P, VSF, W, WSF>,
+    entity: &mut VertexEntity<V, P, VSF, W, WSF, I>,
+    range: impl RangeBounds<usize>,
   ) -> Result<(), VertexEntityError>
   where
     V: Vertex,
@@ -133,14 +266,132 @@ where
     VSF: VertexStorageFamily,
     W: Vertex,
     WSF: VertexStorageFamily,
+    I: Index,
   {
+    let handle = entity.handle();
+    let storage = entity.instance_data();
+    let range = resolve_shared_range(storage.as_vertex_storage(), range)?;
+
     unsafe {
       self
         .backend
-        .vertex_entity_update_instance_data(entity.handle(), entity.instance_data())
+        .vertex_entity_update_instance_data(handle, storage, range)
     }
   }
 
+  /// Create an indirect buffer holding a copy of `commands`.
+  ///
+  /// Vertex entities with indices are rendered with [`DrawCommand`]s, and vertex entities without indices with
+  /// [`DrawArraysCommand`]s.
+  ///
+  /// [`DrawCommand`]: crate::indirect::DrawCommand
+  /// [`DrawArraysCommand`]: crate::indirect::DrawArraysCommand
+  pub fn new_indirect_buffer<C>(
+    &mut self,
+    commands: &[C],
+  ) -> Result<IndirectBuffer<C>, IndirectBufferError>
+  where
+    C: IndirectCommand,
+  {
+    unsafe { self.backend.new_indirect_buffer(commands) }
+  }
+
+  /// Update the commands of an indirect buffer, starting at the command `start`.
+  ///
+  /// Indirect buffers cannot grow: updating commands past the end of the buffer fails.
+  pub fn update_indirect_buffer<C>(
+    &mut self,
+    indirect_buffer: &IndirectBuffer<C>,
+    start: usize,
+    commands: &[C],
+  ) -> Result<(), IndirectBufferError>
+  where
+    C: IndirectCommand,
+  {
+    let len = indirect_buffer.len();
+
+    match start.checked_add(commands.len()) {
+      Some(end) if end <= len => (),
+      end => {
+        return Err(IndirectBufferError::OutOfRange {
+          start,
+          end: end.unwrap_or(usize::MAX),
+          len,
+        })
+      }
+    }
+
+    unsafe {
+      self
+        .backend
+        .indirect_buffer_update(indirect_buffer.handle(), start, commands)
+    }
+  }
+
+  /// Read back the commands of an indirect buffer, for instance after a shader wrote them.
+  pub fn read_indirect_buffer<C>(
+    &mut self,
+    indirect_buffer: &IndirectBuffer<C>,
+  ) -> Result<Vec<C>, IndirectBufferError>
+  where
+    C: IndirectCommand,
+  {
+    unsafe { self.backend.indirect_buffer_read(indirect_buffer.handle()) }
+  }
+
+  /// Create a buffer holding a copy of `values`.
+  ///
+  /// `usage` is a hint of how often the buffer is updated. The length of the buffer cannot change afterwards.
+  pub fn new_buffer<T>(&mut self, values: &[T], usage: Usage) -> Result<Buffer<T>, BufferError>
+  where
+    T: Copy,
+  {
+    unsafe { self.backend.new_buffer(values, usage) }
+  }
+
+  /// Update the values of a buffer, starting at the value `start`.
+  ///
+  /// Buffers cannot grow: updating values past the end of the buffer fails.
+  pub fn update_buffer<T>(
+    &mut self,
+    buffer: &Buffer<T>,
+    start: usize,
+    values: &[T],
+  ) -> Result<(), BufferError>
+  where
+    T: Copy,
+  {
+    let end = start.saturating_add(values.len());
+    let len = buffer.len();
+
+    if end > len {
+      return Err(BufferError::OutOfRange { start, end, len });
+    }
+
+    unsafe { self.backend.buffer_update(buffer.handle(), start, values) }
+  }
+
+  /// Read back the values of a buffer.
+  pub fn read_buffer<T>(&mut self, buffer: &Buffer<T>) -> Result<Vec<T>, BufferError>
+  where
+    T: Copy,
+  {
+    unsafe { self.backend.buffer_read(buffer.handle(), buffer.len()) }
+  }
+
+  /// Map the values of a buffer, to read and write them in place.
+  ///
+  /// The buffer is unmapped when the returned slice is dropped.
+  pub fn map_buffer<'a, T>(
+    &'a mut self,
+    buffer: &'a mut Buffer<T>,
+  ) -> Result<BufferSliceMut<'a, B, T>, BufferError>
+  where
+    T: Copy,
+  {
+    unsafe { self.backend.buffer_map(buffer.handle(), buffer.len()) }
+  }
+
   pub fn new_framebuffer<D, RS, DS>(
     &mut self,
     size: D::Size,
@@ -167,29 +418,49 @@ where
     unsafe { self.backend.back_buffer(size) }
   }
 
-  pub fn new_program<V, W, P, S, E>(
+  pub fn new_program<V, W, P, S, E, F>(
     &mut self,
-    builder: ProgramBuilder<V, W, P, S, E>,
-  ) -> Result<Program<V, W, P, S, E>, ShaderError>
+    builder: ProgramBuilder<V, W, P, S, E, F>,
+  ) -> Result<Program<V, W, P, S, E, F>, ShaderError>
   where
     V: Vertex,
     W: Vertex,
     P: Primitive,
     S: RenderSlots,
     E: Uniforms,
+    F: Vertex,
   {
     unsafe {
       self.backend.new_program(
         builder.vertex_code,
+        builder.tess_control_code,
+        builder.tess_eval_code,
         builder.primitive_code,
         builder.shading_code,
       )
     }
   }
 
-  pub fn update_program<'a, V, W, P, S, E>(
+  /// Reflect the active vertex attributes, uniforms and uniform blocks of a program.
+  ///
+  /// See the [`reflection`](crate::shader::reflection) module for further details.
+  pub fn reflect_program<V, W, P, S, E, F>(
+    &mut self,
+    program: &Program<V, W, P, S, E, F>,
+  ) -> Result<ProgramReflection, ShaderError>
+  where
+    V: Vertex,
+    W: Vertex,
+    P: Primitive,
+    S: RenderSlots,
+    F: Vertex,
+  {
+    unsafe { self.backend.program_reflection(program.handle()) }
+  }
+
+  pub fn update_program<'a, V, W, P, S, E, F>(
     &'a mut self,
-    program: &Program<V, W, P, S, E>,
+    program: &Program<V, W, P, S, E, F>,
     updater: impl FnOnce(ProgramUpdate<'a, B>, &E) -> Result<(), ShaderError>,
   ) -> Result<(), ShaderError>
   where
@@ -197,6 +468,7 @@ where
     W: Vertex,
     P: Primitive,
     S: RenderSlots,
+    F: Vertex,
   {
     let program_update = ProgramUpdate {
       backend: &mut self.backend,
@@ -401,6 +673,21 @@ where
   {
     unsafe { self.backend.use_uni_buffer(uni_buffer.handle()) }
   }
+
+  /// Use the content of a buffer as a uniform buffer.
+  ///
+  /// The values of the buffer must be laid out according to `Scheme`; the first one is the content of the uniform
+  /// buffer.
+  pub fn use_buffer_as_uniform_buffer<T, Scheme>(
+    &mut self,
+    buffer: &Buffer<T::Aligned>,
+  ) -> Result<InUseUniBuffer<T, Scheme>, ShaderError>
+  where
+    B: ShaderBackend,
+    T: MemoryLayout<Scheme>,
+  {
+    unsafe { self.backend.use_uni_buffer(buffer.handle()) }
+  }
 }
 
 impl<B> Drop for Context<B>
@@ -415,3 +702,51 @@ where
     *self.context_active.0.borrow_mut() = false;
   }
 }
+
+/// Turn any range into a [`Range`], checking it’s contained in a storage of length `len`.
+fn resolve_range(
+  range: impl RangeBounds<usize>,
+  len: usize,
+) -> Result<Range<usize>, VertexEntityError> {
+  let Range { start, end } = range_bounds(range, len);
+
+  if start > end || end > len {
+    return Err(VertexEntityError::UpdateOutOfRange { start, end, len });
+  }
+
+  Ok(start..end)
+}
+
+/// Same as [`resolve_range`] for a vertex storage, which must not be shared.
+fn resolve_shared_range<V>(
+  storage: VertexStorage<V>,
+  range: impl RangeBounds<usize>,
+) -> Result<Range<usize>, VertexEntityError>
+where
+  V: Vertex,
+{
+  if let VertexStorage::Shared(_) = storage {
+    return Err(VertexEntityError::SharedBuffer);
+  }
+
+  resolve_range(range, storage.len())
+}
+
+/// Turn `range` into a [`Range`], unbounded ends being `0` and `len`.
+///
+/// Bounds saturate instead of overflowing, leaving callers to reject them as out of range.
+pub(crate) fn range_bounds(range: impl RangeBounds<usize>, len: usize) -> Range<usize> {
+  let start = match range.start_bound() {
+    Bound::Included(&start) => start,
+    Bound::Excluded(&start) => start.saturating_add(1),
+    Bound::Unbounded => 0,
+  };
+
+  let end = match range.end_bound() {
+    Bound::Included(&end) => end.saturating_add(1),
+    Bound::Excluded(&end) => end,
+    Bound::Unbounded => len,
+  };
+
+  start..end
+}
@@ -1,3 +1,9 @@
 pub trait HasField<const NAME: &'static str> {
   type FieldType;
+
+  /// Reference the field.
+  fn field(&self) -> &Self::FieldType;
+
+  /// Mutably reference the field.
+  fn field_mut(&mut self) -> &mut Self::FieldType;
 }
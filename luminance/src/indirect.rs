@@ -0,0 +1,247 @@
+//! Indirect rendering.
+//!
+//! Indirect rendering reads the parameters of draw calls from an [`IndirectBuffer`], living in GPU memory, instead of
+//! passing them from the CPU. Vertex entities with indices are rendered with [`DrawCommand`]s, and vertex entities
+//! without indices with [`DrawArraysCommand`]s.
+//!
+//! The draw commands can be set from the CPU, or written by shaders, to let the GPU decide what to render: indirect
+//! buffers can receive the vertices captured with transform feedback, each captured vertex being a command. See
+//! [`WithRenderState::render_vertex_entity_with_feedback`].
+//!
+//! [`WithRenderState::render_vertex_entity_with_feedback`]: crate::pipeline::WithRenderState::render_vertex_entity_with_feedback
+
+use crate::{
+  pipeline::FeedbackTarget,
+  vertex::{Vertex, VertexAttrib as _, VertexAttribDesc, VertexBufferDesc},
+};
+use std::marker::PhantomData;
+
+/// Draw commands read by the GPU when rendering indirectly.
+///
+/// Commands are vertices made of `uint` attributes named after their fields (`int` for the signed base vertex), so that
+/// they can be captured with transform feedback.
+///
+/// # Safety
+///
+/// The layout of the type must be the one GPUs expect for its kind of commands.
+pub unsafe trait IndirectCommand: Vertex {
+  /// Whether the commands render indices, or vertices.
+  const INDEXED: bool;
+
+  /// Number of vertices (or indices) to render.
+  fn count(&self) -> u32;
+
+  /// Number of instances to render.
+  fn instance_count(&self) -> u32;
+
+  /// First vertex (or index) to render.
+  fn first(&self) -> u32;
+
+  /// Value added to every index before fetching vertices.
+  fn base_vertex(&self) -> i32;
+
+  /// First instance to fetch instance data from.
+  fn base_instance(&self) -> u32;
+}
+
+fn command_desc(fields: &[(&'static str, VertexAttribDesc)]) -> Vec<VertexBufferDesc> {
+  fields
+    .iter()
+    .enumerate()
+    .map(|(index, &(name, desc))| VertexBufferDesc::new(index, name, desc))
+    .collect()
+}
+
+/// A draw command rendering the indices of a vertex entity.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct DrawCommand {
+  /// Number of indices to render.
+  pub count: u32,
+
+  /// Number of instances to render.
+  pub instance_count: u32,
+
+  /// First index to render.
+  pub first: u32,
+
+  /// Value added to every index before fetching vertices; it can be negative.
+  pub base_vertex: i32,
+
+  /// First instance to fetch instance data from.
+  pub base_instance: u32,
+}
+
+impl DrawCommand {
+  pub fn new(count: u32, instance_count: u32, first: u32) -> Self {
+    Self {
+      count,
+      instance_count,
+      first,
+      base_vertex: 0,
+      base_instance: 0,
+    }
+  }
+
+  pub fn set_base_vertex(mut self, base_vertex: i32) -> Self {
+    self.base_vertex = base_vertex;
+    self
+  }
+
+  pub fn set_base_instance(mut self, base_instance: u32) -> Self {
+    self.base_instance = base_instance;
+    self
+  }
+}
+
+unsafe impl Vertex for DrawCommand {
+  fn vertex_desc() -> Vec<VertexBufferDesc> {
+    command_desc(&[
+      ("count", u32::VERTEX_ATTRIB_DESC),
+      ("instance_count", u32::VERTEX_ATTRIB_DESC),
+      ("first", u32::VERTEX_ATTRIB_DESC),
+      ("base_vertex", i32::VERTEX_ATTRIB_DESC),
+      ("base_instance", u32::VERTEX_ATTRIB_DESC),
+    ])
+  }
+}
+
+unsafe impl IndirectCommand for DrawCommand {
+  const INDEXED: bool = true;
+
+  fn count(&self) -> u32 {
+    self.count
+  }
+
+  fn instance_count(&self) -> u32 {
+    self.instance_count
+  }
+
+  fn first(&self) -> u32 {
+    self.first
+  }
+
+  fn base_vertex(&self) -> i32 {
+    self.base_vertex
+  }
+
+  fn base_instance(&self) -> u32 {
+    self.base_instance
+  }
+}
+
+/// A draw command rendering the vertices of a vertex entity without indices.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct DrawArraysCommand {
+  /// Number of vertices to render.
+  pub count: u32,
+
+  /// Number of instances to render.
+  pub instance_count: u32,
+
+  /// First vertex to render.
+  pub first: u32,
+
+  /// First instance to fetch instance data from.
+  pub base_instance: u32,
+}
+
+impl DrawArraysCommand {
+  pub fn new(count: u32, instance_count: u32, first: u32) -> Self {
+    Self {
+      count,
+      instance_count,
+      first,
+      base_instance: 0,
+    }
+  }
+
+  pub fn set_base_instance(mut self, base_instance: u32) -> Self {
+    self.base_instance = base_instance;
+    self
+  }
+}
+
+unsafe impl Vertex for DrawArraysCommand {
+  fn vertex_desc() -> Vec<VertexBufferDesc> {
+    command_desc(&[
+      ("count", u32::VERTEX_ATTRIB_DESC),
+      ("instance_count", u32::VERTEX_ATTRIB_DESC),
+      ("first", u32::VERTEX_ATTRIB_DESC),
+      ("base_instance", u32::VERTEX_ATTRIB_DESC),
+    ])
+  }
+}
+
+unsafe impl IndirectCommand for DrawArraysCommand {
+  const INDEXED: bool = false;
+
+  fn count(&self) -> u32 {
+    self.count
+  }
+
+  fn instance_count(&self) -> u32 {
+    self.instance_count
+  }
+
+  fn first(&self) -> u32 {
+    self.first
+  }
+
+  fn base_vertex(&self) -> i32 {
+    0
+  }
+
+  fn base_instance(&self) -> u32 {
+    self.base_instance
+  }
+}
+
+/// A buffer of draw commands, used to render vertex entities indirectly.
+pub struct IndirectBuffer<C = DrawCommand> {
+  handle: usize,
+  len: usize,
+  dropper: Box<dyn FnMut(usize)>,
+  _phantom: PhantomData<*const C>,
+}
+
+impl<C> IndirectBuffer<C> {
+  pub unsafe fn new(handle: usize, len: usize, dropper: Box<dyn FnMut(usize)>) -> Self {
+    Self {
+      handle,
+      len,
+      dropper,
+      _phantom: PhantomData,
+    }
+  }
+
+  pub fn handle(&self) -> usize {
+    self.handle
+  }
+
+  /// Number of commands in the buffer.
+  pub fn len(&self) -> usize {
+    self.len
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+}
+
+/// Shaders write commands to indirect buffers by capturing them as vertices.
+impl<C> FeedbackTarget<C> for IndirectBuffer<C>
+where
+  C: IndirectCommand,
+{
+  fn feedback_buffer_handle(&self) -> usize {
+    self.handle
+  }
+}
+
+impl<C> Drop for IndirectBuffer<C> {
+  fn drop(&mut self) {
+    (self.dropper)(self.handle)
+  }
+}
@@ -9,12 +9,14 @@ pub use luminance_derive::*;
 
 pub mod backend;
 pub mod blending;
+pub mod buffer;
 pub mod context;
 pub mod depth_stencil;
 pub mod dim;
 pub mod face_culling;
 pub mod framebuffer;
 pub mod has_field;
+pub mod indirect;
 pub mod named_index;
 pub mod pipeline;
 pub mod pixel;
@@ -23,6 +25,7 @@ pub mod render_slots;
 pub mod render_state;
 pub mod scissor;
 pub mod shader;
+pub mod shapes;
 pub mod texture;
 pub mod vertex;
 pub mod vertex_entity;
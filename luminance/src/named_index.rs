@@ -4,39 +4,42 @@ pub trait NamedIndex<const NAME: &'static str> {
   const INDEX: usize;
 }
 
+/// Declare a namespace of names, mapped to consecutive indices with [`NamedIndex`].
+///
+/// A name can reserve several indices with `"name": N`, for vertex attributes spanning several locations, such as
+/// matrices:
+///
+/// ```ignore
+/// namespace! {
+///   Namespace = { "pos", "model": 4, "color" }
+/// }
+/// ```
+///
+/// Here, `"model"` is mapped to `1` and `"color"` to `5`.
 #[macro_export]
 macro_rules! namespace {
-  ($namespace:ident = { $first:literal }) => {
+  ($namespace:ident = { $($names:tt)* }) => {
     #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
     pub struct $namespace;
 
-    impl $crate::named_index::NamedIndex<$first> for $namespace {
-      const INDEX: usize = 0;
-    }
+    namespace!($namespace 0, $($names)*);
   };
 
-  ($namespace:ident = { $first:literal, $($rest:tt)* }) => {
-    #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
-    pub struct $namespace;
-
-    impl $crate::named_index::NamedIndex<$first> for $namespace {
-      const INDEX: usize = 0;
-    }
+  ($namespace:ident $index:expr,) => {};
 
-    namespace!($namespace 1, $($rest)*);
-  };
-
-  ($namespace:ident $index:expr, $name:literal) => {
+  ($namespace:ident $index:expr, $name:literal : $span:literal $(, $($rest:tt)*)?) => {
     impl $crate::named_index::NamedIndex<$name> for $namespace {
       const INDEX: usize = $index;
     }
+
+    namespace!($namespace ($index + $span), $($($rest)*)?);
   };
 
-  ($namespace:ident $index:expr, $name:literal, $($rest:tt)*) => {
+  ($namespace:ident $index:expr, $name:literal $(, $($rest:tt)*)?) => {
     impl $crate::named_index::NamedIndex<$name> for $namespace {
       const INDEX: usize = $index;
     }
 
-    namespace!($namespace ($index + 1), $($rest)*);
-  }
+    namespace!($namespace ($index + 1), $($($rest)*)?);
+  };
 }
@@ -1,19 +1,23 @@
 use crate::{
   backend::{
-    PipelineBackend, PipelineError, ShaderBackend, ShaderError, TextureBackend, TextureError,
+    IndirectBufferError, PipelineBackend, PipelineError, ShaderBackend, ShaderError,
+    TextureBackend, TextureError,
   },
+  context::range_bounds,
   dim::Dimensionable,
+  indirect::{IndirectBuffer, IndirectCommand},
   pixel::Pixel,
-  primitive::Primitive,
+  primitive::{Connector, Primitive},
   render_slots::{CompatibleRenderSlots, RenderSlots},
   render_state::RenderState,
   scissor::Scissor,
   shader::{InUseUniBuffer, MemoryLayout, Program, ProgramUpdate, UniBuffer, Uniforms},
   texture::{InUseTexture, Texture},
   vertex::{CompatibleVertex, Vertex},
-  vertex_entity::VertexEntityView,
+  vertex_entity::{Index, VertexEntity, VertexEntityView},
+  vertex_storage::VertexStorageFamily,
 };
-use std::marker::PhantomData;
+use std::{marker::PhantomData, ops::RangeBounds};
 
 /// The viewport being part of the [`PipelineState`].
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
@@ -201,10 +205,10 @@ where
     }
   }
 
-  pub fn with_program<V, W, P, T, E, Err>(
+  pub fn with_program<V, W, P, T, E, F, Err>(
     &mut self,
-    program: &Program<V, W, P, T, E>,
-    f: impl for<'b> FnOnce(WithProgram<'b, B, V, W, P, T, E>) -> Result<(), Err>,
+    program: &Program<V, W, P, T, E, F>,
+    f: impl for<'b> FnOnce(WithProgram<'b, B, V, W, P, T, E, F>) -> Result<(), Err>,
   ) -> Result<(), Err>
   where
     V: Vertex,
@@ -213,6 +217,7 @@ where
     S: CompatibleRenderSlots<T>,
     T: RenderSlots,
     E: Uniforms,
+    F: Vertex,
     Err: From<PipelineError>,
   {
     unsafe { self.backend.with_program(program, f) }
@@ -242,30 +247,31 @@ where
   }
 }
 
-pub struct WithProgram<'a, B, V, W, P, S, E>
+pub struct WithProgram<'a, B, V, W, P, S, E, F = ()>
 where
   B: 'a + ?Sized,
 {
   backend: &'a mut B,
-  program: &'a Program<V, W, P, S, E>,
+  program: &'a Program<V, W, P, S, E, F>,
 }
 
-impl<'a, B, V, W, P, S, E> WithProgram<'a, B, V, W, P, S, E>
+impl<'a, B, V, W, P, S, E, F> WithProgram<'a, B, V, W, P, S, E, F>
 where
   B: 'a + PipelineBackend,
   V: Vertex,
   W: Vertex,
   P: Primitive,
   S: RenderSlots,
+  F: Vertex,
 {
-  pub unsafe fn new(backend: &'a mut B, program: &'a Program<V, W, P, S, E>) -> Self {
+  pub unsafe fn new(backend: &'a mut B, program: &'a Program<V, W, P, S, E, F>) -> Self {
     Self { backend, program }
   }
 
   pub fn with_render_state<Err>(
     &mut self,
     render_state: &RenderState,
-    f: impl for<'b> FnOnce(WithRenderState<'b, B, V, W, P>) -> Result<(), Err>,
+    f: impl for<'b> FnOnce(WithRenderState<'b, B, V, W, P, F>) -> Result<(), Err>,
   ) -> Result<(), Err>
   where
     Err: From<PipelineError>,
@@ -309,16 +315,24 @@ where
   }
 }
 
+/// Buffers receiving the vertices captured with transform feedback.
+///
+/// See [`WithRenderState::render_vertex_entity_with_feedback`].
+pub trait FeedbackTarget<F> {
+  /// Handle of the buffer the captured vertices are written to.
+  fn feedback_buffer_handle(&self) -> usize;
+}
+
 #[derive(Debug)]
-pub struct WithRenderState<'a, B, V, W, P>
+pub struct WithRenderState<'a, B, V, W, P, F = ()>
 where
   B: 'a + ?Sized,
 {
   backend: &'a mut B,
-  _phantom: PhantomData<*const (V, W, P)>,
+  _phantom: PhantomData<*const (V, W, P, F)>,
 }
 
-impl<'a, B, V, W, P> WithRenderState<'a, B, V, W, P>
+impl<'a, B, V, W, P, F> WithRenderState<'a, B, V, W, P, F>
 where
   B: 'a + PipelineBackend,
   V: Vertex,
@@ -344,6 +358,117 @@ where
     unsafe { self.backend.render_vertex_entity(view) }
   }
 
+  /// Render a batch of views in a single backend call.
+  ///
+  /// This is especially useful to render several meshes packed in a single vertex entity, each view selecting a mesh
+  /// with its start vertex and base vertex.
+  pub fn render_vertex_entity_batch<V2, W2>(
+    &mut self,
+    views: &[VertexEntityView<V2, W2, P>],
+  ) -> Result<(), PipelineError>
+  where
+    V: CompatibleVertex<V2>,
+    W: CompatibleVertex<W2>,
+    V2: Vertex,
+    W2: Vertex,
+  {
+    unsafe { self.backend.render_vertex_entity_batch(views) }
+  }
+
+  /// Render a view and capture the outputs of the vertex stage into `target`.
+  ///
+  /// The captured outputs are the ones declared with [`ProgramBuilder::capture_varyings`]; they are written as `F`
+  /// vertices from the start of the target, primitive by primitive — strips and fans are unrolled into lists, so a
+  /// triangle strip of `n` vertices captures `3 * (n - 2)` vertices. Vertices that don’t fit in the target are dropped.
+  ///
+  /// The target must not be read by the rendered view.
+  ///
+  /// [`ProgramBuilder::capture_varyings`]: crate::shader::ProgramBuilder::capture_varyings
+  pub fn render_vertex_entity_with_feedback<V2, W2, T>(
+    &mut self,
+    view: VertexEntityView<V2, W2, P>,
+    target: &T,
+  ) -> Result<(), PipelineError>
+  where
+    V: CompatibleVertex<V2>,
+    W: CompatibleVertex<W2>,
+    V2: Vertex,
+    W2: Vertex,
+    F: Vertex,
+    T: FeedbackTarget<F>,
+  {
+    if F::vertex_desc().is_empty() {
+      return Err(PipelineError::NoCapturedVaryings);
+    }
+
+    if let Connector::Patch(_) = P::CONNECTOR {
+      return Err(PipelineError::UncapturablePrimitive {
+        connector: P::CONNECTOR,
+      });
+    }
+
+    unsafe {
+      self
+        .backend
+        .render_vertex_entity_with_feedback::<V2, W2, P, F>(view, target.feedback_buffer_handle())
+    }
+  }
+
+  /// Render a vertex entity with the draw commands in `range` of an indirect buffer.
+  ///
+  /// Each command renders vertices (or indices) of the vertex entity, the same way a view would. Vertex entities with
+  /// indices are rendered with [`DrawCommand`]s, and vertex entities without indices with [`DrawArraysCommand`]s.
+  ///
+  /// [`DrawCommand`]: crate::indirect::DrawCommand
+  /// [`DrawArraysCommand`]: crate::indirect::DrawArraysCommand
+  pub fn render_indirect<V2, VSF, W2, WSF, I, C>(
+    &mut self,
+    entity: &VertexEntity<V2, P, VSF, W2, WSF, I>,
+    indirect_buffer: &IndirectBuffer<C>,
+    range: impl RangeBounds<usize>,
+  ) -> Result<(), PipelineError>
+  where
+    V: CompatibleVertex<V2>,
+    W: CompatibleVertex<W2>,
+    V2: Vertex,
+    VSF: VertexStorageFamily,
+    W2: Vertex,
+    WSF: VertexStorageFamily,
+    I: Index,
+    C: IndirectCommand,
+  {
+    let len = indirect_buffer.len();
+    let range = range_bounds(range, len);
+    let render_error = |e| PipelineError::RenderIndirect {
+      start_command: range.start,
+      command_count: range.end.saturating_sub(range.start),
+      cause: Some(Box::new(e)),
+    };
+
+    if range.start > range.end || range.end > len {
+      return Err(render_error(IndirectBufferError::OutOfRange {
+        start: range.start,
+        end: range.end,
+        len,
+      }));
+    }
+
+    if C::INDEXED != (entity.index_count() != 0) {
+      return Err(render_error(IndirectBufferError::MismatchingCommands {
+        indexed_commands: C::INDEXED,
+      }));
+    }
+
+    unsafe {
+      self.backend.vertex_entity_render_indirect::<V2, P, C>(
+        entity.handle(),
+        indirect_buffer.handle(),
+        range.clone(),
+      )
+    }
+    .map_err(render_error)
+  }
+
   pub fn use_texture<D, Px>(
     &mut self,
     texture: &Texture<D, Px>,
@@ -1,3 +1,13 @@
+//! Primitives vertices are assembled into.
+//!
+//! Adjacency primitives ([`LineAdjacency`], [`LineStripAdjacency`], [`TriangleAdjacency`] and
+//! [`TriangleStripAdjacency`]) carry the vertices surrounding each primitive, for primitive stages (geometry shaders)
+//! to read; they are rendered as their plain counterparts otherwise. [`triangle_adjacency_indices`] and
+//! [`line_strip_adjacency_indices`] build their indices out of plain indices.
+
+use crate::vertex_entity::Index;
+use std::collections::HashMap;
+
 pub trait Primitive {
   const CONNECTOR: Connector;
 }
@@ -11,6 +21,16 @@ pub enum Connector {
   TriangleStrip,
   TriangleFan,
   Patch(usize),
+  /// Lines with their two adjacent vertices: 4 vertices per line, the line going from the second to the third one.
+  LineAdjacency,
+  /// Line strip with an adjacent vertex at each end: the first and last vertices are only read as adjacency.
+  LineStripAdjacency,
+  /// Triangles with their three adjacent vertices: 6 vertices per triangle, the triangle being made of the even ones
+  /// and the odd ones being opposite to the edge preceding them.
+  TriangleAdjacency,
+  /// Triangle strip with adjacency: the strip is made of the even vertices, and the odd ones are adjacent to its
+  /// edges.
+  TriangleStripAdjacency,
 }
 
 macro_rules! impl_Primitive {
@@ -30,6 +50,10 @@ impl_Primitive!(LineStrip);
 impl_Primitive!(Triangle);
 impl_Primitive!(TriangleStrip);
 impl_Primitive!(TriangleFan);
+impl_Primitive!(LineAdjacency);
+impl_Primitive!(LineStripAdjacency);
+impl_Primitive!(TriangleAdjacency);
+impl_Primitive!(TriangleStripAdjacency);
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct Patch<const SIZE: usize>;
@@ -37,3 +61,78 @@ pub struct Patch<const SIZE: usize>;
 impl<const SIZE: usize> Primitive for Patch<SIZE> {
   const CONNECTOR: Connector = Connector::Patch(SIZE);
 }
+
+/// Build [`TriangleAdjacency`] indices out of [`Triangle`] indices.
+///
+/// Triangles sharing an edge are adjacent when they share the indices of its vertices and run along it in opposite
+/// directions, as consistently wound triangles do. Edges without adjacent triangle, on the borders of the mesh, get the
+/// opposite vertex of their own triangle as adjacent vertex.
+///
+/// Trailing indices that don’t form a triangle are ignored.
+pub fn triangle_adjacency_indices<I>(indices: &[I]) -> Vec<I>
+where
+  I: Index,
+{
+  triangle_adjacency_indices_by(indices, |index| index.into())
+}
+
+/// Build [`TriangleAdjacency`] indices out of [`Triangle`] indices, matching vertices with `key`.
+///
+/// Vertices with the same key are considered the same when looking for adjacent triangles, so that vertices split
+/// along seams (e.g. to have different normals) can still be matched.
+pub(crate) fn triangle_adjacency_indices_by<I, K>(indices: &[I], key: impl Fn(I) -> K) -> Vec<I>
+where
+  I: Copy,
+  K: Copy + Eq + std::hash::Hash,
+{
+  let triangles = indices.chunks_exact(3);
+
+  // opposite vertex of each directed edge
+  let mut opposites = HashMap::with_capacity(triangles.len() * 3);
+  for triangle in triangles.clone() {
+    for i in 0..3 {
+      let (a, b, c) = (triangle[i], triangle[(i + 1) % 3], triangle[(i + 2) % 3]);
+      opposites.insert((key(a), key(b)), c);
+    }
+  }
+
+  let mut adjacency = Vec::with_capacity(triangles.len() * 6);
+  for triangle in triangles {
+    for i in 0..3 {
+      let (a, b, c) = (triangle[i], triangle[(i + 1) % 3], triangle[(i + 2) % 3]);
+      let opposite = opposites.get(&(key(b), key(a))).copied().unwrap_or(c);
+      adjacency.extend([a, opposite]);
+    }
+  }
+
+  adjacency
+}
+
+/// Build [`LineStripAdjacency`] indices out of [`LineStrip`] indices.
+///
+/// Open strips get their first and last vertices repeated as adjacent vertices. Closed strips are looped: their last
+/// vertex is connected back to the first one, and each end is adjacent to the other side of the loop.
+pub fn line_strip_adjacency_indices<I>(indices: &[I], closed: bool) -> Vec<I>
+where
+  I: Index,
+{
+  let (first, last) = match (indices.first(), indices.last()) {
+    (Some(&first), Some(&last)) => (first, last),
+    _ => return Vec::new(),
+  };
+
+  let mut adjacency = Vec::with_capacity(indices.len() + 3);
+
+  if closed {
+    adjacency.push(last);
+    adjacency.extend_from_slice(indices);
+    adjacency.push(first);
+    adjacency.push(indices.get(1).copied().unwrap_or(first));
+  } else {
+    adjacency.push(first);
+    adjacency.extend_from_slice(indices);
+    adjacency.push(last);
+  }
+
+  adjacency
+}
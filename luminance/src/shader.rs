@@ -1,30 +1,44 @@
+pub mod diagnostic;
+pub mod reflection;
 pub mod types;
 
 use crate::{
   backend::{ShaderBackend, ShaderError},
   dim::{Dim, Dimensionable},
   pixel::{self, PixelType},
-  primitive::Primitive,
+  primitive::{Patch, Primitive},
   render_slots::RenderSlots,
   texture::InUseTexture,
   vertex::Vertex,
 };
 use std::{
+  fmt,
   marker::PhantomData,
   ops::{Deref, DerefMut},
 };
 
-pub struct ProgramBuilder<V, W, P, S, E> {
+/// Builder of [`Program`]s.
+///
+/// `F` is the type of the vertices captured with transform feedback, if any; see
+/// [`ProgramBuilder::capture_varyings`].
+///
+/// Stages are added in pipeline order: vertex, tessellation (optional), primitive (optional) and shading. Tessellation
+/// stages make a program render [`Patch`] primitives; see [`ProgramBuilder::add_tess_control_stage`].
+pub struct ProgramBuilder<V, W, P, S, E, F = ()> {
   pub(crate) vertex_code: String,
+  pub(crate) tess_control_code: String,
+  pub(crate) tess_eval_code: String,
   pub(crate) primitive_code: String,
   pub(crate) shading_code: String,
-  _phantom: PhantomData<*const (V, W, P, S, E)>,
+  _phantom: PhantomData<*const (V, W, P, S, E, F)>,
 }
 
 impl<E> ProgramBuilder<(), (), (), (), E> {
   pub fn new() -> Self {
     Self {
       vertex_code: String::new(),
+      tess_control_code: String::new(),
+      tess_eval_code: String::new(),
       primitive_code: String::new(),
       shading_code: String::new(),
       _phantom: PhantomData,
@@ -32,14 +46,16 @@ impl<E> ProgramBuilder<(), (), (), (), E> {
   }
 }
 
-impl<P, S, E> ProgramBuilder<(), (), P, S, E> {
-  pub fn add_vertex_stage<V, W>(self, code: impl Into<String>) -> ProgramBuilder<V, W, P, S, E>
+impl<P, S, E, F> ProgramBuilder<(), (), P, S, E, F> {
+  pub fn add_vertex_stage<V, W>(self, code: impl Into<String>) -> ProgramBuilder<V, W, P, S, E, F>
   where
     V: Vertex,
     W: Vertex,
   {
     ProgramBuilder {
       vertex_code: code.into(),
+      tess_control_code: self.tess_control_code,
+      tess_eval_code: self.tess_eval_code,
       primitive_code: self.primitive_code,
       shading_code: self.shading_code,
       _phantom: PhantomData,
@@ -47,25 +63,29 @@ impl<P, S, E> ProgramBuilder<(), (), P, S, E> {
   }
 }
 
-impl<V, W, S, E> ProgramBuilder<V, W, (), S, E> {
-  pub fn add_primitive_stage<P>(self, code: impl Into<String>) -> ProgramBuilder<V, W, P, S, E>
+impl<V, W, S, E, F> ProgramBuilder<V, W, (), S, E, F> {
+  pub fn add_primitive_stage<P>(self, code: impl Into<String>) -> ProgramBuilder<V, W, P, S, E, F>
   where
     P: Primitive,
   {
     ProgramBuilder {
       vertex_code: self.vertex_code,
+      tess_control_code: self.tess_control_code,
+      tess_eval_code: self.tess_eval_code,
       primitive_code: code.into(),
       shading_code: self.shading_code,
       _phantom: PhantomData,
     }
   }
 
-  pub fn no_primitive_stage<P>(self) -> ProgramBuilder<V, W, P, S, E>
+  pub fn no_primitive_stage<P>(self) -> ProgramBuilder<V, W, P, S, E, F>
   where
     P: Primitive,
   {
     ProgramBuilder {
       vertex_code: self.vertex_code,
+      tess_control_code: self.tess_control_code,
+      tess_eval_code: self.tess_eval_code,
       primitive_code: String::new(),
       shading_code: self.shading_code,
       _phantom: PhantomData,
@@ -73,13 +93,88 @@ impl<V, W, S, E> ProgramBuilder<V, W, (), S, E> {
   }
 }
 
-impl<V, W, P, E> ProgramBuilder<V, W, P, (), E> {
-  pub fn add_shading_stage<S>(self, code: impl Into<String>) -> ProgramBuilder<V, W, P, S, E>
+impl<V, W, S, E, F> ProgramBuilder<V, W, (), S, E, F> {
+  /// Add a tessellation control stage, tessellating patches of `SIZE` vertices.
+  ///
+  /// It must be followed by a tessellation evaluation stage, with [`ProgramBuilder::add_tess_eval_stage`], and the
+  /// program then renders [`Patch<SIZE>`] primitives.
+  pub fn add_tess_control_stage<const SIZE: usize>(
+    self,
+    code: impl Into<String>,
+  ) -> ProgramBuilder<V, W, TessControlled<SIZE>, S, E, F> {
+    ProgramBuilder {
+      vertex_code: self.vertex_code,
+      tess_control_code: code.into(),
+      tess_eval_code: self.tess_eval_code,
+      primitive_code: self.primitive_code,
+      shading_code: self.shading_code,
+      _phantom: PhantomData,
+    }
+  }
+}
+
+impl<V, W, S, E, F, const SIZE: usize> ProgramBuilder<V, W, TessControlled<SIZE>, S, E, F> {
+  /// Add a tessellation evaluation stage, computing the vertices generated by the tessellator.
+  pub fn add_tess_eval_stage(
+    self,
+    code: impl Into<String>,
+  ) -> ProgramBuilder<V, W, Tessellated<SIZE>, S, E, F> {
+    ProgramBuilder {
+      vertex_code: self.vertex_code,
+      tess_control_code: self.tess_control_code,
+      tess_eval_code: code.into(),
+      primitive_code: self.primitive_code,
+      shading_code: self.shading_code,
+      _phantom: PhantomData,
+    }
+  }
+}
+
+impl<V, W, S, E, F, const SIZE: usize> ProgramBuilder<V, W, Tessellated<SIZE>, S, E, F> {
+  /// Add a primitive stage, reading the primitives output by the tessellation evaluation stage.
+  pub fn add_primitive_stage(
+    self,
+    code: impl Into<String>,
+  ) -> ProgramBuilder<V, W, Patch<SIZE>, S, E, F> {
+    ProgramBuilder {
+      vertex_code: self.vertex_code,
+      tess_control_code: self.tess_control_code,
+      tess_eval_code: self.tess_eval_code,
+      primitive_code: code.into(),
+      shading_code: self.shading_code,
+      _phantom: PhantomData,
+    }
+  }
+
+  pub fn no_primitive_stage(self) -> ProgramBuilder<V, W, Patch<SIZE>, S, E, F> {
+    ProgramBuilder {
+      vertex_code: self.vertex_code,
+      tess_control_code: self.tess_control_code,
+      tess_eval_code: self.tess_eval_code,
+      primitive_code: String::new(),
+      shading_code: self.shading_code,
+      _phantom: PhantomData,
+    }
+  }
+}
+
+/// [`ProgramBuilder`] state after its tessellation control stage, tessellating patches of `SIZE` vertices.
+#[derive(Debug)]
+pub struct TessControlled<const SIZE: usize>;
+
+/// [`ProgramBuilder`] state after its tessellation stages, tessellating patches of `SIZE` vertices.
+#[derive(Debug)]
+pub struct Tessellated<const SIZE: usize>;
+
+impl<V, W, P, E, F> ProgramBuilder<V, W, P, (), E, F> {
+  pub fn add_shading_stage<S>(self, code: impl Into<String>) -> ProgramBuilder<V, W, P, S, E, F>
   where
     S: RenderSlots,
   {
     ProgramBuilder {
       vertex_code: self.vertex_code,
+      tess_control_code: self.tess_control_code,
+      tess_eval_code: self.tess_eval_code,
       primitive_code: self.primitive_code,
       shading_code: code.into(),
       _phantom: PhantomData,
@@ -87,19 +182,44 @@ impl<V, W, P, E> ProgramBuilder<V, W, P, (), E> {
   }
 }
 
-pub struct Program<V, W, P, S, E> {
+impl<V, W, P, S, E> ProgramBuilder<V, W, P, S, E> {
+  /// Capture the outputs of the vertex stage (or of the primitive stage, if any) with transform feedback.
+  ///
+  /// The outputs named after the fields of `F` are captured, interleaved, as vertices of type `F`; see
+  /// [`WithRenderState::render_vertex_entity_with_feedback`]. Fields must be (vectors of) 32-bit, non-normalized
+  /// scalars, without padding between them. A primitive stage must emit the same kind of primitives it receives.
+  ///
+  /// [`WithRenderState::render_vertex_entity_with_feedback`]:
+  /// crate::pipeline::WithRenderState::render_vertex_entity_with_feedback
+  pub fn capture_varyings<F>(self) -> ProgramBuilder<V, W, P, S, E, F>
+  where
+    F: Vertex,
+  {
+    ProgramBuilder {
+      vertex_code: self.vertex_code,
+      tess_control_code: self.tess_control_code,
+      tess_eval_code: self.tess_eval_code,
+      primitive_code: self.primitive_code,
+      shading_code: self.shading_code,
+      _phantom: PhantomData,
+    }
+  }
+}
+
+pub struct Program<V, W, P, S, E, F = ()> {
   handle: usize,
   pub(crate) uniforms: E,
   dropper: Box<dyn FnMut(usize)>,
-  _phantom: PhantomData<*const (V, W, P, S, E)>,
+  _phantom: PhantomData<*const (V, W, P, S, E, F)>,
 }
 
-impl<V, W, P, S, E> Program<V, W, P, S, E>
+impl<V, W, P, S, E, F> Program<V, W, P, S, E, F>
 where
   V: Vertex,
   W: Vertex,
   P: Primitive,
   S: RenderSlots,
+  F: Vertex,
 {
   pub unsafe fn new(handle: usize, uniforms: E, dropper: Box<dyn FnMut(usize)>) -> Self {
     Self {
@@ -115,7 +235,7 @@ where
   }
 }
 
-impl<V, W, P, S, E> Drop for Program<V, W, P, S, E> {
+impl<V, W, P, S, E, F> Drop for Program<V, W, P, S, E, F> {
   fn drop(&mut self) {
     (self.dropper)(self.handle);
   }
@@ -166,6 +286,61 @@ pub enum UniType {
   Buffer,
 }
 
+/// Display the GLSL type of a uniform, e.g. `vec3` or `usampler2D`.
+impl fmt::Display for UniType {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    fn vec(f: &mut fmt::Formatter<'_>, scalar: &str, prefix: &str, dim: UniDim) -> fmt::Result {
+      match dim {
+        UniDim::Dim1 => f.write_str(scalar),
+        UniDim::Dim2 => write!(f, "{prefix}vec2"),
+        UniDim::Dim3 => write!(f, "{prefix}vec3"),
+        UniDim::Dim4 => write!(f, "{prefix}vec4"),
+      }
+    }
+
+    fn mat(f: &mut fmt::Formatter<'_>, prefix: &str, dim: UniMatDim) -> fmt::Result {
+      match dim {
+        UniMatDim::Mat22 => write!(f, "{prefix}mat2"),
+        UniMatDim::Mat33 => write!(f, "{prefix}mat3"),
+        UniMatDim::Mat44 => write!(f, "{prefix}mat4"),
+      }
+    }
+
+    match *self {
+      UniType::Integral(dim) => vec(f, "int", "i", dim),
+      UniType::Unsigned(dim) => vec(f, "uint", "u", dim),
+      UniType::Floating(dim) => vec(f, "float", "", dim),
+      #[cfg(feature = "shader-f64")]
+      UniType::Floating64(dim) => vec(f, "double", "d", dim),
+      UniType::Boolean(dim) => vec(f, "bool", "b", dim),
+      UniType::Matrix(dim) => mat(f, "", dim),
+      #[cfg(feature = "shader-f64")]
+      UniType::Matrix64(dim) => mat(f, "d", dim),
+
+      UniType::Sampler(ty, dim) => {
+        let prefix = match ty {
+          pixel::Type::Integral => "i",
+          pixel::Type::Unsigned => "u",
+          pixel::Type::NormIntegral | pixel::Type::NormUnsigned | pixel::Type::Floating => "",
+        };
+
+        let dim = match dim {
+          Dim::Dim1 => "1D",
+          Dim::Dim2 => "2D",
+          Dim::Dim3 => "3D",
+          Dim::Cubemap => "Cube",
+          Dim::Dim1Array => "1DArray",
+          Dim::Dim2Array => "2DArray",
+        };
+
+        write!(f, "{prefix}sampler{dim}")
+      }
+
+      UniType::Buffer => f.write_str("uniform block"),
+    }
+  }
+}
+
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum UniDim {
   Dim1,
@@ -190,11 +365,31 @@ pub enum UniSamplerDim {
   Dim2Array,
 }
 
+/// Value of a uniform, for backends keeping uniforms in memory (e.g. the recorded
+/// [`Command::SetUniform`](crate::backend::mock::Command::SetUniform) of the mock backend).
+///
+/// Vectors, matrices and arrays are flattened (matrices are column-major). Textures and uniform buffers are identified
+/// by their handle.
+#[derive(Clone, Debug, PartialEq)]
+pub enum UniformValue {
+  Integral(Vec<i32>),
+  Unsigned(Vec<u32>),
+  Floating(Vec<f32>),
+  #[cfg(feature = "shader-f64")]
+  Floating64(Vec<f64>),
+  Boolean(Vec<bool>),
+  Texture(usize),
+  UniBuffer(usize),
+}
+
 pub trait Uniform {
   type Value;
 
   const LEN: usize;
 
+  /// Size of the data of uniform blocks, in bytes; `None` for other uniforms.
+  const BLOCK_SIZE: Option<usize> = None;
+
   fn uni_type() -> UniType;
 
   fn set(
@@ -495,6 +690,8 @@ where
 
   const LEN: usize = 1;
 
+  const BLOCK_SIZE: Option<usize> = Some(std::mem::size_of::<T::Aligned>());
+
   fn uni_type() -> UniType {
     UniType::Buffer
   }
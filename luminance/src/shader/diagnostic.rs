@@ -0,0 +1,240 @@
+//! Shader diagnostics.
+//!
+//! Backends report the errors and warnings of compiling and linking programs as [`Diagnostic`]s. Those are located in
+//! the code of the stages as passed to [`ProgramBuilder`], and not in the code actually compiled by the backend, which
+//! might add a prelude to it.
+//!
+//! [`ProgramBuilder`]: crate::shader::ProgramBuilder
+
+use std::fmt;
+
+/// Stage of a program.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Stage {
+  Vertex,
+  TessControl,
+  TessEval,
+  Primitive,
+  Shading,
+}
+
+impl fmt::Display for Stage {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Stage::Vertex => f.write_str("vertex stage"),
+      Stage::TessControl => f.write_str("tessellation control stage"),
+      Stage::TessEval => f.write_str("tessellation evaluation stage"),
+      Stage::Primitive => f.write_str("primitive stage"),
+      Stage::Shading => f.write_str("shading stage"),
+    }
+  }
+}
+
+/// Severity of a diagnostic.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Severity {
+  Error,
+  Warning,
+}
+
+impl fmt::Display for Severity {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Severity::Error => f.write_str("error"),
+      Severity::Warning => f.write_str("warning"),
+    }
+  }
+}
+
+/// Error or warning reported when compiling or linking a program.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Diagnostic {
+  /// Stage the diagnostic is about, if known.
+  pub stage: Option<Stage>,
+
+  /// Line in the code of the stage, starting at `1`.
+  ///
+  /// `None` if the diagnostic is not located, or is located in code added by the backend.
+  pub line: Option<usize>,
+
+  /// Column in the line, if reported.
+  pub column: Option<usize>,
+
+  pub severity: Severity,
+
+  pub message: String,
+
+  /// Offending line of code, if the diagnostic is located.
+  pub source_line: Option<String>,
+}
+
+impl fmt::Display for Diagnostic {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{}: {}", self.severity, self.message)?;
+
+    match (self.stage, self.line, self.column) {
+      (Some(stage), Some(line), Some(column)) => {
+        write!(f, " ({stage}, line {line}, column {column})")?
+      }
+      (Some(stage), Some(line), None) => write!(f, " ({stage}, line {line})")?,
+      (Some(stage), None, _) => write!(f, " ({stage})")?,
+      _ => (),
+    }
+
+    if let (Some(line), Some(source_line)) = (self.line, &self.source_line) {
+      write!(f, "\n  {line} | {source_line}")?;
+    }
+
+    Ok(())
+  }
+}
+
+/// Parse the log of a GLSL compiler or linker into diagnostics.
+///
+/// `stage` is the stage being compiled, or `None` when linking. `sources` returns the code of a stage, as passed to
+/// [`ProgramBuilder`], along with the number of lines the backend prepended to it; reported lines are remapped to that
+/// code, and the offending lines quoted.
+///
+/// The Mesa (`0:12(5): error: message`) and NVIDIA (`0(12) : error C1008: message`) formats are recognized, as well as
+/// the `Vertex info` headers NVIDIA puts before the diagnostics of each stage when linking. Other lines are appended to
+/// the message of the previous diagnostic.
+///
+/// [`ProgramBuilder`]: crate::shader::ProgramBuilder
+pub fn parse_glsl_log<'a>(
+  log: &str,
+  mut stage: Option<Stage>,
+  sources: impl Fn(Stage) -> Option<(&'a str, usize)>,
+) -> Vec<Diagnostic> {
+  let mut diagnostics: Vec<Diagnostic> = Vec::new();
+
+  for log_line in log.lines() {
+    let log_line = log_line.trim_matches(|c: char| c.is_whitespace() || c == '\0');
+
+    if log_line.is_empty() || log_line.bytes().all(|b| b == b'-') {
+      continue;
+    }
+
+    if let Some(header_stage) = parse_stage_header(log_line) {
+      stage = Some(header_stage);
+      continue;
+    }
+
+    let (reported_line, column, severity, message) = match parse_log_line(log_line) {
+      Some(parsed) => parsed,
+
+      None => {
+        match diagnostics.last_mut() {
+          Some(diagnostic) => {
+            diagnostic.message.push('\n');
+            diagnostic.message.push_str(log_line);
+          }
+
+          None => diagnostics.push(Diagnostic {
+            stage,
+            line: None,
+            column: None,
+            severity: Severity::Error,
+            message: log_line.to_owned(),
+            source_line: None,
+          }),
+        }
+
+        continue;
+      }
+    };
+
+    // lines reported in the prelude of the backend don’t exist in the code of the stage
+    let source = stage.and_then(&sources);
+    let line = reported_line
+      .zip(source)
+      .and_then(|(reported_line, (_, prelude_lines))| {
+        reported_line
+          .checked_sub(prelude_lines)
+          .filter(|&line| line > 0)
+      });
+    let source_line = line.zip(source).and_then(|(line, (code, _))| {
+      code
+        .lines()
+        .nth(line - 1)
+        .map(|source_line| source_line.trim_end().to_owned())
+    });
+
+    diagnostics.push(Diagnostic {
+      stage,
+      line,
+      column: line.and(column),
+      severity,
+      message: message.to_owned(),
+      source_line,
+    });
+  }
+
+  diagnostics
+}
+
+/// Parse the `<stage> info` headers of NVIDIA link logs.
+fn parse_stage_header(log_line: &str) -> Option<Stage> {
+  match log_line {
+    "Vertex info" => Some(Stage::Vertex),
+    "Tessellation control info" => Some(Stage::TessControl),
+    "Tessellation evaluation info" => Some(Stage::TessEval),
+    "Geometry info" => Some(Stage::Primitive),
+    "Fragment info" => Some(Stage::Shading),
+    _ => None,
+  }
+}
+
+/// Parse the reported line, column, severity and message of a log line.
+fn parse_log_line(log_line: &str) -> Option<(Option<usize>, Option<usize>, Severity, &str)> {
+  // Mesa: 0:12(5): error: message
+  if let Some((location, rest)) = log_line.split_once(": ") {
+    if let Some((line, column)) = parse_mesa_location(location) {
+      let (severity, message) = parse_severity(rest)?;
+      return Some((Some(line), Some(column), severity, message));
+    }
+  }
+
+  // NVIDIA: 0(12) : error C1008: message
+  if let Some((location, rest)) = log_line.split_once(" : ") {
+    if let Some(line) = parse_nvidia_location(location) {
+      let (severity, message) = parse_severity(rest)?;
+      return Some((Some(line), None, severity, message));
+    }
+  }
+
+  // not located: error: message
+  let (severity, message) = parse_severity(log_line)?;
+  Some((None, None, severity, message))
+}
+
+/// Parse `<source>:<line>(<column>)`.
+fn parse_mesa_location(location: &str) -> Option<(usize, usize)> {
+  let (source, rest) = location.split_once(':')?;
+  let (line, column) = rest.strip_suffix(')')?.split_once('(')?;
+  source.parse::<usize>().ok()?;
+  Some((line.parse().ok()?, column.parse().ok()?))
+}
+
+/// Parse `<source>(<line>)`; the source is omitted for diagnostics about the whole stage, as in `(0)`.
+fn parse_nvidia_location(location: &str) -> Option<usize> {
+  let (source, line) = location.strip_suffix(')')?.split_once('(')?;
+
+  if !source.is_empty() {
+    source.parse::<usize>().ok()?;
+  }
+
+  line.parse().ok()
+}
+
+/// Parse `<severity>: <message>`, the severity possibly being qualified (`preprocessor error`) or followed by a code
+/// (`error C1008`).
+fn parse_severity(s: &str) -> Option<(Severity, &str)> {
+  let (severity, message) = s.split_once(':')?;
+  let severity = severity.split_whitespace().find_map(|word| match word {
+    "error" | "ERROR" => Some(Severity::Error),
+    "warning" | "WARNING" => Some(Severity::Warning),
+    _ => None,
+  })?;
+
+  Some((severity, message.trim()))
+}
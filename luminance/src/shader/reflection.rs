@@ -0,0 +1,116 @@
+//! Program reflection.
+//!
+//! A [`ProgramReflection`] lists the resources a linked program actually uses: its active vertex attributes, uniforms
+//! and uniform blocks, as reported by the backend with [`Context::reflect_program`]. Resources that the shader compiler
+//! optimized out are not active, and thus not listed.
+//!
+//! [`Context::reflect_program`]: crate::context::Context::reflect_program
+
+use crate::shader::UniType;
+
+/// Active resources of a linked program.
+///
+/// Every list is sorted by name.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProgramReflection {
+  /// Active vertex attributes, i.e. inputs of the vertex stage.
+  pub attributes: Vec<ActiveAttrib>,
+
+  /// Active uniforms, outside of uniform blocks.
+  pub uniforms: Vec<ActiveUniform>,
+
+  /// Active uniform blocks.
+  pub uniform_blocks: Vec<ActiveUniformBlock>,
+}
+
+impl ProgramReflection {
+  /// Find an active vertex attribute by name.
+  pub fn attribute(&self, name: &str) -> Option<&ActiveAttrib> {
+    self.attributes.iter().find(|attrib| attrib.name == name)
+  }
+
+  /// Find an active uniform, outside of uniform blocks, by name.
+  pub fn uniform(&self, name: &str) -> Option<&ActiveUniform> {
+    self.uniforms.iter().find(|uniform| uniform.name == name)
+  }
+
+  /// Find an active uniform block by name.
+  pub fn uniform_block(&self, name: &str) -> Option<&ActiveUniformBlock> {
+    self.uniform_blocks.iter().find(|block| block.name == name)
+  }
+}
+
+/// Active vertex attribute.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActiveAttrib {
+  pub name: String,
+
+  /// Type of the attribute, or `None` if it cannot be represented by a [`UniType`] (e.g. non-square matrices).
+  pub ty: Option<UniType>,
+
+  /// GLSL type of the attribute, or of its elements if it is an array (e.g. `vec3` or `mat3x4`).
+  pub glsl_type: String,
+
+  /// Number of elements of the attribute if it is an array, `1` otherwise.
+  pub len: usize,
+
+  /// Whether the attribute is an array, i.e. reported with a `[0]` suffix.
+  pub array: bool,
+}
+
+/// Active uniform, outside of uniform blocks.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActiveUniform {
+  /// Name of the uniform; arrays are named without their `[0]` suffix (see [`ActiveUniform::array`]).
+  pub name: String,
+
+  /// Type of the uniform, or `None` if it cannot be represented by a [`UniType`] (e.g. non-square matrices).
+  pub ty: Option<UniType>,
+
+  /// GLSL type of the uniform, or of its elements if it is an array (e.g. `vec3` or `mat3x4`).
+  pub glsl_type: String,
+
+  /// Number of elements of the uniform if it is an array, `1` otherwise.
+  pub len: usize,
+
+  /// Whether the uniform is an array, i.e. reported with a `[0]` suffix.
+  ///
+  /// Arrays of which only the first element is used have a length of `1`, just like non-array uniforms.
+  pub array: bool,
+}
+
+/// Active uniform block.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActiveUniformBlock {
+  pub name: String,
+
+  /// Size of the block, in bytes.
+  pub size: usize,
+
+  /// Active members of the block, sorted by offset.
+  pub members: Vec<ActiveBlockMember>,
+}
+
+/// Active member of a uniform block.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActiveBlockMember {
+  /// Name of the member; arrays are named without their `[0]` suffix (see [`ActiveBlockMember::array`]).
+  pub name: String,
+
+  /// Type of the member, or `None` if it cannot be represented by a [`UniType`] (e.g. non-square matrices).
+  pub ty: Option<UniType>,
+
+  /// GLSL type of the member, or of its elements if it is an array (e.g. `vec3` or `mat3x4`).
+  pub glsl_type: String,
+
+  /// Number of elements of the member if it is an array, `1` otherwise.
+  pub len: usize,
+
+  /// Whether the member is an array, i.e. reported with a `[0]` suffix.
+  ///
+  /// Arrays of which only the first element is used have a length of `1`, just like non-array members.
+  pub array: bool,
+
+  /// Offset of the member from the start of the block, in bytes.
+  pub offset: usize,
+}
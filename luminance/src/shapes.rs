@@ -0,0 +1,500 @@
+//! Procedural meshes.
+//!
+//! The functions of this module generate the usual shapes as [`Shape`]s, holding positions, normals, texture
+//! coordinates and indices. A [`Shape`] is turned into a [`VertexEntityBuilder`] for any [`Vertex`] type by writing
+//! its attributes to named fields:
+//!
+//! ```ignore
+//! let builder = shapes::uv_sphere(1., 32, 16)
+//!   .vertices::<MyVertex>()
+//!   .positions::<"pos">()
+//!   .normals::<"nor">()
+//!   .interleaved();
+//! let sphere: VertexEntity<MyVertex, Triangle, Interleaving> = ctx.new_vertex_entity(builder)?;
+//! ```
+//!
+//! Shapes are right-handed, with `Y` up. Front faces are counter-clockwise and normals point outwards. Texture
+//! coordinates start at the bottom left.
+
+use crate::{
+  has_field::HasField,
+  primitive::{self, Primitive, Triangle, TriangleAdjacency, TriangleStrip},
+  vertex::{FieldSetter, FromAttrib, Vertex},
+  vertex_entity::VertexEntityBuilder,
+  vertex_storage::{Deinterleaved, Interleaved},
+};
+use std::{collections::HashMap, f32::consts::PI, marker::PhantomData};
+
+/// A procedurally generated mesh.
+///
+/// The primitive type `P` is the one the mesh must be rendered with. Shapes without indices are rendered directly.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Shape<P> {
+  pub positions: Vec<[f32; 3]>,
+  pub normals: Vec<[f32; 3]>,
+  pub uvs: Vec<[f32; 2]>,
+  pub indices: Vec<u32>,
+  _phantom: PhantomData<P>,
+}
+
+impl<P> Shape<P>
+where
+  P: Primitive,
+{
+  fn new() -> Self {
+    Self {
+      positions: Vec::new(),
+      normals: Vec::new(),
+      uvs: Vec::new(),
+      indices: Vec::new(),
+      _phantom: PhantomData,
+    }
+  }
+
+  fn push(&mut self, position: [f32; 3], normal: [f32; 3], uv: [f32; 2]) {
+    self.positions.push(position);
+    self.normals.push(normal);
+    self.uvs.push(uv);
+  }
+
+  /// Number of vertices.
+  pub fn len(&self) -> usize {
+    self.positions.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.positions.is_empty()
+  }
+
+  /// Start building vertices out of the shape.
+  ///
+  /// Vertices start with their default value; the attributes of the shape are then written to fields with
+  /// [`ShapeVertices::positions`], [`ShapeVertices::normals`] and [`ShapeVertices::uvs`].
+  pub fn vertices<V>(&self) -> ShapeVertices<'_, V, P>
+  where
+    V: Vertex + Default,
+  {
+    ShapeVertices {
+      positions: &self.positions,
+      normals: &self.normals,
+      uvs: &self.uvs,
+      indices: &self.indices,
+      vertices: vec![V::default(); self.len()],
+      _phantom: PhantomData,
+    }
+  }
+}
+
+impl Shape<Triangle> {
+  /// Turn the shape into a [`TriangleAdjacency`] shape, for primitive stages reading adjacent vertices.
+  ///
+  /// Vertices at the same position are matched when looking for adjacent triangles, so that adjacency is found across
+  /// the seams of the shape, where vertices are split. See [`primitive::triangle_adjacency_indices`].
+  pub fn with_adjacency(self) -> Shape<TriangleAdjacency> {
+    let indices = if self.indices.is_empty() {
+      (0..self.len() as u32).collect()
+    } else {
+      self.indices
+    };
+
+    // -0. and 0. must match, as found at the poles of spheres
+    let positions = &self.positions;
+    let indices = primitive::triangle_adjacency_indices_by(&indices, |index| {
+      positions[index as usize].map(|x| if x == 0. { 0 } else { x.to_bits() })
+    });
+
+    Shape {
+      positions: self.positions,
+      normals: self.normals,
+      uvs: self.uvs,
+      indices,
+      _phantom: PhantomData,
+    }
+  }
+}
+
+/// Vertices of a [`Shape`] being built.
+///
+/// The vertex entity builders made out of them can only build vertex entities of the primitive `P` of the shape.
+#[derive(Debug)]
+pub struct ShapeVertices<'a, V, P> {
+  positions: &'a [[f32; 3]],
+  normals: &'a [[f32; 3]],
+  uvs: &'a [[f32; 2]],
+  indices: &'a [u32],
+  vertices: Vec<V>,
+  _phantom: PhantomData<P>,
+}
+
+impl<'a, V, P> ShapeVertices<'a, V, P>
+where
+  V: Vertex,
+  P: Primitive,
+{
+  /// Write the positions to the `NAME` field.
+  pub fn positions<const NAME: &'static str>(mut self) -> Self
+  where
+    V: HasField<NAME>,
+    <V as HasField<NAME>>::FieldType: FromAttrib<[f32; 3]>,
+  {
+    FieldSetter::new::<NAME>().set_all(&mut self.vertices, self.positions.iter().copied());
+    self
+  }
+
+  /// Write the normals to the `NAME` field.
+  pub fn normals<const NAME: &'static str>(mut self) -> Self
+  where
+    V: HasField<NAME>,
+    <V as HasField<NAME>>::FieldType: FromAttrib<[f32; 3]>,
+  {
+    FieldSetter::new::<NAME>().set_all(&mut self.vertices, self.normals.iter().copied());
+    self
+  }
+
+  /// Write the texture coordinates to the `NAME` field.
+  pub fn uvs<const NAME: &'static str>(mut self) -> Self
+  where
+    V: HasField<NAME>,
+    <V as HasField<NAME>>::FieldType: FromAttrib<[f32; 2]>,
+  {
+    FieldSetter::new::<NAME>().set_all(&mut self.vertices, self.uvs.iter().copied());
+    self
+  }
+
+  /// Build a vertex entity with interleaved vertices.
+  pub fn interleaved(self) -> VertexEntityBuilder<Interleaved<V>, (), u32, P> {
+    VertexEntityBuilder::new()
+      .add_vertices(Interleaved::new().set_vertices(self.vertices))
+      .add_indices(self.indices)
+      .set_primitive()
+  }
+
+  /// Build a vertex entity with deinterleaved vertices.
+  pub fn deinterleaved(self) -> VertexEntityBuilder<Deinterleaved<V>, (), u32, P> {
+    VertexEntityBuilder::new()
+      .add_vertices(Deinterleaved::from_vertices(&self.vertices))
+      .add_indices(self.indices)
+      .set_primitive()
+  }
+}
+
+/// A triangle covering the whole viewport, in clip space.
+///
+/// The triangle goes beyond the viewport so that its texture coordinates are in `[0; 1]` over the viewport.
+pub fn fullscreen_triangle() -> Shape<Triangle> {
+  let mut shape = Shape::new();
+
+  shape.push([-1., -1., 0.], [0., 0., 1.], [0., 0.]);
+  shape.push([3., -1., 0.], [0., 0., 1.], [2., 0.]);
+  shape.push([-1., 3., 0.], [0., 0., 1.], [0., 2.]);
+
+  shape
+}
+
+/// A quad in the `XY` plane, facing `+Z`, centered at the origin.
+pub fn quad(width: f32, height: f32) -> Shape<TriangleStrip> {
+  let mut shape = Shape::new();
+  let (x, y) = (width * 0.5, height * 0.5);
+
+  shape.push([-x, -y, 0.], [0., 0., 1.], [0., 0.]);
+  shape.push([x, -y, 0.], [0., 0., 1.], [1., 0.]);
+  shape.push([-x, y, 0.], [0., 0., 1.], [0., 1.]);
+  shape.push([x, y, 0.], [0., 0., 1.], [1., 1.]);
+
+  shape
+}
+
+/// A grid in the `XZ` plane, facing `+Y`, centered at the origin and made of `x_segments` × `z_segments` quads.
+///
+/// Texture coordinates go from `-X` to `+X` and from `+Z` to `-Z`.
+pub fn plane(width: f32, depth: f32, x_segments: u32, z_segments: u32) -> Shape<Triangle> {
+  let mut shape = Shape::new();
+  let (x_segments, z_segments) = (x_segments.max(1), z_segments.max(1));
+
+  for j in 0..=z_segments {
+    let v = j as f32 / z_segments as f32;
+
+    for i in 0..=x_segments {
+      let u = i as f32 / x_segments as f32;
+      shape.push(
+        [(u - 0.5) * width, 0., (0.5 - v) * depth],
+        [0., 1., 0.],
+        [u, v],
+      );
+    }
+  }
+
+  grid_indices(&mut shape.indices, x_segments, z_segments);
+  shape
+}
+
+/// An axis-aligned cube centered at the origin.
+///
+/// Each face has its own vertices, normal and texture coordinates covering `[0; 1]`.
+pub fn cube(size: f32) -> Shape<Triangle> {
+  // normal, then the right and up directions of the face, so that right × up = normal
+  const FACES: [[[f32; 3]; 3]; 6] = [
+    [[1., 0., 0.], [0., 0., -1.], [0., 1., 0.]],
+    [[-1., 0., 0.], [0., 0., 1.], [0., 1., 0.]],
+    [[0., 1., 0.], [1., 0., 0.], [0., 0., -1.]],
+    [[0., -1., 0.], [1., 0., 0.], [0., 0., 1.]],
+    [[0., 0., 1.], [1., 0., 0.], [0., 1., 0.]],
+    [[0., 0., -1.], [-1., 0., 0.], [0., 1., 0.]],
+  ];
+
+  let mut shape = Shape::new();
+  let h = size * 0.5;
+
+  for [normal, right, up] in FACES {
+    let first = shape.len() as u32;
+
+    for [u, v] in [[0., 0.], [1., 0.], [1., 1.], [0., 1.]] {
+      let (s, t) = (u * 2. - 1., v * 2. - 1.);
+      let position = [0, 1, 2].map(|k| (normal[k] + right[k] * s + up[k] * t) * h);
+      shape.push(position, normal, [u, v]);
+    }
+
+    shape.indices.extend([0, 1, 2, 0, 2, 3].map(|i| first + i));
+  }
+
+  shape
+}
+
+/// A sphere made of `sectors` slices around `Y` and `stacks` stacks from the north pole to the south pole.
+///
+/// Texture coordinates are equirectangular: `u` goes around `Y`, starting at `+X`, and `v` goes from the south pole to
+/// the north pole.
+pub fn uv_sphere(radius: f32, sectors: u32, stacks: u32) -> Shape<Triangle> {
+  let mut shape = Shape::new();
+  let (sectors, stacks) = (sectors.max(3), stacks.max(2));
+
+  for i in 0..=stacks {
+    let v = i as f32 / stacks as f32;
+    let phi = v * PI;
+    // sin(PI) is not exactly zero in floating point, which would scatter the vertices of the south pole
+    let sin_phi = if i == stacks { 0. } else { phi.sin() };
+
+    for j in 0..=sectors {
+      let u = j as f32 / sectors as f32;
+      // the seam is computed from the first sector so that its vertices are at the exact same positions
+      let theta = (j % sectors) as f32 / sectors as f32 * 2. * PI;
+      let normal = [sin_phi * theta.cos(), phi.cos(), -sin_phi * theta.sin()];
+
+      shape.push(normal.map(|x| x * radius), normal, [u, 1. - v]);
+    }
+  }
+
+  for i in 0..stacks {
+    for j in 0..sectors {
+      let a = i * (sectors + 1) + j;
+      let b = a + sectors + 1;
+
+      // triangles touching the poles are degenerate
+      if i != 0 {
+        shape.indices.extend([a, b, a + 1]);
+      }
+
+      if i != stacks - 1 {
+        shape.indices.extend([a + 1, b, b + 1]);
+      }
+    }
+  }
+
+  shape
+}
+
+/// A sphere made by subdividing an icosahedron `subdivisions` times.
+///
+/// Triangles are more evenly distributed than with [`uv_sphere`]. Texture coordinates are equirectangular, as with
+/// [`uv_sphere`], but vertices are not duplicated along the seam at `+X`, where `u` wraps around.
+pub fn icosphere(radius: f32, subdivisions: u32) -> Shape<Triangle> {
+  let t = (1. + 5f32.sqrt()) * 0.5;
+  let mut positions = vec![
+    [-1., t, 0.],
+    [1., t, 0.],
+    [-1., -t, 0.],
+    [1., -t, 0.],
+    [0., -1., t],
+    [0., 1., t],
+    [0., -1., -t],
+    [0., 1., -t],
+    [t, 0., -1.],
+    [t, 0., 1.],
+    [-t, 0., -1.],
+    [-t, 0., 1.],
+  ]
+  .into_iter()
+  .map(normalize)
+  .collect::<Vec<_>>();
+
+  let mut faces = vec![
+    [0, 11, 5],
+    [0, 5, 1],
+    [0, 1, 7],
+    [0, 7, 10],
+    [0, 10, 11],
+    [1, 5, 9],
+    [5, 11, 4],
+    [11, 10, 2],
+    [10, 7, 6],
+    [7, 1, 8],
+    [3, 9, 4],
+    [3, 4, 2],
+    [3, 2, 6],
+    [3, 6, 8],
+    [3, 8, 9],
+    [4, 9, 5],
+    [2, 4, 11],
+    [6, 2, 10],
+    [8, 6, 7],
+    [9, 8, 1],
+  ];
+
+  for _ in 0..subdivisions {
+    // edges are shared by two faces; their middle vertex is created once
+    let mut middles = HashMap::new();
+    let mut middle = |a: u32, b: u32| {
+      *middles.entry((a.min(b), a.max(b))).or_insert_with(|| {
+        let (pa, pb): ([f32; 3], [f32; 3]) = (positions[a as usize], positions[b as usize]);
+        positions.push(normalize([0, 1, 2].map(|k| pa[k] + pb[k])));
+        positions.len() as u32 - 1
+      })
+    };
+
+    faces = faces
+      .into_iter()
+      .flat_map(|[a, b, c]| {
+        let (ab, bc, ca) = (middle(a, b), middle(b, c), middle(c, a));
+        [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+      })
+      .collect();
+  }
+
+  let mut shape = Shape::new();
+
+  for normal in positions {
+    let u = ((-normal[2]).atan2(normal[0]) / (2. * PI)).rem_euclid(1.);
+    let v = 0.5 + normal[1].asin() / PI;
+    shape.push(normal.map(|x| x * radius), normal, [u, v]);
+  }
+
+  shape.indices = faces.into_iter().flatten().collect();
+  shape
+}
+
+/// A cylinder along `Y`, centered at the origin, made of `sectors` slices, with caps.
+///
+/// Texture coordinates of the side go around `Y`, starting at `+X`, and from the bottom to the top. Caps map a disk
+/// centered in `[0; 1]`.
+pub fn cylinder(radius: f32, height: f32, sectors: u32) -> Shape<Triangle> {
+  let mut shape = Shape::new();
+  let sectors = sectors.max(3);
+  let h = height * 0.5;
+  let ring = |j: u32| {
+    let u = j as f32 / sectors as f32;
+    let theta = (j % sectors) as f32 / sectors as f32 * 2. * PI;
+    (u, theta.cos(), -theta.sin())
+  };
+
+  // side
+  for j in 0..=sectors {
+    let (u, x, z) = ring(j);
+    shape.push([x * radius, -h, z * radius], [x, 0., z], [u, 0.]);
+    shape.push([x * radius, h, z * radius], [x, 0., z], [u, 1.]);
+  }
+
+  for j in 0..sectors {
+    let (bottom, top) = (2 * j, 2 * j + 1);
+    shape
+      .indices
+      .extend([bottom, bottom + 2, top, top, bottom + 2, top + 2]);
+  }
+
+  // caps
+  for (y, ny) in [(h, 1.), (-h, -1.)] {
+    let center = shape.len() as u32;
+    shape.push([0., y, 0.], [0., ny, 0.], [0.5, 0.5]);
+
+    for j in 0..=sectors {
+      let (_, x, z) = ring(j);
+      shape.push(
+        [x * radius, y, z * radius],
+        [0., ny, 0.],
+        [0.5 + x * 0.5, 0.5 - z * ny * 0.5],
+      );
+    }
+
+    for j in 0..sectors {
+      let (a, b) = (center + 1 + j, center + 2 + j);
+
+      if ny > 0. {
+        shape.indices.extend([center, a, b]);
+      } else {
+        shape.indices.extend([center, b, a]);
+      }
+    }
+  }
+
+  shape
+}
+
+/// A torus around `Y`, centered at the origin.
+///
+/// `major_radius` is the distance from the center to the center of the tube, and `minor_radius` the radius of the
+/// tube. Texture coordinates go around `Y`, starting at `+X`, and around the tube, starting outwards.
+pub fn torus(
+  major_radius: f32,
+  minor_radius: f32,
+  major_segments: u32,
+  minor_segments: u32,
+) -> Shape<Triangle> {
+  let mut shape = Shape::new();
+  let (major_segments, minor_segments) = (major_segments.max(3), minor_segments.max(3));
+
+  for i in 0..=major_segments {
+    let u = i as f32 / major_segments as f32;
+    let theta = (i % major_segments) as f32 / major_segments as f32 * 2. * PI;
+    let (x, z) = (theta.cos(), -theta.sin());
+
+    for j in 0..=minor_segments {
+      let v = j as f32 / minor_segments as f32;
+      let phi = (j % minor_segments) as f32 / minor_segments as f32 * 2. * PI;
+      let normal = [x * phi.cos(), phi.sin(), z * phi.cos()];
+      let position = [
+        x * major_radius + normal[0] * minor_radius,
+        normal[1] * minor_radius,
+        z * major_radius + normal[2] * minor_radius,
+      ];
+
+      shape.push(position, normal, [u, v]);
+    }
+  }
+
+  for i in 0..major_segments {
+    for j in 0..minor_segments {
+      let a = i * (minor_segments + 1) + j;
+      let b = a + minor_segments + 1;
+      shape.indices.extend([a, b, a + 1, a + 1, b, b + 1]);
+    }
+  }
+
+  shape
+}
+
+/// Indices of a grid of `columns` × `rows` quads, whose vertices are stored row by row.
+///
+/// Triangles are counter-clockwise when columns go right and rows go up.
+fn grid_indices(indices: &mut Vec<u32>, columns: u32, rows: u32) {
+  for j in 0..rows {
+    for i in 0..columns {
+      let a = j * (columns + 1) + i;
+      let b = a + columns + 1;
+      indices.extend([a, a + 1, b + 1, a, b + 1, b]);
+    }
+  }
+}
+
+fn normalize([x, y, z]: [f32; 3]) -> [f32; 3] {
+  let len = (x * x + y * y + z * z).sqrt();
+  [x / len, y / len, z / len]
+}
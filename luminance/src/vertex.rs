@@ -9,7 +9,7 @@
 //! [`VertexAttribType`]: crate::vertex::VertexAttribType
 
 use crate::has_field::HasField;
-use std::fmt::Debug;
+use std::fmt::{self, Debug};
 
 /// A type that can be used as a [`Vertex`] has to implement that trait – it must provide an
 /// associated list of [`VertexBufferDesc`] value via a function call. This associated value gives enough
@@ -57,7 +57,8 @@ pub trait Deinterleave<const NAME: &'static str>: HasField<NAME> {
 pub struct VertexBufferDesc {
   /// Internal index of the attribute.
   ///
-  /// That index is used as a mapping with vertex shaders to know how to fetch vertex attributes.
+  /// That index is used as a mapping with vertex shaders to know how to fetch vertex attributes. Attributes spanning
+  /// several [locations](VertexAttribDesc::locations), such as matrices, also use the following indices.
   pub index: usize,
 
   /// The name of the attribute.
@@ -67,17 +68,179 @@ pub struct VertexBufferDesc {
 
   /// Vertex attribute descriptor.
   pub attrib_desc: VertexAttribDesc,
+
+  /// Number of instances sharing the same value of the attribute, when used as instance data.
+  ///
+  /// The attribute of instance `i` is read at `i / divisor`. It’s ignored for per-vertex attributes, and must not be
+  /// zero.
+  pub divisor: usize,
 }
 
 impl VertexBufferDesc {
-  /// Create a new [`VertexBufferDesc`].
+  /// Create a new [`VertexBufferDesc`], advancing once per instance when used as instance data.
   pub fn new(index: usize, name: &'static str, attrib_desc: VertexAttribDesc) -> Self {
     VertexBufferDesc {
       index,
       name,
       attrib_desc,
+      divisor: 1,
     }
   }
+
+  /// Set the number of instances sharing the same value of the attribute.
+  pub fn set_divisor(mut self, divisor: usize) -> Self {
+    self.divisor = divisor;
+    self
+  }
+}
+
+/// Offsets, in bytes, of the attributes described by `descs` in a vertex.
+///
+/// The offsets follow the `#[repr(C)]` layout rules, which is the layout expected from [`Vertex`] types.
+pub fn aligned_offsets(descs: &[VertexBufferDesc]) -> Vec<usize> {
+  let mut offsets = Vec::with_capacity(descs.len());
+  let mut off = 0;
+
+  for desc in descs {
+    let desc = &desc.attrib_desc;
+    let a = desc.align - 1;
+    off = (off + a) & !a;
+    offsets.push(off);
+    off += desc.size();
+  }
+
+  offsets
+}
+
+/// Number of values of instance data, described by `descs`, read to render `instance_count` instances.
+///
+/// This is `instance_count`, unless all the attributes have a [divisor](VertexBufferDesc::divisor) greater than one.
+pub fn instance_data_len(descs: &[VertexBufferDesc], instance_count: usize) -> usize {
+  descs
+    .iter()
+    .map(|desc| (instance_count + desc.divisor.max(1) - 1) / desc.divisor.max(1))
+    .max()
+    .unwrap_or(instance_count)
+}
+
+/// Types of vertex fields mesh attributes can be converted to.
+///
+/// Mesh attributes, such as positions, normals or texture coordinates, are arrays of `f32`. Three-component fields drop
+/// the `w` (or alpha) of four-component attributes, and four-component fields get a `w` of `1` out of three-component
+/// ones.
+pub trait FromAttrib<T> {
+  fn from_attrib(value: T) -> Self;
+}
+
+impl FromAttrib<[f32; 2]> for [f32; 2] {
+  fn from_attrib(value: [f32; 2]) -> Self {
+    value
+  }
+}
+
+impl FromAttrib<[f32; 3]> for [f32; 3] {
+  fn from_attrib(value: [f32; 3]) -> Self {
+    value
+  }
+}
+
+impl FromAttrib<[f32; 4]> for [f32; 3] {
+  fn from_attrib([x, y, z, _]: [f32; 4]) -> Self {
+    [x, y, z]
+  }
+}
+
+impl FromAttrib<[f32; 3]> for [f32; 4] {
+  fn from_attrib([x, y, z]: [f32; 3]) -> Self {
+    [x, y, z, 1.]
+  }
+}
+
+impl FromAttrib<[f32; 4]> for [f32; 4] {
+  fn from_attrib(value: [f32; 4]) -> Self {
+    value
+  }
+}
+
+#[cfg(feature = "mint")]
+impl FromAttrib<[f32; 2]> for mint::Vector2<f32> {
+  fn from_attrib(value: [f32; 2]) -> Self {
+    value.into()
+  }
+}
+
+#[cfg(feature = "mint")]
+impl FromAttrib<[f32; 3]> for mint::Vector3<f32> {
+  fn from_attrib(value: [f32; 3]) -> Self {
+    value.into()
+  }
+}
+
+#[cfg(feature = "mint")]
+impl FromAttrib<[f32; 4]> for mint::Vector3<f32> {
+  fn from_attrib([x, y, z, _]: [f32; 4]) -> Self {
+    [x, y, z].into()
+  }
+}
+
+#[cfg(feature = "mint")]
+impl FromAttrib<[f32; 3]> for mint::Vector4<f32> {
+  fn from_attrib([x, y, z]: [f32; 3]) -> Self {
+    [x, y, z, 1.].into()
+  }
+}
+
+#[cfg(feature = "mint")]
+impl FromAttrib<[f32; 4]> for mint::Vector4<f32> {
+  fn from_attrib(value: [f32; 4]) -> Self {
+    value.into()
+  }
+}
+
+/// Setter of the field of a vertex, converting mesh attributes of type `T` with [`FromAttrib`].
+///
+/// The name of the field is erased, so that setters of different fields can be stored together.
+pub struct FieldSetter<V, T> {
+  set: fn(&mut V, T),
+}
+
+impl<V, T> FieldSetter<V, T> {
+  /// Set the `NAME` field.
+  pub fn new<const NAME: &'static str>() -> Self
+  where
+    V: HasField<NAME>,
+    <V as HasField<NAME>>::FieldType: FromAttrib<T>,
+  {
+    fn set<V, T, const NAME: &'static str>(vertex: &mut V, value: T)
+    where
+      V: HasField<NAME>,
+      <V as HasField<NAME>>::FieldType: FromAttrib<T>,
+    {
+      *vertex.field_mut() = FromAttrib::from_attrib(value);
+    }
+
+    Self {
+      set: set::<V, T, NAME>,
+    }
+  }
+
+  /// Set the field of a vertex.
+  pub fn set(&self, vertex: &mut V, value: T) {
+    (self.set)(vertex, value)
+  }
+
+  /// Set the field of each vertex, in order.
+  pub fn set_all(&self, vertices: &mut [V], values: impl IntoIterator<Item = T>) {
+    for (vertex, value) in vertices.iter_mut().zip(values) {
+      (self.set)(vertex, value);
+    }
+  }
+}
+
+impl<V, T> fmt::Debug for FieldSetter<V, T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("FieldSetter").finish_non_exhaustive()
+  }
 }
 
 /// Vertex attribute format.
@@ -95,18 +258,39 @@ pub struct VertexAttribDesc {
   pub ty: VertexAttribType,
 
   /// Dimension of the attribute. It should be in 1–4. See [`VertexAttribDim`] for further details.
+  ///
+  /// For matrices, it’s the dimension of a column.
   pub dim: VertexAttribDim,
 
   /// Size in bytes that a single element of the attribute takes. That is, if your attribute has
   /// a dimension set to 2, then the unit size should be the size of a single element (not two).
+  ///
+  /// For [packed](VertexAttribType::is_packed) types, it’s the size of the value all the elements are packed in.
   pub unit_size: usize,
 
   /// Alignment of the attribute. The best advice is to respect what Rust does, so it’s highly
   /// recommended to use `::std::mem::align_of` to let it does the job for you.
   pub align: usize,
+
+  /// Number of consecutive attribute locations the attribute spans.
+  ///
+  /// It’s `1` for scalars and vectors. Matrices span one location per column, each column having `dim` components, and
+  /// are stored column after column.
+  pub locations: usize,
 }
 
 impl VertexAttribDesc {
+  /// Size in bytes of the attribute.
+  pub fn size(&self) -> usize {
+    let location_size = if self.ty.is_packed() {
+      self.unit_size
+    } else {
+      self.dim.size() * self.unit_size
+    };
+
+    self.locations * location_size
+  }
+
   /// Normalize a vertex attribute format’s type.
   pub fn normalize(self) -> Self {
     VertexAttribDesc {
@@ -114,6 +298,77 @@ impl VertexAttribDesc {
       ..self
     }
   }
+
+  /// Expose a double-precision floating-point attribute to shaders as such, instead of converting it to
+  /// single-precision.
+  ///
+  /// Other attributes are returned as is.
+  #[cfg(feature = "shader-f64")]
+  pub fn double(self) -> Self {
+    match self.ty {
+      VertexAttribType::Floating if self.unit_size == 8 => VertexAttribDesc {
+        ty: VertexAttribType::Floating64,
+        ..self
+      },
+      _ => self,
+    }
+  }
+}
+
+/// Display the Rust type of the attribute, e.g. `[f32; 3]` or `normalized [u8; 4]`.
+impl fmt::Display for VertexAttribDesc {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let (scalar, normalized) = match self.ty {
+      VertexAttribType::Integral(normalized) => {
+        let scalar = match self.unit_size {
+          1 => "i8",
+          2 => "i16",
+          _ => "i32",
+        };
+
+        (scalar, normalized)
+      }
+
+      VertexAttribType::Unsigned(normalized) => {
+        let scalar = match self.unit_size {
+          1 => "u8",
+          2 => "u16",
+          _ => "u32",
+        };
+
+        (scalar, normalized)
+      }
+
+      VertexAttribType::Floating => {
+        let scalar = match self.unit_size {
+          2 => "f16",
+          8 => "f64",
+          _ => "f32",
+        };
+
+        (scalar, Normalized::No)
+      }
+
+      #[cfg(feature = "shader-f64")]
+      VertexAttribType::Floating64 => ("f64", Normalized::No),
+
+      VertexAttribType::Boolean => ("bool", Normalized::No),
+
+      // packed types already hold all of their elements
+      VertexAttribType::Integral2_10_10_10Rev(normalized) => ("Int2_10_10_10Rev", normalized),
+      VertexAttribType::Unsigned2_10_10_10Rev(normalized) => ("UInt2_10_10_10Rev", normalized),
+    };
+
+    if normalized == Normalized::Yes {
+      f.write_str("normalized ")?;
+    }
+
+    match (self.ty.is_packed(), self.dim.size(), self.locations) {
+      (true, _, _) | (false, 1, 1) => f.write_str(scalar),
+      (false, dim, 1) => write!(f, "[{scalar}; {dim}]"),
+      (false, dim, locations) => write!(f, "[[{scalar}; {dim}]; {locations}]"),
+    }
+  }
 }
 
 /// Possible type of vertex attributes.
@@ -128,9 +383,27 @@ pub enum VertexAttribType {
   /// Typically, `u32` is unsigned but not `i32`.
   Unsigned(Normalized),
   /// A floating point integral type.
+  ///
+  /// Its precision is given by the unit size: `2` for half-precision, `4` for single-precision and `8` for
+  /// double-precision. Values are exposed to shaders as single-precision floating-point values.
   Floating,
+  /// A double-precision floating point type, exposed as such to shaders (`double`, `dvec*` and `dmat*` inputs).
+  ///
+  /// `f64` attributes are [`Floating`](VertexAttribType::Floating); see [`VertexAttribDesc::double`] to opt in.
+  #[cfg(feature = "shader-f64")]
+  Floating64,
   /// A boolean integral type.
   Boolean,
+  /// Four signed integers packed in a 32-bit value: 10 bits for `x`, `y` and `z` and 2 bits for `w`, from the least to
+  /// the most significant bits (`INT_2_10_10_10_REV`).
+  ///
+  /// Non-normalized values are exposed as floating-point values as well.
+  Integral2_10_10_10Rev(Normalized),
+  /// Four unsigned integers packed in a 32-bit value: 10 bits for `x`, `y` and `z` and 2 bits for `w`, from the least to
+  /// the most significant bits (`UNSIGNED_INT_2_10_10_10_REV`).
+  ///
+  /// Non-normalized values are exposed as floating-point values as well.
+  Unsigned2_10_10_10Rev(Normalized),
 }
 
 impl VertexAttribType {
@@ -142,9 +415,23 @@ impl VertexAttribType {
     match self {
       VertexAttribType::Integral(Normalized::No) => VertexAttribType::Integral(Normalized::Yes),
       VertexAttribType::Unsigned(Normalized::No) => VertexAttribType::Unsigned(Normalized::Yes),
+      VertexAttribType::Integral2_10_10_10Rev(Normalized::No) => {
+        VertexAttribType::Integral2_10_10_10Rev(Normalized::Yes)
+      }
+      VertexAttribType::Unsigned2_10_10_10Rev(Normalized::No) => {
+        VertexAttribType::Unsigned2_10_10_10Rev(Normalized::Yes)
+      }
       _ => self,
     }
   }
+
+  /// Whether all the elements of the attribute are packed in a single value.
+  pub fn is_packed(self) -> bool {
+    matches!(
+      self,
+      VertexAttribType::Integral2_10_10_10Rev(_) | VertexAttribType::Unsigned2_10_10_10Rev(_)
+    )
+  }
 }
 
 /// Whether an integral vertex type should be normalized when fetched from a shader program.
@@ -221,12 +508,17 @@ const fn align_of<T>() -> usize {
 // Macro to quickly implement VertexAttrib for a given type.
 macro_rules! impl_vertex_attribute {
   ($t:ty, $q:ty, $attr_ty:expr, $dim:expr) => {
+    impl_vertex_attribute!($t, $q, $attr_ty, $dim, 1);
+  };
+
+  ($t:ty, $q:ty, $attr_ty:expr, $dim:expr, $locations:expr) => {
     unsafe impl VertexAttrib for $t {
       const VERTEX_ATTRIB_DESC: VertexAttribDesc = VertexAttribDesc {
         ty: $attr_ty,
         dim: $dim,
         unit_size: $crate::vertex::size_of::<$q>(),
         align: $crate::vertex::align_of::<$q>(),
+        locations: $locations,
       };
     }
   };
@@ -256,3 +548,176 @@ impl_vertex_attribute!(u32, VertexAttribType::Unsigned(Normalized::No));
 impl_vertex_attribute!(f32, VertexAttribType::Floating);
 impl_vertex_attribute!(f64, VertexAttribType::Floating);
 impl_vertex_attribute!(bool, VertexAttribType::Boolean);
+
+#[cfg(feature = "half")]
+impl_vertex_attribute!(half::f16, VertexAttribType::Floating);
+
+impl_vertex_attribute!(
+  Int2_10_10_10Rev,
+  u32,
+  VertexAttribType::Integral2_10_10_10Rev(Normalized::No),
+  VertexAttribDim::Dim4
+);
+impl_vertex_attribute!(
+  UInt2_10_10_10Rev,
+  u32,
+  VertexAttribType::Unsigned2_10_10_10Rev(Normalized::No),
+  VertexAttribDim::Dim4
+);
+
+// column-major matrices, spanning one attribute location per column
+impl_vertex_attribute!(
+  [[f32; 2]; 2],
+  f32,
+  VertexAttribType::Floating,
+  VertexAttribDim::Dim2,
+  2
+);
+impl_vertex_attribute!(
+  [[f32; 3]; 3],
+  f32,
+  VertexAttribType::Floating,
+  VertexAttribDim::Dim3,
+  3
+);
+impl_vertex_attribute!(
+  [[f32; 4]; 4],
+  f32,
+  VertexAttribType::Floating,
+  VertexAttribDim::Dim4,
+  4
+);
+
+#[cfg(feature = "mint")]
+impl_vertex_attribute!(
+  mint::ColumnMatrix2<f32>,
+  f32,
+  VertexAttribType::Floating,
+  VertexAttribDim::Dim2,
+  2
+);
+#[cfg(feature = "mint")]
+impl_vertex_attribute!(
+  mint::ColumnMatrix3<f32>,
+  f32,
+  VertexAttribType::Floating,
+  VertexAttribDim::Dim3,
+  3
+);
+#[cfg(feature = "mint")]
+impl_vertex_attribute!(
+  mint::ColumnMatrix4<f32>,
+  f32,
+  VertexAttribType::Floating,
+  VertexAttribDim::Dim4,
+  4
+);
+
+impl_vertex_attribute!(
+  [[f64; 2]; 2],
+  f64,
+  VertexAttribType::Floating,
+  VertexAttribDim::Dim2,
+  2
+);
+impl_vertex_attribute!(
+  [[f64; 3]; 3],
+  f64,
+  VertexAttribType::Floating,
+  VertexAttribDim::Dim3,
+  3
+);
+impl_vertex_attribute!(
+  [[f64; 4]; 4],
+  f64,
+  VertexAttribType::Floating,
+  VertexAttribDim::Dim4,
+  4
+);
+
+#[cfg(feature = "mint")]
+impl_vertex_attribute!(
+  mint::ColumnMatrix2<f64>,
+  f64,
+  VertexAttribType::Floating,
+  VertexAttribDim::Dim2,
+  2
+);
+#[cfg(feature = "mint")]
+impl_vertex_attribute!(
+  mint::ColumnMatrix3<f64>,
+  f64,
+  VertexAttribType::Floating,
+  VertexAttribDim::Dim3,
+  3
+);
+#[cfg(feature = "mint")]
+impl_vertex_attribute!(
+  mint::ColumnMatrix4<f64>,
+  f64,
+  VertexAttribType::Floating,
+  VertexAttribDim::Dim4,
+  4
+);
+
+/// Four signed integers packed in 32 bits, as described by [`VertexAttribType::Integral2_10_10_10Rev`].
+///
+/// Normalized, it’s typically used to store normals and tangents in 4 bytes instead of 12 or 16.
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Int2_10_10_10Rev(pub u32);
+
+impl Int2_10_10_10Rev {
+  /// Pack four integers, keeping their 10 (`x`, `y` and `z`) or 2 (`w`) least significant bits.
+  pub fn new(x: i16, y: i16, z: i16, w: i8) -> Self {
+    Self(
+      (x as u32 & 0x3FF)
+        | (y as u32 & 0x3FF) << 10
+        | (z as u32 & 0x3FF) << 20
+        | (w as u32 & 0x3) << 30,
+    )
+  }
+
+  /// Pack four values in `[-1; 1]`, as read back from a normalized attribute.
+  ///
+  /// Values out of that range are clamped.
+  pub fn from_normalized([x, y, z, w]: [f32; 4]) -> Self {
+    let pack = |v: f32, max: f32| (v.clamp(-1., 1.) * max).round() as i16;
+    Self::new(
+      pack(x, 511.),
+      pack(y, 511.),
+      pack(z, 511.),
+      pack(w, 1.) as i8,
+    )
+  }
+}
+
+/// Four unsigned integers packed in 32 bits, as described by [`VertexAttribType::Unsigned2_10_10_10Rev`].
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct UInt2_10_10_10Rev(pub u32);
+
+impl UInt2_10_10_10Rev {
+  /// Pack four integers, keeping their 10 (`x`, `y` and `z`) or 2 (`w`) least significant bits.
+  pub fn new(x: u16, y: u16, z: u16, w: u8) -> Self {
+    Self(
+      (x as u32 & 0x3FF)
+        | (y as u32 & 0x3FF) << 10
+        | (z as u32 & 0x3FF) << 20
+        | (w as u32 & 0x3) << 30,
+    )
+  }
+
+  /// Pack four values in `[0; 1]`, as read back from a normalized attribute.
+  ///
+  /// Values out of that range are clamped.
+  pub fn from_normalized([x, y, z, w]: [f32; 4]) -> Self {
+    let pack = |v: f32, max: f32| (v.clamp(0., 1.) * max).round() as u16;
+    Self::new(
+      pack(x, 1023.),
+      pack(y, 1023.),
+      pack(z, 1023.),
+      pack(w, 3.) as u8,
+    )
+  }
+}
@@ -1,14 +1,121 @@
-use crate::{primitive::Primitive, vertex::Vertex, vertex_storage::VertexStorageFamily};
+use crate::{
+  buffer::Buffer,
+  pipeline::FeedbackTarget,
+  primitive::Primitive,
+  vertex::Vertex,
+  vertex_storage::{AsVertexStorage, Shared, Sharing, VertexStorageFamily},
+};
 use std::{
   marker::PhantomData,
   ops::{Range, RangeFrom, RangeFull, RangeTo, RangeToInclusive},
 };
 
+/// Expected update frequency of the data of a vertex entity storage.
+///
+/// Backends use that hint to pick where to store the data and how to update it.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Usage {
+  /// The data is set once and rendered many times (e.g. static world geometry).
+  Static,
+
+  /// The data is updated from time to time and rendered many times.
+  #[default]
+  Dynamic,
+
+  /// The data is updated about every time it is rendered (e.g. per-frame debug lines).
+  Stream(StreamingStrategy),
+}
+
+/// How streamed data is updated without waiting for the GPU to be done rendering the previous data.
+///
+/// Both strategies upload the whole storage on every update, whatever the updated range.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum StreamingStrategy {
+  /// Orphan the storage before updating it, letting the GPU render the previous data while the new one is uploaded.
+  #[default]
+  Orphaning,
+
+  /// Cycle through the given number of storages, updating the next one while the GPU renders the current one.
+  ///
+  /// The storages are allocated up front. A storage is updated without waiting for the GPU if it is done rendering it,
+  /// so the count should be greater than the number of updates the GPU lags behind. A count of `0` is treated as `1`.
+  RoundRobin(usize),
+}
+
+/// Type of the indices of a vertex entity.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum IndexType {
+  U8,
+  U16,
+  U32,
+}
+
+impl IndexType {
+  /// Size of an index, in bytes.
+  pub fn bytes(self) -> usize {
+    match self {
+      IndexType::U8 => 1,
+      IndexType::U16 => 2,
+      IndexType::U32 => 4,
+    }
+  }
+
+  /// Index restarting primitives when primitive restart is enabled; it is the maximum value of the type.
+  pub fn primitive_restart_index(self) -> u32 {
+    match self {
+      IndexType::U8 => u8::MAX as _,
+      IndexType::U16 => u16::MAX as _,
+      IndexType::U32 => u32::MAX,
+    }
+  }
+}
+
+/// Types that can be used as vertex indices.
+pub trait Index: Copy + Into<u32> + 'static {
+  const INDEX_TYPE: IndexType;
+}
+
+impl Index for u8 {
+  const INDEX_TYPE: IndexType = IndexType::U8;
+}
+
+impl Index for u16 {
+  const INDEX_TYPE: IndexType = IndexType::U16;
+}
+
+impl Index for u32 {
+  const INDEX_TYPE: IndexType = IndexType::U32;
+}
+
+/// Primitive of a [`VertexEntityBuilder`] that can build vertex entities of any primitive.
+///
+/// This is the primitive of builders by default.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct AnyPrimitive;
+
+/// Primitives of [`VertexEntityBuilder`]s that can build vertex entities of primitive `P`.
+///
+/// Builders typed with a primitive, such as the ones made out of [`Shape`]s, can only build vertex entities of that
+/// primitive.
+///
+/// [`Shape`]: crate::shapes::Shape
+pub trait BuilderPrimitive<P> {}
+
+impl<P> BuilderPrimitive<P> for AnyPrimitive where P: Primitive {}
+
+impl<P> BuilderPrimitive<P> for P where P: Primitive {}
+
 #[derive(Debug)]
-pub struct VertexEntityBuilder<VS, WS> {
+pub struct VertexEntityBuilder<VS, WS, I = u32, P = AnyPrimitive> {
   pub vertices: VS,
-  pub indices: Vec<u32>,
+  pub indices: Vec<I>,
+  /// Indices read from a shared buffer instead of `indices`.
+  pub index_buffer: Option<Shared<I>>,
   pub instances: WS,
+  pub vertex_usage: Usage,
+  pub index_usage: Usage,
+  pub instance_usage: Usage,
+  _primitive: PhantomData<P>,
 }
 
 impl VertexEntityBuilder<(), ()> {
@@ -16,41 +123,137 @@ impl VertexEntityBuilder<(), ()> {
     Self {
       vertices: (),
       indices: Vec::new(),
+      index_buffer: None,
       instances: (),
+      vertex_usage: Usage::default(),
+      index_usage: Usage::default(),
+      instance_usage: Usage::default(),
+      _primitive: PhantomData,
     }
   }
 }
 
-impl<WS> VertexEntityBuilder<(), WS> {
-  pub fn add_vertices<VS>(self, vertices: VS) -> VertexEntityBuilder<VS, WS> {
+impl<WS, I, P> VertexEntityBuilder<(), WS, I, P> {
+  pub fn add_vertices<VS>(self, vertices: VS) -> VertexEntityBuilder<VS, WS, I, P> {
     VertexEntityBuilder {
       vertices,
       indices: self.indices,
+      index_buffer: self.index_buffer,
       instances: self.instances,
+      vertex_usage: self.vertex_usage,
+      index_usage: self.index_usage,
+      instance_usage: self.instance_usage,
+      _primitive: PhantomData,
     }
   }
 }
 
-impl<VS> VertexEntityBuilder<VS, ()> {
-  pub fn add_instances<WS>(self, instances: WS) -> VertexEntityBuilder<VS, WS> {
+impl<VS, I, P> VertexEntityBuilder<VS, (), I, P> {
+  pub fn add_instances<WS>(self, instances: WS) -> VertexEntityBuilder<VS, WS, I, P> {
     VertexEntityBuilder {
       vertices: self.vertices,
       indices: self.indices,
+      index_buffer: self.index_buffer,
       instances,
+      vertex_usage: self.vertex_usage,
+      index_usage: self.index_usage,
+      instance_usage: self.instance_usage,
+      _primitive: PhantomData,
     }
   }
 }
 
-impl<VS, WS> VertexEntityBuilder<VS, WS> {
-  pub fn add_indices(self, indices: impl Into<Vec<u32>>) -> Self {
-    Self {
+impl<VS, WS, I, P> VertexEntityBuilder<VS, WS, I, P> {
+  /// Set the indices; their type (`u8`, `u16` or `u32`) becomes the index type of the vertex entity.
+  pub fn add_indices<J>(self, indices: impl Into<Vec<J>>) -> VertexEntityBuilder<VS, WS, J, P>
+  where
+    J: Index,
+  {
+    VertexEntityBuilder {
+      vertices: self.vertices,
       indices: indices.into(),
+      index_buffer: None,
+      instances: self.instances,
+      vertex_usage: self.vertex_usage,
+      index_usage: self.index_usage,
+      instance_usage: self.instance_usage,
+      _primitive: PhantomData,
+    }
+  }
+
+  /// Read the indices from a buffer, which can be shared with other vertex entities, instead of owning them.
+  ///
+  /// The indices of the vertex entity are then updated with the buffer.
+  pub fn add_index_buffer<J>(self, buffer: &Buffer<J>) -> VertexEntityBuilder<VS, WS, J, P>
+  where
+    J: Index,
+  {
+    VertexEntityBuilder {
+      vertices: self.vertices,
+      indices: Vec::new(),
+      index_buffer: Some(Shared::new(buffer)),
+      instances: self.instances,
+      vertex_usage: self.vertex_usage,
+      index_usage: self.index_usage,
+      instance_usage: self.instance_usage,
+      _primitive: PhantomData,
+    }
+  }
+
+  /// Set the primitive the builder can build vertex entities of.
+  pub fn set_primitive<Q>(self) -> VertexEntityBuilder<VS, WS, I, Q>
+  where
+    Q: Primitive,
+  {
+    VertexEntityBuilder {
+      vertices: self.vertices,
+      indices: self.indices,
+      index_buffer: self.index_buffer,
+      instances: self.instances,
+      vertex_usage: self.vertex_usage,
+      index_usage: self.index_usage,
+      instance_usage: self.instance_usage,
+      _primitive: PhantomData,
+    }
+  }
+
+  /// Forget the primitive of the builder, once checked.
+  pub(crate) fn any_primitive(self) -> VertexEntityBuilder<VS, WS, I> {
+    VertexEntityBuilder {
+      vertices: self.vertices,
+      indices: self.indices,
+      index_buffer: self.index_buffer,
+      instances: self.instances,
+      vertex_usage: self.vertex_usage,
+      index_usage: self.index_usage,
+      instance_usage: self.instance_usage,
+      _primitive: PhantomData,
+    }
+  }
+
+  pub fn set_vertex_usage(self, vertex_usage: Usage) -> Self {
+    Self {
+      vertex_usage,
+      ..self
+    }
+  }
+
+  pub fn set_index_usage(self, index_usage: Usage) -> Self {
+    Self {
+      index_usage,
+      ..self
+    }
+  }
+
+  pub fn set_instance_usage(self, instance_usage: Usage) -> Self {
+    Self {
+      instance_usage,
       ..self
     }
   }
 }
 
-pub struct VertexEntity<V, P, VSF, W = (), WSF = ()>
+pub struct VertexEntity<V, P, VSF, W = (), WSF = (), I = u32>
 where
   VSF: VertexStorageFamily,
   WSF: VertexStorageFamily,
@@ -58,25 +261,28 @@ where
   handle: usize,
   vertices: VSF::Storage<V>,
   instance_data: WSF::Storage<W>,
-  indices: Vec<u32>,
+  indices: Vec<I>,
+  index_buffer: Option<Shared<I>>,
   vertex_count: usize,
   dropper: Box<dyn FnMut(usize)>,
   _phantom: PhantomData<*const (V, P, W)>,
 }
 
-impl<V, P, VSF, W, WSF> VertexEntity<V, P, VSF, W, WSF>
+impl<V, P, VSF, W, WSF, I> VertexEntity<V, P, VSF, W, WSF, I>
 where
   V: Vertex,
   P: Primitive,
   VSF: VertexStorageFamily,
   W: Vertex,
   WSF: VertexStorageFamily,
+  I: Index,
 {
   pub unsafe fn new(
     handle: usize,
     vertices: VSF::Storage<V>,
     instance_data: WSF::Storage<W>,
-    indices: Vec<u32>,
+    indices: Vec<I>,
+    index_buffer: Option<Shared<I>>,
     vertex_count: usize,
     dropper: Box<dyn FnMut(usize)>,
   ) -> Self {
@@ -85,6 +291,7 @@ where
       vertices,
       instance_data,
       indices,
+      index_buffer,
       vertex_count,
       dropper,
       _phantom: PhantomData,
@@ -100,7 +307,10 @@ where
   }
 
   pub fn index_count(&self) -> usize {
-    self.indices.len()
+    match &self.index_buffer {
+      Some(index_buffer) => index_buffer.len(),
+      None => self.indices.len(),
+    }
   }
 
   pub fn vertices(&mut self) -> &mut VSF::Storage<V> {
@@ -111,12 +321,28 @@ where
     &mut self.instance_data
   }
 
-  pub fn indices(&mut self) -> &mut Vec<u32> {
+  pub fn indices(&mut self) -> &mut Vec<I> {
     &mut self.indices
   }
+
+  /// Shared buffer the indices are read from, if any.
+  pub fn index_buffer(&self) -> Option<&Shared<I>> {
+    self.index_buffer.as_ref()
+  }
+
+  /// Recompute the number of vertices to render after the vertices or the indices have changed.
+  ///
+  /// Indices are rendered if any; vertices are rendered otherwise.
+  pub(crate) fn update_vertex_count(&mut self) {
+    self.vertex_count = if self.index_count() == 0 {
+      self.vertices.as_vertex_storage().len()
+    } else {
+      self.index_count()
+    };
+  }
 }
 
-impl<V, P, VSF, W, WSF> Drop for VertexEntity<V, P, VSF, W, WSF>
+impl<V, P, VSF, W, WSF, I> Drop for VertexEntity<V, P, VSF, W, WSF, I>
 where
   VSF: VertexStorageFamily,
   WSF: VertexStorageFamily,
@@ -126,6 +352,16 @@ where
   }
 }
 
+/// Vertex entities with shared vertices capture into the buffer their vertices are stored in.
+impl<V, P, W, WSF, I> FeedbackTarget<V> for VertexEntity<V, P, Sharing, W, WSF, I>
+where
+  WSF: VertexStorageFamily,
+{
+  fn feedback_buffer_handle(&self) -> usize {
+    self.vertices.buffer_handle()
+  }
+}
+
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct VertexEntityView<V, W, P> {
   handle: usize,
@@ -139,11 +375,17 @@ pub struct VertexEntityView<V, W, P> {
   /// How many instances to render.
   instance_count: usize,
 
+  /// Value added to every index before fetching vertices; offsets the start vertex for direct rendering.
+  base_vertex: usize,
+
+  /// First instance to fetch instance data from.
+  base_instance: usize,
+
   _phantom: PhantomData<*const (V, W, P)>,
 }
 
 impl<'a, V, W, P> VertexEntityView<V, W, P> {
-  pub fn new<VSF, WSF>(vertex_entity: &VertexEntity<V, P, VSF, W, WSF>) -> Self
+  pub fn new<VSF, WSF, I>(vertex_entity: &VertexEntity<V, P, VSF, W, WSF, I>) -> Self
   where
     VSF: VertexStorageFamily,
     WSF: VertexStorageFamily,
@@ -156,6 +398,8 @@ impl<'a, V, W, P> VertexEntityView<V, W, P> {
       start_vertex: 0,
       vertex_count,
       instance_count: 1,
+      base_vertex: 0,
+      base_instance: 0,
       _phantom: PhantomData,
     }
   }
@@ -190,6 +434,27 @@ impl<'a, V, W, P> VertexEntityView<V, W, P> {
     self.instance_count = count;
     self
   }
+
+  pub fn base_vertex(&self) -> usize {
+    self.base_vertex
+  }
+
+  /// Offset the vertices of the view, e.g. to render a mesh packed with others in a single vertex entity with its own
+  /// indices.
+  pub fn set_base_vertex(mut self, base_vertex: usize) -> Self {
+    self.base_vertex = base_vertex;
+    self
+  }
+
+  pub fn base_instance(&self) -> usize {
+    self.base_instance
+  }
+
+  /// Offset the instances of the view. Not all backends support it.
+  pub fn set_base_instance(mut self, base_instance: usize) -> Self {
+    self.base_instance = base_instance;
+    self
+  }
 }
 
 pub trait View<R> {
@@ -200,13 +465,14 @@ pub trait View<R> {
   fn view(&self, range: R) -> VertexEntityView<Self::Vertex, Self::Instance, Self::Primitive>;
 }
 
-impl<V, P, VSF, W, WSF> View<RangeFull> for VertexEntity<V, P, VSF, W, WSF>
+impl<V, P, VSF, W, WSF, I> View<RangeFull> for VertexEntity<V, P, VSF, W, WSF, I>
 where
   V: Vertex,
   P: Primitive,
   VSF: VertexStorageFamily,
   W: Vertex,
   WSF: VertexStorageFamily,
+  I: Index,
 {
   type Vertex = V;
   type Instance = W;
@@ -217,13 +483,14 @@ where
   }
 }
 
-impl<V, P, VSF, W, WSF> View<Range<usize>> for VertexEntity<V, P, VSF, W, WSF>
+impl<V, P, VSF, W, WSF, I> View<Range<usize>> for VertexEntity<V, P, VSF, W, WSF, I>
 where
   V: Vertex,
   P: Primitive,
   VSF: VertexStorageFamily,
   W: Vertex,
   WSF: VertexStorageFamily,
+  I: Index,
 {
   type Vertex = V;
   type Instance = W;
@@ -238,18 +505,21 @@ where
       start_vertex: range.start,
       vertex_count: range.end,
       instance_count: 1,
+      base_vertex: 0,
+      base_instance: 0,
       _phantom: PhantomData,
     }
   }
 }
 
-impl<V, P, VSF, W, WSF> View<RangeFrom<usize>> for VertexEntity<V, P, VSF, W, WSF>
+impl<V, P, VSF, W, WSF, I> View<RangeFrom<usize>> for VertexEntity<V, P, VSF, W, WSF, I>
 where
   V: Vertex,
   P: Primitive,
   VSF: VertexStorageFamily,
   W: Vertex,
   WSF: VertexStorageFamily,
+  I: Index,
 {
   type Vertex = V;
   type Instance = W;
@@ -264,18 +534,21 @@ where
       start_vertex: range.start,
       vertex_count: self.vertex_count,
       instance_count: 1,
+      base_vertex: 0,
+      base_instance: 0,
       _phantom: PhantomData,
     }
   }
 }
 
-impl<V, P, VSF, W, WSF> View<RangeTo<usize>> for VertexEntity<V, P, VSF, W, WSF>
+impl<V, P, VSF, W, WSF, I> View<RangeTo<usize>> for VertexEntity<V, P, VSF, W, WSF, I>
 where
   V: Vertex,
   P: Primitive,
   VSF: VertexStorageFamily,
   W: Vertex,
   WSF: VertexStorageFamily,
+  I: Index,
 {
   type Vertex = V;
   type Instance = W;
@@ -290,18 +563,21 @@ where
       start_vertex: 0,
       vertex_count: range.end,
       instance_count: 1,
+      base_vertex: 0,
+      base_instance: 0,
       _phantom: PhantomData,
     }
   }
 }
 
-impl<V, P, VSF, W, WSF> View<RangeToInclusive<usize>> for VertexEntity<V, P, VSF, W, WSF>
+impl<V, P, VSF, W, WSF, I> View<RangeToInclusive<usize>> for VertexEntity<V, P, VSF, W, WSF, I>
 where
   V: Vertex,
   P: Primitive,
   VSF: VertexStorageFamily,
   W: Vertex,
   WSF: VertexStorageFamily,
+  I: Index,
 {
   type Vertex = V;
   type Instance = W;
@@ -316,6 +592,8 @@ where
       start_vertex: 0,
       vertex_count: range.end + 1,
       instance_count: 1,
+      base_vertex: 0,
+      base_instance: 0,
       _phantom: PhantomData,
     }
   }
@@ -1,16 +1,44 @@
 //! Vertex storage containers.
 
 use crate::{
+  buffer::{Buffer, BufferResource},
   has_field::HasField,
-  vertex::{Deinterleave, Vertex},
+  vertex::{aligned_offsets, Deinterleave, Vertex},
+};
+use std::{
+  fmt,
+  marker::PhantomData,
+  mem,
+  ops::{Deref, DerefMut},
+  rc::Rc,
+  slice,
 };
-use std::{marker::PhantomData, mem};
 
 #[derive(Debug)]
 pub enum VertexStorage<'a, V> {
   NoStorage,
   Interleaved(&'a mut Interleaved<V>),
   Deinterleaved(&'a mut Deinterleaved<V>),
+  Shared(&'a mut Shared<V>),
+}
+
+impl<'a, V> VertexStorage<'a, V>
+where
+  V: Vertex,
+{
+  /// Number of vertices in the storage.
+  pub fn len(&self) -> usize {
+    match self {
+      VertexStorage::NoStorage => 0,
+      VertexStorage::Interleaved(storage) => storage.vertices().len(),
+      VertexStorage::Deinterleaved(storage) => storage.len(),
+      VertexStorage::Shared(storage) => storage.len(),
+    }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
 }
 
 pub trait AsVertexStorage<V> {
@@ -35,6 +63,12 @@ impl<V> AsVertexStorage<V> for Deinterleaved<V> {
   }
 }
 
+impl<V> AsVertexStorage<V> for Shared<V> {
+  fn as_vertex_storage(&mut self) -> VertexStorage<V> {
+    VertexStorage::Shared(self)
+  }
+}
+
 pub trait VertexStorageFamily {
   type Storage<V>: AsVertexStorage<V>;
 }
@@ -57,6 +91,13 @@ impl VertexStorageFamily for Deinterleaving {
   type Storage<V> = Deinterleaved<V>;
 }
 
+#[derive(Debug)]
+pub struct Sharing;
+
+impl VertexStorageFamily for Sharing {
+  type Storage<V> = Shared<V>;
+}
+
 /// Store vertices as an interleaved array.
 #[derive(Debug)]
 pub struct Interleaved<V> {
@@ -107,10 +148,76 @@ impl<V> Interleaved<V> {
   }
 }
 
+/// Bytes aligned for any vertex attribute type, so that they can be viewed as their components.
+#[derive(Clone, Debug, Default)]
+pub struct AlignedBytes {
+  // f64 is the most aligned type of vertex attributes
+  units: Vec<u64>,
+  len: usize,
+}
+
+impl AlignedBytes {
+  /// Create empty bytes.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Copy `bytes`.
+  pub fn from_slice(bytes: &[u8]) -> Self {
+    let mut aligned = Self::new();
+    aligned.extend_from_slice(bytes);
+    aligned
+  }
+
+  /// Append `bytes`.
+  pub fn extend_from_slice(&mut self, bytes: &[u8]) {
+    let start = self.len;
+    let unit_size = mem::size_of::<u64>();
+
+    self.len += bytes.len();
+    self.units.resize((self.len + unit_size - 1) / unit_size, 0);
+    self[start..].copy_from_slice(bytes);
+  }
+}
+
+impl Deref for AlignedBytes {
+  type Target = [u8];
+
+  fn deref(&self) -> &Self::Target {
+    unsafe { slice::from_raw_parts(self.units.as_ptr() as *const u8, self.len) }
+  }
+}
+
+impl DerefMut for AlignedBytes {
+  fn deref_mut(&mut self) -> &mut Self::Target {
+    unsafe { slice::from_raw_parts_mut(self.units.as_mut_ptr() as *mut u8, self.len) }
+  }
+}
+
+impl PartialEq for AlignedBytes {
+  fn eq(&self, other: &Self) -> bool {
+    **self == **other
+  }
+}
+
+impl Eq for AlignedBytes {}
+
+impl PartialEq<[u8]> for AlignedBytes {
+  fn eq(&self, other: &[u8]) -> bool {
+    **self == *other
+  }
+}
+
+impl PartialEq<Vec<u8>> for AlignedBytes {
+  fn eq(&self, other: &Vec<u8>) -> bool {
+    **self == **other
+  }
+}
+
 /// Store vertices as deinterleaved arrays.
 #[derive(Debug)]
 pub struct Deinterleaved<V> {
-  components_list: Vec<Vec<u8>>,
+  components_list: Vec<AlignedBytes>,
   primitive_restart: bool,
   _phantom: PhantomData<V>,
 }
@@ -124,12 +231,37 @@ where
     let components_count = V::components_count();
 
     Self {
-      components_list: vec![Vec::new(); components_count],
+      components_list: vec![AlignedBytes::new(); components_count],
       primitive_restart: false,
       _phantom: PhantomData,
     }
   }
 
+  /// Create a deinterleaved storage from interleaved vertices, splitting them field by field.
+  pub fn from_vertices(vertices: &[V]) -> Self {
+    let mut storage = Self::new();
+    storage.extend_from_vertices(vertices);
+    storage
+  }
+
+  /// Append interleaved vertices, splitting them field by field.
+  ///
+  /// This is how storages grow, including the ones created empty with [`Deinterleaved::new`].
+  pub fn extend_from_vertices(&mut self, vertices: &[V]) {
+    let descs = V::vertex_desc();
+    let offsets = aligned_offsets(&descs);
+    let bytes =
+      unsafe { slice::from_raw_parts(vertices.as_ptr() as *const u8, mem::size_of_val(vertices)) };
+
+    for ((components, desc), offset) in self.components_list.iter_mut().zip(&descs).zip(offsets) {
+      let size = desc.attrib_desc.size();
+
+      for vertex in bytes.chunks_exact(mem::size_of::<V>()) {
+        components.extend_from_slice(&vertex[offset..offset + size]);
+      }
+    }
+  }
+
   /// Set named components.
   pub fn set_components<const NAME: &'static str>(
     mut self,
@@ -138,22 +270,106 @@ where
   where
     V: Deinterleave<NAME>,
   {
-    // turn the components into a raw vector (Vec<u8>)
-    let boxed_slice = components.into().into_boxed_slice();
-    let len = boxed_slice.len();
-    let len_bytes = len * std::mem::size_of::<<V as HasField<NAME>>::FieldType>();
-    let ptr = Box::into_raw(boxed_slice);
-    let raw = unsafe { Vec::from_raw_parts(ptr as _, len_bytes, len_bytes) };
-
-    self.components_list[<V as Deinterleave<NAME>>::RANK] = raw;
+    // copy the components as aligned bytes
+    let components = components.into();
+    let bytes = unsafe {
+      slice::from_raw_parts(
+        components.as_ptr() as *const u8,
+        mem::size_of_val(components.as_slice()),
+      )
+    };
+
+    self.components_list[<V as Deinterleave<NAME>>::RANK] = AlignedBytes::from_slice(bytes);
     self
   }
 
+  /// Get named components.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the field type is aligned to more than 8 bytes, which no vertex attribute type is.
+  pub fn components<const NAME: &'static str>(&self) -> &[<V as HasField<NAME>>::FieldType]
+  where
+    V: Deinterleave<NAME>,
+  {
+    let components = &self.components_list[<V as Deinterleave<NAME>>::RANK];
+    let len = Self::typed_len::<NAME>(components);
+
+    if len == 0 {
+      return &[];
+    }
+
+    unsafe { slice::from_raw_parts(components.as_ptr() as *const _, len) }
+  }
+
+  /// Get named components, mutably.
+  ///
+  /// Components can be edited in place but not added or removed; use [`Deinterleaved::set_components`] or
+  /// [`Deinterleaved::extend_from_vertices`] for that. Once edited, only their buffer can be sent to the backend with
+  /// [`Context::update_vertex_attribute`].
+  ///
+  /// # Panics
+  ///
+  /// Panics if the field type is aligned to more than 8 bytes, which no vertex attribute type is.
+  ///
+  /// [`Context::update_vertex_attribute`]: crate::context::Context::update_vertex_attribute
+  pub fn components_mut<const NAME: &'static str>(
+    &mut self,
+  ) -> &mut [<V as HasField<NAME>>::FieldType]
+  where
+    V: Deinterleave<NAME>,
+  {
+    let components = &mut self.components_list[<V as Deinterleave<NAME>>::RANK];
+    let len = Self::typed_len::<NAME>(components);
+
+    if len == 0 {
+      return &mut [];
+    }
+
+    unsafe { slice::from_raw_parts_mut(components.as_mut_ptr() as *mut _, len) }
+  }
+
+  /// Number of typed components stored in `components`, checking that they are correctly aligned.
+  fn typed_len<const NAME: &'static str>(components: &[u8]) -> usize
+  where
+    V: Deinterleave<NAME>,
+  {
+    let len = components.len() / mem::size_of::<<V as HasField<NAME>>::FieldType>();
+
+    assert!(
+      components
+        .as_ptr()
+        .align_offset(mem::align_of::<<V as HasField<NAME>>::FieldType>())
+        == 0,
+      "components of {} are not aligned for their type",
+      NAME
+    );
+
+    len
+  }
+
   /// Get all components
-  pub fn components_list(&self) -> &Vec<Vec<u8>> {
+  pub fn components_list(&self) -> &Vec<AlignedBytes> {
     &self.components_list
   }
 
+  /// Number of vertices in the storage.
+  ///
+  /// If components don’t all have the same length, the length of the shortest one is returned.
+  pub fn len(&self) -> usize {
+    self
+      .components_list
+      .iter()
+      .zip(V::vertex_desc())
+      .map(|(components, desc)| components.len() / desc.attrib_desc.size())
+      .min()
+      .unwrap_or(0)
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
   pub fn primitive_restart(&self) -> bool {
     self.primitive_restart
   }
@@ -163,3 +379,54 @@ where
     self
   }
 }
+
+/// Reference values stored in a [`Buffer`] instead of owning them.
+///
+/// Shared storages are used as vertices, instance data or indices of vertex entities (see
+/// [`VertexEntityBuilder::add_index_buffer`]), so that several vertex entities can use the same buffer. The values are
+/// then updated with the buffer and not with the vertex entities.
+///
+/// The buffer is kept alive as long as it is referenced, even if the [`Buffer`] itself is dropped.
+///
+/// [`VertexEntityBuilder::add_index_buffer`]: crate::vertex_entity::VertexEntityBuilder::add_index_buffer
+pub struct Shared<T> {
+  buffer_handle: usize,
+  len: usize,
+  _resource: Rc<BufferResource>,
+  _phantom: PhantomData<*const T>,
+}
+
+impl<T> Shared<T> {
+  /// Reference the values of a buffer.
+  pub fn new(buffer: &Buffer<T>) -> Self {
+    Self {
+      buffer_handle: buffer.handle(),
+      len: buffer.len(),
+      _resource: BufferResource::share(buffer),
+      _phantom: PhantomData,
+    }
+  }
+
+  /// Handle of the referenced buffer.
+  pub fn buffer_handle(&self) -> usize {
+    self.buffer_handle
+  }
+
+  /// Number of values in the referenced buffer.
+  pub fn len(&self) -> usize {
+    self.len
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+}
+
+impl<T> fmt::Debug for Shared<T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("Shared")
+      .field("buffer_handle", &self.buffer_handle)
+      .field("len", &self.len)
+      .finish()
+  }
+}
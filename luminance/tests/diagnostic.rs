@@ -0,0 +1,175 @@
+use luminance::shader::diagnostic::{parse_glsl_log, Diagnostic, Severity, Stage};
+
+const VS: &str = "in vec2 pos;
+
+void main() {
+  gl_Position = vec4(pos, 0., foo);
+}";
+
+const FS: &str = "out vec4 frag;
+
+void main() {
+  frag = vec4(1.);
+}";
+
+// lines the backend would prepend to every stage
+const PRELUDE_LINES: usize = 3;
+
+fn sources(stage: Stage) -> Option<(&'static str, usize)> {
+  match stage {
+    Stage::Vertex => Some((VS, PRELUDE_LINES)),
+    Stage::Shading => Some((FS, PRELUDE_LINES)),
+    _ => None,
+  }
+}
+
+#[test]
+fn mesa_compile_log() {
+  let log = "0:7(31): error: `foo' undeclared\n\
+             0:7(17): warning: `pos' used without being normalized\n\
+             0:2(1): error: error in the prelude\n";
+  let diagnostics = parse_glsl_log(log, Some(Stage::Vertex), sources);
+
+  assert_eq!(
+    diagnostics,
+    [
+      Diagnostic {
+        stage: Some(Stage::Vertex),
+        line: Some(4),
+        column: Some(31),
+        severity: Severity::Error,
+        message: "`foo' undeclared".to_owned(),
+        source_line: Some("  gl_Position = vec4(pos, 0., foo);".to_owned()),
+      },
+      Diagnostic {
+        stage: Some(Stage::Vertex),
+        line: Some(4),
+        column: Some(17),
+        severity: Severity::Warning,
+        message: "`pos' used without being normalized".to_owned(),
+        source_line: Some("  gl_Position = vec4(pos, 0., foo);".to_owned()),
+      },
+      Diagnostic {
+        stage: Some(Stage::Vertex),
+        line: None,
+        column: None,
+        severity: Severity::Error,
+        message: "error in the prelude".to_owned(),
+        source_line: None,
+      },
+    ]
+  );
+
+  assert_eq!(
+    diagnostics[0].to_string(),
+    "error: `foo' undeclared (vertex stage, line 4, column 31)\n  4 |   gl_Position = vec4(pos, 0., foo);"
+  );
+}
+
+#[test]
+fn nvidia_compile_log() {
+  let log = "0(7) : error C1008: undefined variable \"foo\"\n\
+             0(4) : warning C7533: global variable gl_FragColor is deprecated\n";
+  let diagnostics = parse_glsl_log(log, Some(Stage::Vertex), sources);
+
+  assert_eq!(
+    diagnostics,
+    [
+      Diagnostic {
+        stage: Some(Stage::Vertex),
+        line: Some(4),
+        column: None,
+        severity: Severity::Error,
+        message: "undefined variable \"foo\"".to_owned(),
+        source_line: Some("  gl_Position = vec4(pos, 0., foo);".to_owned()),
+      },
+      Diagnostic {
+        stage: Some(Stage::Vertex),
+        line: Some(1),
+        column: None,
+        severity: Severity::Warning,
+        message: "global variable gl_FragColor is deprecated".to_owned(),
+        source_line: Some("in vec2 pos;".to_owned()),
+      },
+    ]
+  );
+}
+
+#[test]
+fn mesa_link_log() {
+  let log = "error: fragment shader input `v_color' has no matching output in the previous stage\n";
+  let diagnostics = parse_glsl_log(log, None, sources);
+
+  assert_eq!(
+    diagnostics,
+    [Diagnostic {
+      stage: None,
+      line: None,
+      column: None,
+      severity: Severity::Error,
+      message: "fragment shader input `v_color' has no matching output in the previous stage"
+        .to_owned(),
+      source_line: None,
+    }]
+  );
+}
+
+#[test]
+fn nvidia_link_log() {
+  let log = "Vertex info\n\
+             -----------\n\
+             0(7) : error C1008: undefined variable \"foo\"\n\
+             \n\
+             Fragment info\n\
+             -------------\n\
+             0(7) : warning C7050: \"frag\" might be used before being initialized\n\
+             (0) : error C5145: must write to gl_Position\n";
+  let diagnostics = parse_glsl_log(log, None, sources);
+
+  assert_eq!(
+    diagnostics,
+    [
+      Diagnostic {
+        stage: Some(Stage::Vertex),
+        line: Some(4),
+        column: None,
+        severity: Severity::Error,
+        message: "undefined variable \"foo\"".to_owned(),
+        source_line: Some("  gl_Position = vec4(pos, 0., foo);".to_owned()),
+      },
+      Diagnostic {
+        stage: Some(Stage::Shading),
+        line: Some(4),
+        column: None,
+        severity: Severity::Warning,
+        message: "\"frag\" might be used before being initialized".to_owned(),
+        source_line: Some("  frag = vec4(1.);".to_owned()),
+      },
+      Diagnostic {
+        stage: Some(Stage::Shading),
+        line: None,
+        column: None,
+        severity: Severity::Error,
+        message: "must write to gl_Position".to_owned(),
+        source_line: None,
+      },
+    ]
+  );
+}
+
+#[test]
+fn unrecognized_log_lines() {
+  let log = "something went wrong\n\
+             0:5(1): error: syntax error\n\
+             while parsing the declaration\n";
+  let diagnostics = parse_glsl_log(log, Some(Stage::Shading), sources);
+
+  assert_eq!(diagnostics.len(), 2);
+  assert_eq!(diagnostics[0].message, "something went wrong");
+  assert_eq!(diagnostics[0].line, None);
+  assert_eq!(
+    diagnostics[1].message,
+    "syntax error\nwhile parsing the declaration"
+  );
+  assert_eq!(diagnostics[1].line, Some(2));
+}
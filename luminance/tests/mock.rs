@@ -0,0 +1,724 @@
+#![allow(incomplete_features)]
+#![feature(adt_const_params)]
+
+use luminance::{
+  backend::{
+    mock::{Command, MockBackend},
+    BufferError, IndirectBufferError, PipelineError, VertexEntityError,
+  },
+  context::Context,
+  dim::{Dim2, Off2, Size2},
+  indirect::{DrawArraysCommand, DrawCommand},
+  namespace,
+  pipeline::PipelineState,
+  pixel::{RGB32F, RGBA8UI},
+  primitive::{Patch, Triangle},
+  render_state::RenderState,
+  shader::{Program, ProgramBuilder, Uni, UniformValue},
+  texture::{Mipmaps, TextureSampling},
+  vertex_entity::{IndexType, Usage, VertexEntity, VertexEntityBuilder, View},
+  vertex_storage::{Deinterleaved, Deinterleaving, Interleaved, Interleaving, Shared, Sharing},
+  RenderSlots, Uniforms, Vertex,
+};
+use std::ops::Bound;
+
+namespace! {
+  VertexNamespace = { "pos", "color" }
+}
+
+namespace! {
+  RenderSlotNamespace = { "frag" }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Vertex)]
+#[vertex(namespace = "VertexNamespace")]
+struct Vertex {
+  pos: [f32; 2],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Vertex)]
+#[vertex(namespace = "VertexNamespace")]
+struct ColoredVertex {
+  pos: [f32; 2],
+  color: [u8; 4],
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, RenderSlots)]
+#[slot(namespace = "RenderSlotNamespace")]
+struct Slots {
+  frag: RGB32F,
+}
+
+#[derive(Debug, Uniforms)]
+struct ShaderUniforms {
+  time: Uni<f32>,
+  #[uniform(unbound)]
+  _unused: Uni<[f32; 2]>,
+}
+
+const VERTICES: [Vertex; 3] = [
+  Vertex { pos: [-0.5, -0.5] },
+  Vertex { pos: [0.5, -0.5] },
+  Vertex { pos: [0., 0.5] },
+];
+
+fn new_triangle(ctx: &mut Context<MockBackend>) -> VertexEntity<Vertex, Triangle, Interleaving> {
+  ctx
+    .new_vertex_entity(
+      VertexEntityBuilder::new().add_vertices(Interleaved::new().set_vertices(VERTICES)),
+    )
+    .unwrap()
+}
+
+fn new_program(
+  ctx: &mut Context<MockBackend>,
+) -> Program<Vertex, (), Triangle, Slots, ShaderUniforms> {
+  ctx
+    .new_program(
+      ProgramBuilder::new()
+        .add_vertex_stage("vs")
+        .no_primitive_stage()
+        .add_shading_stage("fs"),
+    )
+    .unwrap()
+}
+
+#[test]
+fn records_pipeline() {
+  let mut ctx = Context::new(MockBackend::new).unwrap();
+  let triangle = new_triangle(&mut ctx);
+  let program = new_program(&mut ctx);
+  let back_buffer = ctx
+    .back_buffer::<Dim2, Slots, ()>(Size2::new(800, 600))
+    .unwrap();
+  ctx.backend().clear_commands();
+
+  ctx
+    .with_framebuffer(
+      &back_buffer,
+      &PipelineState::default(),
+      |mut with_framebuffer| {
+        with_framebuffer.with_program(&program, |mut with_program| {
+          with_program.update(|mut program, unis| program.set(&unis.time, &1.5))?;
+          with_program.with_render_state(&RenderState::default(), |mut with_render_state| {
+            with_render_state
+              .render_vertex_entity(triangle.view(..).set_start_vertex(1).set_vertex_count(2))
+          })
+        })
+      },
+    )
+    .unwrap();
+
+  let commands = ctx.backend().take_commands();
+  assert_eq!(commands.len(), 5);
+  assert!(matches!(
+    commands[0],
+    Command::WithFramebuffer { handle: 0, .. }
+  ));
+  assert!(matches!(commands[1], Command::WithProgram { handle } if handle == program.handle()));
+  assert!(matches!(
+    &commands[2],
+    Command::SetUniform { program: p, name, value: UniformValue::Floating(v) }
+      if *p == program.handle() && name == "time" && v == &[1.5]
+  ));
+  assert!(matches!(commands[3], Command::WithRenderState { .. }));
+  assert!(matches!(
+    commands[4],
+    Command::RenderVertexEntity {
+      handle,
+      start_vertex: 1,
+      vertex_count: 2,
+      instance_count: 1,
+      base_vertex: 0,
+      base_instance: 0,
+    } if handle == triangle.handle()
+  ));
+
+  assert!(ctx.backend().commands().is_empty());
+}
+
+#[test]
+fn out_of_bounds_render() {
+  let mut ctx = Context::new(MockBackend::new).unwrap();
+  let triangle = new_triangle(&mut ctx);
+  let program = new_program(&mut ctx);
+  let back_buffer = ctx
+    .back_buffer::<Dim2, Slots, ()>(Size2::new(800, 600))
+    .unwrap();
+
+  let res = ctx.with_framebuffer(
+    &back_buffer,
+    &PipelineState::default(),
+    |mut with_framebuffer| {
+      with_framebuffer.with_program(&program, |mut with_program| {
+        with_program.with_render_state(&RenderState::default(), |mut with_render_state| {
+          with_render_state
+            .render_vertex_entity(triangle.view(..).set_start_vertex(2).set_vertex_count(3))
+        })
+      })
+    },
+  );
+
+  assert!(matches!(
+    res,
+    Err(PipelineError::RenderVertexEntity {
+      start_vertex: 2,
+      vertex_count: 3,
+      ..
+    })
+  ));
+}
+
+#[test]
+fn render_batch() {
+  let mut ctx = Context::new(MockBackend::new).unwrap();
+  let triangle = new_triangle(&mut ctx);
+  let program = new_program(&mut ctx);
+  let back_buffer = ctx
+    .back_buffer::<Dim2, Slots, ()>(Size2::new(800, 600))
+    .unwrap();
+  ctx.backend().clear_commands();
+
+  let render = |ctx: &mut Context<MockBackend>, views: &[_]| {
+    ctx.with_framebuffer(
+      &back_buffer,
+      &PipelineState::default(),
+      |mut with_framebuffer| {
+        with_framebuffer.with_program(&program, |mut with_program| {
+          with_program.with_render_state(&RenderState::default(), |mut with_render_state| {
+            with_render_state.render_vertex_entity_batch(views)
+          })
+        })
+      },
+    )
+  };
+
+  let first = triangle.view(..).set_vertex_count(1);
+  let second = first.clone().set_base_vertex(2);
+  render(&mut ctx, &[first.clone(), second.clone()]).unwrap();
+
+  let commands = ctx.backend().take_commands();
+  assert!(matches!(
+    &commands[3..],
+    [
+      Command::RenderVertexEntityBatch { view_count: 2 },
+      Command::RenderVertexEntity { base_vertex: 0, .. },
+      Command::RenderVertexEntity { base_vertex: 2, .. },
+    ]
+  ));
+
+  // the base vertex offsets direct rendering past the end of the vertices
+  let third = second.set_vertex_count(2);
+  assert!(matches!(
+    render(&mut ctx, &[first, third]),
+    Err(PipelineError::RenderVertexEntityBatch { view_count: 2, .. })
+  ));
+}
+
+#[test]
+fn render_indirect() {
+  let mut ctx = Context::new(MockBackend::new).unwrap();
+  let triangle = new_triangle(&mut ctx);
+  let program = new_program(&mut ctx);
+  let back_buffer = ctx
+    .back_buffer::<Dim2, Slots, ()>(Size2::new(800, 600))
+    .unwrap();
+  let indirect_buffer = ctx
+    .new_indirect_buffer(&[
+      DrawArraysCommand::new(3, 1, 0),
+      DrawArraysCommand::new(0, 1, 0),
+    ])
+    .unwrap();
+
+  ctx
+    .update_indirect_buffer(&indirect_buffer, 1, &[DrawArraysCommand::new(1, 2, 2)])
+    .unwrap();
+  assert_eq!(
+    ctx.read_indirect_buffer(&indirect_buffer).unwrap(),
+    [
+      DrawArraysCommand::new(3, 1, 0),
+      DrawArraysCommand::new(1, 2, 2)
+    ]
+  );
+  assert!(matches!(
+    ctx.update_indirect_buffer(&indirect_buffer, 1, &[DrawArraysCommand::default(); 2]),
+    Err(IndirectBufferError::OutOfRange {
+      start: 1,
+      end: 3,
+      len: 2
+    })
+  ));
+  ctx.backend().clear_commands();
+
+  let mut render = |range| {
+    ctx.with_framebuffer(
+      &back_buffer,
+      &PipelineState::default(),
+      |mut with_framebuffer| {
+        with_framebuffer.with_program(&program, |mut with_program| {
+          with_program.with_render_state(&RenderState::default(), |mut with_render_state| {
+            with_render_state.render_indirect(&triangle, &indirect_buffer, range)
+          })
+        })
+      },
+    )
+  };
+
+  render(0..2).unwrap();
+  assert!(matches!(
+    render(1..3),
+    Err(PipelineError::RenderIndirect {
+      start_command: 1,
+      command_count: 2,
+      ..
+    })
+  ));
+
+  let commands = ctx.backend().take_commands();
+  assert!(matches!(
+    &commands[3..6],
+    [
+      Command::RenderIndirect { handle, indirect_buffer: ib, range },
+      Command::RenderVertexEntity {
+        start_vertex: 0,
+        vertex_count: 3,
+        instance_count: 1,
+        ..
+      },
+      Command::RenderVertexEntity {
+        start_vertex: 2,
+        vertex_count: 1,
+        instance_count: 2,
+        ..
+      },
+    ] if *handle == triangle.handle() && *ib == indirect_buffer.handle() && *range == (0..2)
+  ));
+
+  // the triangle has no indices
+  let indexed_buffer = ctx
+    .new_indirect_buffer(&[DrawCommand::new(3, 1, 0)])
+    .unwrap();
+  let result = ctx.with_framebuffer(
+    &back_buffer,
+    &PipelineState::default(),
+    |mut with_framebuffer| {
+      with_framebuffer.with_program(&program, |mut with_program| {
+        with_program.with_render_state(&RenderState::default(), |mut with_render_state| {
+          with_render_state.render_indirect(&triangle, &indexed_buffer, ..)
+        })
+      })
+    },
+  );
+  assert!(matches!(
+    result,
+    Err(PipelineError::RenderIndirect { cause: Some(cause), .. })
+      if matches!(
+        cause.downcast_ref(),
+        Some(IndirectBufferError::MismatchingCommands {
+          indexed_commands: true
+        })
+      )
+  ));
+}
+
+#[test]
+fn update_vertices_range() {
+  let mut ctx = Context::new(MockBackend::new).unwrap();
+  let mut triangle = new_triangle(&mut ctx);
+  ctx.backend().clear_commands();
+
+  triangle.vertices().vertices_mut()[1].pos = [1., -1.];
+  ctx.update_vertices_range(&mut triangle, 1..2).unwrap();
+  ctx.update_vertices(&mut triangle).unwrap();
+
+  let commands = ctx.backend().take_commands();
+  assert!(matches!(
+    &commands[..],
+    [
+      Command::UpdateVertices { handle: h0, range: r0 },
+      Command::UpdateVertices { handle: h1, range: r1 },
+    ] if *h0 == triangle.handle() && *r0 == (1..2) && *h1 == triangle.handle() && *r1 == (0..3)
+  ));
+
+  assert!(matches!(
+    ctx.update_vertices_range(&mut triangle, 2..=3),
+    Err(VertexEntityError::UpdateOutOfRange {
+      start: 2,
+      end: 4,
+      len: 3
+    })
+  ));
+
+  // bounds at the edge of usize must not overflow
+  assert!(matches!(
+    ctx.update_vertices_range(&mut triangle, ..=usize::MAX),
+    Err(VertexEntityError::UpdateOutOfRange {
+      start: 0,
+      end: usize::MAX,
+      len: 3
+    })
+  ));
+  assert!(matches!(
+    ctx.update_vertices_range(
+      &mut triangle,
+      (Bound::Excluded(usize::MAX), Bound::Unbounded)
+    ),
+    Err(VertexEntityError::UpdateOutOfRange {
+      start: usize::MAX,
+      end: 3,
+      len: 3
+    })
+  ));
+}
+
+#[test]
+fn grow_vertices_and_indices() {
+  let mut ctx = Context::new(MockBackend::new).unwrap();
+  let mut triangle = new_triangle(&mut ctx);
+  assert_eq!(triangle.vertex_count(), 3);
+
+  triangle.vertices().vertices_mut().extend(VERTICES);
+  ctx.update_vertices(&mut triangle).unwrap();
+  assert_eq!(triangle.vertex_count(), 6);
+  assert_eq!(triangle.view(..).vertex_count(), 6);
+
+  // switch to indexed rendering
+  triangle.indices().extend([0, 1, 2]);
+  ctx.update_indices(&mut triangle).unwrap();
+  assert_eq!(triangle.vertex_count(), 3);
+
+  // and back to direct rendering
+  triangle.indices().clear();
+  ctx.update_indices(&mut triangle).unwrap();
+  assert_eq!(triangle.vertex_count(), 6);
+
+  let commands = ctx.backend().take_commands();
+  assert!(matches!(
+    &commands[commands.len() - 3..],
+    [
+      Command::UpdateVertices { range: r0, .. },
+      Command::UpdateIndices { range: r1, .. },
+      Command::UpdateIndices { range: r2, .. },
+    ] if *r0 == (0..6) && *r1 == (0..3) && r2.is_empty()
+  ));
+}
+
+#[test]
+fn grow_empty_deinterleaved_vertices() {
+  let mut ctx = Context::new(MockBackend::new).unwrap();
+  let mut triangle: VertexEntity<ColoredVertex, Triangle, Deinterleaving> = ctx
+    .new_vertex_entity(VertexEntityBuilder::new().add_vertices(Deinterleaved::new()))
+    .unwrap();
+  assert_eq!(triangle.vertex_count(), 0);
+
+  let vertices = VERTICES.map(|v| ColoredVertex {
+    pos: v.pos,
+    color: [255, 0, 0, 255],
+  });
+  triangle.vertices().extend_from_vertices(&vertices[..1]);
+  triangle.vertices().extend_from_vertices(&vertices[1..]);
+  ctx.update_vertices(&mut triangle).unwrap();
+
+  assert_eq!(triangle.vertex_count(), 3);
+  assert_eq!(
+    triangle.vertices().components::<"pos">(),
+    VERTICES.map(|v| v.pos)
+  );
+  assert_eq!(
+    triangle.vertices().components::<"color">(),
+    [[255, 0, 0, 255]; 3]
+  );
+}
+
+#[test]
+fn update_vertex_attribute() {
+  let mut ctx = Context::new(MockBackend::new).unwrap();
+  let vertices = Deinterleaved::<ColoredVertex>::new()
+    .set_components::<"pos">(VERTICES.map(|v| v.pos))
+    .set_components::<"color">([[255, 0, 0, 255]; 3]);
+  let mut triangle: VertexEntity<ColoredVertex, Triangle, Deinterleaving> = ctx
+    .new_vertex_entity(VertexEntityBuilder::new().add_vertices(vertices))
+    .unwrap();
+  ctx.backend().clear_commands();
+
+  assert_eq!(triangle.vertices().components::<"pos">()[2], [0., 0.5]);
+
+  for color in triangle.vertices().components_mut::<"color">() {
+    color[1] = 255;
+  }
+  ctx
+    .update_vertex_attribute::<"color", _, _, _, _, _>(&mut triangle)
+    .unwrap();
+
+  assert_eq!(
+    triangle.vertices().components::<"color">(),
+    [[255, 255, 0, 255]; 3]
+  );
+  assert_eq!(
+    triangle.vertices().components_list()[1],
+    [255, 255, 0, 255].repeat(3)
+  );
+
+  let commands = ctx.backend().take_commands();
+  assert!(matches!(
+    &commands[..],
+    [Command::UpdateVertexAttribute { handle, rank: 1 }] if *handle == triangle.handle()
+  ));
+
+  // the number of vertices cannot change when updating a single attribute
+  *triangle.vertices() = Deinterleaved::new()
+    .set_components::<"pos">(VERTICES.map(|v| v.pos).repeat(2))
+    .set_components::<"color">([[0; 4]; 6]);
+  assert!(matches!(
+    ctx.update_vertex_attribute::<"color", _, _, _, _, _>(&mut triangle),
+    Err(VertexEntityError::UpdateVertexStorage { .. })
+  ));
+}
+
+#[test]
+fn index_type() {
+  let mut ctx = Context::new(MockBackend::new).unwrap();
+  let mut triangle: VertexEntity<Vertex, Triangle, Interleaving, (), (), u16> = ctx
+    .new_vertex_entity(
+      VertexEntityBuilder::new()
+        .add_vertices(Interleaved::new().set_vertices(VERTICES))
+        .add_indices([0u16, 1, 2]),
+    )
+    .unwrap();
+
+  triangle.indices()[2] = 0;
+  ctx.update_indices(&mut triangle).unwrap();
+
+  let commands = ctx.backend().take_commands();
+  assert!(matches!(
+    &commands[..],
+    [
+      Command::NewVertexEntity {
+        index_count: 3,
+        index_type: IndexType::U16,
+        ..
+      },
+      Command::UpdateIndices { .. },
+    ]
+  ));
+}
+
+#[test]
+fn shared_buffers() {
+  let mut ctx = Context::new(MockBackend::new).unwrap();
+  let vertices = ctx.new_buffer(&VERTICES, Usage::Static).unwrap();
+  let indices = ctx
+    .new_buffer(&[0u32, 1, 2, 2, 1, 0], Usage::Static)
+    .unwrap();
+  let mut entities: Vec<VertexEntity<Vertex, Triangle, Sharing>> = (0..2)
+    .map(|_| {
+      ctx
+        .new_vertex_entity(
+          VertexEntityBuilder::new()
+            .add_vertices(Shared::new(&vertices))
+            .add_index_buffer(&indices),
+        )
+        .unwrap()
+    })
+    .collect();
+
+  assert_eq!(entities[0].vertex_count(), 6);
+  assert_eq!(
+    entities[1].index_buffer().map(Shared::buffer_handle),
+    Some(indices.handle())
+  );
+
+  // shared values are updated through their buffers only
+  assert!(matches!(
+    ctx.update_vertices(&mut entities[0]),
+    Err(VertexEntityError::SharedBuffer)
+  ));
+  assert!(matches!(
+    ctx.update_indices(&mut entities[1]),
+    Err(VertexEntityError::SharedBuffer)
+  ));
+
+  ctx
+    .update_buffer(&vertices, 2, &[Vertex { pos: [0., 1.] }])
+    .unwrap();
+  assert_eq!(ctx.read_buffer(&vertices).unwrap()[2].pos, [0., 1.]);
+
+  let commands = ctx.backend().take_commands();
+  assert!(matches!(
+    &commands[2..4],
+    [
+      Command::NewVertexEntity {
+        vertex_count: 6,
+        index_count: 6,
+        ..
+      },
+      Command::NewVertexEntity {
+        vertex_count: 6,
+        index_count: 6,
+        ..
+      },
+    ]
+  ));
+}
+
+#[test]
+fn map_buffer() {
+  let mut ctx = Context::new(MockBackend::new).unwrap();
+  let mut buffer = ctx.new_buffer(&[1u32, 2, 3], Usage::Dynamic).unwrap();
+  let handle = buffer.handle();
+
+  ctx.map_buffer(&mut buffer).unwrap()[1] = 4;
+  assert_eq!(ctx.read_buffer(&buffer).unwrap(), [1, 4, 3]);
+  assert!(matches!(
+    ctx.update_buffer(&buffer, 2, &[0, 0]),
+    Err(BufferError::OutOfRange {
+      start: 2,
+      end: 4,
+      len: 3
+    })
+  ));
+
+  drop(buffer);
+
+  let commands = ctx.backend().take_commands();
+  assert!(matches!(
+    commands[..],
+    [
+      Command::NewBuffer { len: 3, .. },
+      Command::MapBuffer { handle: h0 },
+      Command::UnmapBuffer { handle: h1 },
+      Command::ReadBuffer { .. },
+      Command::DropBuffer { handle: h2 },
+    ] if h0 == handle && h1 == handle && h2 == handle
+  ));
+}
+
+#[test]
+fn transform_feedback() {
+  let mut ctx = Context::new(MockBackend::new).unwrap();
+  let triangle = new_triangle(&mut ctx);
+  let program: Program<Vertex, (), Triangle, Slots, ShaderUniforms, Vertex> = ctx
+    .new_program(
+      ProgramBuilder::new()
+        .add_vertex_stage("vs")
+        .no_primitive_stage()
+        .add_shading_stage("fs")
+        .capture_varyings(),
+    )
+    .unwrap();
+  let captured = ctx
+    .new_buffer(&[Vertex { pos: [0.; 2] }; 3], Usage::Dynamic)
+    .unwrap();
+  let back_buffer = ctx
+    .back_buffer::<Dim2, Slots, ()>(Size2::new(800, 600))
+    .unwrap();
+
+  let commands = ctx.backend().take_commands();
+  assert!(commands.iter().any(|command| matches!(
+    command,
+    Command::NewProgram { handle, captured_varyings, .. }
+      if *handle == program.handle() && captured_varyings == &["pos"]
+  )));
+
+  ctx
+    .with_framebuffer(
+      &back_buffer,
+      &PipelineState::default(),
+      |mut with_framebuffer| {
+        with_framebuffer.with_program(&program, |mut with_program| {
+          with_program.with_render_state(&RenderState::default(), |mut with_render_state| {
+            with_render_state.render_vertex_entity_with_feedback(triangle.view(..), &captured)
+          })
+        })
+      },
+    )
+    .unwrap();
+
+  let commands = ctx.backend().take_commands();
+  assert!(matches!(
+    commands[3..],
+    [
+      Command::RenderFeedback { buffer },
+      Command::RenderVertexEntity {
+        handle,
+        vertex_count: 3,
+        ..
+      },
+    ] if buffer == captured.handle() && handle == triangle.handle()
+  ));
+}
+
+#[test]
+fn tessellation_stages() {
+  let mut ctx = Context::new(MockBackend::new).unwrap();
+  let program: Program<Vertex, (), Patch<4>, Slots, ShaderUniforms> = ctx
+    .new_program(
+      ProgramBuilder::new()
+        .add_vertex_stage("vs")
+        .add_tess_control_stage::<4>("tcs")
+        .add_tess_eval_stage("tes")
+        .add_primitive_stage("gs")
+        .add_shading_stage("fs"),
+    )
+    .unwrap();
+
+  let commands = ctx.backend().take_commands();
+  assert!(commands.iter().any(|command| matches!(
+    command,
+    Command::NewProgram { handle, vertex_code, tess_control_code, tess_eval_code, primitive_code, shading_code, .. }
+      if *handle == program.handle()
+        && vertex_code == "vs"
+        && tess_control_code == "tcs"
+        && tess_eval_code == "tes"
+        && primitive_code == "gs"
+        && shading_code == "fs"
+  )));
+}
+
+#[test]
+fn reflect_program() {
+  let mut ctx = Context::new(MockBackend::new).unwrap();
+  let program = new_program(&mut ctx);
+
+  // nothing is compiled, so nothing is active
+  let reflection = ctx.reflect_program(&program).unwrap();
+  assert!(reflection.attributes.is_empty());
+  assert!(reflection.uniforms.is_empty());
+  assert!(reflection.uniform_blocks.is_empty());
+}
+
+#[test]
+fn texture_round_trip() {
+  let mut ctx = Context::new(MockBackend::new).unwrap();
+  let texture = ctx
+    .reserve_texture::<Dim2, RGBA8UI>(Size2::new(2, 2), Mipmaps::No, &TextureSampling::default())
+    .unwrap();
+
+  ctx
+    .clear_texture_data(&texture, Off2::new(0, 0), Size2::new(2, 2), 0)
+    .unwrap();
+  ctx
+    .set_texture_base_level(&texture, Off2::new(1, 1), Size2::new(1, 1), &[1, 2, 3, 4])
+    .unwrap();
+
+  let texels = ctx.read_texture(&texture).unwrap();
+  assert_eq!(texels, [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3, 4]);
+}
+
+#[test]
+fn drop_resources() {
+  let mut ctx = Context::new(MockBackend::new).unwrap();
+  let triangle = new_triangle(&mut ctx);
+  let triangle_handle = triangle.handle();
+  ctx.backend().clear_commands();
+
+  drop(triangle);
+
+  let commands = ctx.backend().take_commands();
+  assert!(matches!(
+    commands[..],
+    [Command::DropVertexEntity { handle }] if handle == triangle_handle
+  ));
+}
@@ -0,0 +1,36 @@
+use luminance::primitive::{line_strip_adjacency_indices, triangle_adjacency_indices};
+
+#[test]
+fn triangle_adjacency() {
+  // a quad split along its diagonal, plus a triangle sharing its top edge in the wrong direction
+  let indices = [0u16, 1, 2, 0, 2, 3, 2, 3, 4];
+
+  assert_eq!(
+    triangle_adjacency_indices(&indices),
+    [
+      0, 2, 1, 0, 2, 3, // the first triangle is only adjacent to the second one
+      0, 1, 2, 0, 3,
+      2, // the third triangle isn’t wound consistently, so it’s not adjacent
+      2, 4, 3, 2, 4, 3, // no adjacent triangles
+    ]
+  );
+
+  // trailing indices are ignored
+  assert_eq!(triangle_adjacency_indices(&indices[..4]).len(), 6);
+  assert!(triangle_adjacency_indices::<u32>(&[]).is_empty());
+}
+
+#[test]
+fn line_strip_adjacency() {
+  let indices = [3u8, 1, 4];
+
+  assert_eq!(
+    line_strip_adjacency_indices(&indices, false),
+    [3, 3, 1, 4, 4]
+  );
+  assert_eq!(
+    line_strip_adjacency_indices(&indices, true),
+    [4, 3, 1, 4, 3, 1]
+  );
+  assert!(line_strip_adjacency_indices::<u8>(&[], true).is_empty());
+}
@@ -0,0 +1,265 @@
+#![allow(incomplete_features)]
+#![feature(adt_const_params)]
+
+use luminance::{
+  backend::mock::MockBackend,
+  context::Context,
+  namespace,
+  primitive::{Point, Primitive, Triangle, TriangleStrip},
+  shapes::{self, Shape},
+  vertex_entity::VertexEntity,
+  vertex_storage::{Deinterleaving, Interleaving},
+  Vertex,
+};
+
+namespace! {
+  Namespace = { "position", "normal", "uv" }
+}
+
+// positions get a w of 1
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Vertex)]
+#[vertex(namespace = "Namespace")]
+struct Vertex {
+  position: [f32; 4],
+  normal: [f32; 3],
+  uv: [f32; 2],
+}
+
+fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+  [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+  a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+  [
+    a[1] * b[2] - a[2] * b[1],
+    a[2] * b[0] - a[0] * b[2],
+    a[0] * b[1] - a[1] * b[0],
+  ]
+}
+
+/// Check that normals are unit vectors and that triangles are counter-clockwise when seen from their normals.
+fn check_triangles<P>(shape: &Shape<P>, triangles: &[[u32; 3]])
+where
+  P: Primitive,
+{
+  assert_eq!(shape.normals.len(), shape.len());
+  assert_eq!(shape.uvs.len(), shape.len());
+
+  for normal in &shape.normals {
+    assert!((dot(*normal, *normal) - 1.).abs() < 1e-5);
+  }
+
+  for &[a, b, c] in triangles {
+    let [pa, pb, pc] = [a, b, c].map(|i| shape.positions[i as usize]);
+    let face = cross(sub(pb, pa), sub(pc, pa));
+    let normal = [a, b, c]
+      .map(|i| shape.normals[i as usize])
+      .into_iter()
+      .fold([0.; 3], |n, v| [n[0] + v[0], n[1] + v[1], n[2] + v[2]]);
+
+    assert!(dot(face, face) > 0., "degenerate triangle {:?}", [a, b, c]);
+    assert!(dot(face, normal) > 0., "clockwise triangle {:?}", [a, b, c]);
+  }
+}
+
+fn indexed_triangles<P>(shape: &Shape<P>) -> Vec<[u32; 3]> {
+  assert_eq!(shape.indices.len() % 3, 0);
+  assert!(shape
+    .indices
+    .iter()
+    .all(|&i| (i as usize) < shape.positions.len()));
+
+  shape
+    .indices
+    .chunks(3)
+    .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+    .collect()
+}
+
+#[test]
+fn fullscreen_triangle() {
+  let shape = shapes::fullscreen_triangle();
+
+  assert_eq!(shape.len(), 3);
+  assert!(shape.indices.is_empty());
+  check_triangles(&shape, &[[0, 1, 2]]);
+}
+
+#[test]
+fn quad() {
+  let shape = shapes::quad(2., 4.);
+
+  assert_eq!(shape.len(), 4);
+  assert!(shape.indices.is_empty());
+  assert_eq!(shape.positions[3], [1., 2., 0.]);
+
+  // every other triangle of a strip is reversed
+  check_triangles(&shape, &[[0, 1, 2], [2, 1, 3]]);
+}
+
+#[test]
+fn indexed_shapes() {
+  let plane = shapes::plane(2., 2., 4, 3);
+  assert_eq!(plane.len(), 5 * 4);
+  assert_eq!(plane.indices.len(), 4 * 3 * 6);
+  check_triangles(&plane, &indexed_triangles(&plane));
+
+  let cube = shapes::cube(2.);
+  assert_eq!(cube.len(), 24);
+  assert_eq!(cube.indices.len(), 36);
+  assert!(cube
+    .positions
+    .iter()
+    .all(|p| p.iter().all(|x| x.abs() == 1.)));
+  check_triangles(&cube, &indexed_triangles(&cube));
+
+  let uv_sphere = shapes::uv_sphere(2., 8, 4);
+  assert_eq!(uv_sphere.len(), 9 * 5);
+  assert_eq!(uv_sphere.indices.len(), 8 * (4 * 2 - 2) * 3);
+  check_triangles(&uv_sphere, &indexed_triangles(&uv_sphere));
+
+  let icosphere = shapes::icosphere(2., 2);
+  assert_eq!(icosphere.len(), 162);
+  assert_eq!(icosphere.indices.len(), 320 * 3);
+  assert!(icosphere
+    .positions
+    .iter()
+    .all(|p| (dot(*p, *p) - 4.).abs() < 1e-4));
+  check_triangles(&icosphere, &indexed_triangles(&icosphere));
+
+  let cylinder = shapes::cylinder(1., 2., 8);
+  assert_eq!(cylinder.len(), 9 * 2 + 2 * 10);
+  assert_eq!(cylinder.indices.len(), 8 * 4 * 3);
+  check_triangles(&cylinder, &indexed_triangles(&cylinder));
+
+  let torus = shapes::torus(2., 0.5, 8, 6);
+  assert_eq!(torus.len(), 9 * 7);
+  assert_eq!(torus.indices.len(), 8 * 6 * 6);
+  check_triangles(&torus, &indexed_triangles(&torus));
+}
+
+#[test]
+fn vertices() {
+  let shape = shapes::cube(2.);
+  let builder = shape
+    .vertices::<Vertex>()
+    .positions::<"position">()
+    .uvs::<"uv">()
+    .interleaved();
+
+  let [x, y, z] = shape.positions[1];
+  assert_eq!(builder.indices, shape.indices);
+  assert_eq!(
+    builder.vertices.vertices()[1],
+    Vertex {
+      position: [x, y, z, 1.],
+      normal: [0.; 3],
+      uv: shape.uvs[1],
+    }
+  );
+
+  let interleaved = shape
+    .vertices::<Vertex>()
+    .positions::<"position">()
+    .normals::<"normal">()
+    .uvs::<"uv">()
+    .interleaved();
+  let deinterleaved = shape
+    .vertices::<Vertex>()
+    .positions::<"position">()
+    .normals::<"normal">()
+    .uvs::<"uv">()
+    .deinterleaved();
+  let components = deinterleaved.vertices.components_list();
+  let floats = |components: &[u8]| -> Vec<f32> {
+    components
+      .chunks_exact(4)
+      .map(|bytes| f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+      .collect()
+  };
+
+  assert_eq!(deinterleaved.indices, interleaved.indices);
+  assert_eq!(
+    floats(&components[0]),
+    shape
+      .positions
+      .iter()
+      .flat_map(|&[x, y, z]| [x, y, z, 1.])
+      .collect::<Vec<_>>()
+  );
+  assert_eq!(floats(&components[1]), shape.normals.concat());
+  assert_eq!(floats(&components[2]), shape.uvs.concat());
+  assert!(interleaved
+    .vertices
+    .vertices()
+    .iter()
+    .zip(&shape.normals)
+    .all(|(vertex, normal)| vertex.normal == *normal));
+}
+
+#[test]
+fn vertex_entities() {
+  let mut ctx = Context::new(MockBackend::new).unwrap();
+
+  // the primitive of the shape is the one of the vertex entity
+  let quad = shapes::quad(1., 1.);
+  let strip: VertexEntity<Vertex, TriangleStrip, Interleaving> = ctx
+    .new_vertex_entity(quad.vertices().positions::<"position">().interleaved())
+    .unwrap();
+  assert_eq!(strip.vertex_count(), 4);
+
+  let cube = shapes::cube(1.);
+  let triangles: VertexEntity<Vertex, Triangle, Deinterleaving> = ctx
+    .new_vertex_entity(cube.vertices().positions::<"position">().deinterleaved())
+    .unwrap();
+  assert_eq!(triangles.vertex_count(), 36);
+
+  // unless explicitly changed
+  let points: VertexEntity<Vertex, Point, Interleaving> = ctx
+    .new_vertex_entity(
+      cube
+        .vertices()
+        .positions::<"position">()
+        .interleaved()
+        .add_indices(Vec::<u32>::new())
+        .set_primitive::<Point>(),
+    )
+    .unwrap();
+  assert_eq!(points.vertex_count(), 24);
+}
+
+#[test]
+fn with_adjacency() {
+  // closed shapes have adjacent triangles everywhere, even across the seams where vertices are split
+  for shape in [
+    shapes::cube(2.),
+    shapes::uv_sphere(1., 8, 4),
+    shapes::icosphere(1., 1),
+    shapes::cylinder(1., 2., 8),
+    shapes::torus(2., 0.5, 8, 6),
+  ] {
+    let triangles = indexed_triangles(&shape);
+    let shape = shape.with_adjacency();
+
+    assert_eq!(shape.indices.len(), triangles.len() * 6);
+
+    for (triangle, adjacency) in triangles.iter().zip(shape.indices.chunks(6)) {
+      assert_eq!([adjacency[0], adjacency[2], adjacency[4]], *triangle);
+
+      for i in 0..3 {
+        let own_opposite = shape.positions[triangle[(i + 2) % 3] as usize];
+        let opposite = shape.positions[adjacency[2 * i + 1] as usize];
+        assert_ne!(opposite, own_opposite, "border edge in {:?}", triangle);
+      }
+    }
+  }
+
+  // shapes without indices get some, and their borders are adjacent to their own triangles
+  let shape = shapes::fullscreen_triangle().with_adjacency();
+  assert_eq!(shape.indices, [0, 2, 1, 0, 2, 1]);
+}
@@ -3,8 +3,12 @@
 
 use luminance::{
   has_field::HasField,
+  named_index::NamedIndex,
   namespace,
-  vertex::{CompatibleVertex, Vertex as _, VertexAttrib, VertexBufferDesc},
+  vertex::{
+    self, CompatibleVertex, Int2_10_10_10Rev, Normalized, UInt2_10_10_10Rev, Vertex as _,
+    VertexAttrib, VertexAttribDim, VertexAttribType, VertexBufferDesc,
+  },
   Vertex,
 };
 
@@ -12,6 +16,10 @@ namespace! {
   Namespace = { "pos", "nor", "col", "weight" }
 }
 
+namespace! {
+  MatrixNamespace = { "pos", "model": 4, "normal": 3, "col" }
+}
+
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Vertex)]
 #[vertex(namespace = "Namespace")]
@@ -32,6 +40,162 @@ fn vertex_desc() {
   assert_eq!(Vertex::vertex_desc(), expected_desc);
 }
 
+#[test]
+fn vertex_divisor() {
+  #[repr(C)]
+  #[derive(Clone, Copy, Debug, Vertex)]
+  #[vertex(namespace = "Namespace")]
+  struct Instance {
+    pos: [f32; 3],
+    #[vertex(divisor = 4)]
+    col: [u8; 4],
+    #[vertex(divisor = "2")]
+    weight: f32,
+  }
+
+  let desc = Instance::vertex_desc();
+  assert_eq!(
+    desc.iter().map(|desc| desc.divisor).collect::<Vec<_>>(),
+    [1, 4, 2]
+  );
+  assert_eq!(vertex::instance_data_len(&desc, 9), 9);
+  assert_eq!(vertex::instance_data_len(&desc[1..], 9), 5);
+  assert_eq!(vertex::instance_data_len(&desc[1..2], 8), 2);
+}
+
+#[test]
+fn vertex_matrix() {
+  #[repr(C)]
+  #[derive(Clone, Copy, Debug, Vertex)]
+  #[vertex(namespace = "MatrixNamespace")]
+  struct Instance {
+    pos: [f32; 3],
+    model: [[f32; 4]; 4],
+    normal: [[f32; 3]; 3],
+    col: [u8; 4],
+  }
+
+  assert_eq!(<MatrixNamespace as NamedIndex<"model">>::INDEX, 1);
+  assert_eq!(<MatrixNamespace as NamedIndex<"normal">>::INDEX, 5);
+  assert_eq!(<MatrixNamespace as NamedIndex<"col">>::INDEX, 8);
+
+  let desc = Instance::vertex_desc();
+  assert_eq!(
+    desc
+      .iter()
+      .map(|desc| (desc.index, desc.attrib_desc.locations))
+      .collect::<Vec<_>>(),
+    [(0, 1), (1, 4), (5, 3), (8, 1)]
+  );
+  assert_eq!(desc[1].attrib_desc.dim, VertexAttribDim::Dim4);
+  assert_eq!(desc[2].attrib_desc.dim, VertexAttribDim::Dim3);
+  assert_eq!(desc[1].attrib_desc.size(), 64);
+  assert_eq!(desc[2].attrib_desc.size(), 36);
+  assert_eq!(vertex::aligned_offsets(&desc), [0, 12, 76, 112]);
+}
+
+#[test]
+fn vertex_packed() {
+  #[repr(C)]
+  #[derive(Clone, Copy, Debug, Vertex)]
+  #[vertex(namespace = "Namespace")]
+  struct Packed {
+    #[vertex(normalized = true)]
+    nor: Int2_10_10_10Rev,
+    col: UInt2_10_10_10Rev,
+  }
+
+  let desc = Packed::vertex_desc();
+  assert_eq!(
+    desc[0].attrib_desc.ty,
+    VertexAttribType::Integral2_10_10_10Rev(Normalized::Yes)
+  );
+  assert_eq!(
+    desc[1].attrib_desc.ty,
+    VertexAttribType::Unsigned2_10_10_10Rev(Normalized::No)
+  );
+  assert!(desc.iter().all(|desc| desc.attrib_desc.ty.is_packed()
+    && desc.attrib_desc.dim == VertexAttribDim::Dim4
+    && desc.attrib_desc.size() == 4));
+  assert_eq!(vertex::aligned_offsets(&desc), [0, 4]);
+
+  assert_eq!(Int2_10_10_10Rev::new(1, -1, -512, -2).0, 0xa00f_fc01);
+  assert_eq!(
+    Int2_10_10_10Rev::from_normalized([1., -1., 0., 2.]),
+    Int2_10_10_10Rev::new(511, -511, 0, 1)
+  );
+  assert_eq!(UInt2_10_10_10Rev::new(1023, 0, 1, 3).0, 0xc010_03ff);
+  assert_eq!(
+    UInt2_10_10_10Rev::from_normalized([1., 0.5, -1., 1.]),
+    UInt2_10_10_10Rev::new(1023, 512, 0, 3)
+  );
+}
+
+#[cfg(feature = "half")]
+#[test]
+fn vertex_half() {
+  #[repr(C)]
+  #[derive(Clone, Copy, Debug, Vertex)]
+  #[vertex(namespace = "Namespace")]
+  struct Half {
+    pos: [half::f16; 3],
+    weight: half::f16,
+  }
+
+  let desc = Half::vertex_desc();
+  assert!(desc.iter().all(
+    |desc| desc.attrib_desc.ty == VertexAttribType::Floating && desc.attrib_desc.unit_size == 2
+  ));
+  assert_eq!(desc[0].attrib_desc.size(), 6);
+  assert_eq!(vertex::aligned_offsets(&desc), [0, 6]);
+}
+
+#[test]
+fn vertex_f64() {
+  #[repr(C)]
+  #[derive(Clone, Copy, Debug, Vertex)]
+  #[vertex(namespace = "MatrixNamespace")]
+  struct F64 {
+    pos: [f64; 3],
+    model: [[f64; 4]; 4],
+  }
+
+  // converted to single-precision by default
+  let desc = F64::vertex_desc();
+  assert!(desc.iter().all(
+    |desc| desc.attrib_desc.ty == VertexAttribType::Floating && desc.attrib_desc.unit_size == 8
+  ));
+  assert_eq!(desc[1].attrib_desc.size(), 128);
+}
+
+#[cfg(feature = "shader-f64")]
+#[test]
+fn vertex_double() {
+  #[repr(C)]
+  #[derive(Clone, Copy, Debug, Vertex)]
+  #[vertex(namespace = "MatrixNamespace")]
+  struct Double {
+    #[vertex(double)]
+    pos: [f64; 3],
+    #[vertex(double)]
+    model: [[f64; 4]; 4],
+    normal: [[f64; 3]; 3],
+  }
+
+  let desc = Double::vertex_desc();
+  assert_eq!(
+    desc
+      .iter()
+      .map(|desc| desc.attrib_desc.ty)
+      .collect::<Vec<_>>(),
+    [
+      VertexAttribType::Floating64,
+      VertexAttribType::Floating64,
+      VertexAttribType::Floating
+    ]
+  );
+}
+
 #[test]
 fn has_field() {
   fn must_have_field<const NAME: &'static str, V, F>()